@@ -0,0 +1,76 @@
+//! Selecting the latest version from a repository with 20,000 releases.
+//!
+//! Compare the sequential and the `rayon` paths by saving a baseline with one
+//! and measuring the other against it:
+//!
+//! ```sh
+//! cargo bench --features test-util -- --save-baseline sequential
+//! cargo bench --features test-util,rayon -- --baseline sequential
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use github_release_check::GitHub;
+use serde_json::{json, Value};
+use std::{fs, hint::black_box};
+
+const RELEASES: usize = 20_000;
+
+/// A release as the API lists it, with its tag and empty values for everything else.
+fn release(id: usize, tag: &str) -> Value {
+    json!({
+        "tag_name": tag,
+        "url": "",
+        "assets_url": "",
+        "upload_url": "",
+        "html_url": "",
+        "id": id,
+        "name": "",
+        "draft": false,
+        "prerelease": false,
+        "created_at": "",
+        "published_at": "",
+        "body": ""
+    })
+}
+
+/// Tags like a busy monorepo's, some of them not versions.
+fn tags() -> Vec<String> {
+    let mut state = 0x2545_f491_u64;
+    (0..RELEASES)
+        .map(|id| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1);
+            let (major, minor, patch) = (state >> 58, (state >> 40) % 20, (state >> 20) % 20);
+            match state % 4 {
+                0 => format!("v{major}.{minor}.{patch}"),
+                1 => format!("{major}.{minor}.{patch}-rc.{}", state % 3),
+                2 => format!("nightly-{id}"),
+                _ => format!("{major}.{minor}.{patch}"),
+            }
+        })
+        .collect()
+}
+
+fn latest_version(c: &mut Criterion) {
+    let releases: Vec<Value> = tags()
+        .iter()
+        .enumerate()
+        .map(|(id, tag)| release(id, tag))
+        .collect();
+    let path = std::env::temp_dir().join("github_release_check_bench_releases.json");
+    fs::write(&path, json!({ "foo/monorepo": releases }).to_string()).unwrap();
+    let github = GitHub::from_fixture(&path).unwrap();
+
+    c.bench_function("get_latest_version", |b| {
+        b.iter(|| {
+            github
+                .get_latest_version(black_box("foo/monorepo"))
+                .unwrap()
+        });
+    });
+    fs::remove_file(&path).unwrap();
+}
+
+criterion_group!(benches, latest_version);
+criterion_main!(benches);
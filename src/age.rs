@@ -0,0 +1,207 @@
+//! How long ago releases were published.
+
+use crate::GitHubReleaseItem;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Whether the year has a February 29th.
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// The number of days in the month of the year.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The number of days from the Unix epoch to the date.
+fn days_from_epoch(year: i64, month: i64, day: i64) -> i64 {
+    // shift the year to start in March, so the leap day is the last day of it
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Read the digits of the text as a number, if it's all digits.
+fn number(text: &str) -> Option<i64> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// Split the string in two at byte `mid`, if that's on a character boundary.
+fn split_at(s: &str, mid: usize) -> Option<(&str, &str)> {
+    Some((s.get(..mid)?, s.get(mid..)?))
+}
+
+/// Read an RFC 3339 timestamp, like `"2024-02-29T23:30:00+05:30"`, as the
+/// number of seconds since the Unix epoch.
+///
+/// Fractions of a second are ignored. Returns `None` for anything else,
+/// including dates that don't exist.
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    let timestamp = timestamp.trim();
+    let (date, rest) = split_at(timestamp, 10)?;
+    let rest = rest.strip_prefix(['T', 't', ' '])?;
+    let (time, offset) = split_at(rest, 8)?;
+
+    let mut date_parts = date.split('-');
+    let year = number(date_parts.next().filter(|part| part.len() == 4)?)?;
+    let month = number(date_parts.next().filter(|part| part.len() == 2)?)?;
+    let day = number(date_parts.next().filter(|part| part.len() == 2)?)?;
+    let mut time_parts = time.split(':');
+    let hour = number(time_parts.next().filter(|part| part.len() == 2)?)?;
+    let minute = number(time_parts.next().filter(|part| part.len() == 2)?)?;
+    let second = number(time_parts.next().filter(|part| part.len() == 2)?)?;
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        // a leap second counts as the last second of the minute
+        || second > 60
+    {
+        return None;
+    }
+
+    let offset = match offset.strip_prefix('.') {
+        Some(fraction) => {
+            let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 {
+                return None;
+            }
+            &fraction[digits..]
+        }
+        None => offset,
+    };
+    let offset_seconds = match offset {
+        "Z" | "z" => 0,
+        _ => {
+            let (sign, offset) = match split_at(offset, 1)? {
+                ("+", offset) => (1, offset),
+                ("-", offset) => (-1, offset),
+                _ => return None,
+            };
+            let (hours, minutes) = offset.split_once(':')?;
+            let hours = number(hours).filter(|_| hours.len() == 2)?;
+            let minutes = number(minutes).filter(|_| minutes.len() == 2)?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+
+    let seconds =
+        days_from_epoch(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second.min(59);
+    Some(seconds - offset_seconds)
+}
+
+/// The time of the timestamp from [`parse_timestamp`].
+fn system_time(seconds: i64) -> Option<SystemTime> {
+    let since = Duration::from_secs(seconds.unsigned_abs());
+    if seconds >= 0 {
+        UNIX_EPOCH.checked_add(since)
+    } else {
+        UNIX_EPOCH.checked_sub(since)
+    }
+}
+
+impl GitHubReleaseItem {
+    /// Get how long before `now` the release was published.
+    ///
+    /// The `published_at` timestamp is read as RFC 3339, with any UTC offset
+    /// applied, so the age is the same whatever the local time zone. A release
+    /// published after `now`, like when clocks disagree, has an age of zero.
+    /// Returns `None` for releases that haven't been published, like drafts,
+    /// or if the timestamp can't be read.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// use std::time::SystemTime;
+    /// let github = GitHub::new().unwrap();
+    /// let release = &github.query("celeo/github_release_check").unwrap()[0];
+    /// if let Some(age) = release.age(SystemTime::now()) {
+    ///     println!("released {} days ago", age.as_secs() / 86_400);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn age(&self, now: SystemTime) -> Option<Duration> {
+        let published = system_time(parse_timestamp(&self.published_at)?)?;
+        Some(now.duration_since(published).unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_timestamp;
+    use crate::{tests::release_json, GitHubReleaseItem};
+    use serde_json::json;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn release(published_at: Option<&str>) -> GitHubReleaseItem {
+        serde_json::from_value(release_json(
+            "v1.0.0",
+            json!({ "draft": published_at.is_none(), "published_at": published_at }),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let cases: &[(&str, Option<i64>)] = &[
+            ("1970-01-01T00:00:00Z", Some(0)),
+            ("2024-02-29T12:00:00Z", Some(1_709_208_000)),
+            ("2024-02-29T23:30:00+05:30", Some(1_709_229_600)),
+            ("2024-02-28T18:00:00-06:00", Some(1_709_164_800)),
+            ("2024-02-29t12:00:00.123456z", Some(1_709_208_000)),
+            ("2000-02-29 00:00:00Z", Some(951_782_400)),
+            ("1969-12-31T23:59:59Z", Some(-1)),
+            ("2016-12-31T23:59:60Z", Some(1_483_228_799)),
+            ("2023-02-29T12:00:00Z", None),
+            ("1900-02-29T12:00:00Z", None),
+            ("2024-04-31T12:00:00Z", None),
+            ("2024-02-29T24:00:00Z", None),
+            ("2024-02-29T12:00:00", None),
+            ("2024-02-29T12:00:00+0530", None),
+            ("2024-02-29T12:00:00.Z", None),
+            ("2024-2-29T12:00:00Z", None),
+            ("", None),
+        ];
+        for (timestamp, expected) in cases {
+            assert_eq!(parse_timestamp(timestamp), *expected, "{timestamp:?}");
+        }
+    }
+
+    #[test]
+    fn test_age() {
+        // 2024-03-01T00:00:00Z
+        let now = UNIX_EPOCH + Duration::from_secs(474_792 * 3600);
+        let hours = |hours: u64| Some(Duration::from_secs(hours * 3600));
+        assert_eq!(release(Some("2024-02-29T00:00:00Z")).age(now), hours(24));
+        // the same instant, written in two other time zones
+        assert_eq!(
+            release(Some("2024-02-29T05:30:00+05:30")).age(now),
+            hours(24)
+        );
+        assert_eq!(
+            release(Some("2024-02-28T19:00:00-05:00")).age(now),
+            hours(24)
+        );
+        assert_eq!(
+            release(Some("2024-03-01T02:00:00Z")).age(now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(release(None).age(now), None);
+        assert_eq!(release(Some("last tuesday")).age(now), None);
+    }
+}
@@ -0,0 +1,408 @@
+//! Keeping downloaded release assets on disk, so that they're only downloaded once.
+
+use crate::{redact_url, Call, GitHub, GitHubAssetItem, LookupError, RepositorySpec, Result};
+use log::debug;
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::SystemTime,
+};
+
+/// Tells apart the partial downloads of the same asset in one process.
+static NEXT_DOWNLOAD: AtomicUsize = AtomicUsize::new(0);
+
+/// Replace anything but letters, digits, `.`, `-`, and `_`, so that the text
+/// can be part of a file name.
+fn file_name_part(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            ':' => '-',
+            _ => '_',
+        })
+        .collect()
+}
+
+/// The file name of the asset in the cache.
+///
+/// Assets with a digest are kept by the digest, so the same file is only kept
+/// once. Others are kept by id and `updated_at`, which changes when the file is
+/// replaced.
+fn entry_name(asset: &GitHubAssetItem) -> String {
+    let key = match asset.digest.as_deref().filter(|digest| !digest.is_empty()) {
+        Some(digest) => digest.to_owned(),
+        None => format!("{}-{}", asset.id, asset.updated_at),
+    };
+    format!("{}-{}", file_name_part(&key), file_name_part(&asset.name))
+}
+
+/// The error for the path in the cache.
+fn cache_error(path: &Path) -> impl Fn(io::Error) -> LookupError + '_ {
+    move |source| LookupError::AssetCache {
+        path: path.display().to_string(),
+        source,
+    }
+}
+
+/// Mark the cached file as just used, for [`prune_asset_cache`].
+fn touch(path: &Path) -> io::Result<()> {
+    File::options()
+        .append(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+/// Remove the least recently used assets from the cache until it takes up at
+/// most `max_bytes`, returning how many bytes were removed.
+///
+/// An asset counts as used when it's downloaded into the cache, or returned
+/// from it by [`GitHub::download_asset_cached`]. Downloads still in progress
+/// are left alone, and a cache directory that doesn't exist is empty.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::prune_asset_cache;
+/// let removed = prune_asset_cache("/var/cache/tool-assets", 1 << 30).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// This function fails with [`LookupError::AssetCache`] if the cache directory
+/// can't be read or an asset can't be removed.
+pub fn prune_asset_cache(cache_dir: impl AsRef<Path>, max_bytes: u64) -> Result<u64> {
+    let cache_dir = cache_dir.as_ref();
+    let entries = match fs::read_dir(cache_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(cache_error(cache_dir)(e)),
+    };
+    let mut cached = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(cache_error(cache_dir))?;
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let metadata = entry.metadata().map_err(cache_error(&path))?;
+        if metadata.is_file() {
+            let used = metadata.modified().map_err(cache_error(&path))?;
+            cached.push((used, path, metadata.len()));
+        }
+    }
+    cached.sort();
+
+    let mut total: u64 = cached.iter().map(|(_, _, size)| size).sum();
+    let mut removed = 0;
+    for (_, path, size) in cached {
+        if total <= max_bytes {
+            break;
+        }
+        debug!("Removing {} from the asset cache", path.display());
+        fs::remove_file(&path).map_err(cache_error(&path))?;
+        total -= size;
+        removed += size;
+    }
+    Ok(removed)
+}
+
+impl GitHub {
+    /// Get an asset of a release in the repository, downloading it into
+    /// `cache_dir` unless it's already there, and return the path of the file.
+    ///
+    /// The asset is looked up first, so that a file that was replaced on GitHub
+    /// is downloaded again; see [`GitHubAssetItem::updated_at`]. A cached file
+    /// that isn't the size GitHub lists is downloaded again too, but the digest
+    /// isn't checked. Downloads go to a temporary file in `cache_dir` that is
+    /// renamed once complete, so other processes sharing the cache never see a
+    /// partial file.
+    ///
+    /// The cache isn't pruned here; see [`prune_asset_cache`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{AssetPattern, GitHub};
+    /// let github = GitHub::new().unwrap();
+    /// let repository = "celeo/github_release_check";
+    /// let (_, asset) = github
+    ///     .get_latest_asset(repository, &AssetPattern::glob("*.tar.gz"))
+    ///     .unwrap();
+    /// let path = github
+    ///     .download_asset_cached(repository, asset.id, "/var/cache/tool-assets")
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::AssetNotFound`] if the repository
+    /// has no such asset, [`LookupError::AssetSizeMismatch`] if the download is
+    /// cut short, [`LookupError::AssetCache`] if the cache can't be read or
+    /// written, or for any of the reasons in `query`.
+    pub fn download_asset_cached<R>(
+        &self,
+        repository: R,
+        asset_id: usize,
+        cache_dir: impl AsRef<Path>,
+    ) -> Result<PathBuf>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let cache_dir = cache_dir.as_ref();
+        let url = self.spec_url(
+            &repository.try_into()?,
+            &format!("releases/assets/{asset_id}"),
+        );
+        let asset: GitHubAssetItem = match self.get(&url, &[], &mut Call::default()) {
+            Ok(response) => self.read_json(response)?,
+            Err(LookupError::RepositoryNotFound) => return Err(LookupError::AssetNotFound),
+            Err(e) => return Err(e),
+        };
+
+        let path = cache_dir.join(entry_name(&asset));
+        match fs::metadata(&path) {
+            Ok(metadata) if metadata.len() == asset.size => {
+                debug!("Using {} from the asset cache", path.display());
+                touch(&path).map_err(cache_error(&path))?;
+                return Ok(path);
+            }
+            Ok(metadata) => debug!(
+                "Cached {} is {} bytes instead of {}, downloading it again",
+                path.display(),
+                metadata.len(),
+                asset.size
+            ),
+            Err(_) => debug!("{} isn't in the asset cache", path.display()),
+        }
+
+        fs::create_dir_all(cache_dir).map_err(cache_error(cache_dir))?;
+        let temp = cache_dir.join(format!(
+            ".{}.{}.{}.part",
+            entry_name(&asset),
+            process::id(),
+            NEXT_DOWNLOAD.fetch_add(1, Ordering::SeqCst)
+        ));
+        let downloaded = self.download_to(&url, &temp).and_then(|received| {
+            if received == asset.size {
+                Ok(received)
+            } else {
+                Err(LookupError::AssetSizeMismatch {
+                    expected: asset.size,
+                    received,
+                })
+            }
+        });
+        let received = match downloaded {
+            Ok(received) => received,
+            Err(e) => {
+                let _removed = fs::remove_file(&temp);
+                return Err(e);
+            }
+        };
+        fs::rename(&temp, &path).map_err(cache_error(&path))?;
+        debug!("Downloaded {received} bytes into {}", path.display());
+        Ok(path)
+    }
+
+    /// Download the asset at the API URL into the file, returning how many
+    /// bytes were written.
+    fn download_to(&self, url: &str, path: &Path) -> Result<u64> {
+        debug!("Downloading asset from {}", redact_url(url));
+        let mut headers = HeaderMap::new();
+        let _prev = headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("application/octet-stream"),
+        );
+        let mut response = self.send(
+            &reqwest::Method::GET,
+            url,
+            &[],
+            None::<&()>,
+            &headers,
+            &mut Call::default(),
+        )?;
+        let mut file = File::create(path).map_err(cache_error(path))?;
+        let received = io::copy(&mut response, &mut file).map_err(cache_error(path))?;
+        file.sync_all().map_err(cache_error(path))?;
+        Ok(received)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::prune_asset_cache;
+    use crate::{tests::mock_github, LookupError};
+    use mockito::{mock, Matcher};
+    use std::{
+        fs::{self, File},
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "github_release_check_{}_asset_cache_{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn mock_asset(path: &str, size: u64, updated_at: &str, digest: &str) -> mockito::Mock {
+        mock("GET", path)
+            .match_header("accept", Matcher::Regex("json".into()))
+            .with_body(format!(
+                r#"{{ "url": "", "browser_download_url": "", "id": 7, "name": "tool.tar.gz", "label": null, "content_type": "application/gzip", "size": {size}, "download_count": 0, "created_at": "", "updated_at": "{updated_at}", "digest": {digest} }}"#
+            ))
+            .create()
+    }
+
+    fn mock_download(path: &str, body: &str) -> mockito::Mock {
+        mock("GET", path)
+            .match_header("accept", "application/octet-stream")
+            .with_body(body)
+            .create()
+    }
+
+    /// The names of the files in the directory, in order.
+    fn files(dir: &PathBuf) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_download_miss_then_hit() {
+        let path = "/repos/foo/cache-hit/releases/assets/7";
+        let _asset = mock_asset(path, 5, "2024-01-01T00:00:00Z", "null");
+        let download = mock_download(path, "hello").expect(2);
+        let dir = cache_dir("hit");
+        let github = mock_github();
+
+        let cached = github
+            .download_asset_cached("foo/cache-hit", 7, &dir)
+            .unwrap();
+        assert_eq!(cached, dir.join("7-2024-01-01T00-00-00Z-tool.tar.gz"));
+        assert_eq!(fs::read_to_string(&cached).unwrap(), "hello");
+        assert_eq!(
+            github
+                .download_asset_cached("foo/cache-hit", 7, &dir)
+                .unwrap(),
+            cached
+        );
+
+        // a cached file of the wrong size is downloaded again
+        fs::write(&cached, "hel").unwrap();
+        let again = github
+            .download_asset_cached("foo/cache-hit", 7, &dir)
+            .unwrap();
+        assert_eq!(fs::read_to_string(again).unwrap(), "hello");
+        assert_eq!(files(&dir), vec!["7-2024-01-01T00-00-00Z-tool.tar.gz"]);
+        download.assert();
+    }
+
+    #[test]
+    fn test_download_stale() {
+        let path = "/repos/foo/cache-stale/releases/assets/7";
+        let dir = cache_dir("stale");
+        let github = mock_github();
+        let asset = mock_asset(path, 3, "2024-01-01T00:00:00Z", "null");
+        let download = mock_download(path, "old");
+        let old = github
+            .download_asset_cached("foo/cache-stale", 7, &dir)
+            .unwrap();
+        drop((asset, download));
+
+        // the file was replaced on GitHub
+        let _asset = mock_asset(path, 3, "2024-02-01T00:00:00Z", "null");
+        let download = mock_download(path, "new").expect(1);
+        let new = github
+            .download_asset_cached("foo/cache-stale", 7, &dir)
+            .unwrap();
+        assert_ne!(old, new);
+        assert_eq!(fs::read_to_string(&old).unwrap(), "old");
+        assert_eq!(fs::read_to_string(&new).unwrap(), "new");
+        download.assert();
+    }
+
+    #[test]
+    fn test_download_by_digest() {
+        let path = "/repos/foo/cache-digest/releases/assets/7";
+        let _asset = mock_asset(path, 4, "", r#""sha256:abc123""#);
+        let _download = mock_download(path, "data");
+        let dir = cache_dir("digest");
+        let cached = mock_github()
+            .download_asset_cached("foo/cache-digest", 7, &dir)
+            .unwrap();
+        assert_eq!(cached, dir.join("sha256-abc123-tool.tar.gz"));
+    }
+
+    #[test]
+    fn test_download_cut_short() {
+        let path = "/repos/foo/cache-short/releases/assets/7";
+        let _asset = mock_asset(path, 10, "", "null");
+        let _download = mock_download(path, "part");
+        let dir = cache_dir("short");
+        let error = mock_github()
+            .download_asset_cached("foo/cache-short", 7, &dir)
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                LookupError::AssetSizeMismatch {
+                    expected: 10,
+                    received: 4
+                }
+            ),
+            "{error:?}"
+        );
+        // the partial download isn't left behind
+        assert!(files(&dir).is_empty());
+    }
+
+    #[test]
+    fn test_download_missing_asset() {
+        let _m = mock("GET", "/repos/foo/cache-missing/releases/assets/7")
+            .with_status(404)
+            .create();
+        let dir = cache_dir("missing");
+        assert!(matches!(
+            mock_github().download_asset_cached("foo/cache-missing", 7, &dir),
+            Err(LookupError::AssetNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = cache_dir("prune");
+        fs::create_dir_all(&dir).unwrap();
+        let now = SystemTime::now();
+        for (name, age) in [("oldest", 30), ("newest", 10), ("older", 20)] {
+            let path = dir.join(name);
+            fs::write(&path, "12345").unwrap();
+            File::options()
+                .append(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(now - Duration::from_secs(age))
+                .unwrap();
+        }
+        fs::write(dir.join(".in-progress.part"), "12345").unwrap();
+
+        assert_eq!(prune_asset_cache(&dir, 15).unwrap(), 0);
+        assert_eq!(prune_asset_cache(&dir, 14).unwrap(), 5);
+        assert_eq!(files(&dir), vec![".in-progress.part", "newest", "older"]);
+        assert_eq!(prune_asset_cache(&dir, 0).unwrap(), 10);
+        assert_eq!(files(&dir), vec![".in-progress.part"]);
+
+        assert_eq!(prune_asset_cache(cache_dir("prune-missing"), 0).unwrap(), 0);
+    }
+}
@@ -0,0 +1,643 @@
+//! Picking a file out of the latest release.
+
+use crate::{
+    encode_segment, versions, Call, GitHub, GitHubAssetItem, GitHubReleaseItem, Host, LookupError,
+    RepositorySpec, Result,
+};
+use log::debug;
+use regex::Regex;
+use reqwest::{header::HeaderMap, Url};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Which asset names [`GitHub::get_latest_asset`] looks for.
+///
+/// When more than one asset matches, the first one GitHub lists is used,
+/// unless the pattern is made [strict](AssetPattern::strict).
+///
+/// # Example
+///
+/// ```rust
+/// use github_release_check::AssetPattern;
+///
+/// let installer = AssetPattern::glob("*.msi");
+/// let archive = AssetPattern::regex(r"^tool-.*-linux\.tar\.gz$").unwrap().strict();
+/// ```
+#[derive(Debug, Clone)]
+pub struct AssetPattern {
+    regex: Regex,
+    strict: bool,
+}
+
+impl AssetPattern {
+    /// Match the asset with exactly this name.
+    #[must_use]
+    pub fn exact(name: &str) -> Self {
+        Self::from_regex(&format!("^{}$", regex::escape(name)))
+    }
+
+    /// Match asset names with a glob, where `*` matches any run of characters
+    /// and `?` matches a single character.
+    #[must_use]
+    pub fn glob(pattern: &str) -> Self {
+        let translated: String = pattern
+            .chars()
+            .map(|c| match c {
+                '*' => ".*".to_owned(),
+                '?' => ".".to_owned(),
+                c => regex::escape(&c.to_string()),
+            })
+            .collect();
+        Self::from_regex(&format!("^{translated}$"))
+    }
+
+    /// Match asset names with a regular expression.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::InvalidAssetPattern`] if the
+    /// pattern isn't a valid regular expression.
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Ok(Self {
+            regex: Regex::new(pattern).map_err(LookupError::InvalidAssetPattern)?,
+            strict: false,
+        })
+    }
+
+    /// Fail with [`LookupError::AmbiguousAsset`] if more than one asset matches,
+    /// instead of using the first.
+    #[must_use]
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Build from a pattern that is known to be valid.
+    fn from_regex(pattern: &str) -> Self {
+        Self {
+            regex: Regex::new(pattern).expect("Escaped patterns are valid"),
+            strict: false,
+        }
+    }
+
+    /// Whether the asset's name matches.
+    fn is_match(&self, asset: &GitHubAssetItem) -> bool {
+        self.regex.is_match(&asset.name)
+    }
+
+    /// Pick the matching asset from the release.
+    fn select<'a>(&self, assets: &'a [GitHubAssetItem]) -> Result<&'a GitHubAssetItem> {
+        let matching: Vec<_> = assets.iter().filter(|asset| self.is_match(asset)).collect();
+        match matching.as_slice() {
+            [] => Err(LookupError::AssetNotFound),
+            [asset] => Ok(asset),
+            [first, ..] if !self.strict => Ok(first),
+            _ => Err(LookupError::AmbiguousAsset {
+                names: matching.iter().map(|asset| asset.name.clone()).collect(),
+            }),
+        }
+    }
+}
+
+/// Fill in the `{name}` variables of an asset name template.
+///
+/// Values in `vars` come first; `{tag}` and `{version}`, the tag without its
+/// version prefix, like `'v'` or one of `prefixes`, are filled in from the
+/// tag otherwise.
+fn fill_template(
+    template: &str,
+    tag: &str,
+    vars: &HashMap<&str, &str>,
+    prefixes: &[String],
+) -> Result<String> {
+    let invalid = || LookupError::InvalidAssetTemplate {
+        template: template.to_owned(),
+    };
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(invalid)? + start;
+        let name = &rest[start + 1..end];
+        let value = match (vars.get(name), name) {
+            (Some(value), _) => value,
+            (None, "tag") => tag,
+            (None, "version") => versions::normalize_tag(tag, prefixes),
+            (None, _) => {
+                return Err(LookupError::MissingTemplateVariable {
+                    name: name.to_owned(),
+                })
+            }
+        };
+        filled.push_str(value);
+        rest = &rest[end + 1..];
+    }
+    filled.push_str(rest);
+    if filled.is_empty() {
+        return Err(invalid());
+    }
+    Ok(filled)
+}
+
+/// How often the assets of a repository's releases were downloaded.
+///
+/// See [`GitHub::get_download_stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DownloadStats {
+    /// Downloads of each release's assets, keyed by tag name.
+    pub releases: BTreeMap<String, u64>,
+    /// Downloads of every release's assets.
+    pub total: u64,
+}
+
+impl GitHub {
+    /// Count the downloads of the assets of every release in the repository.
+    ///
+    /// Releases without assets are counted with 0 downloads.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let stats = github.get_download_stats("celeo/github_release_check").unwrap();
+    /// println!("{} downloads in total", stats.total);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn get_download_stats<R>(&self, repository: R) -> Result<DownloadStats>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        self.download_stats(&repository.try_into()?, None)
+    }
+
+    /// Count the downloads of the assets with names matching the pattern, like
+    /// leaving out checksum files.
+    ///
+    /// This is otherwise the same as [`GitHub::get_download_stats`]; whether the
+    /// pattern is [strict](AssetPattern::strict) doesn't matter here.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{AssetPattern, GitHub};
+    /// let github = GitHub::new().unwrap();
+    /// let stats = github
+    ///     .get_download_stats_matching("celeo/github_release_check", &AssetPattern::glob("*.tar.gz"))
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn get_download_stats_matching<R>(
+        &self,
+        repository: R,
+        pattern: &AssetPattern,
+    ) -> Result<DownloadStats>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        self.download_stats(&repository.try_into()?, Some(pattern))
+    }
+
+    /// Count downloads, optionally only of assets matching the pattern.
+    fn download_stats(
+        &self,
+        spec: &RepositorySpec,
+        pattern: Option<&AssetPattern>,
+    ) -> Result<DownloadStats> {
+        let mut stats = DownloadStats::default();
+        for release in self.query(spec)? {
+            let downloads: u64 = release
+                .assets
+                .iter()
+                .filter(|asset| pattern.map_or(true, |p| p.is_match(asset)))
+                .map(|asset| asset.download_count)
+                .sum();
+            stats.total += downloads;
+            *stats.releases.entry(release.tag_name).or_default() += downloads;
+        }
+        Ok(stats)
+    }
+
+    /// Get the latest release and its asset with a name matching the pattern.
+    ///
+    /// The release is selected the same way as in [`GitHub::get_latest_version`],
+    /// so [`GitHubBuilder::latest_strategy`](crate::GitHubBuilder::latest_strategy)
+    /// picks between the highest version and the release GitHub marks as latest.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{AssetPattern, GitHub};
+    /// let github = GitHub::new().unwrap();
+    /// let (release, asset) = github
+    ///     .get_latest_asset("celeo/github_release_check", &AssetPattern::glob("*.msi"))
+    ///     .unwrap();
+    /// println!("download {} from {}", release.tag_name, asset.browser_download_url);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::AssetNotFound`] if no asset matches,
+    /// [`LookupError::AmbiguousAsset`] if the pattern is strict and more than one
+    /// matches, or for any of the reasons in `get_latest_version`.
+    pub fn get_latest_asset<R>(
+        &self,
+        repository: R,
+        pattern: &AssetPattern,
+    ) -> Result<(GitHubReleaseItem, GitHubAssetItem)>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec = repository.try_into()?;
+        let release = match self.latest_release(&spec)? {
+            (_, Some(release)) => release,
+            (tagged, None) => {
+                debug!("Fetching release {} for its assets", tagged.tag);
+                self.release_by_tag(&spec, &tagged.tag)?
+                    .ok_or(LookupError::ReleaseNotFound)?
+            }
+        };
+        let asset = pattern.select(&release.assets)?.clone();
+        Ok((release, asset))
+    }
+
+    /// Build the download link of a release asset from a template of its name,
+    /// for releases whose assets are named predictably, without listing them.
+    ///
+    /// In the template, `{tag}` is the tag and `{version}` the tag without its
+    /// version prefix, read as in [`GitHub::get_latest_version`]; any other `{name}`, like `{target}`, comes from `vars`,
+    /// which can also replace the first two. The link is to the website, like
+    /// `https://github.com/{owner}/{repo}/releases/download/{tag}/{name}`.
+    ///
+    /// With `check_exists`, a `HEAD` request checks that the asset is there,
+    /// sent without the access token, as the link is to the website.
+    /// Otherwise no request is sent, and the link may not work.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// use std::collections::HashMap;
+    /// let github = GitHub::new().unwrap();
+    /// let vars = HashMap::from([("target", "x86_64-unknown-linux-gnu")]);
+    /// let url = github
+    ///     .asset_download_url(
+    ///         "celeo/github_release_check",
+    ///         "v0.2.1",
+    ///         "tool-{version}-{target}.tar.gz",
+    ///         &vars,
+    ///         true,
+    ///     )
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::MissingTemplateVariable`] if a
+    /// variable has no value, [`LookupError::InvalidAssetTemplate`] if the
+    /// template isn't valid, [`LookupError::InvalidRepository`] if the repository
+    /// is given by ID, as the website only knows it by name, or with
+    /// `check_exists`, [`LookupError::ReleaseNotFound`] if there's no such
+    /// asset, or for any of the reasons in `query`.
+    pub fn asset_download_url<R>(
+        &self,
+        repository: R,
+        tag: &str,
+        template: &str,
+        vars: &HashMap<&str, &str>,
+        check_exists: bool,
+    ) -> Result<String>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec = repository.try_into()?;
+        let name = fill_template(template, tag, vars, &self.settings.version_prefixes)?;
+        let url = self.spec_web_url(
+            &spec,
+            &format!(
+                "releases/download/{}/{}",
+                encode_segment(tag),
+                encode_segment(&name)
+            ),
+        )?;
+        let url = Url::parse(&url)
+            .map_err(|_| LookupError::InvalidAssetTemplate {
+                template: template.to_owned(),
+            })?
+            .to_string();
+        if check_exists {
+            debug!("Checking that asset {name} exists");
+            let result = self.send_to(
+                Host::Web,
+                &reqwest::Method::HEAD,
+                &url,
+                &[],
+                None::<&()>,
+                &HeaderMap::new(),
+                &mut Call::default(),
+            );
+            match result {
+                Ok(_) => {}
+                Err(LookupError::RepositoryNotFound) => return Err(LookupError::ReleaseNotFound),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fill_template, AssetPattern};
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHub, LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    fn asset(name: &str, size: u64) -> String {
+        counted_asset(name, size, 0)
+    }
+
+    fn counted_asset(name: &str, size: u64, downloads: u64) -> String {
+        format!(
+            r#"{{ "url": "", "browser_download_url": "https://example.com/{name}", "id": 1, "name": "{name}", "label": null, "content_type": "application/octet-stream", "size": {size}, "download_count": {downloads}, "created_at": "", "updated_at": "" }}"#
+        )
+    }
+
+    fn release(tag: &str, assets: &[String]) -> String {
+        let assets: Vec<Value> = assets
+            .iter()
+            .map(|a| serde_json::from_str(a).unwrap())
+            .collect();
+        release_json(tag, json!({ "assets": assets })).to_string()
+    }
+
+    fn mock_releases() -> mockito::Mock {
+        mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}]",
+                release("v1.0.0", &[asset("tool-1.0.0.msi", 10)]),
+                release(
+                    "v1.1.0",
+                    &[
+                        asset("tool-1.1.0.msi", 20),
+                        asset("tool-1.1.0-debug.msi", 40),
+                        asset("tool-1.1.0.tar.gz", 30),
+                    ]
+                ),
+            ))
+            .create()
+    }
+
+    #[test]
+    fn test_latest_asset_exact() {
+        let _m = mock_releases();
+        let (release, asset) = mock_github()
+            .get_latest_asset("foo/bar", &AssetPattern::exact("tool-1.1.0.tar.gz"))
+            .unwrap();
+        assert_eq!(release.tag_name, "v1.1.0");
+        assert_eq!(asset.size, 30);
+    }
+
+    #[test]
+    fn test_latest_asset_glob() {
+        let _m = mock_releases();
+        let (_, asset) = mock_github()
+            .get_latest_asset("foo/bar", &AssetPattern::glob("*.msi"))
+            .unwrap();
+        assert_eq!(asset.name, "tool-1.1.0.msi");
+        let (_, asset) = mock_github()
+            .get_latest_asset("foo/bar", &AssetPattern::glob("tool-?.?.?.tar.gz"))
+            .unwrap();
+        assert_eq!(asset.name, "tool-1.1.0.tar.gz");
+    }
+
+    #[test]
+    fn test_latest_asset_no_match() {
+        let _m = mock_releases();
+        let res = mock_github().get_latest_asset("foo/bar", &AssetPattern::exact("tool-1.0.0.msi"));
+        assert!(matches!(res, Err(LookupError::AssetNotFound)));
+    }
+
+    #[test]
+    fn test_latest_asset_ambiguous() {
+        let _m = mock_releases();
+        let res = mock_github()
+            .get_latest_asset("foo/bar", &AssetPattern::regex(r"\.msi$").unwrap().strict());
+        match res {
+            Err(LookupError::AmbiguousAsset { names }) => {
+                assert_eq!(names, vec!["tool-1.1.0.msi", "tool-1.1.0-debug.msi"]);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_download_stats() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}, {}]",
+                release(
+                    "v1.1.0",
+                    &[
+                        counted_asset("tool.tar.gz", 1, 120),
+                        counted_asset("tool.tar.gz.sha256", 1, 30),
+                    ]
+                ),
+                release("v1.0.1", &[]),
+                release(
+                    "v1.0.0",
+                    &[
+                        counted_asset("tool.tar.gz", 1, 400),
+                        counted_asset("tool.zip", 1, 50),
+                    ]
+                ),
+            ))
+            .create();
+
+        let stats = mock_github().get_download_stats("foo/bar").unwrap();
+        assert_eq!(stats.total, 600);
+        assert_eq!(stats.releases["v1.1.0"], 150);
+        assert_eq!(stats.releases["v1.0.1"], 0);
+        assert_eq!(stats.releases["v1.0.0"], 450);
+
+        let stats = mock_github()
+            .get_download_stats_matching("foo/bar", &AssetPattern::glob("*.sha256"))
+            .unwrap();
+        assert_eq!(stats.total, 30);
+        assert_eq!(
+            serde_json::to_value(&stats).unwrap(),
+            serde_json::json!({
+                "releases": { "v1.1.0": 30, "v1.0.1": 0, "v1.0.0": 0 },
+                "total": 30,
+            })
+        );
+    }
+
+    #[test]
+    fn test_fill_template() {
+        let vars = HashMap::from([("target", "x86_64-unknown-linux-gnu"), ("ext", "zip")]);
+        assert_eq!(
+            fill_template("tool-{version}-{target}.{ext}", "v1.2.0", &vars, &[]).unwrap(),
+            "tool-1.2.0-x86_64-unknown-linux-gnu.zip"
+        );
+        assert_eq!(
+            fill_template("tool-{tag}.tar.gz", "v1.2.0", &vars, &[]).unwrap(),
+            "tool-v1.2.0.tar.gz"
+        );
+        assert_eq!(
+            fill_template("tool-{version}", "1.2.0", &HashMap::new(), &[]).unwrap(),
+            "tool-1.2.0"
+        );
+        let custom = HashMap::from([("version", "1.2")]);
+        assert_eq!(
+            fill_template("tool-{version}", "v1.2.0", &custom, &[]).unwrap(),
+            "tool-1.2"
+        );
+        assert_eq!(
+            fill_template("checksums.txt", "v1", &vars, &[]).unwrap(),
+            "checksums.txt"
+        );
+    }
+
+    #[test]
+    fn test_fill_template_version_prefixes() {
+        let vars = HashMap::new();
+        assert_eq!(
+            fill_template("tool-{version}", "release/1.2.0", &vars, &[]).unwrap(),
+            "tool-1.2.0"
+        );
+        let prefixes = [String::from("tool-v")];
+        assert_eq!(
+            fill_template("tool-{version}.zip", "tool-v1.2.0", &vars, &prefixes).unwrap(),
+            "tool-1.2.0.zip"
+        );
+        assert_eq!(
+            fill_template("tool-{version}.zip", "tool-v1.2.0", &vars, &[]).unwrap(),
+            "tool-tool-v1.2.0.zip"
+        );
+    }
+
+    #[test]
+    fn test_fill_template_errors() {
+        let error = fill_template(
+            "tool-{version}-{arch}.tar.gz",
+            "v1.2.0",
+            &HashMap::new(),
+            &[],
+        )
+        .unwrap_err();
+        assert!(
+            matches!(&error, LookupError::MissingTemplateVariable { name } if name == "arch"),
+            "{error:?}"
+        );
+        assert_eq!(
+            error.to_string(),
+            "no value for {arch} in the asset name template"
+        );
+        for template in ["tool-{version", "", "{empty}"] {
+            let vars = HashMap::from([("empty", "")]);
+            assert!(
+                matches!(
+                    fill_template(template, "v1.2.0", &vars, &[]),
+                    Err(LookupError::InvalidAssetTemplate { .. })
+                ),
+                "{template}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_asset_download_url() {
+        let github = GitHub::new().unwrap();
+        let vars = HashMap::from([("target", "aarch64-apple-darwin")]);
+        assert_eq!(
+            github
+                .asset_download_url("foo/bar", "v1.2.0", "bar {version}-{target}.tar.gz", &vars, false)
+                .unwrap(),
+            "https://github.com/foo/bar/releases/download/v1.2.0/bar%201.2.0-aarch64-apple-darwin.tar.gz"
+        );
+        assert!(matches!(
+            github.asset_download_url("not a repository", "v1", "x", &vars, false),
+            Err(LookupError::InvalidRepository(_))
+        ));
+    }
+
+    #[test]
+    fn test_asset_download_url_head_check() {
+        let found = mock("HEAD", "/foo/bar/releases/download/v1.2.0/bar-1.2.0.tar.gz")
+            .with_status(200)
+            .expect(1)
+            .create();
+        let _missing = mock("HEAD", "/foo/bar/releases/download/v1.2.0/bar-1.2.0.zip")
+            .with_status(404)
+            .create();
+        let github = mock_github();
+        let vars = HashMap::from([("ext", "tar.gz")]);
+        assert_eq!(
+            github
+                .asset_download_url("foo/bar", "v1.2.0", "bar-{version}.{ext}", &vars, true)
+                .unwrap(),
+            format!(
+                "{}/foo/bar/releases/download/v1.2.0/bar-1.2.0.tar.gz",
+                mockito::server_url()
+            )
+        );
+        found.assert();
+
+        let vars = HashMap::from([("ext", "zip")]);
+        assert!(matches!(
+            github.asset_download_url("foo/bar", "v1.2.0", "bar-{version}.{ext}", &vars, true),
+            Err(LookupError::ReleaseNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_asset_download_url_head_check_leaves_out_token() {
+        let path = "/foo/head/releases/download/bar-v2.0.0/bar-2.0.0.zip";
+        let found = mock("HEAD", path)
+            .match_header("authorization", Matcher::Missing)
+            .match_header("accept", "*/*")
+            .with_status(200)
+            .expect(1)
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/api/", mockito::server_url()))
+            .web_root(&format!("{}/", mockito::server_url()))
+            .token("secret")
+            .version_prefixes(&["bar-v"])
+            .build()
+            .unwrap();
+        assert_eq!(
+            github
+                .asset_download_url(
+                    "foo/head",
+                    "bar-v2.0.0",
+                    "bar-{version}.zip",
+                    &HashMap::new(),
+                    true
+                )
+                .unwrap(),
+            format!(
+                "{}/foo/head/releases/download/bar-v2.0.0/bar-2.0.0.zip",
+                mockito::server_url()
+            )
+        );
+        found.assert();
+    }
+}
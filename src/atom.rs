@@ -0,0 +1,243 @@
+//! Release versions from a repository's Atom feed.
+
+use crate::{redact_url, Call, GitHub, Host, LookupError, RepositorySpec, Result};
+use log::debug;
+use percent_encoding::percent_decode_str;
+use quick_xml::{events::Event, Reader};
+use reqwest::header::{self, HeaderMap, HeaderValue};
+
+impl GitHub {
+    /// Get the tag names of the most recent releases from the repository's Atom feed.
+    ///
+    /// The feed at `https://github.com/{owner}/{repo}/releases.atom` is served by the
+    /// website rather than the REST API, and so isn't subject to the API rate limit.
+    /// The web root can be changed with
+    /// [`GitHubBuilder::web_root`](crate::GitHubBuilder::web_root). The access
+    /// token isn't sent there, but the request budgets and circuit breaker apply.
+    ///
+    /// Note that the feed only contains the **10 most recent** releases, so this function
+    /// can't see older releases, and so can't be used to get every version of a repository.
+    /// It also isn't available for private repositories.
+    ///
+    /// Only available with the `atom` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let versions_result = github.get_versions_from_feed("celeo/github_release_check");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the HTTP request cannot be sent, the server returns
+    /// a status code indicating something other than a success, of if the returned
+    /// data is not a valid Atom feed. As the website only knows repositories by
+    /// name, a repository given by ID fails with [`LookupError::InvalidRepository`].
+    pub fn get_versions_from_feed<R>(&self, repository: R) -> Result<Vec<String>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let url = self.spec_web_url(&repository.try_into()?, "releases.atom")?;
+        debug!("Querying release feed at {}", redact_url(&url));
+        let mut headers = HeaderMap::new();
+        let _prev = headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("application/atom+xml"),
+        );
+        let response = self.send_to(
+            Host::Web,
+            &reqwest::Method::GET,
+            &url,
+            &[],
+            None::<&()>,
+            &headers,
+            &mut Call::default(),
+        )?;
+        parse_feed(&self.read_text(response)?)
+    }
+}
+
+/// Extract the tag names of the entries in the Atom feed.
+///
+/// The tag is taken from the entry's `/releases/tag/` link, decoding any
+/// percent-encoded characters, falling back to the final segment of the
+/// entry's ID.
+fn parse_feed(xml: &str) -> Result<Vec<String>> {
+    const TAG_PATH: &str = "/releases/tag/";
+    let invalid = |e: &dyn std::fmt::Display| LookupError::InvalidFeed(e.to_string());
+    let mut reader = Reader::from_str(xml);
+    let mut tags = Vec::new();
+    let mut in_entry = false;
+    let mut in_id = false;
+    let mut link_tag: Option<String> = None;
+    let mut id_tag: Option<String> = None;
+
+    loop {
+        match reader.read_event().map_err(|e| invalid(&e))? {
+            Event::Start(e) if e.name().as_ref() == b"entry" => {
+                in_entry = true;
+                link_tag = None;
+                id_tag = None;
+            }
+            Event::End(e) if e.name().as_ref() == b"entry" => {
+                in_entry = false;
+                if let Some(tag) = link_tag.take().or_else(|| id_tag.take()) {
+                    tags.push(tag);
+                }
+            }
+            Event::Start(e) if in_entry && e.name().as_ref() == b"id" => in_id = true,
+            Event::End(e) if e.name().as_ref() == b"id" => in_id = false,
+            Event::Text(t) if in_id => {
+                let id = t.xml10_content().map_err(|e| invalid(&e))?;
+                id_tag = id.rsplit('/').next().map(str::to_owned);
+            }
+            Event::Start(e) | Event::Empty(e) if in_entry && e.name().as_ref() == b"link" => {
+                if let Some(href) = e.try_get_attribute("href").map_err(|e| invalid(&e))? {
+                    let href = href.unescape_value().map_err(|e| invalid(&e))?;
+                    if let Some((_, tag)) = href.split_once(TAG_PATH) {
+                        link_tag = Some(percent_decode_str(tag).decode_utf8_lossy().into_owned());
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_feed;
+    use crate::{GitHub, LookupError};
+    use mockito::{mock, Matcher};
+
+    fn entry(tag: &str) -> String {
+        format!(
+            r#"<entry>
+    <id>tag:github.com,2008:Repository/1/{tag}</id>
+    <updated>2022-06-01T12:00:00Z</updated>
+    <link rel="alternate" type="text/html" href="https://github.com/foo/bar/releases/tag/{tag}"/>
+    <title>{tag}</title>
+    <content type="html">&lt;p&gt;notes&lt;/p&gt;</content>
+    <author><name>foo</name></author>
+  </entry>"#
+        )
+    }
+
+    fn feed(tags: &[&str]) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="en-US">
+  <id>tag:github.com,2008:https://github.com/foo/bar/releases</id>
+  <link type="text/html" rel="alternate" href="https://github.com/foo/bar/releases"/>
+  <title>Release notes from bar</title>
+  {}
+</feed>"#,
+            tags.iter().map(|t| entry(t)).collect::<String>()
+        )
+    }
+
+    fn github(feed_fallback: bool) -> GitHub {
+        GitHub::builder()
+            .api_root(&format!("{}/api/", mockito::server_url()))
+            .web_root(&format!("{}/", mockito::server_url()))
+            .feed_fallback(feed_fallback)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_feed() {
+        let tags = parse_feed(&feed(&["v1.2.0", "v1.1.0"])).unwrap();
+        assert_eq!(tags, vec!["v1.2.0", "v1.1.0"]);
+    }
+
+    #[test]
+    fn test_parse_feed_id_fallback() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry><id>tag:github.com,2008:Repository/1/v3.0.0</id></entry></feed>"#;
+        assert_eq!(parse_feed(xml).unwrap(), vec!["v3.0.0"]);
+    }
+
+    #[test]
+    fn test_parse_feed_invalid() {
+        let res = parse_feed("<feed><entry></feed>");
+        assert!(matches!(res, Err(LookupError::InvalidFeed(_))));
+    }
+
+    #[test]
+    fn test_parse_feed_decodes_link_tags() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry><id>tag:github.com,2008:Repository/1/release/2.0</id><link href="https://github.com/foo/bar/releases/tag/release%2F2.0%2Bbuild"/></entry></feed>"#;
+        assert_eq!(parse_feed(xml).unwrap(), vec!["release/2.0+build"]);
+    }
+
+    #[test]
+    fn test_feed_request_leaves_out_token() {
+        let m = mock("GET", "/foo/feed-token/releases.atom")
+            .match_header("authorization", Matcher::Missing)
+            .match_header("accept", "application/atom+xml")
+            .with_body(feed(&["v1.0.0"]))
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/api/", mockito::server_url()))
+            .web_root(&format!("{}/", mockito::server_url()))
+            .token("secret")
+            .build()
+            .unwrap();
+        let versions = github.get_versions_from_feed("foo/feed-token").unwrap();
+        assert_eq!(versions, vec!["v1.0.0"]);
+        m.assert();
+    }
+
+    #[test]
+    fn test_feed_request_counts_against_budget() {
+        let m = mock("GET", "/foo/feed-budget/releases.atom")
+            .with_body(feed(&["v1.0.0"]))
+            .expect(0)
+            .create();
+        let github = GitHub::builder()
+            .web_root(&format!("{}/", mockito::server_url()))
+            .max_requests_per_call(0)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            github.get_versions_from_feed("foo/feed-budget"),
+            Err(LookupError::BudgetExceeded { .. })
+        ));
+        m.assert();
+    }
+
+    #[test]
+    fn test_feed_is_limited_to_recent_releases() {
+        // mirrors GitHub, which only puts the 10 most recent releases in the feed
+        let tags: Vec<String> = (1..=10).rev().map(|i| format!("v1.{i}.0")).collect();
+        let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+        let _m = mock("GET", "/foo/bar/releases.atom")
+            .with_body(feed(&tags))
+            .create();
+        let versions = github(false).get_versions_from_feed("foo/bar").unwrap();
+        assert_eq!(versions.len(), 10);
+        assert!(!versions.contains(&String::from("v1.0.0")));
+    }
+
+    #[test]
+    fn test_latest_version_falls_back_on_rate_limit() {
+        let _api = mock("GET", "/api/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .create();
+        let _feed = mock("GET", "/foo/bar/releases.atom")
+            .with_body(feed(&["v2.0.0", "v1.0.0"]))
+            .create();
+        let version = github(true).get_latest_version("foo/bar").unwrap();
+        assert_eq!(version, semver::Version::new(2, 0, 0));
+        assert!(matches!(
+            github(false).get_latest_version("foo/bar"),
+            Err(LookupError::RateLimited { .. })
+        ));
+    }
+}
@@ -0,0 +1,266 @@
+//! Looking up many repositories at once.
+
+use crate::{sleep_chunked, GitHub, LookupError, Result};
+use log::debug;
+use semver::Version;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+pub(crate) const DEFAULT_CONCURRENCY: usize = 4;
+/// GitHub asks for at least a minute of waiting when no `Retry-After` is sent.
+const DEFAULT_SECONDARY_PAUSE: Duration = Duration::from_secs(60);
+const MAX_ATTEMPTS: usize = 3;
+
+/// Pacing shared between the workers of a single batch operation.
+///
+/// Once a secondary rate limit is hit, only one worker keeps going,
+/// and every worker waits for the pause GitHub asked for.
+struct Throttle {
+    concurrency: AtomicUsize,
+    paused_until: Mutex<Option<SystemTime>>,
+}
+
+impl Throttle {
+    fn new(concurrency: usize) -> Self {
+        Self {
+            concurrency: AtomicUsize::new(concurrency),
+            paused_until: Mutex::new(None),
+        }
+    }
+
+    /// Whether the worker with this index should keep taking work.
+    fn allows(&self, worker: usize) -> bool {
+        worker < self.concurrency.load(Ordering::SeqCst)
+    }
+
+    /// Block until any requested pause has passed.
+    fn wait(&self) {
+        let until = *self
+            .paused_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(remaining) = until.and_then(|u| u.duration_since(SystemTime::now()).ok()) {
+            sleep_chunked(remaining);
+        }
+    }
+
+    /// Drop to a single worker and pause for the duration.
+    fn back_off(&self, retry_after: Option<Duration>) {
+        let pause = retry_after.unwrap_or(DEFAULT_SECONDARY_PAUSE);
+        debug!("Secondary rate limit hit, pausing for {pause:?} with a single worker");
+        self.concurrency.store(1, Ordering::SeqCst);
+        let until = SystemTime::now() + pause;
+        let mut paused_until = self
+            .paused_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if paused_until.map_or(true, |current| current < until) {
+            *paused_until = Some(until);
+        }
+    }
+}
+
+/// Run the lookup for every repository on a pool of worker threads.
+///
+/// Results are returned in the same order as `repositories`. Lookups that hit
+/// the secondary rate limit are retried after backing off.
+pub(crate) fn run_batch<T, F>(
+    github: &GitHub,
+    repositories: &[&str],
+    lookup: F,
+) -> Vec<(String, Result<T>)>
+where
+    T: Send,
+    F: Fn(&str) -> Result<T> + Sync,
+{
+    let workers = github
+        .settings
+        .concurrency
+        .unwrap_or(DEFAULT_CONCURRENCY)
+        .min(repositories.len());
+    let throttle = Throttle::new(workers);
+    let queue: Mutex<VecDeque<(usize, &str, usize)>> = Mutex::new(
+        repositories
+            .iter()
+            .enumerate()
+            .map(|(index, repository)| (index, *repository, 1))
+            .collect(),
+    );
+    let results: Mutex<Vec<Option<Result<T>>>> =
+        Mutex::new((0..repositories.len()).map(|_| None).collect());
+    // lookups without a result yet, including those being retried
+    let outstanding = AtomicUsize::new(repositories.len());
+
+    thread::scope(|scope| {
+        for worker in 0..workers {
+            let (throttle, queue, results, lookup, outstanding) =
+                (&throttle, &queue, &results, &lookup, &outstanding);
+            let _handle = scope.spawn(move || {
+                while throttle.allows(worker) {
+                    let next = queue
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .pop_front();
+                    let Some((index, repository, attempt)) = next else {
+                        if outstanding.load(Ordering::SeqCst) == 0 {
+                            break;
+                        }
+                        // another worker may still queue a retry
+                        thread::sleep(Duration::from_millis(10));
+                        continue;
+                    };
+                    throttle.wait();
+                    match lookup(repository) {
+                        Err(LookupError::SecondaryRateLimited { retry_after, .. })
+                            if attempt < MAX_ATTEMPTS =>
+                        {
+                            throttle.back_off(retry_after);
+                            queue
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .push_front((index, repository, attempt + 1));
+                        }
+                        result => {
+                            results.lock().unwrap_or_else(PoisonError::into_inner)[index] =
+                                Some(result);
+                            let _remaining = outstanding.fetch_sub(1, Ordering::SeqCst);
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    repositories
+        .iter()
+        .zip(results.into_inner().unwrap_or_else(PoisonError::into_inner))
+        .map(|(repository, result)| {
+            (
+                (*repository).to_owned(),
+                result.expect("Every queued repository produces a result"),
+            )
+        })
+        .collect()
+}
+
+impl GitHub {
+    /// Get the latest release version of each of the repositories.
+    ///
+    /// Lookups run concurrently, up to the number set with
+    /// [`GitHubBuilder::concurrency`](crate::GitHubBuilder::concurrency). A failure for one
+    /// repository does not stop the others; each entry in the returned `Vec` pairs the
+    /// repository with its own `Result`, in the same order as `repositories`.
+    ///
+    /// If GitHub's secondary rate limit is hit, the remaining lookups continue one at
+    /// a time after pausing for as long as GitHub asked (or a minute, if it didn't say).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let results = github.get_latest_versions(&["celeo/github_release_check", "rust-lang/rust"]);
+    /// ```
+    #[must_use]
+    pub fn get_latest_versions(&self, repositories: &[&str]) -> Vec<(String, Result<Version>)> {
+        run_batch(self, repositories, |repository| {
+            self.get_latest_version(repository)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHub, LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+    use std::time::{Duration, Instant};
+
+    const SECONDARY_BODY: &str = r#"{"message": "You have exceeded a secondary rate limit. Please wait a few minutes before you try again.", "documentation_url": "https://docs.github.com/rest/overview/resources-in-the-rest-api#secondary-rate-limits"}"#;
+
+    #[test]
+    fn test_secondary_rate_limit_classified() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .with_header("retry-after", "30")
+            .with_body(SECONDARY_BODY)
+            .create();
+        let res = mock_github().query("foo/bar");
+        match res {
+            Err(LookupError::SecondaryRateLimited { retry_after, .. }) => {
+                assert_eq!(retry_after, Some(Duration::from_secs(30)));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_batch_results_in_order() {
+        let _a = mock("GET", "/repos/foo/a/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+            .create();
+        let _b = mock("GET", "/repos/foo/b/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        let _c = mock("GET", "/repos/foo/c/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v3.0.0", json!({}))]).to_string())
+            .create();
+        let results = mock_github().get_latest_versions(&["foo/a", "foo/b", "foo/c"]);
+        let names: Vec<_> = results.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["foo/a", "foo/b", "foo/c"]);
+        assert_eq!(
+            *results[0].1.as_ref().unwrap(),
+            semver::Version::new(1, 0, 0)
+        );
+        assert!(matches!(results[1].1, Err(LookupError::RepositoryNotFound)));
+        assert_eq!(
+            *results[2].1.as_ref().unwrap(),
+            semver::Version::new(3, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_batch_backs_off_on_secondary_rate_limit() {
+        let limited = mock("GET", "/repos/foo/a/releases")
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .with_header("retry-after", "1")
+            .with_body(SECONDARY_BODY)
+            .expect(1)
+            .create();
+        let a = mock("GET", "/repos/foo/a/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+            .expect(1)
+            .create();
+        let _b = mock("GET", "/repos/foo/b/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v2.0.0", json!({}))]).to_string())
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .concurrency(2)
+            .build()
+            .unwrap();
+        let start = Instant::now();
+        let results = github.get_latest_versions(&["foo/a", "foo/b"]);
+        assert!(start.elapsed() >= Duration::from_secs(1));
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        limited.assert();
+        a.assert();
+    }
+}
@@ -0,0 +1,332 @@
+//! Check the releases of a GitHub repository from the command line.
+//!
+//! ```text
+//! github-release-check --repo owner/name [--api-root URL] [--token-env VAR] [--json] <command>
+//! ```
+//!
+//! Commands are `latest`, `list`, `check --current <version>`, and `scan`, which
+//! takes `--repo` more than once. The exit codes are listed in the usage message.
+
+use github_release_check::{
+    GitHub, LookupError, ScanReport, TaggedVersion, UpdateStatus, DEFAULT_API_ROOT,
+};
+use semver::Version;
+use serde_json::{json, Value};
+use std::{env, process::ExitCode};
+
+const USAGE: &str = "\
+Usage: github-release-check --repo <owner/name> [options] <command>
+
+Commands:
+  latest                     Print the latest release version
+  list                       Print every release version, newest first
+  check --current <version>  Compare a version against the latest release
+  scan                       Print the latest release of every --repo
+
+Options:
+  --repo <owner/name>        The repository to look up; scan takes more than one
+  --api-root <url>           The API root, for GitHub enterprise
+  --token-env <var>          Read an access token from the environment variable
+  --output <format>          text, json, or versions-only for one tag per line
+  --json                     The same as --output json; errors are JSON too
+  -h, --help                 Print this message
+
+Exit codes:
+  0   Success, or up to date
+  1   An error, including a repository that failed in a scan
+  3   Rate limited by GitHub; try again later
+  10  check found a newer release";
+
+/// The exit code for being rate limited, so that throttling can be told apart
+/// from other failures.
+const RATE_LIMITED: u8 = 3;
+
+/// The exit code for `check` finding a newer release.
+const UPDATE_AVAILABLE: u8 = 10;
+
+/// What to look up.
+enum Command {
+    Latest,
+    List,
+    Check(Version),
+    Scan,
+}
+
+/// How to print the results.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Text,
+    Json,
+    /// One tag per line, for shell pipelines.
+    VersionsOnly,
+}
+
+/// The parsed command line.
+struct Args {
+    repos: Vec<String>,
+    api_root: String,
+    token_env: Option<String>,
+    output: Output,
+    command: Command,
+}
+
+/// Why a command failed.
+struct Failure {
+    message: String,
+    /// The [`LookupError::code`], if the failure was a lookup error.
+    code: Option<&'static str>,
+}
+
+impl Failure {
+    /// The exit code for the failure.
+    fn exit_code(&self) -> ExitCode {
+        if self.code.is_some_and(is_rate_limited) {
+            ExitCode::from(RATE_LIMITED)
+        } else {
+            ExitCode::FAILURE
+        }
+    }
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            code: None,
+        }
+    }
+}
+
+impl From<LookupError> for Failure {
+    fn from(error: LookupError) -> Self {
+        Self {
+            message: error.to_string(),
+            code: Some(error.code()),
+        }
+    }
+}
+
+/// Whether the error code is for either of GitHub's rate limits.
+fn is_rate_limited(code: &str) -> bool {
+    matches!(code, "rate_limited" | "secondary_rate_limited")
+}
+
+/// Parse the command line, or return the message to fail with.
+///
+/// `Ok(None)` means help was asked for.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut repos = Vec::new();
+    let mut api_root = DEFAULT_API_ROOT.to_owned();
+    let mut token_env = None;
+    let mut output = Output::Text;
+    let mut command = None;
+    let mut current = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} needs a value"));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--repo" => repos.push(value("--repo")?),
+            "--api-root" => api_root = value("--api-root")?,
+            "--token-env" => token_env = Some(value("--token-env")?),
+            "--current" => current = Some(value("--current")?),
+            "--json" => output = Output::Json,
+            "--output" => {
+                output = match value("--output")?.as_str() {
+                    "text" => Output::Text,
+                    "json" => Output::Json,
+                    "versions-only" => Output::VersionsOnly,
+                    other => return Err(format!("unknown output format \"{other}\"")),
+                }
+            }
+            "latest" | "list" | "check" | "scan" if command.is_none() => command = Some(arg),
+            _ => return Err(format!("unexpected argument \"{arg}\"")),
+        }
+    }
+    if repos.is_empty() {
+        return Err("--repo is required".to_owned());
+    }
+    let command = match (command.as_deref(), current) {
+        (Some("latest"), None) => Command::Latest,
+        (Some("list"), None) => Command::List,
+        (Some("scan"), None) => Command::Scan,
+        (Some("check"), Some(current)) => Command::Check(
+            Version::parse(current.trim_start_matches('v'))
+                .map_err(|e| format!("--current \"{current}\" isn't a version: {e}"))?,
+        ),
+        (Some("check"), None) => return Err("check needs --current".to_owned()),
+        (Some(_), _) => return Err("--current only goes with check".to_owned()),
+        (None, _) => return Err("a command is required".to_owned()),
+    };
+    if repos.len() > 1 && !matches!(command, Command::Scan) {
+        return Err("only scan takes more than one --repo".to_owned());
+    }
+    Ok(Some(Args {
+        repos,
+        api_root,
+        token_env,
+        output,
+        command,
+    }))
+}
+
+/// The struct as a JSON object, with the extra fields added to it.
+fn object_with(value: impl serde::Serialize, extra: Value) -> Value {
+    let mut value = serde_json::to_value(value).unwrap_or_default();
+    if let (Value::Object(fields), Value::Object(extra)) = (&mut value, extra) {
+        fields.extend(extra);
+    }
+    value
+}
+
+/// Print the scan, returning the exit code: a failure if any repository failed,
+/// and rate limited if every one that failed was rate limited.
+fn print_scan(report: &ScanReport, output: Output) -> ExitCode {
+    match output {
+        Output::Json => println!("{}", report.to_json_pretty()),
+        Output::VersionsOnly => {
+            for tag in report
+                .repositories
+                .values()
+                .filter_map(|r| r.latest_tag.as_ref())
+            {
+                println!("{tag}");
+            }
+        }
+        Output::Text => {
+            for (repository, scan) in &report.repositories {
+                match (&scan.latest_version, &scan.error) {
+                    (Some(version), _) => println!("{repository} {version}"),
+                    (None, error) => {
+                        println!("{repository} error: {}", error.as_deref().unwrap_or("-"));
+                    }
+                }
+            }
+        }
+    }
+    let failed: Vec<_> = report
+        .repositories
+        .values()
+        .filter(|scan| scan.error.is_some())
+        .collect();
+    if failed.is_empty() {
+        ExitCode::SUCCESS
+    } else if failed
+        .iter()
+        .all(|scan| scan.error_code.as_deref().is_some_and(is_rate_limited))
+    {
+        ExitCode::from(RATE_LIMITED)
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Print the latest release of the repository.
+fn print_latest(repository: &str, latest: &TaggedVersion, output: Output) {
+    match output {
+        Output::Json => println!(
+            "{}",
+            object_with(
+                latest,
+                json!({ "repository": repository, "latest": latest.version })
+            )
+        ),
+        Output::VersionsOnly => println!("{}", latest.tag),
+        Output::Text => println!("{}", latest.version),
+    }
+}
+
+/// Run the command, returning the exit code.
+fn run(args: &Args) -> Result<ExitCode, Failure> {
+    let token = match &args.token_env {
+        Some(var) => env::var(var).map_err(|_| format!("environment variable {var} isn't set"))?,
+        None => String::new(),
+    };
+    let github = GitHub::builder()
+        .api_root(&args.api_root)
+        .token(&token)
+        .build()?;
+    let repository = args.repos[0].as_str();
+    match &args.command {
+        Command::Latest => {
+            let latest = github.get_latest_tagged_version(repository)?;
+            print_latest(repository, &latest, args.output);
+        }
+        Command::List => {
+            let versions = github.get_all_versions(repository)?;
+            if args.output == Output::Json {
+                println!(
+                    "{}",
+                    json!({ "repository": repository, "versions": versions })
+                );
+            } else {
+                for version in versions {
+                    println!("{version}");
+                }
+            }
+        }
+        Command::Check(current) => {
+            let latest = github.get_latest_tagged_version(repository)?;
+            let status = if latest.version > *current {
+                UpdateStatus::Outdated {
+                    current: current.clone(),
+                    latest: latest.version.clone(),
+                }
+            } else {
+                UpdateStatus::UpToDate
+            };
+            let update_available = status != UpdateStatus::UpToDate;
+            match args.output {
+                Output::Json => println!(
+                    "{}",
+                    object_with(
+                        &status,
+                        json!({
+                            "repository": repository,
+                            "current": current,
+                            "latest": latest.version,
+                            "update_available": update_available,
+                        })
+                    )
+                ),
+                Output::VersionsOnly => println!("{}", latest.tag),
+                Output::Text if update_available => {
+                    println!("update available: {current} -> {}", latest.version);
+                }
+                Output::Text => println!("up to date: {current}"),
+            }
+            if update_available {
+                return Ok(ExitCode::from(UPDATE_AVAILABLE));
+            }
+        }
+        Command::Scan => {
+            let repos: Vec<&str> = args.repos.iter().map(String::as_str).collect();
+            return Ok(print_scan(&github.scan(&repos), args.output));
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    run(&args).unwrap_or_else(|failure| {
+        if args.output == Output::Json {
+            eprintln!(
+                "{}",
+                json!({ "error": failure.message, "code": failure.code })
+            );
+        } else {
+            eprintln!("error: {}", failure.message);
+        }
+        failure.exit_code()
+    })
+}
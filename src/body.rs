@@ -0,0 +1,369 @@
+//! Reading response bodies, within the size limit.
+
+use crate::{GitHub, LookupError, Result};
+use reqwest::{blocking::Response, header};
+use serde::de::DeserializeOwned;
+use std::io::{self, BufRead, BufReader, Read};
+
+/// The default for [`GitHubBuilder::max_response_bytes`](crate::GitHubBuilder::max_response_bytes).
+pub(crate) const DEFAULT_MAX_RESPONSE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// The `Accept-Encoding` for the compressions built in, if any are.
+///
+/// See [`GitHubBuilder::compression`](crate::GitHubBuilder::compression).
+pub(crate) fn accept_encoding() -> Option<&'static str> {
+    match (cfg!(feature = "gzip"), cfg!(feature = "brotli")) {
+        (true, true) => Some("gzip, br"),
+        (true, false) => Some("gzip"),
+        (false, true) => Some("br"),
+        (false, false) => None,
+    }
+}
+
+/// The `Content-Encoding` of the response, if it has one.
+pub(crate) fn content_encoding(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_ascii_lowercase())
+}
+
+/// Read the response body, undoing its compression if it's one the crate built in.
+///
+/// Other encodings are read as they are, which fails to parse rather than
+/// going unnoticed.
+pub(crate) fn decoded(response: Response) -> Box<dyn Read> {
+    match content_encoding(&response).as_deref() {
+        #[cfg(feature = "gzip")]
+        Some("gzip") => Box::new(flate2::read::GzDecoder::new(response)),
+        #[cfg(feature = "brotli")]
+        Some("br") => Box::new(brotli::Decompressor::new(response, 4096)),
+        _ => Box::new(response),
+    }
+}
+
+/// A reader that stops with an error once more than `remaining` bytes are read.
+struct LimitedReader<R> {
+    inner: R,
+    remaining: u64,
+    exceeded: bool,
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            // only an error if there's actually more to read
+            if self.inner.read(&mut [0])? == 0 {
+                return Ok(0);
+            }
+            self.exceeded = true;
+            return Err(io::Error::other("response body is too large"));
+        }
+        let max = buf
+            .len()
+            .min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        let read = self.inner.read(&mut buf[..max])?;
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+/// The first byte of the body that isn't whitespace, if the start of the body
+/// can be read and isn't only whitespace.
+fn first_byte<R: Read>(reader: &mut BufReader<R>) -> Option<u8> {
+    reader
+        .fill_buf()
+        .ok()
+        .and_then(|start| start.iter().copied().find(|b| !b.is_ascii_whitespace()))
+}
+
+/// The most of a body to put into an error.
+const SNIPPET_CHARS: usize = 200;
+
+/// The start of the body, for an error message.
+fn snippet(body: &str) -> String {
+    match body.char_indices().nth(SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.to_owned(),
+    }
+}
+
+impl GitHub {
+    /// The limit on the size of a response body.
+    fn max_response_bytes(&self) -> u64 {
+        self.settings
+            .max_response_bytes
+            .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES)
+    }
+
+    /// Start reading the response body, failing early if it says it's too large.
+    ///
+    /// The limit applies to the body after decompression.
+    fn limited_reader(
+        &self,
+        response: Response,
+    ) -> Result<BufReader<LimitedReader<Box<dyn Read>>>> {
+        let limit = self.max_response_bytes();
+        if response
+            .content_length()
+            .is_some_and(|length| length > limit)
+        {
+            return Err(LookupError::ResponseTooLarge {
+                limit,
+                url: response.url().to_string(),
+            });
+        }
+        Ok(BufReader::new(LimitedReader {
+            inner: decoded(response),
+            remaining: limit,
+            exceeded: false,
+        }))
+    }
+
+    /// Read a JSON response body as it comes in, rather than buffering it first.
+    ///
+    /// A body that starts like an HTML page is a [`LookupError::NotAnApiEndpoint`].
+    pub(crate) fn read_json<T: DeserializeOwned>(&self, response: Response) -> Result<T> {
+        self.read_json_counted(response, &mut 0)
+    }
+
+    /// Read a JSON response body, adding the number of bytes read to `bytes`.
+    pub(crate) fn read_json_counted<T: DeserializeOwned>(
+        &self,
+        response: Response,
+        bytes: &mut u64,
+    ) -> Result<T> {
+        let url = response.url().to_string();
+        let mut reader = self.limited_reader(response)?;
+        if first_byte(&mut reader) == Some(b'<') {
+            return Err(self.not_an_api_endpoint());
+        }
+        self.deserialize_from(reader, url, bytes)
+    }
+
+    /// Read a JSON response body that should be a page of items, adding the
+    /// number of bytes read to `bytes`.
+    ///
+    /// A `null` body is an empty page. Any other JSON that isn't an array is a
+    /// [`LookupError::UnexpectedBody`], rather than an error about the first
+    /// field that didn't match.
+    pub(crate) fn read_page_counted<T: DeserializeOwned>(
+        &self,
+        response: Response,
+        bytes: &mut u64,
+    ) -> Result<Vec<T>> {
+        let url = response.url().to_string();
+        let mut reader = self.limited_reader(response)?;
+        match first_byte(&mut reader) {
+            Some(b'<') => Err(self.not_an_api_endpoint()),
+            None | Some(b'[') => self.deserialize_from(reader, url, bytes),
+            Some(_) => match self.deserialize_from(reader, url, bytes)? {
+                serde_json::Value::Null => Ok(Vec::new()),
+                other => Err(LookupError::UnexpectedBody {
+                    body_snippet: snippet(&other.to_string()),
+                }),
+            },
+        }
+    }
+
+    /// Deserialize the rest of the body from the reader, adding the number of
+    /// bytes read to `bytes`.
+    fn deserialize_from<T: DeserializeOwned>(
+        &self,
+        mut reader: BufReader<LimitedReader<Box<dyn Read>>>,
+        url: String,
+        bytes: &mut u64,
+    ) -> Result<T> {
+        let limit = self.max_response_bytes();
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut reader);
+        let result =
+            T::deserialize(&mut deserializer).and_then(|value| deserializer.end().map(|()| value));
+        *bytes += limit - reader.get_ref().remaining;
+        result.map_err(|e| {
+            if reader.get_ref().exceeded {
+                LookupError::ResponseTooLarge { limit, url }
+            } else {
+                LookupError::InvalidResponse {
+                    line: e.line(),
+                    column: e.column(),
+                    message: e.to_string(),
+                }
+            }
+        })
+    }
+
+    /// Read a text response body.
+    pub(crate) fn read_text(&self, response: Response) -> Result<String> {
+        let url = response.url().to_string();
+        let mut reader = self.limited_reader(response)?;
+        let mut text = String::new();
+        match reader.read_to_string(&mut text) {
+            Ok(_) => Ok(text),
+            Err(_) if reader.get_ref().exceeded => Err(LookupError::ResponseTooLarge {
+                limit: self.max_response_bytes(),
+                url,
+            }),
+            Err(e) => Err(LookupError::InvalidResponse {
+                line: 0,
+                column: 0,
+                message: e.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::release_json, GitHub, LookupError};
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    fn github(limit: u64) -> GitHub {
+        GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .max_response_bytes(limit)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_page_too_large() {
+        let _m = mock("GET", "/repos/foo/huge/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+            .create();
+        match github(64).query("foo/huge") {
+            Err(LookupError::ResponseTooLarge { limit, url }) => {
+                assert_eq!(limit, 64);
+                assert!(url.contains("/repos/foo/huge/releases"));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert_eq!(github(4096).query("foo/huge").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_streamed_page_too_large() {
+        let _m = mock("GET", "/repos/foo/streamed/releases")
+            .match_query(Matcher::Any)
+            .with_body_from_fn(|w| {
+                w.write_all(b"[")?;
+                for _ in 0..100 {
+                    w.write_all(format!("{},", release_json("v1.0.0", json!({}))).as_bytes())?;
+                }
+                w.write_all(b"]")
+            })
+            .create();
+        assert!(matches!(
+            github(1024).query("foo/streamed"),
+            Err(LookupError::ResponseTooLarge { limit: 1024, .. })
+        ));
+    }
+
+    #[test]
+    fn test_non_array_pages() {
+        let cases = [
+            (
+                "object",
+                r#"{"message": "Moved Permanently"}"#,
+                Some(r#"{"message":"Moved Permanently"}"#),
+            ),
+            ("string", r#"  "maintenance""#, Some(r#""maintenance""#)),
+            ("null", "null", None),
+        ];
+        for (name, body, snippet) in cases {
+            let path = format!("/repos/foo/{name}-body/releases");
+            let _m = mock("GET", path.as_str())
+                .match_query(Matcher::Any)
+                .with_body(body)
+                .create();
+            let result = github(4096).query(format!("foo/{name}-body").as_str());
+            match (result, snippet) {
+                (Err(LookupError::UnexpectedBody { body_snippet }), Some(snippet)) => {
+                    assert_eq!(body_snippet, snippet);
+                }
+                (Ok(releases), None) => assert!(releases.is_empty()),
+                (other, _) => panic!("unexpected result for {name}: {other:?}"),
+            }
+        }
+
+        let long = format!(r#"{{"message": "{}"}}"#, "x".repeat(500));
+        let _m = mock("GET", "/repos/foo/long-body/releases")
+            .match_query(Matcher::Any)
+            .with_body(&long)
+            .create();
+        match github(4096).query("foo/long-body") {
+            Err(e @ LookupError::UnexpectedBody { .. }) => {
+                assert_eq!(e.code(), "unexpected_body");
+                assert!(e.to_string().starts_with("expected a JSON array"));
+                assert!(e.to_string().ends_with("x..."));
+                assert!(e.to_string().len() < 300);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_item_keeps_position() {
+        let _m = mock("GET", "/repos/foo/bad-item/releases")
+            .match_query(Matcher::Any)
+            .with_body(r#"[{"tag_name": 5}]"#)
+            .create();
+        assert!(matches!(
+            github(4096).query("foo/bad-item"),
+            Err(LookupError::InvalidResponse { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_accept_encoding_follows_toggle() {
+        let body = json!([release_json("v1.0.0", json!({}))]).to_string();
+        let off = mock("GET", "/repos/foo/uncompressed/releases")
+            .match_query(Matcher::Any)
+            .match_header("accept-encoding", Matcher::Missing)
+            .with_body(&body)
+            .expect(1)
+            .create();
+        let uncompressed = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .compression(false)
+            .build()
+            .unwrap();
+        assert_eq!(uncompressed.query("foo/uncompressed").unwrap().len(), 1);
+        off.assert();
+
+        let expected = match super::accept_encoding() {
+            Some(encoding) => Matcher::Exact(encoding.to_owned()),
+            None => Matcher::Missing,
+        };
+        let on = mock("GET", "/repos/foo/compressed/releases")
+            .match_query(Matcher::Any)
+            .match_header("accept-encoding", expected)
+            .with_body(&body)
+            .expect(1)
+            .create();
+        assert_eq!(github(4096).query("foo/compressed").unwrap().len(), 1);
+        on.assert();
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_response() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let body = json!([release_json("v1.0.0", json!({}))]).to_string();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        let _m = mock("GET", "/repos/foo/gzipped/releases")
+            .match_query(Matcher::Any)
+            .with_header("content-encoding", "gzip")
+            .with_body(encoder.finish().unwrap())
+            .create();
+        let (releases, stats) = github(4096).query_with_stats("foo/gzipped").unwrap();
+        assert_eq!(releases[0].tag_name, "v1.0.0");
+        assert_eq!(stats.content_encoding.as_deref(), Some("gzip"));
+        assert_eq!(stats.bytes, body.len() as u64);
+    }
+}
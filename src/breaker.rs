@@ -0,0 +1,201 @@
+//! Failing fast while the GitHub instance is having trouble.
+
+use crate::{LookupError, Result};
+use log::debug;
+use std::{
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime},
+};
+
+/// When the breaker opens, and for how long.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Config {
+    pub(crate) threshold: usize,
+    pub(crate) window: Duration,
+    pub(crate) cool_down: Duration,
+}
+
+#[derive(Debug)]
+enum State {
+    /// Requests go through; recent transient failures are tracked.
+    Closed { failures: Vec<SystemTime> },
+    /// Requests fail fast until the time passes.
+    Open { until: SystemTime },
+    /// A single probe request is in progress.
+    HalfOpen,
+}
+
+/// Circuit breaker shared between clones of a `GitHub` instance.
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    config: Config,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(config: Config) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State::Closed {
+                failures: Vec::new(),
+            }),
+        }
+    }
+
+    /// When the breaker opens, and for how long.
+    pub(crate) fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Check whether a request may be sent.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with `CircuitOpen` while the breaker is open,
+    /// or while another request is probing whether to close it.
+    pub(crate) fn before_request(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if SystemTime::now() >= until => {
+                debug!("Circuit breaker cool-down over, allowing a probe request");
+                *state = State::HalfOpen;
+                Ok(())
+            }
+            State::Open { until } => Err(LookupError::CircuitOpen { retry_at: until }),
+            State::HalfOpen => Err(LookupError::CircuitOpen {
+                retry_at: SystemTime::now() + self.config.cool_down,
+            }),
+        }
+    }
+
+    /// Record the outcome of a request.
+    pub(crate) fn record(&self, transient_failure: bool) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = SystemTime::now();
+        if !transient_failure {
+            *state = State::Closed {
+                failures: Vec::new(),
+            };
+            return;
+        }
+        let open = match &mut *state {
+            State::Closed { failures } => {
+                failures.retain(|at| {
+                    now.duration_since(*at)
+                        .is_ok_and(|age| age <= self.config.window)
+                });
+                failures.push(now);
+                failures.len() >= self.config.threshold
+            }
+            State::Open { .. } | State::HalfOpen => true,
+        };
+        if open {
+            debug!(
+                "Circuit breaker opening for {:?} after repeated failures",
+                self.config.cool_down
+            );
+            *state = State::Open {
+                until: now + self.config.cool_down,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Call, GitHub, LookupError};
+    use mockito::{mock, Matcher};
+    use reqwest::{header::HeaderMap, Method};
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn test_breaker_opens_and_closes() {
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .circuit_breaker(3, Duration::from_secs(60), Duration::from_millis(500))
+            .build()
+            .unwrap();
+        let failing = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_status(503)
+            .expect(3)
+            .create();
+        for _ in 0..3 {
+            assert!(matches!(
+                github.query("foo/bar"),
+                Err(LookupError::ErrorHttpResponse { status: 503, .. })
+            ));
+        }
+        let res = github.clone().query("foo/bar");
+        assert!(matches!(res, Err(LookupError::CircuitOpen { .. })));
+        failing.assert();
+        drop(failing);
+
+        let working = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .expect(2)
+            .create();
+        thread::sleep(Duration::from_millis(600));
+        assert!(github.query("foo/bar").is_ok());
+        assert!(github.query("foo/bar").is_ok());
+        working.assert();
+    }
+
+    #[test]
+    fn test_breaker_ignores_non_transient_errors() {
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .circuit_breaker(2, Duration::from_secs(60), Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        for _ in 0..3 {
+            assert!(matches!(
+                github.query("foo/bar"),
+                Err(LookupError::RepositoryNotFound)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unsent_request_does_not_take_the_probe() {
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .circuit_breaker(1, Duration::from_secs(60), Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let failing = mock("GET", "/repos/foo/breaker-probe/releases")
+            .match_query(Matcher::Any)
+            .with_status(503)
+            .create();
+        assert!(matches!(
+            github.query("foo/breaker-probe"),
+            Err(LookupError::ErrorHttpResponse { status: 503, .. })
+        ));
+        drop(failing);
+        thread::sleep(Duration::from_millis(300));
+
+        // a request that can't be built is never sent, so it isn't the probe
+        let unsent = github.send(
+            &Method::GET,
+            "not a url",
+            &[],
+            None::<&()>,
+            &HeaderMap::new(),
+            &mut Call::default(),
+        );
+        assert!(matches!(unsent, Err(LookupError::HttpClient(_))));
+
+        let _working = mock("GET", "/repos/foo/breaker-probe/releases")
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .create();
+        assert!(github.query("foo/breaker-probe").is_ok());
+        assert!(github.query("foo/breaker-probe").is_ok());
+    }
+}
@@ -0,0 +1,265 @@
+//! How often a repository publishes releases.
+
+use crate::{
+    age::parse_timestamp, DateField, GitHub, GitHubReleaseItem, LookupError, RepositorySpec, Result,
+};
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often releases were published, from [`GitHub::release_cadence`].
+///
+/// With fewer than two published releases there are no intervals to measure,
+/// so [`CadenceStats::is_degenerate`] is `true` and the intervals are `None`;
+/// the counts are still filled in.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CadenceStats {
+    /// The number of published releases that were counted.
+    pub releases: usize,
+    /// The mean time between consecutive releases.
+    pub mean_interval: Option<Duration>,
+    /// The median time between consecutive releases.
+    pub median_interval: Option<Duration>,
+    /// Releases published in the 30 days before the stats were made.
+    pub last_30_days: usize,
+    /// Releases published in the 90 days before the stats were made.
+    pub last_90_days: usize,
+    /// Releases published in the 365 days before the stats were made.
+    pub last_365_days: usize,
+    /// The `published_at` of the most recent release, if there is one.
+    pub latest_published_at: Option<String>,
+}
+
+impl CadenceStats {
+    /// Work out the cadence of the releases, in any order, as of `now`.
+    ///
+    /// Drafts, and releases whose `published_at` can't be read, are left out.
+    /// Releases published at the same time are ordered as in [`DateField::compare`].
+    #[must_use]
+    pub fn from_releases(releases: &[GitHubReleaseItem], now: SystemTime) -> Self {
+        let mut published: Vec<(i64, &GitHubReleaseItem)> = releases
+            .iter()
+            .filter(|release| !release.draft)
+            .filter_map(|release| Some((parse_timestamp(&release.published_at)?, release)))
+            .collect();
+        published.sort_by(|(a_at, a), (b_at, b)| {
+            a_at.cmp(b_at)
+                .then_with(|| DateField::Published.compare(a, b))
+        });
+
+        let now = match now.duration_since(UNIX_EPOCH) {
+            Ok(since) => i64::try_from(since.as_secs()).unwrap_or(i64::MAX),
+            Err(e) => -i64::try_from(e.duration().as_secs()).unwrap_or(i64::MAX),
+        };
+        let within = |days: i64| {
+            published
+                .iter()
+                .filter(|(at, _)| (0..=days * 86_400).contains(&(now - at)))
+                .count()
+        };
+
+        let mut intervals: Vec<u64> = published
+            .windows(2)
+            .map(|pair| (pair[1].0 - pair[0].0).unsigned_abs())
+            .collect();
+        intervals.sort_unstable();
+        let (mean_interval, median_interval) = if intervals.is_empty() {
+            (None, None)
+        } else {
+            let count = intervals.len() as u64;
+            let mean = intervals.iter().sum::<u64>() / count;
+            let middle = intervals.len() / 2;
+            let median = if intervals.len() % 2 == 0 {
+                // sorted, so the difference can't underflow
+                intervals[middle - 1] + (intervals[middle] - intervals[middle - 1]) / 2
+            } else {
+                intervals[middle]
+            };
+            (
+                Some(Duration::from_secs(mean)),
+                Some(Duration::from_secs(median)),
+            )
+        };
+
+        Self {
+            releases: published.len(),
+            mean_interval,
+            median_interval,
+            last_30_days: within(30),
+            last_90_days: within(90),
+            last_365_days: within(365),
+            latest_published_at: published
+                .last()
+                .map(|(_, release)| release.published_at.clone()),
+        }
+    }
+
+    /// Whether there were too few releases to measure the time between them.
+    #[must_use]
+    pub fn is_degenerate(&self) -> bool {
+        self.releases < 2
+    }
+}
+
+impl GitHub {
+    /// Work out how often the repository publishes releases.
+    ///
+    /// This is [`CadenceStats::from_releases`] over all of the repository's
+    /// releases, as of now. A repository with fewer than two releases gives
+    /// [degenerate](CadenceStats::is_degenerate) stats rather than an error.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let cadence = github.release_cadence("celeo/github_release_check").unwrap();
+    /// if let Some(median) = cadence.median_interval {
+    ///     println!("a release every {} days", median.as_secs() / 86_400);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn release_cadence<R>(&self, repository: R) -> Result<CadenceStats>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        Ok(CadenceStats::from_releases(
+            &self.query(repository)?,
+            SystemTime::now(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CadenceStats;
+    use crate::{
+        tests::{mock_github, permutations, release_json},
+        GitHubReleaseItem,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn published_json(published_at: &str, draft: bool) -> serde_json::Value {
+        release_json(
+            "v1.0.0",
+            json!({ "draft": draft, "published_at": published_at }),
+        )
+    }
+
+    fn release(published_at: &str, draft: bool) -> GitHubReleaseItem {
+        serde_json::from_value(published_json(published_at, draft)).unwrap()
+    }
+
+    fn days(days: u64) -> Duration {
+        Duration::from_secs(days * 24 * 3600)
+    }
+
+    #[test]
+    fn test_cadence() {
+        // 2024-03-31T00:00:00Z
+        let now = UNIX_EPOCH + Duration::from_secs(475_512 * 3600);
+        let releases = vec![
+            release("2024-03-21T00:00:00Z", false),
+            release("2023-06-01T00:00:00Z", false),
+            release("2024-03-30T00:00:00Z", true),
+            release("2024-02-29T00:00:00Z", false),
+            release("2024-03-01T00:00:00+00:00", false),
+            release("2024-01-30T00:00:00Z", false),
+            release("", true),
+        ];
+        let stats = CadenceStats::from_releases(&releases, now);
+        assert_eq!(stats.releases, 5);
+        assert!(!stats.is_degenerate());
+        // intervals of 20, 1, 30, and 243 days
+        assert_eq!(
+            stats.mean_interval,
+            Some(Duration::from_secs(294 * 6 * 3600))
+        );
+        assert_eq!(stats.median_interval, Some(days(25)));
+        assert_eq!(stats.last_30_days, 2);
+        assert_eq!(stats.last_90_days, 4);
+        assert_eq!(stats.last_365_days, 5);
+        assert_eq!(
+            stats.latest_published_at.as_deref(),
+            Some("2024-03-21T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_cadence_ties_ignore_order() {
+        let now = UNIX_EPOCH + Duration::from_secs(475_512 * 3600);
+        let mut created_later = published_json("2024-03-01T05:30:00+05:30", false);
+        created_later["id"] = json!(2);
+        created_later["created_at"] = json!("2024-02-29T00:00:00Z");
+        let releases = vec![
+            release("2024-02-01T00:00:00Z", false),
+            release("2024-03-01T00:00:00Z", false),
+            serde_json::from_value(created_later).unwrap(),
+        ];
+        for shuffled in permutations(&releases) {
+            let stats = CadenceStats::from_releases(&shuffled, now);
+            // the same instant, but the release created later is the latest
+            assert_eq!(
+                stats.latest_published_at.as_deref(),
+                Some("2024-03-01T05:30:00+05:30")
+            );
+            assert_eq!(
+                stats.median_interval,
+                Some(Duration::from_secs(29 * 12 * 3600))
+            );
+        }
+    }
+
+    #[test]
+    fn test_cadence_odd_median() {
+        let now = UNIX_EPOCH + Duration::from_secs(475_512 * 3600);
+        let releases = vec![
+            release("2024-01-10T00:00:00Z", false),
+            release("2024-01-01T00:00:00Z", false),
+            release("2024-01-03T00:00:00Z", false),
+            release("2024-01-04T00:00:00Z", false),
+        ];
+        let stats = CadenceStats::from_releases(&releases, now);
+        // intervals of 2, 1, and 6 days
+        assert_eq!(stats.mean_interval, Some(days(3)));
+        assert_eq!(stats.median_interval, Some(days(2)));
+    }
+
+    #[test]
+    fn test_cadence_degenerate() {
+        let now = UNIX_EPOCH + Duration::from_secs(475_512 * 3600);
+        let empty = CadenceStats::from_releases(&[], now);
+        assert!(empty.is_degenerate());
+        assert_eq!(empty, CadenceStats::default());
+
+        let single = CadenceStats::from_releases(&[release("2024-03-30T12:00:00Z", false)], now);
+        assert!(single.is_degenerate());
+        assert_eq!(single.releases, 1);
+        assert_eq!(single.mean_interval, None);
+        assert_eq!(single.median_interval, None);
+        assert_eq!(single.last_30_days, 1);
+    }
+
+    #[test]
+    fn test_release_cadence() {
+        let mut older = published_json("2020-01-01T00:00:00Z", false);
+        older["id"] = json!(2);
+        let _m = mock("GET", "/repos/foo/cadence/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {older}]",
+                published_json("2020-01-11T00:00:00Z", false)
+            ))
+            .create();
+        let github = mock_github();
+        let stats = github.release_cadence("foo/cadence").unwrap();
+        assert_eq!(stats.releases, 2);
+        assert_eq!(stats.median_interval, Some(days(10)));
+        assert_eq!(stats.last_365_days, 0);
+    }
+}
@@ -0,0 +1,229 @@
+//! Summarizing releases by release channel.
+
+use crate::{GitHub, LookupError, RepositorySpec, Result, TaggedVersion};
+use std::collections::BTreeMap;
+
+/// The newest version on each release channel of a repository.
+///
+/// See [`GitHub::get_channel_summary`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelSummary {
+    /// The newest version without a pre-release part.
+    pub stable: Option<TaggedVersion>,
+    /// The newest pre-release version per channel, keyed by the first
+    /// non-numeric identifier of the pre-release part (like `"beta"`).
+    pub prereleases: BTreeMap<String, TaggedVersion>,
+}
+
+/// A release channel, as used by [`GitHub::group_by_channel`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Channel {
+    /// Versions without a pre-release part.
+    Stable,
+    /// Pre-release versions, by the first non-numeric identifier of the
+    /// pre-release part (like `"beta"`).
+    Pre(String),
+    /// Pre-release versions with only numeric identifiers, like `1.0.0-1`.
+    Unknown,
+}
+
+impl Channel {
+    /// The channel the version is on.
+    fn of(version: &semver::Version) -> Self {
+        if version.pre.is_empty() {
+            Self::Stable
+        } else {
+            channel(version).map_or(Self::Unknown, |name| Self::Pre(name.to_owned()))
+        }
+    }
+}
+
+/// The channel of a pre-release version, if it names one.
+fn channel(version: &semver::Version) -> Option<&str> {
+    version
+        .pre
+        .as_str()
+        .split('.')
+        .find(|identifier| !identifier.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl ChannelSummary {
+    /// Sort the versions onto their channels, keeping the newest of each.
+    fn from_versions(versions: Vec<TaggedVersion>) -> Self {
+        let mut summary = Self::default();
+        for tagged in versions {
+            if tagged.version.pre.is_empty() {
+                if is_newer(summary.stable.as_ref(), &tagged) {
+                    summary.stable = Some(tagged);
+                }
+            } else if let Some(channel) = channel(&tagged.version).map(str::to_owned) {
+                if is_newer(summary.prereleases.get(&channel), &tagged) {
+                    let _prev = summary.prereleases.insert(channel, tagged);
+                }
+            }
+        }
+        summary
+    }
+}
+
+/// Whether the candidate should replace the current newest version.
+fn is_newer(current: Option<&TaggedVersion>, candidate: &TaggedVersion) -> bool {
+    current.map_or(true, |current| candidate.version > current.version)
+}
+
+impl GitHub {
+    /// Get the newest stable version and the newest pre-release version of each channel.
+    ///
+    /// Versions are read the same way as in [`GitHub::get_latest_version`]. Pre-releases
+    /// are grouped by the first non-numeric identifier of their pre-release part, so
+    /// `1.3.0-beta.5` is on the `"beta"` channel; those with only numeric identifiers
+    /// are left out.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let summary = github.get_channel_summary("celeo/github_release_check").unwrap();
+    /// if let Some(beta) = summary.prereleases.get("beta") {
+    ///     println!("newest beta is {}", beta.version);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions`.
+    pub fn get_channel_summary<R>(&self, repository: R) -> Result<ChannelSummary>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        Ok(ChannelSummary::from_versions(
+            self.candidate_versions(&repository.try_into()?)?,
+        ))
+    }
+
+    /// Get every version of the repository, grouped by channel, highest first.
+    ///
+    /// Versions are read the same way as in [`GitHub::get_latest_version`]; tags
+    /// without a version aren't on any channel.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{Channel, GitHub};
+    /// let github = GitHub::new().unwrap();
+    /// let channels = github.group_by_channel("celeo/github_release_check").unwrap();
+    /// for beta in channels.get(&Channel::Pre("beta".to_owned())).into_iter().flatten() {
+    ///     println!("{}", beta.version);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions`.
+    pub fn group_by_channel<R>(
+        &self,
+        repository: R,
+    ) -> Result<BTreeMap<Channel, Vec<TaggedVersion>>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let mut channels: BTreeMap<Channel, Vec<TaggedVersion>> = BTreeMap::new();
+        for tagged in self.candidate_versions(&repository.try_into()?)? {
+            channels
+                .entry(Channel::of(&tagged.version))
+                .or_default()
+                .push(tagged);
+        }
+        for versions in channels.values_mut() {
+            versions.sort_by(|a, b| b.version.cmp(&a.version));
+        }
+        Ok(channels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Channel;
+    use crate::tests::{mock_github, release_json};
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+
+    #[test]
+    fn test_channel_summary() {
+        let body = [
+            "v1.2.0",
+            "v1.3.0-beta.2",
+            "v1.3.0-beta.5",
+            "2.0.0-rc.1",
+            "nightly",
+        ]
+        .iter()
+        .map(|t| release_json(t, json!({})).to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!("[{body}]"))
+            .create();
+        let github = mock_github();
+        let summary = github.get_channel_summary("foo/bar").unwrap();
+
+        let stable = summary.stable.unwrap();
+        assert_eq!(stable.tag, "v1.2.0");
+        assert_eq!(stable.version, Version::new(1, 2, 0));
+        assert_eq!(summary.prereleases.len(), 2);
+        assert_eq!(summary.prereleases["beta"].tag, "v1.3.0-beta.5");
+        assert_eq!(
+            summary.prereleases["rc"].version,
+            Version::parse("2.0.0-rc.1").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_group_by_channel() {
+        let body = [
+            "v1.2.0",
+            "v1.3.0-beta.2",
+            "v2.0.0-rc.1",
+            "v1.3.0-beta.5",
+            "v1.0.0",
+            "v1.3.0",
+            "v1.1.0-1",
+            "nightly",
+        ]
+        .iter()
+        .map(|t| release_json(t, json!({})).to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!("[{body}]"))
+            .create();
+        let github = mock_github();
+        let channels = github.group_by_channel("foo/bar").unwrap();
+
+        let tags = |channel: &Channel| -> Vec<&str> {
+            channels[channel].iter().map(|t| t.tag.as_str()).collect()
+        };
+        assert_eq!(
+            channels.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                Channel::Stable,
+                Channel::Pre("beta".to_owned()),
+                Channel::Pre("rc".to_owned()),
+                Channel::Unknown,
+            ]
+        );
+        assert_eq!(tags(&Channel::Stable), vec!["v1.3.0", "v1.2.0", "v1.0.0"]);
+        assert_eq!(
+            tags(&Channel::Pre("beta".to_owned())),
+            vec!["v1.3.0-beta.5", "v1.3.0-beta.2"]
+        );
+        assert_eq!(tags(&Channel::Pre("rc".to_owned())), vec!["v2.0.0-rc.1"]);
+        assert_eq!(tags(&Channel::Unknown), vec!["v1.1.0-1"]);
+    }
+}
@@ -0,0 +1,355 @@
+//! Sharing one fetch between concurrent queries for the same repository.
+
+use crate::{LookupError, PartialQuery, Result};
+use log::debug;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Condvar, Mutex, PoisonError},
+};
+
+type SharedResult = std::result::Result<PartialQuery, Arc<LookupError>>;
+
+/// A fetch that callers can wait on.
+#[derive(Debug, Default)]
+struct Flight {
+    result: Mutex<Option<Arc<SharedResult>>>,
+    done: Condvar,
+}
+
+impl Flight {
+    fn wait(&self) -> Arc<SharedResult> {
+        let guard = self.result.lock().unwrap_or_else(PoisonError::into_inner);
+        let guard = self
+            .done
+            .wait_while(guard, |result| result.is_none())
+            .unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(guard.as_ref().expect("Waited until the result was set"))
+    }
+
+    fn finish(&self, result: Arc<SharedResult>) {
+        *self.result.lock().unwrap_or_else(PoisonError::into_inner) = Some(result);
+        self.done.notify_all();
+    }
+}
+
+/// Fetches currently in progress, keyed by repository.
+#[derive(Debug, Default)]
+pub(crate) struct InFlight {
+    flights: Mutex<HashMap<String, Arc<Flight>>>,
+}
+
+impl InFlight {
+    /// Run the fetch for the repository, unless one is already running,
+    /// in which case wait for it and return a copy of its result.
+    pub(crate) fn run<F>(&self, repository: &str, fetch: F) -> Result<PartialQuery>
+    where
+        F: FnOnce() -> Result<PartialQuery>,
+    {
+        let (flight, leader) = {
+            let mut flights = self.flights.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(flight) = flights.get(repository) {
+                (Arc::clone(flight), false)
+            } else {
+                let flight = Arc::new(Flight::default());
+                let _prev = flights.insert(repository.to_owned(), Arc::clone(&flight));
+                (flight, true)
+            }
+        };
+
+        if !leader {
+            debug!("Waiting on in-flight query for {repository}");
+            return unshare(&flight.wait());
+        }
+
+        let mut leader = Leader {
+            in_flight: self,
+            repository,
+            flight,
+            result: None,
+        };
+        let result = Arc::new(fetch().map_err(Arc::new));
+        leader.result = Some(Arc::clone(&result));
+        drop(leader);
+        unshare(&result)
+    }
+}
+
+/// The caller running a fetch, which hands the result to the waiting callers
+/// when dropped, even if the fetch panicked.
+struct Leader<'a> {
+    in_flight: &'a InFlight,
+    repository: &'a str,
+    flight: Arc<Flight>,
+    result: Option<Arc<SharedResult>>,
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        // remove the flight first, so later queries don't join a finished one
+        let _prev = self
+            .in_flight
+            .flights
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(self.repository);
+        let result = self.result.take().unwrap_or_else(|| {
+            debug!("Query for {} panicked", self.repository);
+            Arc::new(Err(Arc::new(LookupError::FetchPanicked)))
+        });
+        self.flight.finish(result);
+    }
+}
+
+/// Give a caller its own copy of a shared result.
+fn unshare(result: &SharedResult) -> Result<PartialQuery> {
+    match result {
+        Ok(partial) => Ok(partial.clone()),
+        Err(error) => Err(error
+            .try_clone()
+            .unwrap_or_else(|| LookupError::Shared(Arc::clone(error)))),
+    }
+}
+
+impl LookupError {
+    /// Copy the error, if it doesn't wrap an error that can't be copied.
+    #[allow(clippy::too_many_lines)]
+    fn try_clone(&self) -> Option<Self> {
+        Some(match self {
+            Self::HttpClient(_)
+            | Self::HeaderValue(_)
+            | Self::HeaderName(_)
+            | Self::HeaderToString(_)
+            | Self::StateWrite { .. }
+            | Self::AssetCache { .. } => return None,
+            #[cfg(feature = "test-util")]
+            Self::FixtureRead { .. } => return None,
+            #[cfg(feature = "local-git")]
+            Self::LocalGit(_) => return None,
+            #[cfg(feature = "test-util")]
+            Self::InvalidFixture {
+                path,
+                line,
+                column,
+                message,
+            } => Self::InvalidFixture {
+                path: path.clone(),
+                line: *line,
+                column: *column,
+                message: message.clone(),
+            },
+            Self::NoReleases => Self::NoReleases,
+            Self::RepositoryNotFound => Self::RepositoryNotFound,
+            Self::InvalidRepository(repository) => Self::InvalidRepository(repository.clone()),
+            Self::ReleaseNotFound => Self::ReleaseNotFound,
+            Self::AuthenticationError { status, request_id } => Self::AuthenticationError {
+                status: *status,
+                request_id: request_id.clone(),
+            },
+            Self::AuthenticationRequiredForDrafts => Self::AuthenticationRequiredForDrafts,
+            Self::RateLimited {
+                reset_at,
+                request_id,
+            } => Self::RateLimited {
+                reset_at: *reset_at,
+                request_id: request_id.clone(),
+            },
+            Self::SecondaryRateLimited {
+                retry_after,
+                request_id,
+            } => Self::SecondaryRateLimited {
+                retry_after: *retry_after,
+                request_id: request_id.clone(),
+            },
+            Self::BudgetExceeded {
+                fetched_pages,
+                total_pages,
+            } => Self::BudgetExceeded {
+                fetched_pages: *fetched_pages,
+                total_pages: *total_pages,
+            },
+            Self::PaginationLoop { pages_fetched } => Self::PaginationLoop {
+                pages_fetched: *pages_fetched,
+            },
+            Self::ErrorHttpResponse {
+                status,
+                message,
+                request_id,
+            } => Self::ErrorHttpResponse {
+                status: *status,
+                message: message.clone(),
+                request_id: request_id.clone(),
+            },
+            Self::Timeout { phase } => Self::Timeout { phase: *phase },
+            Self::InvalidVersion(version) => Self::InvalidVersion(version.clone()),
+            Self::ResponseTooLarge { limit, url } => Self::ResponseTooLarge {
+                limit: *limit,
+                url: url.clone(),
+            },
+            Self::NotModified => Self::NotModified,
+            Self::NotAnApiEndpoint { api_root } => Self::NotAnApiEndpoint {
+                api_root: api_root.clone(),
+            },
+            Self::PaginationParse(message) => Self::PaginationParse(message.clone()),
+            Self::InvalidResponse {
+                line,
+                column,
+                message,
+            } => Self::InvalidResponse {
+                line: *line,
+                column: *column,
+                message: message.clone(),
+            },
+            Self::UnexpectedBody { body_snippet } => Self::UnexpectedBody {
+                body_snippet: body_snippet.clone(),
+            },
+            Self::CertificatePinFailed { host } => {
+                Self::CertificatePinFailed { host: host.clone() }
+            }
+            #[cfg(feature = "atom")]
+            Self::InvalidFeed(message) => Self::InvalidFeed(message.clone()),
+            Self::MissingTemplateVariable { name } => {
+                Self::MissingTemplateVariable { name: name.clone() }
+            }
+            Self::InvalidAssetTemplate { template } => Self::InvalidAssetTemplate {
+                template: template.clone(),
+            },
+            Self::AssetSizeMismatch { expected, received } => Self::AssetSizeMismatch {
+                expected: *expected,
+                received: *received,
+            },
+            Self::ApiRootNotFound { attempted } => Self::ApiRootNotFound {
+                attempted: attempted.clone(),
+            },
+            #[cfg(feature = "write")]
+            Self::ValidationFailed {
+                messages,
+                request_id,
+            } => Self::ValidationFailed {
+                messages: messages.clone(),
+                request_id: request_id.clone(),
+            },
+            #[cfg(feature = "keyring")]
+            Self::TokenNotFound { service } => Self::TokenNotFound {
+                service: service.clone(),
+            },
+            #[cfg(feature = "keyring")]
+            Self::TokenStore { service, message } => Self::TokenStore {
+                service: service.clone(),
+                message: message.clone(),
+            },
+            Self::InvalidTagPattern(e) => Self::InvalidTagPattern(e.clone()),
+            Self::InvalidAssetPattern(e) => Self::InvalidAssetPattern(e.clone()),
+            Self::InvalidSearchPattern(e) => Self::InvalidSearchPattern(e.clone()),
+            Self::AssetNotFound => Self::AssetNotFound,
+            Self::AmbiguousAsset { names } => Self::AmbiguousAsset {
+                names: names.clone(),
+            },
+            Self::CircuitOpen { retry_at } => Self::CircuitOpen {
+                retry_at: *retry_at,
+            },
+            Self::Shared(inner) => Self::Shared(Arc::clone(inner)),
+            Self::FetchPanicked => Self::FetchPanicked,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InFlight;
+    use crate::{tests::release_json, GitHub, LookupError, PartialQuery};
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+    use std::{thread, time::Duration};
+
+    fn github() -> GitHub {
+        GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .coalesce_requests(true)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_concurrent_queries_share_one_fetch() {
+        let m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body_from_fn(|w| {
+                thread::sleep(Duration::from_millis(300));
+                w.write_all(
+                    json!([release_json("v1.2.3", json!({}))])
+                        .to_string()
+                        .as_bytes(),
+                )
+            })
+            .expect(1)
+            .create();
+        let github = github();
+        let versions: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..5)
+                .map(|_| {
+                    let github = github.clone();
+                    scope.spawn(move || github.get_latest_version("foo/bar"))
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for version in versions {
+            assert_eq!(version.unwrap(), semver::Version::new(1, 2, 3));
+        }
+        m.assert();
+    }
+
+    #[test]
+    fn test_concurrent_queries_share_errors() {
+        let m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .with_body_from_fn(|_| {
+                thread::sleep(Duration::from_millis(300));
+                Ok(())
+            })
+            .expect(1)
+            .create();
+        let github = github();
+        let results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..5)
+                .map(|_| scope.spawn(|| github.query("foo/bar")))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for result in results {
+            assert!(matches!(result, Err(LookupError::RepositoryNotFound)));
+        }
+        m.assert();
+    }
+
+    #[test]
+    fn test_panicked_fetch_releases_waiters() {
+        let in_flight = InFlight::default();
+        let (leader, waiter) = thread::scope(|scope| {
+            let leader = scope.spawn(|| {
+                in_flight.run("foo/bar", || {
+                    thread::sleep(Duration::from_millis(300));
+                    panic!("fetch failed");
+                })
+            });
+            thread::sleep(Duration::from_millis(100));
+            let waiter = scope.spawn(|| {
+                in_flight.run("foo/bar", || unreachable!("the fetch is already running"))
+            });
+            (leader.join(), waiter.join().unwrap())
+        });
+        assert!(leader.is_err());
+        assert!(matches!(waiter, Err(LookupError::FetchPanicked)));
+        assert_eq!(waiter.unwrap_err().code(), "fetch_panicked");
+
+        // a later query runs its own fetch rather than joining the dead one
+        let result = in_flight.run("foo/bar", || {
+            Ok(PartialQuery {
+                releases: Vec::new(),
+                truncated: false,
+            })
+        });
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,231 @@
+//! A smaller representation of releases, for holding very many at once.
+//!
+//! [`GitHubReleaseItem`] keeps every field as its own `String`, with the spare
+//! capacity that comes with it. [`CompactRelease`] keeps fewer fields, stores
+//! them as `Box<str>`, and shares one copy of the strings that repeat across
+//! releases, like branch names and asset content types. The release bodies,
+//! usually the largest part of a release, are only kept if asked for.
+//!
+//! The trade-offs are that the API URLs of a release are dropped (they can be
+//! rebuilt from the repository and ID), and that the releases are still read
+//! in full before being shrunk, so this lowers the memory held afterwards
+//! rather than the peak while querying.
+
+use crate::{GitHub, GitHubAssetItem, GitHubReleaseItem, LookupError, RepositorySpec, Result};
+use std::{collections::HashSet, sync::Arc};
+
+/// A release, stored compactly.
+///
+/// See [`GitHub::query_compact`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactRelease {
+    /// ID of the release.
+    pub id: usize,
+    /// Name of the tag the release is for.
+    pub tag_name: Box<str>,
+    /// Display name of the release.
+    pub name: Option<Box<str>>,
+    /// Whether the release is a draft.
+    pub draft: bool,
+    /// Whether the release is marked as a pre-release.
+    pub prerelease: bool,
+    /// When the release was created.
+    pub created_at: Box<str>,
+    /// When the release was published.
+    pub published_at: Box<str>,
+    /// Link to the release page.
+    pub html_url: Box<str>,
+    /// The branch or commit the release was created from, shared between releases.
+    pub target_commitish: Arc<str>,
+    /// The release notes, if bodies were kept.
+    pub body: Option<Box<str>>,
+    /// Files attached to the release.
+    pub assets: Box<[CompactAsset]>,
+}
+
+/// A file attached to a release, stored compactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactAsset {
+    /// ID of the asset.
+    pub id: usize,
+    /// File name of the asset.
+    pub name: Box<str>,
+    /// Display label of the asset.
+    pub label: Option<Box<str>>,
+    /// Media type of the asset, shared between assets.
+    pub content_type: Arc<str>,
+    /// Size of the asset in bytes.
+    pub size: u64,
+    /// How many times the asset has been downloaded.
+    pub download_count: u64,
+    /// Link to download the asset.
+    pub browser_download_url: Box<str>,
+}
+
+/// Shared copies of strings that repeat across releases.
+#[derive(Debug, Default)]
+struct StringPool(HashSet<Arc<str>>);
+
+impl StringPool {
+    /// Get the shared copy of the string, adding it if it's new.
+    fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(shared) = self.0.get(s) {
+            return Arc::clone(shared);
+        }
+        let shared: Arc<str> = Arc::from(s);
+        let _new = self.0.insert(Arc::clone(&shared));
+        shared
+    }
+}
+
+impl CompactRelease {
+    /// Shrink a release, keeping its body if `body` is set.
+    fn new(release: GitHubReleaseItem, body: bool, pool: &mut StringPool) -> Self {
+        Self {
+            id: release.id,
+            tag_name: release.tag_name.into_boxed_str(),
+            name: release.name.map(String::into_boxed_str),
+            draft: release.draft,
+            prerelease: release.prerelease,
+            created_at: release.created_at.into_boxed_str(),
+            published_at: release.published_at.into_boxed_str(),
+            html_url: release.html_url.into_boxed_str(),
+            target_commitish: pool.intern(&release.target_commitish),
+            body: release.body.filter(|_| body).map(String::into_boxed_str),
+            assets: release
+                .assets
+                .into_iter()
+                .map(|asset| CompactAsset::new(asset, pool))
+                .collect(),
+        }
+    }
+}
+
+impl CompactAsset {
+    /// Shrink an asset.
+    fn new(asset: GitHubAssetItem, pool: &mut StringPool) -> Self {
+        Self {
+            id: asset.id,
+            name: asset.name.into_boxed_str(),
+            label: asset.label.map(String::into_boxed_str),
+            content_type: pool.intern(&asset.content_type),
+            size: asset.size,
+            download_count: asset.download_count,
+            browser_download_url: asset.browser_download_url.into_boxed_str(),
+        }
+    }
+}
+
+impl GitHub {
+    /// Get all releases from the repository, stored compactly.
+    ///
+    /// The releases are the same as from [`GitHub::query`], but with the fields
+    /// of [`CompactRelease`]. Release bodies are only kept if `bodies` is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let releases = github.query_compact("celeo/github_release_check", false).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn query_compact<R>(&self, repository: R, bodies: bool) -> Result<Vec<CompactRelease>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let mut pool = StringPool::default();
+        Ok(self
+            .query(repository)?
+            .into_iter()
+            .map(|release| CompactRelease::new(release, bodies, &mut pool))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{mock_github, release_json};
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn releases() -> String {
+        json!([
+            release_json(
+                "v2.0.0",
+                json!({
+                    "html_url": "https://github.com/foo/bar/releases/tag/v2.0.0", "id": 2,
+                    "name": "Two", "prerelease": true, "created_at": "2024-02-01",
+                    "published_at": "2024-02-02", "body": "Second", "target_commitish": "main",
+                    "assets": [{ "url": "", "browser_download_url": "https://example.com/bar.tar.gz", "id": 20, "name": "bar.tar.gz", "label": null, "content_type": "application/gzip", "size": 10, "download_count": 3, "created_at": "", "updated_at": "" }],
+                }),
+            ),
+            release_json(
+                "v1.0.0",
+                json!({
+                    "name": null, "draft": true, "created_at": "2024-01-01",
+                    "published_at": "2024-01-02", "body": "First", "target_commitish": "main",
+                    "assets": [{ "url": "", "browser_download_url": "https://example.com/bar.zip", "id": 10, "name": "bar.zip", "label": "Zip", "content_type": "application/gzip", "size": 20, "download_count": 4, "created_at": "", "updated_at": "" }],
+                }),
+            ),
+        ])
+        .to_string()
+    }
+
+    #[test]
+    fn test_compact_matches_full() {
+        let _m = mock("GET", "/repos/foo/compact/releases")
+            .match_query(Matcher::Any)
+            .with_body(releases())
+            .create();
+        let github = mock_github();
+        let full = github.query("foo/compact").unwrap();
+        let compact = github.query_compact("foo/compact", true).unwrap();
+        assert_eq!(full.len(), compact.len());
+        for (full, compact) in full.iter().zip(&compact) {
+            assert_eq!(full.id, compact.id);
+            assert_eq!(full.tag_name, &*compact.tag_name);
+            assert_eq!(full.name.as_deref(), compact.name.as_deref());
+            assert_eq!(full.draft, compact.draft);
+            assert_eq!(full.prerelease, compact.prerelease);
+            assert_eq!(full.created_at, &*compact.created_at);
+            assert_eq!(full.published_at, &*compact.published_at);
+            assert_eq!(full.html_url, &*compact.html_url);
+            assert_eq!(full.target_commitish, &*compact.target_commitish);
+            assert_eq!(full.body.as_deref(), compact.body.as_deref());
+            for (full, compact) in full.assets.iter().zip(compact.assets.iter()) {
+                assert_eq!(full.id, compact.id);
+                assert_eq!(full.name, &*compact.name);
+                assert_eq!(full.label.as_deref(), compact.label.as_deref());
+                assert_eq!(full.content_type, &*compact.content_type);
+                assert_eq!(full.size, compact.size);
+                assert_eq!(full.download_count, compact.download_count);
+                assert_eq!(full.browser_download_url, &*compact.browser_download_url);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_shares_repeated_strings() {
+        let _m = mock("GET", "/repos/foo/shared/releases")
+            .match_query(Matcher::Any)
+            .with_body(releases())
+            .create();
+        let github = mock_github();
+        let compact = github.query_compact("foo/shared", false).unwrap();
+        assert!(Arc::ptr_eq(
+            &compact[0].target_commitish,
+            &compact[1].target_commitish
+        ));
+        assert!(Arc::ptr_eq(
+            &compact[0].assets[0].content_type,
+            &compact[1].assets[0].content_type
+        ));
+        assert!(compact.iter().all(|release| release.body.is_none()));
+    }
+}
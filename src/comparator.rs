@@ -0,0 +1,178 @@
+//! Ordering tags with a caller's own comparison, for schemes semver can't express.
+
+use crate::{versions, GitHub, GitHubReleaseItem, LookupError, RepositorySpec, Result};
+use semver::Version;
+use std::{cmp::Ordering, fmt, sync::Arc};
+
+/// A comparison of two tags.
+pub(crate) type CompareTags = dyn Fn(&str, &str) -> Ordering + Send + Sync;
+
+/// The comparison set with [`GitHubBuilder::version_comparator`](crate::GitHubBuilder::version_comparator).
+#[derive(Clone)]
+pub(crate) struct VersionComparator(Arc<CompareTags>);
+
+impl VersionComparator {
+    /// Wrap the comparison.
+    pub(crate) fn new(compare: Box<CompareTags>) -> Self {
+        Self(Arc::from(compare))
+    }
+}
+
+impl fmt::Debug for VersionComparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("VersionComparator(<fn>)")
+    }
+}
+
+/// The tag of the latest release, and its version if it has one.
+///
+/// See [`GitHub::get_latest_tag`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatestTag {
+    /// The tag name, as it appears in the repository.
+    pub tag: String,
+    /// The version parsed from the tag, if it is one.
+    pub version: Option<Version>,
+}
+
+impl GitHub {
+    /// Select the latest release by comparing tags with the comparison, leaving
+    /// out ignored tags, and drafts unless they're included. Also returns how many releases were looked at.
+    pub(crate) fn latest_by_comparator(
+        &self,
+        spec: &RepositorySpec,
+        comparator: &VersionComparator,
+    ) -> Result<(LatestTag, GitHubReleaseItem, usize)> {
+        let releases = self.query(spec)?;
+        let considered = releases.len();
+        let release = releases
+            .into_iter()
+            .filter(|release| {
+                (self.settings.include_drafts || !release.draft)
+                    && !self.is_ignored(&release.tag_name)
+            })
+            .max_by(|a, b| (comparator.0)(&a.tag_name, &b.tag_name))
+            .ok_or(LookupError::NoReleases)?;
+        let latest = LatestTag {
+            tag: release.tag_name.clone(),
+            version: versions::parse_tag(&release.tag_name, &self.settings.version_prefixes),
+        };
+        Ok((latest, release, considered))
+    }
+
+    /// Get the tag of the latest release in the repository.
+    ///
+    /// Without a comparison set with
+    /// [`GitHubBuilder::version_comparator`](crate::GitHubBuilder::version_comparator),
+    /// this is the tag of the release [`GitHub::get_latest_version`] picks. With
+    /// one, tags are compared with it instead, and the version is only set if the
+    /// winning tag happens to be a Semantic Version.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let latest = github.get_latest_tag("celeo/github_release_check").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_latest_version`.
+    pub fn get_latest_tag<R>(&self, repository: R) -> Result<LatestTag>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec = repository.try_into()?;
+        if let Some(comparator) = &self.settings.version_comparator {
+            return Ok(self.latest_by_comparator(&spec, comparator)?.0);
+        }
+        let (tagged, _) = self.latest_release(&spec)?;
+        Ok(LatestTag {
+            tag: tagged.tag,
+            version: Some(tagged.version),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHub, LookupError, ResolutionStrategy,
+    };
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+    use std::cmp::Ordering;
+
+    /// Compare tags like `2!1.4.0` by epoch, then version.
+    fn by_epoch(a: &str, b: &str) -> Ordering {
+        let split = |tag: &str| match tag.split_once('!') {
+            Some((epoch, rest)) => (epoch.parse::<u64>().unwrap_or(0), rest.to_owned()),
+            None => (0, tag.to_owned()),
+        };
+        let ((a_epoch, a_rest), (b_epoch, b_rest)) = (split(a), split(b));
+        let version = |rest: &str| Version::parse(rest.trim_start_matches('v')).ok();
+        a_epoch
+            .cmp(&b_epoch)
+            .then_with(|| version(&a_rest).cmp(&version(&b_rest)))
+    }
+
+    fn release(tag: &str, draft: bool) -> String {
+        release_json(tag, json!({ "draft": draft })).to_string()
+    }
+
+    fn github() -> GitHub {
+        GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .version_comparator(Box::new(by_epoch))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_comparator_picks_epoch() {
+        let _m = mock("GET", "/repos/foo/epochs/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}, {}, {}, {}]",
+                release("10.0.0", false),
+                release("3!0.1.0", true),
+                release("1!9.0.0", false),
+                release("2!1.4.0", false),
+                release("v3.0.0", false),
+            ))
+            .create();
+        let latest = github().get_latest_tag("foo/epochs").unwrap();
+        assert_eq!(latest.tag, "2!1.4.0");
+        assert_eq!(latest.version, None);
+
+        match github().get_latest_version("foo/epochs") {
+            Err(LookupError::InvalidVersion(tag)) => assert_eq!(tag, "2!1.4.0"),
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        let default = mock_github();
+        assert_eq!(default.get_latest_tag("foo/epochs").unwrap().tag, "10.0.0");
+    }
+
+    #[test]
+    fn test_comparator_winner_with_version() {
+        let _m = mock("GET", "/repos/foo/epochs-semver/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}]",
+                release("v1.2.0", false),
+                release("v1.10.0", false)
+            ))
+            .create();
+        let latest = github().get_latest_tag("foo/epochs-semver").unwrap();
+        assert_eq!(latest.version, Some(Version::new(1, 10, 0)));
+        let (tagged, trace) = github().get_latest_with_trace("foo/epochs-semver").unwrap();
+        assert_eq!(tagged.tag, "v1.10.0");
+        assert_eq!(trace.strategy, ResolutionStrategy::Comparator);
+        assert_eq!(trace.considered, 2);
+    }
+}
@@ -0,0 +1,186 @@
+//! Comparing the release versions of two repositories, like a fork and its upstream.
+
+use crate::{versions, GitHub, LookupError, RepositorySpec, Result};
+use semver::Version;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Which versions two repositories have released, from
+/// [`GitHub::compare_repositories`].
+///
+/// Each list is sorted from lowest to highest version.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RepoComparison {
+    /// Versions only the upstream repository has released, like those a fork
+    /// hasn't caught up with yet.
+    pub only_in_upstream: Vec<Version>,
+    /// Versions only our repository has released.
+    pub only_in_ours: Vec<Version>,
+    /// Versions both repositories have released.
+    pub common: Vec<Version>,
+}
+
+impl RepoComparison {
+    /// Compare two sets of versions.
+    fn new(ours: &BTreeSet<Version>, upstream: &BTreeSet<Version>) -> Self {
+        Self {
+            only_in_upstream: upstream.difference(ours).cloned().collect(),
+            only_in_ours: ours.difference(upstream).cloned().collect(),
+            common: ours.intersection(upstream).cloned().collect(),
+        }
+    }
+
+    /// Whether our repository has released every version the upstream has.
+    #[must_use]
+    pub fn is_caught_up(&self) -> bool {
+        self.only_in_upstream.is_empty()
+    }
+}
+
+impl GitHub {
+    /// Compare the versions released by two repositories.
+    ///
+    /// Tags are read the same way as in [`GitHub::get_latest_version`], so
+    /// `v1.2.3` in one repository and `1.2.3` in the other are the same
+    /// version. Tags that aren't Semantic Versions and tags the instance
+    /// ignores are left out. Releases are fetched as in
+    /// [`GitHub::get_all_versions_minimal`], so this suits repositories with
+    /// many releases.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let comparison = github
+    ///     .compare_repositories("my-org/rust-fork", "rust-lang/rust")
+    ///     .unwrap();
+    /// for version in &comparison.only_in_upstream {
+    ///     println!("not rebased onto {version} yet");
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions_minimal`,
+    /// for either repository.
+    pub fn compare_repositories<R, U>(&self, ours: R, upstream: U) -> Result<RepoComparison>
+    where
+        R: TryInto<RepositorySpec>,
+        U: TryInto<RepositorySpec>,
+        LookupError: From<R::Error> + From<U::Error>,
+    {
+        let ours = self.released_versions(&ours.try_into()?)?;
+        let upstream = self.released_versions(&upstream.try_into()?)?;
+        Ok(RepoComparison::new(&ours, &upstream))
+    }
+
+    /// Get the versions of the repository's releases, leaving out ignored tags.
+    fn released_versions(&self, spec: &RepositorySpec) -> Result<BTreeSet<Version>> {
+        Ok(self
+            .get_all_versions_minimal(spec)?
+            .iter()
+            .filter(|tag| !self.is_ignored(tag))
+            .filter_map(|tag| versions::parse_tag(tag, &self.settings.version_prefixes))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RepoComparison;
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHub, LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+
+    fn mock_tags(repository: &str, tags: &[&str]) -> mockito::Mock {
+        let releases: Vec<String> = tags
+            .iter()
+            .enumerate()
+            .map(|(id, tag)| release_json(tag, json!({ "id": id })).to_string())
+            .collect();
+        mock("GET", format!("/repos/{repository}/releases").as_str())
+            .match_query(Matcher::Any)
+            .with_body(format!("[{}]", releases.join(", ")))
+            .create()
+    }
+
+    fn versions(versions: &[&str]) -> Vec<Version> {
+        versions
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_repositories() {
+        let _ours = mock_tags(
+            "foo/compare-fork",
+            &["1.1.0-fork.1", "1.1.0", "v1.0.0", "0.9.0"],
+        );
+        let _upstream = mock_tags(
+            "foo/compare-upstream",
+            &["v1.2.0", "nightly", "v1.1.0", "v1.0.0", "V0.9.0"],
+        );
+        let github = mock_github();
+        let comparison = github
+            .compare_repositories("foo/compare-fork", "foo/compare-upstream")
+            .unwrap();
+        assert_eq!(
+            comparison,
+            RepoComparison {
+                only_in_upstream: versions(&["1.2.0"]),
+                only_in_ours: versions(&["1.1.0-fork.1"]),
+                common: versions(&["0.9.0", "1.0.0", "1.1.0"]),
+            }
+        );
+        assert!(!comparison.is_caught_up());
+
+        let reversed = github
+            .compare_repositories("foo/compare-upstream", "foo/compare-fork")
+            .unwrap();
+        assert_eq!(reversed.only_in_ours, comparison.only_in_upstream);
+        assert_eq!(reversed.common, comparison.common);
+    }
+
+    #[test]
+    fn test_compare_disjoint_repositories() {
+        let _ours = mock_tags("foo/compare-old", &["v0.2.0", "v0.1.0"]);
+        let _upstream = mock_tags("foo/compare-new", &["v2.0.0", "2.0.0", "v1.0.0"]);
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .ignore_tags(&["0.1.0"])
+            .build()
+            .unwrap();
+        let comparison = github
+            .compare_repositories("foo/compare-old", "foo/compare-new")
+            .unwrap();
+        assert_eq!(comparison.only_in_upstream, versions(&["1.0.0", "2.0.0"]));
+        assert_eq!(comparison.only_in_ours, versions(&["0.2.0"]));
+        assert!(comparison.common.is_empty());
+
+        let same = github
+            .compare_repositories("foo/compare-new", "foo/compare-new")
+            .unwrap();
+        assert!(same.is_caught_up());
+        assert!(same.only_in_ours.is_empty());
+    }
+
+    #[test]
+    fn test_compare_missing_repository() {
+        let _ours = mock_tags("foo/compare-exists", &["v1.0.0"]);
+        let _missing = mock("GET", "/repos/foo/compare-missing/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        let github = mock_github();
+        assert!(matches!(
+            github.compare_repositories("foo/compare-exists", "foo/compare-missing"),
+            Err(LookupError::RepositoryNotFound)
+        ));
+    }
+}
@@ -0,0 +1,322 @@
+//! Describing how an instance is configured, for bug reports.
+
+use crate::{
+    batch::DEFAULT_CONCURRENCY, body::DEFAULT_MAX_RESPONSE_BYTES, page_guard::DEFAULT_MAX_PAGES,
+    BudgetBehavior, DateField, GitHub, LatestStrategy, VersionSource, PAGINATION_REQUEST_AMOUNT,
+};
+use serde::Serialize;
+use std::{net::SocketAddr, time::Duration};
+
+/// How long a request may take when no timeout is set, which is the HTTP client's default.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The optional features the crate was built with.
+const FEATURES: [(&str, bool); 13] = [
+    ("atom", cfg!(feature = "atom")),
+    ("brotli", cfg!(feature = "brotli")),
+    ("cli", cfg!(feature = "cli")),
+    ("crates-io", cfg!(feature = "crates-io")),
+    ("gitea", cfg!(feature = "gitea")),
+    ("gitlab", cfg!(feature = "gitlab")),
+    ("gzip", cfg!(feature = "gzip")),
+    ("interop", cfg!(feature = "interop")),
+    ("keyring", cfg!(feature = "keyring")),
+    ("local-git", cfg!(feature = "local-git")),
+    ("rayon", cfg!(feature = "rayon")),
+    ("test-util", cfg!(feature = "test-util")),
+    ("write", cfg!(feature = "write")),
+];
+
+/// The settings of [`GitHubBuilder::circuit_breaker`](crate::GitHubBuilder::circuit_breaker).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CircuitBreakerSnapshot {
+    /// How many transient failures open the breaker.
+    pub threshold: usize,
+    /// How far back failures are counted.
+    pub window: Duration,
+    /// How long the breaker stays open.
+    pub cool_down: Duration,
+}
+
+/// How a [`GitHub`] instance is configured, from [`GitHub::config_snapshot`].
+///
+/// Options that weren't set hold their defaults. The access token is never
+/// included, only whether there is one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ConfigSnapshot {
+    /// The REST API root.
+    pub api_root: String,
+    /// The root of the website.
+    pub web_root: String,
+    /// Whether requests are sent with an access token.
+    pub authenticated: bool,
+    /// How many releases are asked for per page.
+    pub per_page: usize,
+    /// See [`GitHubBuilder::wait_on_rate_limit`](crate::GitHubBuilder::wait_on_rate_limit).
+    pub wait_on_rate_limit: Option<Duration>,
+    /// See [`GitHubBuilder::concurrency`](crate::GitHubBuilder::concurrency).
+    pub concurrency: usize,
+    /// See [`GitHubBuilder::max_requests_per_call`](crate::GitHubBuilder::max_requests_per_call).
+    pub max_requests_per_call: Option<usize>,
+    /// See [`GitHubBuilder::max_pages`](crate::GitHubBuilder::max_pages).
+    pub max_pages: usize,
+    /// See [`GitHubBuilder::max_response_bytes`](crate::GitHubBuilder::max_response_bytes).
+    pub max_response_bytes: u64,
+    /// See [`GitHubBuilder::on_budget`](crate::GitHubBuilder::on_budget).
+    pub on_budget: BudgetBehavior,
+    /// See [`GitHubBuilder::skip_archived`](crate::GitHubBuilder::skip_archived).
+    pub skip_archived: bool,
+    /// See [`GitHubBuilder::tag_scan_threshold`](crate::GitHubBuilder::tag_scan_threshold).
+    pub tag_scan_threshold: Option<usize>,
+    /// See [`GitHubBuilder::ignore_tag_v_prefix`](crate::GitHubBuilder::ignore_tag_v_prefix).
+    pub ignore_tag_v_prefix: bool,
+    /// See [`GitHubBuilder::version_source`](crate::GitHubBuilder::version_source).
+    pub version_source: VersionSource,
+    /// See [`GitHubBuilder::version_prefixes`](crate::GitHubBuilder::version_prefixes).
+    pub version_prefixes: Vec<String>,
+    /// See [`GitHubBuilder::ignore_tags`](crate::GitHubBuilder::ignore_tags).
+    pub ignored_tags: Vec<String>,
+    /// See [`GitHubBuilder::ignore_tags_matching`](crate::GitHubBuilder::ignore_tags_matching).
+    pub ignored_tag_pattern: Option<String>,
+    /// See [`GitHubBuilder::hide_ignored_tags`](crate::GitHubBuilder::hide_ignored_tags).
+    pub hide_ignored_tags: bool,
+    /// See [`GitHubBuilder::dedupe_versions`](crate::GitHubBuilder::dedupe_versions).
+    pub dedupe_versions: bool,
+    /// See [`GitHubBuilder::include_drafts`](crate::GitHubBuilder::include_drafts).
+    pub include_drafts: bool,
+    /// See [`GitHubBuilder::latest_strategy`](crate::GitHubBuilder::latest_strategy).
+    pub latest_strategy: LatestStrategy,
+    /// Whether tags are compared with
+    /// [`GitHubBuilder::version_comparator`](crate::GitHubBuilder::version_comparator).
+    pub version_comparator: bool,
+    /// See [`GitHubBuilder::date_field`](crate::GitHubBuilder::date_field).
+    pub date_field: DateField,
+    /// See [`GitHubBuilder::restart_on_page_shift`](crate::GitHubBuilder::restart_on_page_shift).
+    pub restart_on_page_shift: bool,
+    /// See [`GitHubBuilder::coalesce_requests`](crate::GitHubBuilder::coalesce_requests).
+    pub coalesce_requests: bool,
+    /// See [`GitHubBuilder::retry_server_errors`](crate::GitHubBuilder::retry_server_errors).
+    pub server_error_retries: usize,
+    /// See [`GitHubBuilder::connect_timeout`](crate::GitHubBuilder::connect_timeout).
+    pub connect_timeout: Option<Duration>,
+    /// See [`GitHubBuilder::timeout`](crate::GitHubBuilder::timeout).
+    pub timeout: Duration,
+    /// See [`GitHubBuilder::correlation_header`](crate::GitHubBuilder::correlation_header).
+    pub correlation_header: Option<(String, String)>,
+    /// See [`GitHubBuilder::accept_language`](crate::GitHubBuilder::accept_language).
+    pub accept_language: Option<String>,
+    /// How many certificates are set with
+    /// [`GitHubBuilder::pin_certificates`](crate::GitHubBuilder::pin_certificates).
+    pub pinned_certificates: usize,
+    /// See [`GitHubBuilder::resolve_host`](crate::GitHubBuilder::resolve_host).
+    pub resolved_hosts: Vec<(String, SocketAddr)>,
+    /// Whether compressed responses are asked for, which needs the `gzip` or
+    /// `brotli` feature; see [`GitHubBuilder::compression`](crate::GitHubBuilder::compression).
+    pub compression: bool,
+    /// See [`GitHubBuilder::lenient_headers`](crate::GitHubBuilder::lenient_headers).
+    pub lenient_headers: bool,
+    /// See [`GitHubBuilder::circuit_breaker`](crate::GitHubBuilder::circuit_breaker).
+    pub circuit_breaker: Option<CircuitBreakerSnapshot>,
+    /// Whether the release feed is read when releases can't be fetched, which
+    /// needs the `atom` feature.
+    pub feed_fallback: bool,
+    /// The optional features the crate was built with.
+    pub features: Vec<String>,
+}
+
+impl GitHub {
+    /// Get how the instance is configured, for bug reports.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::builder().token("secret").build().unwrap();
+    /// let snapshot = github.config_snapshot();
+    /// assert!(snapshot.authenticated);
+    /// println!("{}", serde_json::to_string_pretty(&snapshot).unwrap());
+    /// ```
+    #[must_use]
+    pub fn config_snapshot(&self) -> ConfigSnapshot {
+        let settings = &self.settings;
+        ConfigSnapshot {
+            api_root: self.api_root.clone(),
+            web_root: self.web_root.clone(),
+            authenticated: self.authenticated,
+            per_page: PAGINATION_REQUEST_AMOUNT,
+            wait_on_rate_limit: settings.wait_on_rate_limit,
+            concurrency: settings.concurrency.unwrap_or(DEFAULT_CONCURRENCY),
+            max_requests_per_call: settings.max_requests_per_call,
+            max_pages: settings.max_pages.unwrap_or(DEFAULT_MAX_PAGES),
+            max_response_bytes: settings
+                .max_response_bytes
+                .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES),
+            on_budget: settings.on_budget,
+            skip_archived: settings.skip_archived,
+            tag_scan_threshold: settings.tag_scan_threshold,
+            ignore_tag_v_prefix: settings.ignore_tag_v_prefix,
+            version_source: settings.version_source,
+            version_prefixes: settings.version_prefixes.clone(),
+            ignored_tags: settings.ignored_tags.clone(),
+            ignored_tag_pattern: settings
+                .ignored_tag_pattern
+                .as_ref()
+                .map(|pattern| pattern.as_str().to_owned()),
+            hide_ignored_tags: settings.hide_ignored_tags,
+            dedupe_versions: settings.dedupe_versions,
+            include_drafts: settings.include_drafts,
+            latest_strategy: settings.latest_strategy,
+            version_comparator: settings.version_comparator.is_some(),
+            date_field: settings.date_field,
+            restart_on_page_shift: settings.restart_on_page_shift,
+            coalesce_requests: self.in_flight.is_some(),
+            server_error_retries: settings.server_error_retries,
+            connect_timeout: settings.connect_timeout,
+            timeout: settings.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            correlation_header: settings.correlation_header.clone(),
+            accept_language: settings.accept_language.clone(),
+            pinned_certificates: settings.pinned_certificates.len(),
+            resolved_hosts: settings.resolved_hosts.clone(),
+            compression: settings.compression,
+            lenient_headers: settings.lenient_headers,
+            circuit_breaker: self.breaker.as_ref().map(|breaker| {
+                let config = breaker.config();
+                CircuitBreakerSnapshot {
+                    threshold: config.threshold,
+                    window: config.window,
+                    cool_down: config.cool_down,
+                }
+            }),
+            #[cfg(feature = "atom")]
+            feed_fallback: settings.feed_fallback,
+            #[cfg(not(feature = "atom"))]
+            feed_fallback: false,
+            features: FEATURES
+                .iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(name, _)| (*name).to_owned())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BudgetBehavior, DateField, GitHub, LatestStrategy, VersionSource};
+    use std::time::Duration;
+
+    #[test]
+    fn test_defaults() {
+        let snapshot = GitHub::new().unwrap().config_snapshot();
+        assert_eq!(snapshot.api_root, "https://api.github.com/");
+        assert_eq!(snapshot.web_root, "https://github.com/");
+        assert!(!snapshot.authenticated);
+        assert_eq!(snapshot.per_page, 100);
+        assert_eq!(snapshot.concurrency, 4);
+        assert_eq!(snapshot.max_pages, 1000);
+        assert_eq!(snapshot.max_response_bytes, 20 * 1024 * 1024);
+        assert_eq!(snapshot.timeout, Duration::from_secs(30));
+        assert!(snapshot.compression);
+        assert_eq!(snapshot.circuit_breaker, None);
+        assert!(snapshot.resolved_hosts.is_empty());
+        assert_eq!(
+            snapshot.features.contains(&"atom".to_owned()),
+            cfg!(feature = "atom")
+        );
+    }
+
+    #[test]
+    fn test_every_option_shows_up() {
+        let builder = GitHub::builder()
+            .api_root("https://github.example.com/api/v3/")
+            .web_root("https://github.example.com/")
+            .token("super-secret-token")
+            .wait_on_rate_limit(Duration::from_secs(90))
+            .concurrency(8)
+            .max_requests_per_call(12)
+            .max_pages(50)
+            .max_response_bytes(4096)
+            .on_budget(BudgetBehavior::Truncate)
+            .skip_archived(true)
+            .tag_scan_threshold(5)
+            .ignore_tag_v_prefix(true)
+            .version_source(VersionSource::NameFirst)
+            .version_prefixes(&["app-"])
+            .ignore_tags(&["v0.0.0"])
+            .ignore_tags_matching("^nightly")
+            .hide_ignored_tags(true)
+            .dedupe_versions(true)
+            .include_drafts(true)
+            .latest_strategy(LatestStrategy::MarkedLatest)
+            .version_comparator(Box::new(|a: &str, b: &str| a.cmp(b)))
+            .date_field(DateField::Created)
+            .restart_on_page_shift(true)
+            .coalesce_requests(true)
+            .retry_server_errors(2)
+            .connect_timeout(Duration::from_secs(3))
+            .timeout(Duration::from_secs(20))
+            .correlation_header("X-Request-Id", "deploy-4821")
+            .accept_language("de-DE")
+            .resolve_host("github.example.com", "10.0.0.5:443".parse().unwrap())
+            .compression(false)
+            .lenient_headers(true)
+            .circuit_breaker(3, Duration::from_secs(60), Duration::from_secs(120));
+        #[cfg(feature = "atom")]
+        let builder = builder.feed_fallback(true);
+        let github = builder.build().unwrap();
+        let snapshot = github.config_snapshot();
+
+        assert_eq!(snapshot.api_root, "https://github.example.com/api/v3/");
+        assert_eq!(snapshot.web_root, "https://github.example.com/");
+        assert!(snapshot.authenticated);
+        assert_eq!(snapshot.wait_on_rate_limit, Some(Duration::from_secs(90)));
+        assert_eq!(snapshot.concurrency, 8);
+        assert_eq!(snapshot.max_requests_per_call, Some(12));
+        assert_eq!(snapshot.max_pages, 50);
+        assert_eq!(snapshot.max_response_bytes, 4096);
+        assert_eq!(snapshot.on_budget, BudgetBehavior::Truncate);
+        assert!(snapshot.skip_archived);
+        assert_eq!(snapshot.tag_scan_threshold, Some(5));
+        assert!(snapshot.ignore_tag_v_prefix);
+        assert_eq!(snapshot.version_source, VersionSource::NameFirst);
+        assert_eq!(snapshot.version_prefixes, vec!["app-"]);
+        assert_eq!(snapshot.ignored_tags, vec!["v0.0.0"]);
+        assert_eq!(snapshot.ignored_tag_pattern.as_deref(), Some("^nightly"));
+        assert!(snapshot.hide_ignored_tags);
+        assert!(snapshot.dedupe_versions);
+        assert!(snapshot.include_drafts);
+        assert_eq!(snapshot.latest_strategy, LatestStrategy::MarkedLatest);
+        assert!(snapshot.version_comparator);
+        assert_eq!(snapshot.date_field, DateField::Created);
+        assert!(snapshot.restart_on_page_shift);
+        assert!(snapshot.coalesce_requests);
+        assert_eq!(snapshot.server_error_retries, 2);
+        assert_eq!(snapshot.connect_timeout, Some(Duration::from_secs(3)));
+        assert_eq!(snapshot.timeout, Duration::from_secs(20));
+        assert_eq!(
+            snapshot.correlation_header,
+            Some(("X-Request-Id".to_owned(), "deploy-4821".to_owned()))
+        );
+        assert_eq!(snapshot.accept_language.as_deref(), Some("de-DE"));
+        assert_eq!(
+            snapshot.resolved_hosts,
+            vec![(
+                "github.example.com".to_owned(),
+                "10.0.0.5:443".parse().unwrap()
+            )]
+        );
+        assert!(!snapshot.compression);
+        assert!(snapshot.lenient_headers);
+        let breaker = snapshot.circuit_breaker.unwrap();
+        assert_eq!(breaker.threshold, 3);
+        assert_eq!(breaker.window, Duration::from_secs(60));
+        assert_eq!(breaker.cool_down, Duration::from_secs(120));
+        assert_eq!(snapshot.feed_fallback, cfg!(feature = "atom"));
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let debug = format!("{snapshot:?}");
+        assert!(!json.contains("super-secret-token"));
+        assert!(!debug.contains("super-secret-token"));
+    }
+}
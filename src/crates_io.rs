@@ -0,0 +1,300 @@
+//! Published versions of crates on crates.io.
+
+use crate::{error_from_response, generate_headers, LookupError, Result};
+use log::debug;
+use reqwest::blocking::{Client, ClientBuilder};
+use semver::Version;
+use serde::Deserialize;
+
+/// The crates.io sparse index root endpoint.
+pub const DEFAULT_CRATES_INDEX_ROOT: &str = "https://index.crates.io/";
+
+/// A line of a crate's sparse index file, one per published version.
+///
+/// Only the keys needed here are read; the rest are documented in
+/// [the Cargo book](https://doc.rust-lang.org/cargo/reference/registry-index.html#json-schema).
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// Struct to look up published crate versions through the crates.io sparse index.
+///
+/// The sparse index is served from a CDN without the crates.io API's crawler
+/// policy, so it's the friendlier way to ask about versions.
+///
+/// Yanked versions are left out unless [`CratesIo::include_yanked`] is set.
+///
+/// Only available with the `crates-io` feature.
+#[derive(Debug, Clone)]
+pub struct CratesIo {
+    client: Client,
+    index_root: String,
+    include_yanked: bool,
+}
+
+impl CratesIo {
+    /// Create a new instance of the struct for <https://crates.io>.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::CratesIo;
+    /// let crates_io = CratesIo::new().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed.
+    pub fn new() -> Result<Self> {
+        Self::from_custom(DEFAULT_CRATES_INDEX_ROOT)
+    }
+
+    /// Create a new instance of the struct for another sparse index, like a mirror.
+    ///
+    /// The `index_root` should end in a trailing slash.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::CratesIo;
+    /// let crates_io = CratesIo::from_custom("https://crates-mirror.example.com/index/").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed.
+    pub fn from_custom(index_root: &str) -> Result<Self> {
+        let client = ClientBuilder::new()
+            .default_headers(generate_headers(None)?)
+            .build()?;
+        Ok(Self {
+            client,
+            index_root: index_root.to_owned(),
+            include_yanked: false,
+        })
+    }
+
+    /// Set whether yanked versions are included in lookups.
+    #[must_use]
+    pub fn include_yanked(mut self, include: bool) -> Self {
+        self.include_yanked = include;
+        self
+    }
+
+    /// Get all published version strings of the crate, oldest first.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::CratesIo;
+    /// let crates_io = CratesIo::new().unwrap();
+    /// let versions_result = crates_io.get_all_versions("github_release_check");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the HTTP request cannot be sent, or the index returns
+    /// a status code indicating something other than a success. A crate that doesn't
+    /// exist results in [`LookupError::RepositoryNotFound`], and a name that can't
+    /// be a crate's in [`LookupError::InvalidRepository`].
+    pub fn get_all_versions(&self, crate_name: &str) -> Result<Vec<String>> {
+        let url = format!("{}{}", self.index_root, index_path(crate_name)?);
+        debug!("Querying crates.io index at {url}");
+        let response = self.client.get(&url).send()?;
+        if !response.status().is_success() {
+            debug!("Got status \"{}\" from crates.io index", response.status());
+            return Err(error_from_response(response));
+        }
+        let text = response.text()?;
+        let mut versions = Vec::new();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            // like Cargo, skip lines that can't be understood rather than failing
+            let entry: IndexEntry = match serde_json::from_str(line) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    debug!("Skipping index line that could not be parsed: {e}");
+                    continue;
+                }
+            };
+            if self.include_yanked || !entry.yanked {
+                versions.push(entry.vers);
+            }
+        }
+        Ok(versions)
+    }
+
+    /// Get the latest published version of the crate.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::CratesIo;
+    /// let crates_io = CratesIo::new().unwrap();
+    /// let version_result = crates_io.get_latest_version("github_release_check");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions`, or
+    /// if there are no (non-yanked) versions.
+    pub fn get_latest_version(&self, crate_name: &str) -> Result<Version> {
+        self.get_all_versions(crate_name)?
+            .iter()
+            .filter_map(|v| Version::parse(v).ok())
+            .max()
+            .ok_or(LookupError::NoReleases)
+    }
+}
+
+/// Get the path of a crate's file in the index, which is bucketed by name length.
+///
+/// Crate names are ASCII letters, digits, `_` and `-`, so anything else is
+/// rejected before the name is split into buckets.
+fn index_path(crate_name: &str) -> Result<String> {
+    let valid = !crate_name.is_empty()
+        && crate_name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+    if !valid {
+        return Err(LookupError::InvalidRepository(crate_name.to_owned()));
+    }
+    let name = crate_name.to_ascii_lowercase();
+    Ok(match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    })
+}
+
+/// Pick the higher of a GitHub release and a crates.io version.
+///
+/// If only one of the lookups succeeded, its version is returned. If both failed,
+/// the GitHub error is returned.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::{max_of, CratesIo, GitHub};
+///
+/// let repository = "celeo/github_release_check";
+/// let github_latest = GitHub::new().unwrap().get_latest_version(repository);
+/// let crates_latest = CratesIo::new().unwrap().get_latest_version("github_release_check");
+/// let latest = max_of(github_latest, crates_latest).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// This function fails if both lookups failed.
+pub fn max_of(github_latest: Result<Version>, crates_latest: Result<Version>) -> Result<Version> {
+    match (github_latest, crates_latest) {
+        (Ok(a), Ok(b)) => Ok(a.max(b)),
+        (Ok(v), Err(_)) | (Err(_), Ok(v)) => Ok(v),
+        (Err(e), Err(_)) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{index_path, max_of, CratesIo};
+    use crate::LookupError;
+    use mockito::mock;
+    use semver::Version;
+
+    const INDEX: &str = r#"{"name":"foo","vers":"0.1.0","deps":[],"cksum":"","features":{},"yanked":false}
+{"name":"foo","vers":"1.0.0","deps":[],"cksum":"","features":{},"yanked":false}
+{"name":"foo","vers":"1.1.0","deps":[],"cksum":"","features":{},"yanked":true}
+"#;
+
+    fn crates_io() -> CratesIo {
+        CratesIo::from_custom(&format!("{}/", mockito::server_url())).unwrap()
+    }
+
+    #[test]
+    fn test_index_path() {
+        assert_eq!(index_path("a").unwrap(), "1/a");
+        assert_eq!(index_path("ab").unwrap(), "2/ab");
+        assert_eq!(index_path("abc").unwrap(), "3/a/abc");
+        assert_eq!(index_path("Serde_Json").unwrap(), "se/rd/serde_json");
+    }
+
+    #[test]
+    fn test_invalid_crate_name() {
+        for name in ["", "éa", "ab/c", "serde json", "dé"] {
+            assert!(
+                matches!(
+                    index_path(name),
+                    Err(LookupError::InvalidRepository(n)) if n == name
+                ),
+                "{name:?}"
+            );
+        }
+        assert!(matches!(
+            crates_io().get_all_versions(""),
+            Err(LookupError::InvalidRepository(_))
+        ));
+        assert!(matches!(
+            crates_io().get_latest_version("éa"),
+            Err(LookupError::InvalidRepository(_))
+        ));
+    }
+
+    #[test]
+    fn test_versions_skip_yanked() {
+        let _m = mock("GET", "/3/f/foo").with_body(INDEX).create();
+        let crates_io = crates_io();
+        assert_eq!(
+            crates_io.get_all_versions("foo").unwrap(),
+            vec!["0.1.0", "1.0.0"]
+        );
+        assert_eq!(
+            crates_io.get_latest_version("foo").unwrap(),
+            Version::new(1, 0, 0)
+        );
+        let crates_io = crates_io.include_yanked(true);
+        assert_eq!(
+            crates_io.get_latest_version("foo").unwrap(),
+            Version::new(1, 1, 0)
+        );
+    }
+
+    #[test]
+    fn test_yanked_only() {
+        let _m = mock("GET", "/3/f/foo")
+            .with_body(
+                r#"{"name":"foo","vers":"1.0.0","deps":[],"cksum":"","features":{},"yanked":true}"#,
+            )
+            .create();
+        assert!(matches!(
+            crates_io().get_latest_version("foo"),
+            Err(LookupError::NoReleases)
+        ));
+    }
+
+    #[test]
+    fn test_missing_crate() {
+        let _m = mock("GET", "/3/f/foo").with_status(404).create();
+        assert!(matches!(
+            crates_io().get_latest_version("foo"),
+            Err(LookupError::RepositoryNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_max_of() {
+        let max = max_of(Ok(Version::new(1, 2, 0)), Ok(Version::new(1, 3, 0))).unwrap();
+        assert_eq!(max, Version::new(1, 3, 0));
+        let max = max_of(Err(LookupError::NoReleases), Ok(Version::new(1, 3, 0))).unwrap();
+        assert_eq!(max, Version::new(1, 3, 0));
+        assert!(matches!(
+            max_of(
+                Err(LookupError::NoReleases),
+                Err(LookupError::RepositoryNotFound)
+            ),
+            Err(LookupError::NoReleases)
+        ));
+    }
+}
@@ -0,0 +1,211 @@
+//! Collapsing tags that are the same version, like `1.0.0` and `v1.0.0`.
+
+use crate::{versions, DateField, GitHub, GitHubReleaseItem, LookupError, RepositorySpec, Result};
+use semver::Version;
+use std::collections::HashMap;
+
+/// A tag left out because another tag has the same version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionAlias {
+    /// The tag that was kept.
+    pub kept: String,
+    /// The tag that was left out.
+    pub discarded: String,
+}
+
+/// Release versions with tags of the same version collapsed into one.
+///
+/// See [`GitHub::get_all_versions_deduped`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DedupedVersions {
+    /// The tags that were kept, in the order the API returns them.
+    pub versions: Vec<String>,
+    /// The tags that were left out, and which tag each was collapsed into.
+    pub aliases: Vec<VersionAlias>,
+}
+
+/// Whether `candidate` should be kept over `kept` when both have the same version.
+///
+/// Published releases win over drafts, and otherwise the later-published release,
+/// as in [`DateField::compare`].
+fn preferred(candidate: &GitHubReleaseItem, kept: &GitHubReleaseItem) -> bool {
+    let published =
+        |release: &GitHubReleaseItem| !release.draft && !release.published_at.is_empty();
+    published(candidate)
+        .cmp(&published(kept))
+        .then_with(|| DateField::Published.compare(candidate, kept))
+        .is_gt()
+}
+
+impl GitHub {
+    /// Collapse releases whose tags are the same version, keeping the preferred one.
+    ///
+    /// Tags that aren't versions are all kept.
+    pub(crate) fn dedupe_versions(
+        &self,
+        releases: Vec<GitHubReleaseItem>,
+    ) -> (Vec<GitHubReleaseItem>, Vec<VersionAlias>) {
+        let mut kept: Vec<GitHubReleaseItem> = Vec::with_capacity(releases.len());
+        let mut by_version: HashMap<Version, usize> = HashMap::new();
+        let mut aliases = Vec::new();
+        for release in releases {
+            let Some(version) =
+                versions::parse_tag(&release.tag_name, &self.settings.version_prefixes)
+            else {
+                kept.push(release);
+                continue;
+            };
+            let Some(&index) = by_version.get(&version) else {
+                let _prev = by_version.insert(version, kept.len());
+                kept.push(release);
+                continue;
+            };
+            let discarded = if preferred(&release, &kept[index]) {
+                std::mem::replace(&mut kept[index], release)
+            } else {
+                release
+            };
+            aliases.push(VersionAlias {
+                kept: kept[index].tag_name.clone(),
+                discarded: discarded.tag_name,
+            });
+        }
+        (kept, aliases)
+    }
+
+    /// Get all release version strings from the repository, with tags of the
+    /// same version collapsed into one, and the tags that were left out.
+    ///
+    /// Tags are compared by the version they're read as, so `1.0.0` and `v1.0.0`
+    /// are the same. Of those, the tag of a published release is kept over a
+    /// draft, and otherwise the tag of the later-published release. The versions
+    /// are what [`GitHub::get_all_versions`] returns with
+    /// [`GitHubBuilder::dedupe_versions`](crate::GitHubBuilder::dedupe_versions) set.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let deduped = github.get_all_versions_deduped("celeo/github_release_check").unwrap();
+    /// for alias in deduped.aliases {
+    ///     println!("{} is the same version as {}", alias.discarded, alias.kept);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions`.
+    pub fn get_all_versions_deduped<R>(&self, repository: R) -> Result<DedupedVersions>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let releases = self
+            .query(repository)?
+            .into_iter()
+            .filter(|release| {
+                !(self.settings.hide_ignored_tags && self.is_ignored(&release.tag_name))
+            })
+            .collect();
+        let (kept, aliases) = self.dedupe_versions(releases);
+        Ok(DedupedVersions {
+            versions: kept.into_iter().map(|release| release.tag_name).collect(),
+            aliases,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VersionAlias;
+    use crate::{
+        tests::{permutations, release_json},
+        GitHub, GitHubReleaseItem,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    fn release(id: usize, tag: &str, draft: bool, published: &str) -> String {
+        release_json(
+            tag,
+            json!({ "id": id, "draft": draft, "published_at": published }),
+        )
+        .to_string()
+    }
+
+    fn github(dedupe: bool) -> GitHub {
+        GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .dedupe_versions(dedupe)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dedupe_versions() {
+        let _m = mock("GET", "/repos/foo/dupes/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}, {}, {}]",
+                release(4, "nightly", false, "2024-04-01T00:00:00Z"),
+                release(3, "1.1.0", false, "2024-03-01T00:00:00Z"),
+                release(2, "1.0.0", false, "2024-01-01T00:00:00Z"),
+                release(1, "v1.0.0", false, "2024-02-01T00:00:00Z"),
+            ))
+            .create();
+        assert_eq!(
+            github(false).get_all_versions("foo/dupes").unwrap(),
+            vec!["nightly", "1.1.0", "1.0.0", "v1.0.0"]
+        );
+        assert_eq!(
+            github(true).get_all_versions("foo/dupes").unwrap(),
+            vec!["nightly", "1.1.0", "v1.0.0"]
+        );
+        let deduped = github(false).get_all_versions_deduped("foo/dupes").unwrap();
+        assert_eq!(deduped.versions, vec!["nightly", "1.1.0", "v1.0.0"]);
+        assert_eq!(
+            deduped.aliases,
+            vec![VersionAlias {
+                kept: String::from("v1.0.0"),
+                discarded: String::from("1.0.0"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_dedupe_ties_ignore_order() {
+        let same = "2024-01-01T00:00:00Z";
+        let releases: Vec<GitHubReleaseItem> = [
+            release(7, "1.0.0", false, same),
+            release(9, "v1.0.0", false, same),
+            release(8, "V1.0.0", false, same),
+        ]
+        .iter()
+        .map(|json| serde_json::from_str(json).unwrap())
+        .collect();
+        for shuffled in permutations(&releases) {
+            let (kept, aliases) = github(false).dedupe_versions(shuffled);
+            assert_eq!(kept.len(), 1);
+            assert_eq!(kept[0].tag_name, "v1.0.0");
+            assert_eq!(aliases.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_dedupe_prefers_published() {
+        let _m = mock("GET", "/repos/foo/draft-dupes/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}]",
+                release(2, "v2.0.0", true, ""),
+                release(1, "2.0.0", false, "2024-01-01T00:00:00Z"),
+            ))
+            .create();
+        let deduped = github(true)
+            .get_all_versions_deduped("foo/draft-dupes")
+            .unwrap();
+        assert_eq!(deduped.versions, vec!["2.0.0"]);
+        assert_eq!(deduped.aliases[0].discarded, "v2.0.0");
+    }
+}
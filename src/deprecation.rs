@@ -0,0 +1,163 @@
+//! Noticing when the API says an endpoint is deprecated or going away.
+
+use crate::{redact_url, GitHub};
+use log::warn;
+use once_cell::sync::Lazy;
+use reqwest::blocking::Response;
+use std::{
+    collections::HashSet,
+    sync::{Mutex, PoisonError},
+};
+
+/// The endpoints a deprecation has been logged for, so each is only logged once per process.
+static WARNED: Lazy<Mutex<HashSet<String>>> = Lazy::new(Mutex::default);
+
+/// The `Deprecation` and `Sunset` headers of a response.
+///
+/// See [`GitHub::api_deprecations`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecationNotice {
+    /// The URL of the request the headers came with, without any credentials.
+    pub url: String,
+    /// The `Deprecation` header, like `"true"` or a date, if there was one.
+    pub deprecation: Option<String>,
+    /// The `Sunset` header, the date the endpoint stops working, if there was one.
+    pub sunset: Option<String>,
+}
+
+impl DeprecationNotice {
+    /// Read the headers, if the response has either of them.
+    fn from_response(response: &Response) -> Option<Self> {
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned)
+        };
+        let deprecation = header("deprecation");
+        let sunset = header("sunset");
+        if deprecation.is_none() && sunset.is_none() {
+            return None;
+        }
+        Some(Self {
+            url: redact_url(response.url().as_str()),
+            deprecation,
+            sunset,
+        })
+    }
+
+    /// The URL without its query, so that every page of an endpoint counts as one.
+    fn endpoint(&self) -> &str {
+        self.url.split('?').next().unwrap_or_default()
+    }
+}
+
+/// Log a warning for the endpoint, unless one was already logged.
+fn warn_once(notice: &DeprecationNotice) {
+    let first = WARNED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(notice.endpoint().to_owned());
+    if first {
+        warn!(
+            "GitHub API endpoint {} is deprecated (deprecation: {}, sunset: {})",
+            notice.endpoint(),
+            notice.deprecation.as_deref().unwrap_or("-"),
+            notice.sunset.as_deref().unwrap_or("-"),
+        );
+    }
+}
+
+/// The most recent deprecation notice, shared between clones of a `GitHub` instance.
+#[derive(Debug, Default)]
+pub(crate) struct SharedDeprecation(Mutex<Option<DeprecationNotice>>);
+
+impl SharedDeprecation {
+    /// Keep the notice from the response, if it has one, and warn about it.
+    pub(crate) fn update(&self, response: &Response) {
+        if let Some(notice) = DeprecationNotice::from_response(response) {
+            warn_once(&notice);
+            *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(notice);
+        }
+    }
+}
+
+impl GitHub {
+    /// Get the most recent deprecation notice the API sent, if any.
+    ///
+    /// GitHub sends `Deprecation` and `Sunset` headers with responses from
+    /// endpoints that are going away. They're also logged as a warning, once
+    /// per endpoint for the whole process. Like the rate limit, the notice is
+    /// shared between clones of the instance.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let _releases = github.query("celeo/github_release_check").unwrap();
+    /// if let Some(notice) = github.api_deprecations() {
+    ///     eprintln!("{} is deprecated, sunset {:?}", notice.url, notice.sunset);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn api_deprecations(&self) -> Option<DeprecationNotice> {
+        self.deprecation
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        logging::tests::captured_logs,
+        tests::{mock_github, release_json},
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    #[test]
+    fn test_deprecation_notice() {
+        let _m = mock("GET", "/repos/foo/deprecated/releases")
+            .match_query(Matcher::Any)
+            .with_header("deprecation", "@1718928000")
+            .with_header("sunset", "Wed, 31 Dec 2025 23:59:59 GMT")
+            .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+            .expect(2)
+            .create();
+        drop(captured_logs());
+        let github = mock_github();
+        assert_eq!(github.api_deprecations(), None);
+
+        let _releases = github.query("foo/deprecated").unwrap();
+        let notice = github.clone().api_deprecations().unwrap();
+        assert!(notice.url.contains("/repos/foo/deprecated/releases"));
+        assert_eq!(notice.deprecation.as_deref(), Some("@1718928000"));
+        assert_eq!(
+            notice.sunset.as_deref(),
+            Some("Wed, 31 Dec 2025 23:59:59 GMT")
+        );
+
+        let _releases = github.query("foo/deprecated").unwrap();
+        let warnings = captured_logs()
+            .iter()
+            .filter(|line| line.contains("/repos/foo/deprecated/releases is deprecated"))
+            .count();
+        assert_eq!(warnings, 1);
+    }
+
+    #[test]
+    fn test_no_notice_without_headers() {
+        let _m = mock("GET", "/repos/foo/current/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+            .create();
+        let github = mock_github();
+        let _releases = github.query("foo/current").unwrap();
+        assert_eq!(github.api_deprecations(), None);
+    }
+}
@@ -0,0 +1,324 @@
+//! Telling what changed between two scans, or two lists of releases.
+
+use crate::{GitHubReleaseItem, ScanReport};
+use semver::Version;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// How the latest version of a repository changed between two scans.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionChange {
+    /// The latest version in the older scan, or `None` if its lookup failed.
+    pub from: Option<Version>,
+    /// The latest version in the newer scan, or `None` if its lookup failed.
+    pub to: Option<Version>,
+}
+
+/// What changed between two scans, from [`ScanReport::diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ScanDiff {
+    /// Repositories only in the newer scan.
+    pub added: Vec<String>,
+    /// Repositories only in the older scan.
+    pub removed: Vec<String>,
+    /// Repositories in both scans whose latest version changed.
+    pub changed: BTreeMap<String, VersionChange>,
+}
+
+impl ScanDiff {
+    /// Whether the scans found the same.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl ScanReport {
+    /// Compare this scan with an older one.
+    ///
+    /// Only the latest versions are compared, so a repository whose lookup
+    /// failed in one of the scans is changed to or from `None`, and the
+    /// times of the scans don't count as a change.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{GitHub, ScanReport};
+    /// let github = GitHub::new().unwrap();
+    /// let yesterday =
+    ///     ScanReport::from_json(&std::fs::read_to_string("releases.json").unwrap()).unwrap();
+    /// let today = github.scan(&["celeo/github_release_check"]);
+    /// for (repository, change) in &today.diff(&yesterday).changed {
+    ///     println!("{repository}: {:?} -> {:?}", change.from, change.to);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn diff(&self, older: &ScanReport) -> ScanDiff {
+        let mut diff = ScanDiff::default();
+        for (repository, scan) in &self.repositories {
+            match older.repositories.get(repository) {
+                None => diff.added.push(repository.clone()),
+                Some(old) if old.latest_version != scan.latest_version => {
+                    let _prev = diff.changed.insert(
+                        repository.clone(),
+                        VersionChange {
+                            from: old.latest_version.clone(),
+                            to: scan.latest_version.clone(),
+                        },
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = older
+            .repositories
+            .keys()
+            .filter(|repository| !self.repositories.contains_key(*repository))
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
+/// A release named in a [`ReleaseDiff`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseRef {
+    /// ID of the release.
+    pub id: usize,
+    /// Name of the tag the release is for.
+    pub tag_name: String,
+    /// Link to the release page.
+    pub html_url: String,
+}
+
+impl From<&GitHubReleaseItem> for ReleaseRef {
+    fn from(release: &GitHubReleaseItem) -> Self {
+        Self {
+            id: release.id,
+            tag_name: release.tag_name.clone(),
+            html_url: release.html_url.clone(),
+        }
+    }
+}
+
+/// A release in both lists that was edited in between.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseChange {
+    /// The release, as it is in the newer list.
+    pub release: ReleaseRef,
+    /// The fields that differ, like `"body"` or `"assets"`.
+    pub fields: Vec<String>,
+}
+
+/// What changed between two lists of releases, from [`diff_releases`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReleaseDiff {
+    /// Releases only in the newer list.
+    pub added: Vec<ReleaseRef>,
+    /// Releases only in the older list, like deleted ones.
+    pub removed: Vec<ReleaseRef>,
+    /// Releases in both lists that were edited.
+    pub changed: Vec<ReleaseChange>,
+}
+
+impl ReleaseDiff {
+    /// Whether the lists hold the same releases.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// The fields of the release that differ between the two versions of it.
+///
+/// Asset download counts change all the time, so assets are compared by
+/// name, size, and when they were last replaced.
+fn changed_fields(new: &GitHubReleaseItem, old: &GitHubReleaseItem) -> Vec<String> {
+    let assets = |release: &GitHubReleaseItem| -> Vec<(String, u64, String)> {
+        release
+            .assets
+            .iter()
+            .map(|asset| (asset.name.clone(), asset.size, asset.updated_at.clone()))
+            .collect()
+    };
+    [
+        ("id", new.id != old.id),
+        ("tag_name", new.tag_name != old.tag_name),
+        ("name", new.name != old.name),
+        ("body", new.body != old.body),
+        ("draft", new.draft != old.draft),
+        ("prerelease", new.prerelease != old.prerelease),
+        ("published_at", new.published_at != old.published_at),
+        (
+            "target_commitish",
+            new.target_commitish != old.target_commitish,
+        ),
+        ("assets", assets(new) != assets(old)),
+    ]
+    .into_iter()
+    .filter(|(_, changed)| *changed)
+    .map(|(field, _)| field.to_owned())
+    .collect()
+}
+
+/// Compare two lists of releases of the same repository.
+///
+/// Releases are matched by ID, or by tag for those whose ID has no match, so
+/// a release that was deleted and published again under the same tag counts
+/// as changed rather than removed and added. The buckets keep the order of
+/// the lists they came from.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::{diff_releases, GitHub};
+/// let github = GitHub::new().unwrap();
+/// let old = github.query("celeo/github_release_check").unwrap();
+/// // ... later
+/// let new = github.query("celeo/github_release_check").unwrap();
+/// for removed in diff_releases(&new, &old).removed {
+///     println!("{} was deleted", removed.tag_name);
+/// }
+/// ```
+#[must_use]
+pub fn diff_releases(new: &[GitHubReleaseItem], old: &[GitHubReleaseItem]) -> ReleaseDiff {
+    let mut matched = HashSet::new();
+    // IDs first, so a tag match can't take the release another one has the ID of
+    let mut indexes: Vec<Option<usize>> = new
+        .iter()
+        .map(|release| {
+            let index = old.iter().position(|o| o.id == release.id)?;
+            matched.insert(index).then_some(index)
+        })
+        .collect();
+    for (release, index) in new.iter().zip(&mut indexes) {
+        if index.is_none() {
+            *index = (0..old.len())
+                .find(|i| !matched.contains(i) && old[*i].tag_name == release.tag_name);
+            if let Some(i) = *index {
+                let _new = matched.insert(i);
+            }
+        }
+    }
+    let mut pairs = Vec::new();
+    let mut added = Vec::new();
+    for (release, index) in new.iter().zip(indexes) {
+        match index {
+            Some(index) => pairs.push((release, &old[index])),
+            None => added.push(ReleaseRef::from(release)),
+        }
+    }
+    let changed = pairs
+        .into_iter()
+        .filter_map(|(new, old)| {
+            let fields = changed_fields(new, old);
+            (!fields.is_empty()).then(|| ReleaseChange {
+                release: ReleaseRef::from(new),
+                fields,
+            })
+        })
+        .collect();
+    let removed = old
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !matched.contains(i))
+        .map(|(_, release)| ReleaseRef::from(release))
+        .collect();
+    ReleaseDiff {
+        added,
+        removed,
+        changed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_releases;
+    use crate::{tests::release_json, GitHubReleaseItem, RepositoryScan, ScanReport};
+    use semver::Version;
+    use serde_json::json;
+
+    fn scan(version: Option<Version>) -> RepositoryScan {
+        RepositoryScan {
+            latest_tag: version.as_ref().map(|v| format!("v{v}")),
+            html_url: version.as_ref().map(|_| String::new()),
+            error: version.is_none().then(|| "repository not found".to_owned()),
+            error_code: version.is_none().then(|| "repo_not_found".to_owned()),
+            latest_version: version,
+            scanned_at: 1_700_000_000,
+        }
+    }
+
+    fn report(repositories: &[(&str, Option<Version>)]) -> ScanReport {
+        ScanReport {
+            repositories: repositories
+                .iter()
+                .map(|(name, version)| ((*name).to_owned(), scan(version.clone())))
+                .collect(),
+        }
+    }
+
+    fn release(id: usize, tag: &str, body: &str) -> GitHubReleaseItem {
+        serde_json::from_value(release_json(tag, json!({ "id": id, "body": body }))).unwrap()
+    }
+
+    #[test]
+    fn test_scan_diff() {
+        let older = report(&[
+            ("foo/same", Some(Version::new(1, 0, 0))),
+            ("foo/bumped", Some(Version::new(1, 0, 0))),
+            ("foo/broken", Some(Version::new(2, 0, 0))),
+            ("foo/dropped", Some(Version::new(1, 0, 0))),
+        ]);
+        let mut newer = report(&[
+            ("foo/same", Some(Version::new(1, 0, 0))),
+            ("foo/bumped", Some(Version::new(1, 1, 0))),
+            ("foo/broken", None),
+            ("foo/new", Some(Version::new(0, 1, 0))),
+        ]);
+        newer.repositories.get_mut("foo/same").unwrap().scanned_at += 86_400;
+
+        let diff = newer.diff(&older);
+        assert_eq!(diff.added, vec!["foo/new"]);
+        assert_eq!(diff.removed, vec!["foo/dropped"]);
+        let changed: Vec<_> = diff.changed.keys().map(String::as_str).collect();
+        assert_eq!(changed, vec!["foo/broken", "foo/bumped"]);
+        assert_eq!(diff.changed["foo/bumped"].to, Some(Version::new(1, 1, 0)));
+        assert_eq!(diff.changed["foo/broken"].to, None);
+        assert!(newer.diff(&newer).is_empty());
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["changed"]["foo/bumped"]["from"], "1.0.0");
+    }
+
+    #[test]
+    fn test_release_diff() {
+        let old = vec![
+            release(3, "v1.2.0", "notes"),
+            release(2, "v1.1.0", "notes"),
+            release(1, "v1.0.0", "notes"),
+        ];
+        let new = vec![
+            release(4, "v1.3.0", "notes"),
+            release(3, "v1.2.0", "fixed typo in notes"),
+            release(9, "v1.1.0", "notes"),
+        ];
+        let diff = diff_releases(&new, &old);
+
+        let tags = |releases: &[super::ReleaseRef]| -> Vec<String> {
+            releases.iter().map(|r| r.tag_name.clone()).collect()
+        };
+        assert_eq!(tags(&diff.added), vec!["v1.3.0"]);
+        assert_eq!(tags(&diff.removed), vec!["v1.0.0"]);
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].release.tag_name, "v1.2.0");
+        assert_eq!(diff.changed[0].fields, vec!["body"]);
+        // published again under the same tag
+        assert_eq!(diff.changed[1].release.id, 9);
+        assert_eq!(diff.changed[1].fields, vec!["id"]);
+        assert!(diff_releases(&old, &old).is_empty());
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["removed"][0]["id"], 1);
+    }
+}
@@ -0,0 +1,319 @@
+//! Finding the API root of a GitHub enterprise server.
+
+use crate::{logging::redact_url, Call, GitHub, LookupError, Result};
+use log::debug;
+use serde::Deserialize;
+
+/// Information about the GitHub instance that was detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerInfo {
+    /// The REST API root the instance answered on.
+    pub api_root: String,
+    /// The GitHub enterprise server version, if the instance reported one.
+    pub installed_version: Option<String>,
+    /// The login of the user the token belongs to, from [`GitHub::verify`] when
+    /// a token is set.
+    pub user_login: Option<String>,
+}
+
+/// The part of the `/meta` response that's used to recognize a GitHub API.
+#[derive(Debug, Deserialize)]
+struct Meta {
+    installed_version: Option<String>,
+    verifiable_password_authentication: Option<bool>,
+}
+
+impl Meta {
+    /// Whether the response has any of the fields a GitHub API sends.
+    fn is_github(&self) -> bool {
+        self.installed_version.is_some() || self.verifiable_password_authentication.is_some()
+    }
+}
+
+/// The part of the `/user` response that's used.
+#[derive(Debug, Deserialize)]
+struct User {
+    login: String,
+}
+
+impl GitHub {
+    /// Create a new instance of the struct for a GitHub enterprise server, given just its hostname.
+    ///
+    /// GitHub enterprise serves its API either under `/api/v3/` on the main host, or on an
+    /// `api.` subdomain. This function tries `https://{host}/api/v3/meta` and then
+    /// `https://api.{host}/meta`, and uses whichever answers like a GitHub API. The server
+    /// version it reports is available afterwards from [`GitHub::server_info`].
+    ///
+    /// `host` may include a scheme (like `"http://github.internal"`), in which case it's
+    /// used instead of `https`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::for_enterprise("github.your_domain_root.com", "your-access-token").unwrap();
+    /// println!("{:?}", github.server_info());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed, or with
+    /// [`LookupError::ApiRootNotFound`] (naming the attempted URLs) if
+    /// neither location answers like a GitHub API.
+    pub fn for_enterprise(host: &str, access_token: &str) -> Result<Self> {
+        let (scheme, host) = host.split_once("://").unwrap_or(("https", host));
+        let host = host.trim_end_matches('/');
+        let candidates = [
+            format!("{scheme}://{host}/api/v3/"),
+            format!("{scheme}://api.{host}/"),
+        ];
+        Self::detect_api_root(&candidates, access_token)
+    }
+
+    /// Use the first of the candidate API roots that answers like a GitHub API.
+    fn detect_api_root(candidates: &[String], access_token: &str) -> Result<Self> {
+        for api_root in candidates {
+            let mut github = Self::from_custom(api_root, access_token)?;
+            match github.probe_meta() {
+                Ok(info) => {
+                    debug!("Found GitHub API at {api_root}");
+                    github.server_info = Some(info);
+                    return Ok(github);
+                }
+                Err(e) => debug!("No GitHub API at {api_root}: {e}"),
+            }
+        }
+        Err(LookupError::ApiRootNotFound {
+            attempted: candidates.iter().map(|c| format!("{c}meta")).collect(),
+        })
+    }
+
+    /// Check that the API root serves GitHub's `/meta` endpoint.
+    fn probe_meta(&self) -> Result<ServerInfo> {
+        let url = format!("{}meta", self.api_root);
+        let response = self.client.get(&url).send()?;
+        if !response.status().is_success() {
+            return Err(LookupError::ErrorHttpResponse {
+                status: response.status().as_u16(),
+                request_id: crate::request_id(response.headers()),
+                message: crate::error_message(&crate::error_body(response)),
+            });
+        }
+        let meta: Meta = self.read_json(response)?;
+        if !meta.is_github() {
+            return Err(LookupError::ApiRootNotFound {
+                attempted: vec![url],
+            });
+        }
+        Ok(ServerInfo {
+            api_root: self.api_root.clone(),
+            installed_version: meta.installed_version,
+            user_login: None,
+        })
+    }
+
+    /// Check that the API root and token work, with a request to `/meta`, and
+    /// to `/user` if a token is set.
+    ///
+    /// Call it after building an instance to find a wrong API root, a proxy
+    /// that's down, or a revoked token before the first lookup does. Tokens
+    /// that can't read a user, like those of GitHub Apps, give no login rather
+    /// than failing.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::from_custom("https://github.your_domain.com/api/v3/", "your-access-token").unwrap();
+    /// let info = github.verify().unwrap();
+    /// println!("GitHub {:?} as {:?}", info.installed_version, info.user_login);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::NotAnApiEndpoint`] if the API root
+    /// doesn't answer like a GitHub API, [`LookupError::AuthenticationError`] if
+    /// the token isn't accepted, and otherwise for any of the reasons in
+    /// [`GitHub::query`], like [`LookupError::HttpClient`] if the server can't
+    /// be reached.
+    pub fn verify(&self) -> Result<ServerInfo> {
+        let url = format!("{}meta", self.api_root);
+        debug!("Verifying the API at {}", redact_url(&url));
+        let meta: Meta = match self.get(&url, &[], &mut Call::default()) {
+            Ok(response) => self.read_json(response)?,
+            Err(LookupError::RepositoryNotFound) => return Err(self.not_an_api_endpoint()),
+            Err(e) => return Err(e),
+        };
+        if !meta.is_github() {
+            return Err(self.not_an_api_endpoint());
+        }
+        let user_login = if self.is_authenticated() {
+            let url = format!("{}user", self.api_root);
+            match self.get(&url, &[], &mut Call::default()) {
+                Ok(response) => Some(self.read_json::<User>(response)?.login),
+                Err(LookupError::AuthenticationError { status: 403, .. }) => {
+                    debug!("The token can't read its user");
+                    None
+                }
+                Err(e) => return Err(e),
+            }
+        } else {
+            None
+        };
+        Ok(ServerInfo {
+            api_root: self.api_root.clone(),
+            installed_version: meta.installed_version,
+            user_login,
+        })
+    }
+
+    /// Get the information detected about the server by [`GitHub::for_enterprise`].
+    ///
+    /// Instances created any other way return `None`.
+    #[must_use]
+    pub fn server_info(&self) -> Option<&ServerInfo> {
+        self.server_info.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{GitHub, LookupError};
+    use mockito::{mock, Matcher};
+
+    const META: &str =
+        r#"{"verifiable_password_authentication": false, "installed_version": "3.9.2"}"#;
+
+    fn candidates() -> Vec<String> {
+        vec![
+            format!("{}/api/v3/", mockito::server_url()),
+            format!("{}/subdomain/", mockito::server_url()),
+        ]
+    }
+
+    #[test]
+    fn test_detects_api_v3_path() {
+        let _m = mock("GET", "/api/v3/meta").with_body(META).create();
+        let github = GitHub::detect_api_root(&candidates(), "abc").unwrap();
+        assert_eq!(github.api_root(), candidates()[0]);
+        assert!(github.is_authenticated());
+        let info = github.server_info().unwrap();
+        assert_eq!(info.api_root, candidates()[0]);
+        assert_eq!(info.installed_version.as_deref(), Some("3.9.2"));
+    }
+
+    #[test]
+    fn test_detects_api_subdomain() {
+        let _html = mock("GET", "/api/v3/meta")
+            .with_header("content-type", "text/html")
+            .with_body("<!DOCTYPE html><html></html>")
+            .create();
+        let _m = mock("GET", "/subdomain/meta")
+            .with_body(r#"{"verifiable_password_authentication": true}"#)
+            .create();
+        let github = GitHub::detect_api_root(&candidates(), "abc").unwrap();
+        let info = github.server_info().unwrap();
+        assert_eq!(info.api_root, candidates()[1]);
+        assert_eq!(info.installed_version, None);
+    }
+
+    #[test]
+    fn test_detects_nothing() {
+        let _m = mock("GET", "/api/v3/meta").with_status(404).create();
+        let res = GitHub::detect_api_root(&candidates(), "abc");
+        match res {
+            Err(e @ LookupError::ApiRootNotFound { .. }) => {
+                let message = e.to_string();
+                assert!(message.contains("/api/v3/meta"));
+                assert!(message.contains("/subdomain/meta"));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    fn github(path: &str, token: &str) -> GitHub {
+        GitHub::from_custom(&format!("{}/{path}/", mockito::server_url()), token).unwrap()
+    }
+
+    #[test]
+    fn test_verify() {
+        let _meta = mock("GET", "/verify-good/meta")
+            .match_header("authorization", "Bearer abc")
+            .with_body(META)
+            .create();
+        let _user = mock("GET", "/verify-good/user")
+            .match_header("authorization", "Bearer abc")
+            .with_body(r#"{"login": "octocat", "id": 1}"#)
+            .create();
+        let info = github("verify-good", "abc").verify().unwrap();
+        assert_eq!(
+            info.api_root,
+            format!("{}/verify-good/", mockito::server_url())
+        );
+        assert_eq!(info.installed_version.as_deref(), Some("3.9.2"));
+        assert_eq!(info.user_login.as_deref(), Some("octocat"));
+
+        // without a token, `/user` isn't asked for
+        let _public = mock("GET", "/verify-public/meta")
+            .with_body(r#"{"verifiable_password_authentication": true}"#)
+            .create();
+        let info = github("verify-public", "").verify().unwrap();
+        assert_eq!(info.installed_version, None);
+        assert_eq!(info.user_login, None);
+    }
+
+    #[test]
+    fn test_verify_app_token() {
+        let _meta = mock("GET", "/verify-app/meta").with_body(META).create();
+        let _user = mock("GET", "/verify-app/user")
+            .with_status(403)
+            .with_body(r#"{"message": "Resource not accessible by integration"}"#)
+            .create();
+        let info = github("verify-app", "abc").verify().unwrap();
+        assert_eq!(info.user_login, None);
+    }
+
+    #[test]
+    fn test_verify_html() {
+        let _m = mock("GET", "/verify-html/meta")
+            .with_header("content-type", "text/html")
+            .with_body("<!DOCTYPE html><html><body>Sign in</body></html>")
+            .create();
+        assert!(matches!(
+            github("verify-html", "abc").verify(),
+            Err(LookupError::NotAnApiEndpoint { .. })
+        ));
+        let _missing = mock("GET", "/verify-missing/meta")
+            .with_status(404)
+            .create();
+        assert!(matches!(
+            github("verify-missing", "").verify(),
+            Err(LookupError::NotAnApiEndpoint { .. })
+        ));
+        let _json = mock("GET", "/verify-json/meta")
+            .with_body(r#"{"status": "ok"}"#)
+            .create();
+        assert!(matches!(
+            github("verify-json", "").verify(),
+            Err(LookupError::NotAnApiEndpoint { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_revoked_token() {
+        let _m = mock("GET", "/verify-revoked/meta")
+            .match_header("authorization", Matcher::Any)
+            .with_status(401)
+            .with_body(r#"{"message": "Bad credentials"}"#)
+            .create();
+        assert!(matches!(
+            github("verify-revoked", "revoked").verify(),
+            Err(LookupError::AuthenticationError { status: 401, .. })
+        ));
+    }
+
+    #[test]
+    fn test_server_info_absent_by_default() {
+        assert!(GitHub::new().unwrap().server_info().is_none());
+    }
+}
@@ -0,0 +1,371 @@
+//! Stable codes for telling errors apart without reading their messages.
+
+use crate::LookupError;
+
+impl LookupError {
+    /// Get a short identifier for the kind of error, like `"rate_limited"`.
+    ///
+    /// Unlike the messages, which may be reworded in any release, codes are part
+    /// of the crate's stable API: a code is never changed or reused for another
+    /// kind of error, so they can be matched on, stored, or used to look up a
+    /// translated message. New kinds of errors get new codes. An error shared
+    /// between coalesced requests has the code of the error it wraps.
+    ///
+    /// | Code | Variant |
+    /// | --- | --- |
+    /// | `http_client` | [`LookupError::HttpClient`] |
+    /// | `invalid_header_value` | [`LookupError::HeaderValue`] |
+    /// | `invalid_header_name` | [`LookupError::HeaderName`] |
+    /// | `unreadable_header` | [`LookupError::HeaderToString`] |
+    /// | `invalid_pagination` | [`LookupError::PaginationParse`] |
+    /// | `no_releases` | [`LookupError::NoReleases`] |
+    /// | `repo_not_found` | [`LookupError::RepositoryNotFound`] |
+    /// | `invalid_repo` | [`LookupError::InvalidRepository`] |
+    /// | `release_not_found` | [`LookupError::ReleaseNotFound`] |
+    /// | `auth` | [`LookupError::AuthenticationError`] |
+    /// | `auth_required_for_drafts` | [`LookupError::AuthenticationRequiredForDrafts`] |
+    /// | `rate_limited` | [`LookupError::RateLimited`] |
+    /// | `secondary_rate_limited` | [`LookupError::SecondaryRateLimited`] |
+    /// | `budget_exceeded` | [`LookupError::BudgetExceeded`] |
+    /// | `pagination_loop` | [`LookupError::PaginationLoop`] |
+    /// | `timeout` | [`LookupError::Timeout`] |
+    /// | `http_error` | [`LookupError::ErrorHttpResponse`] |
+    /// | `not_modified` | [`LookupError::NotModified`] |
+    /// | `not_an_api_endpoint` | [`LookupError::NotAnApiEndpoint`] |
+    /// | `response_too_large` | [`LookupError::ResponseTooLarge`] |
+    /// | `invalid_response` | [`LookupError::InvalidResponse`] |
+    /// | `unexpected_body` | [`LookupError::UnexpectedBody`] |
+    /// | `certificate_pin_failed` | [`LookupError::CertificatePinFailed`] |
+    /// | `invalid_feed` | `LookupError::InvalidFeed`, with the `atom` feature |
+    /// | `local_git` | `LookupError::LocalGit`, with the `local-git` feature |
+    /// | `validation_failed` | `LookupError::ValidationFailed`, with the `write` feature |
+    /// | `token_not_found` | `LookupError::TokenNotFound`, with the `keyring` feature |
+    /// | `token_store` | `LookupError::TokenStore`, with the `keyring` feature |
+    /// | `invalid_version` | [`LookupError::InvalidVersion`] |
+    /// | `state_write` | [`LookupError::StateWrite`] |
+    /// | `invalid_tag_pattern` | [`LookupError::InvalidTagPattern`] |
+    /// | `invalid_asset_pattern` | [`LookupError::InvalidAssetPattern`] |
+    /// | `invalid_search_pattern` | [`LookupError::InvalidSearchPattern`] |
+    /// | `asset_not_found` | [`LookupError::AssetNotFound`] |
+    /// | `ambiguous_asset` | [`LookupError::AmbiguousAsset`] |
+    /// | `missing_template_variable` | [`LookupError::MissingTemplateVariable`] |
+    /// | `invalid_asset_template` | [`LookupError::InvalidAssetTemplate`] |
+    /// | `asset_cache` | [`LookupError::AssetCache`] |
+    /// | `asset_size_mismatch` | [`LookupError::AssetSizeMismatch`] |
+    /// | `api_root_not_found` | [`LookupError::ApiRootNotFound`] |
+    /// | `circuit_open` | [`LookupError::CircuitOpen`] |
+    /// | `fetch_panicked` | [`LookupError::FetchPanicked`] |
+    /// | `fixture_read` | `LookupError::FixtureRead`, with the `test-util` feature |
+    /// | `invalid_fixture` | `LookupError::InvalidFixture`, with the `test-util` feature |
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// if let Err(e) = github.get_latest_version("celeo/github_release_check") {
+    ///     match e.code() {
+    ///         "rate_limited" | "secondary_rate_limited" => eprintln!("try again later"),
+    ///         code => eprintln!("lookup failed ({code}): {e}"),
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        // no wildcard arm, so a new variant doesn't compile until it has a code
+        match self {
+            Self::HttpClient(_) => "http_client",
+            Self::HeaderValue(_) => "invalid_header_value",
+            Self::HeaderName(_) => "invalid_header_name",
+            Self::HeaderToString(_) => "unreadable_header",
+            Self::PaginationParse(_) => "invalid_pagination",
+            Self::NoReleases => "no_releases",
+            Self::RepositoryNotFound => "repo_not_found",
+            Self::InvalidRepository(_) => "invalid_repo",
+            Self::ReleaseNotFound => "release_not_found",
+            Self::AuthenticationError { .. } => "auth",
+            Self::AuthenticationRequiredForDrafts => "auth_required_for_drafts",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::SecondaryRateLimited { .. } => "secondary_rate_limited",
+            Self::BudgetExceeded { .. } => "budget_exceeded",
+            Self::PaginationLoop { .. } => "pagination_loop",
+            Self::Timeout { .. } => "timeout",
+            Self::ErrorHttpResponse { .. } => "http_error",
+            Self::NotModified => "not_modified",
+            Self::NotAnApiEndpoint { .. } => "not_an_api_endpoint",
+            Self::ResponseTooLarge { .. } => "response_too_large",
+            Self::InvalidResponse { .. } => "invalid_response",
+            Self::UnexpectedBody { .. } => "unexpected_body",
+            Self::CertificatePinFailed { .. } => "certificate_pin_failed",
+            #[cfg(feature = "atom")]
+            Self::InvalidFeed(_) => "invalid_feed",
+            #[cfg(feature = "local-git")]
+            Self::LocalGit(_) => "local_git",
+            #[cfg(feature = "write")]
+            Self::ValidationFailed { .. } => "validation_failed",
+            #[cfg(feature = "keyring")]
+            Self::TokenNotFound { .. } => "token_not_found",
+            #[cfg(feature = "keyring")]
+            Self::TokenStore { .. } => "token_store",
+            Self::InvalidVersion(_) => "invalid_version",
+            Self::StateWrite { .. } => "state_write",
+            Self::InvalidTagPattern(_) => "invalid_tag_pattern",
+            Self::InvalidAssetPattern(_) => "invalid_asset_pattern",
+            Self::InvalidSearchPattern(_) => "invalid_search_pattern",
+            Self::AssetNotFound => "asset_not_found",
+            Self::AmbiguousAsset { .. } => "ambiguous_asset",
+            Self::MissingTemplateVariable { .. } => "missing_template_variable",
+            Self::InvalidAssetTemplate { .. } => "invalid_asset_template",
+            Self::AssetCache { .. } => "asset_cache",
+            Self::AssetSizeMismatch { .. } => "asset_size_mismatch",
+            Self::ApiRootNotFound { .. } => "api_root_not_found",
+            Self::CircuitOpen { .. } => "circuit_open",
+            Self::Shared(error) => error.code(),
+            Self::FetchPanicked => "fetch_panicked",
+            #[cfg(feature = "test-util")]
+            Self::FixtureRead { .. } => "fixture_read",
+            #[cfg(feature = "test-util")]
+            Self::InvalidFixture { .. } => "invalid_fixture",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LookupError, TimeoutPhase};
+    use reqwest::header::{HeaderName, HeaderValue};
+    use std::{collections::HashSet, sync::Arc, time::SystemTime};
+
+    /// One of every variant; see [`check_listed`].
+    #[allow(clippy::too_many_lines)]
+    fn every_variant() -> Vec<LookupError> {
+        let io_error = || std::io::Error::other("disk full");
+        let unclosed = String::from("(");
+        let regex_error = || regex::Regex::new(&unclosed).unwrap_err();
+        let errors = vec![
+            LookupError::HttpClient(reqwest::blocking::get("not a url").unwrap_err()),
+            LookupError::HeaderValue(HeaderValue::from_str("\n").unwrap_err()),
+            LookupError::HeaderName(HeaderName::from_bytes(b" ").unwrap_err()),
+            LookupError::HeaderToString(
+                HeaderValue::from_bytes(&[0xff])
+                    .unwrap()
+                    .to_str()
+                    .unwrap_err(),
+            ),
+            LookupError::PaginationParse(String::new()),
+            LookupError::NoReleases,
+            LookupError::RepositoryNotFound,
+            LookupError::InvalidRepository(String::new()),
+            LookupError::ReleaseNotFound,
+            LookupError::AuthenticationError {
+                status: 401,
+                request_id: None,
+            },
+            LookupError::AuthenticationRequiredForDrafts,
+            LookupError::RateLimited {
+                reset_at: None,
+                request_id: None,
+            },
+            LookupError::SecondaryRateLimited {
+                retry_after: None,
+                request_id: None,
+            },
+            LookupError::BudgetExceeded {
+                fetched_pages: 1,
+                total_pages: None,
+            },
+            LookupError::PaginationLoop { pages_fetched: 2 },
+            LookupError::Timeout {
+                phase: TimeoutPhase::Connect,
+            },
+            LookupError::ErrorHttpResponse {
+                status: 500,
+                message: None,
+                request_id: None,
+            },
+            LookupError::NotModified,
+            LookupError::NotAnApiEndpoint {
+                api_root: String::new(),
+            },
+            LookupError::ResponseTooLarge {
+                limit: 1,
+                url: String::new(),
+            },
+            LookupError::InvalidResponse {
+                line: 1,
+                column: 1,
+                message: String::new(),
+            },
+            LookupError::UnexpectedBody {
+                body_snippet: String::new(),
+            },
+            LookupError::CertificatePinFailed {
+                host: String::new(),
+            },
+            #[cfg(feature = "atom")]
+            LookupError::InvalidFeed(String::new()),
+            #[cfg(feature = "local-git")]
+            LookupError::LocalGit(io_error()),
+            #[cfg(feature = "write")]
+            LookupError::ValidationFailed {
+                messages: Vec::new(),
+                request_id: None,
+            },
+            #[cfg(feature = "keyring")]
+            LookupError::TokenNotFound {
+                service: String::new(),
+            },
+            #[cfg(feature = "keyring")]
+            LookupError::TokenStore {
+                service: String::new(),
+                message: String::new(),
+            },
+            LookupError::InvalidVersion(String::new()),
+            LookupError::StateWrite {
+                path: String::new(),
+                source: io_error(),
+            },
+            LookupError::InvalidTagPattern(regex_error()),
+            LookupError::InvalidAssetPattern(regex_error()),
+            LookupError::InvalidSearchPattern(regex_error()),
+            LookupError::AssetNotFound,
+            LookupError::AmbiguousAsset { names: Vec::new() },
+            LookupError::MissingTemplateVariable {
+                name: String::new(),
+            },
+            LookupError::InvalidAssetTemplate {
+                template: String::new(),
+            },
+            LookupError::AssetCache {
+                path: String::new(),
+                source: io_error(),
+            },
+            LookupError::AssetSizeMismatch {
+                expected: 0,
+                received: 0,
+            },
+            LookupError::ApiRootNotFound {
+                attempted: Vec::new(),
+            },
+            LookupError::CircuitOpen {
+                retry_at: SystemTime::now(),
+            },
+            LookupError::Shared(Arc::new(LookupError::NoReleases)),
+            LookupError::FetchPanicked,
+            #[cfg(feature = "test-util")]
+            LookupError::FixtureRead {
+                path: String::new(),
+                source: io_error(),
+            },
+            #[cfg(feature = "test-util")]
+            LookupError::InvalidFixture {
+                path: String::new(),
+                line: 1,
+                column: 1,
+                message: String::new(),
+            },
+        ];
+        errors.iter().for_each(check_listed);
+        errors
+    }
+
+    /// A match with no wildcard arm, so that the tests stop compiling when a
+    /// variant is added without adding it to [`every_variant`] too.
+    fn check_listed(error: &LookupError) {
+        match error {
+            LookupError::HttpClient(_)
+            | LookupError::HeaderValue(_)
+            | LookupError::HeaderName(_)
+            | LookupError::HeaderToString(_)
+            | LookupError::PaginationParse(_)
+            | LookupError::NoReleases
+            | LookupError::RepositoryNotFound
+            | LookupError::InvalidRepository(_)
+            | LookupError::ReleaseNotFound
+            | LookupError::AuthenticationError { .. }
+            | LookupError::AuthenticationRequiredForDrafts
+            | LookupError::RateLimited { .. }
+            | LookupError::SecondaryRateLimited { .. }
+            | LookupError::BudgetExceeded { .. }
+            | LookupError::PaginationLoop { .. }
+            | LookupError::Timeout { .. }
+            | LookupError::ErrorHttpResponse { .. }
+            | LookupError::NotModified
+            | LookupError::NotAnApiEndpoint { .. }
+            | LookupError::ResponseTooLarge { .. }
+            | LookupError::InvalidResponse { .. }
+            | LookupError::UnexpectedBody { .. }
+            | LookupError::CertificatePinFailed { .. }
+            | LookupError::InvalidVersion(_)
+            | LookupError::StateWrite { .. }
+            | LookupError::InvalidTagPattern(_)
+            | LookupError::InvalidAssetPattern(_)
+            | LookupError::InvalidSearchPattern(_)
+            | LookupError::AssetNotFound
+            | LookupError::AmbiguousAsset { .. }
+            | LookupError::MissingTemplateVariable { .. }
+            | LookupError::InvalidAssetTemplate { .. }
+            | LookupError::AssetCache { .. }
+            | LookupError::AssetSizeMismatch { .. }
+            | LookupError::ApiRootNotFound { .. }
+            | LookupError::CircuitOpen { .. }
+            | LookupError::Shared(_)
+            | LookupError::FetchPanicked => {}
+            #[cfg(feature = "atom")]
+            LookupError::InvalidFeed(_) => {}
+            #[cfg(feature = "local-git")]
+            LookupError::LocalGit(_) => {}
+            #[cfg(feature = "write")]
+            LookupError::ValidationFailed { .. } => {}
+            #[cfg(feature = "keyring")]
+            LookupError::TokenNotFound { .. } | LookupError::TokenStore { .. } => {}
+            #[cfg(feature = "test-util")]
+            LookupError::FixtureRead { .. } | LookupError::InvalidFixture { .. } => {}
+        }
+    }
+
+    #[test]
+    fn test_codes_are_distinct() {
+        let errors = every_variant();
+        let codes: Vec<_> = errors
+            .iter()
+            .filter(|error| !matches!(error, LookupError::Shared(_)))
+            .map(LookupError::code)
+            .collect();
+        let unique: HashSet<_> = codes.iter().collect();
+        assert_eq!(unique.len(), codes.len(), "{codes:?}");
+        for code in codes {
+            assert!(
+                code.bytes().all(|b| b.is_ascii_lowercase() || b == b'_'),
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_codes_are_stable() {
+        let code = |error: LookupError| error.code();
+        assert_eq!(code(LookupError::NoReleases), "no_releases");
+        assert_eq!(code(LookupError::RepositoryNotFound), "repo_not_found");
+        assert_eq!(
+            code(LookupError::AuthenticationError {
+                status: 403,
+                request_id: None
+            }),
+            "auth"
+        );
+        assert_eq!(
+            code(LookupError::RateLimited {
+                reset_at: None,
+                request_id: None
+            }),
+            "rate_limited"
+        );
+        assert_eq!(
+            code(LookupError::Shared(Arc::new(
+                LookupError::RepositoryNotFound
+            ))),
+            "repo_not_found"
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Offline release data loaded from a JSON fixture file.
+
+use crate::{GitHub, GitHubReleaseItem, LookupError, Result};
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
+
+/// Release data for a set of repositories, keyed by `"owner/repo"`.
+#[derive(Debug)]
+pub(crate) struct Fixture {
+    repositories: HashMap<String, Vec<GitHubReleaseItem>>,
+}
+
+impl Fixture {
+    /// Load and validate a fixture from the file at `path`.
+    fn load(path: &Path) -> Result<Self> {
+        let display = path.display().to_string();
+        let content = fs::read_to_string(path).map_err(|source| LookupError::FixtureRead {
+            path: display.clone(),
+            source,
+        })?;
+        let repositories: HashMap<String, Vec<GitHubReleaseItem>> = serde_json::from_str(&content)
+            .map_err(|e| LookupError::InvalidFixture {
+                path: display.clone(),
+                line: e.line(),
+                column: e.column(),
+                message: e.to_string(),
+            })?;
+        if let Some(key) = repositories.keys().find(|k| k.split('/').count() != 2) {
+            let (line, column) = position_of(&content, &format!("\"{key}\""));
+            return Err(LookupError::InvalidFixture {
+                path: display,
+                line,
+                column,
+                message: format!("repository key \"{key}\" is not in the format \"owner/repo\""),
+            });
+        }
+        Ok(Self { repositories })
+    }
+
+    /// Get the releases for the repository, if it's in the fixture.
+    pub(crate) fn releases(&self, repository: &str) -> Result<Vec<GitHubReleaseItem>> {
+        self.repositories
+            .get(repository)
+            .cloned()
+            .ok_or(LookupError::RepositoryNotFound)
+    }
+}
+
+/// Find the 1-based line and column of the first occurrence of `needle`.
+fn position_of(content: &str, needle: &str) -> (usize, usize) {
+    let Some(offset) = content.find(needle) else {
+        return (0, 0);
+    };
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = offset - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+impl GitHub {
+    /// Create a new instance of the struct that answers from a fixture file
+    /// instead of the GitHub API.
+    ///
+    /// The file should contain a JSON object mapping `"owner/repo"` strings to arrays
+    /// of release objects, as returned by the GitHub API. Repositories not in the file
+    /// return [`LookupError::RepositoryNotFound`].
+    ///
+    /// Only available with the `test-util` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// use std::path::Path;
+    ///
+    /// let github = GitHub::from_fixture(Path::new("tests/fixtures/releases.json")).unwrap();
+    /// let version = github.get_latest_version("celeo/github_release_check").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the file cannot be read, or if its contents
+    /// are not valid fixture data. Errors for the latter include the line
+    /// and column of the problem.
+    pub fn from_fixture(path: &Path) -> Result<Self> {
+        let mut github = Self::new()?;
+        github.fixture = Some(Arc::new(Fixture::load(path)?));
+        Ok(github)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{GitHub, LookupError};
+    use std::{fs, path::Path};
+
+    const SAMPLE_FIXTURE: &str =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/releases.json");
+
+    fn write_temp(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "github_release_check_{}_{name}.json",
+            std::process::id()
+        ));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_fixture_hit() {
+        let github = GitHub::from_fixture(Path::new(SAMPLE_FIXTURE)).unwrap();
+        let versions = github.get_all_versions("foo/bar").unwrap();
+        assert_eq!(versions, vec!["v1.0.0", "v1.9.10", "v0.3.0"]);
+        let latest = github.get_latest_version("foo/bar").unwrap();
+        assert_eq!(latest, semver::Version::new(1, 9, 10));
+    }
+
+    #[test]
+    fn test_fixture_miss() {
+        let github = GitHub::from_fixture(Path::new(SAMPLE_FIXTURE)).unwrap();
+        assert!(!github.is_authenticated());
+        let res = github.query("foo/missing");
+        assert!(matches!(res, Err(LookupError::RepositoryNotFound)));
+    }
+
+    #[test]
+    fn test_fixture_reuploaded_asset() {
+        let github = GitHub::from_fixture(Path::new(SAMPLE_FIXTURE)).unwrap();
+        let release = github.query("foo/reuploaded").unwrap().remove(0);
+        let replaced = release.assets_updated_after(&release.published_at);
+        assert_eq!(replaced.len(), 1);
+        assert_eq!(replaced[0].name, "tool-linux.tar.gz");
+        assert_eq!(replaced[0].state, "uploaded");
+        let uploader = replaced[0].uploader.as_ref().unwrap();
+        assert_eq!(uploader.login, "release-bot");
+        assert_eq!(uploader.kind, "Bot");
+        assert_eq!(release.assets[1].uploader, None);
+    }
+
+    #[test]
+    fn test_fixture_malformed() {
+        let path = write_temp(
+            "malformed",
+            "{\n  \"foo/bar\": [\n    { \"tag_name\": 1 }\n  ]\n}",
+        );
+        let res = GitHub::from_fixture(&path);
+        let _ = fs::remove_file(&path);
+        match res {
+            Err(LookupError::InvalidFixture { line, .. }) => assert_eq!(line, 3),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_fixture_bad_key() {
+        let path = write_temp("bad_key", "{\n  \"foo\": []\n}");
+        let res = GitHub::from_fixture(&path);
+        let _ = fs::remove_file(&path);
+        match res {
+            Err(LookupError::InvalidFixture { line, column, .. }) => {
+                assert_eq!((line, column), (2, 3));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_fixture_missing_file() {
+        let res = GitHub::from_fixture(Path::new("/does/not/exist.json"));
+        assert!(matches!(res, Err(LookupError::FixtureRead { .. })));
+    }
+}
@@ -0,0 +1,366 @@
+//! Releases from Gitea, Forgejo, and Codeberg repositories.
+
+use crate::{
+    encode_repository, error_from_response, generate_headers, versions::latest_version,
+    LookupError, ReleaseItem, Result,
+};
+use log::debug;
+use reqwest::{
+    blocking::{Client, ClientBuilder},
+    header::{self, HeaderMap, HeaderValue},
+};
+use semver::Version;
+use serde::Deserialize;
+
+/// The Codeberg API root endpoint.
+pub const CODEBERG_API_ROOT: &str = "https://codeberg.org/api/v1/";
+
+/// Gitea's default maximum page size; larger requests are silently lowered.
+const GITEA_PAGE_SIZE: usize = 50;
+
+/// Data for a release in the Gitea API response.
+///
+/// The shape is close to GitHub's, but not close enough to share a struct:
+/// several of the URL keys are missing on older Gitea versions.
+#[derive(Debug, Deserialize)]
+struct GiteaReleaseItem {
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    published_at: Option<String>,
+    #[serde(default)]
+    html_url: String,
+}
+
+impl From<GiteaReleaseItem> for ReleaseItem {
+    fn from(item: GiteaReleaseItem) -> Self {
+        Self {
+            tag_name: item.tag_name,
+            name: item.name,
+            body: item.body,
+            prerelease: item.prerelease,
+            published_at: item.published_at,
+            html_url: item.html_url,
+        }
+    }
+}
+
+/// Struct to communicate with the REST API of a Gitea instance.
+///
+/// This mirrors [`GitHub`](crate::GitHub), and works with Forgejo and
+/// Codeberg as well, since they share Gitea's API.
+///
+/// Only available with the `gitea` feature.
+#[derive(Debug, Clone)]
+pub struct Gitea {
+    client: Client,
+    api_root: String,
+}
+
+impl Gitea {
+    /// Create a new instance of the struct for public repositories on the Gitea instance.
+    ///
+    /// The `api_endpoint` should be the REST API root, ending in a trailing slash,
+    /// like `"https://gitea.your_domain_root.com/api/v1/"`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::Gitea;
+    /// let gitea = Gitea::new("https://gitea.example.com/api/v1/").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed.
+    pub fn new(api_endpoint: &str) -> Result<Self> {
+        Self::build(api_endpoint, generate_headers(None)?)
+    }
+
+    /// Create a new instance of the struct for public repositories on <https://codeberg.org>.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::Gitea;
+    /// let codeberg = Gitea::codeberg().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed.
+    pub fn codeberg() -> Result<Self> {
+        Self::new(CODEBERG_API_ROOT)
+    }
+
+    /// Create a new instance of the struct for any repository that
+    /// the [Gitea access token] can view, or for public repositories if the
+    /// token is empty, in which case no token is sent.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::Gitea;
+    /// let gitea = Gitea::from_custom("https://gitea.example.com/api/v1/", "abcdef").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed.
+    ///
+    /// [Gitea access token]: https://docs.gitea.com/development/api-usage#generating-and-listing-api-tokens
+    pub fn from_custom(api_endpoint: &str, access_token: &str) -> Result<Self> {
+        let mut headers = generate_headers(None)?;
+        if !access_token.is_empty() {
+            let _prev = headers.insert(
+                header::AUTHORIZATION,
+                HeaderValue::from_str(&format!("token {access_token}"))?,
+            );
+        }
+        Self::build(api_endpoint, headers)
+    }
+
+    fn build(api_endpoint: &str, headers: HeaderMap) -> Result<Self> {
+        let client = ClientBuilder::new().default_headers(headers).build()?;
+        Ok(Self {
+            client,
+            api_root: api_endpoint.to_owned(),
+        })
+    }
+
+    /// Get all releases from the repository.
+    ///
+    /// Note that `repository` should be in the format "owner/repo".
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::Gitea;
+    /// let codeberg = Gitea::codeberg().unwrap();
+    /// let releases_result = codeberg.query("forgejo/forgejo");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the HTTP request cannot be sent, the API returns
+    /// a status code indicating something other than a success (outside of the
+    /// 2xx range), of if the returned data does not match the expected model.
+    pub fn query(&self, repository: &str) -> Result<Vec<ReleaseItem>> {
+        Ok(self
+            .releases(repository)?
+            .into_iter()
+            .map(ReleaseItem::from)
+            .collect())
+    }
+
+    /// Get all releases from the repository as Gitea sends them, following pages.
+    fn releases(&self, repository: &str) -> Result<Vec<GiteaReleaseItem>> {
+        let url = format!(
+            "{}repos/{}/releases",
+            self.api_root,
+            encode_repository(repository)
+        );
+        let mut releases = Vec::new();
+        let mut page = 1usize;
+        loop {
+            debug!("Querying Gitea at {url}, page {page}");
+            let query = [("limit", GITEA_PAGE_SIZE), ("page", page)];
+            let response = self.client.get(&url).query(&query).send()?;
+            if !response.status().is_success() {
+                debug!(
+                    "Got status \"{}\" from Gitea release check",
+                    response.status()
+                );
+                return Err(error_from_response(response));
+            }
+            let total = total_count(response.headers())?;
+            let items: Vec<GiteaReleaseItem> = response.json()?;
+            let fetched = items.len();
+            releases.extend(items);
+            // the count of items is compared rather than computing a page count,
+            // as the server may use a smaller page size than was asked for
+            match total {
+                Some(total) if fetched > 0 && releases.len() < total => page += 1,
+                _ => break,
+            }
+        }
+        Ok(releases)
+    }
+
+    /// Get all release version strings from the repository.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::Gitea;
+    /// let codeberg = Gitea::codeberg().unwrap();
+    /// let versions_result = codeberg.get_all_versions("forgejo/forgejo");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn get_all_versions(&self, repository: &str) -> Result<Vec<String>> {
+        Ok(self
+            .query(repository)?
+            .into_iter()
+            .map(|release| release.tag_name)
+            .collect())
+    }
+
+    /// Get the latest release version from the repository.
+    ///
+    /// Version selection works the same as [`GitHub::get_latest_version`](crate::GitHub::get_latest_version),
+    /// and draft releases are left out.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::Gitea;
+    /// let codeberg = Gitea::codeberg().unwrap();
+    /// let version_result = codeberg.get_latest_version("forgejo/forgejo");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions`, or
+    /// if no versions are returned from the API.
+    pub fn get_latest_version(&self, repository: &str) -> Result<Version> {
+        let versions: Vec<String> = self
+            .releases(repository)?
+            .into_iter()
+            .filter(|release| !release.draft)
+            .map(|release| release.tag_name)
+            .collect();
+        latest_version(&versions).ok_or(LookupError::NoReleases)
+    }
+}
+
+/// Get the total number of items (if given) from the Gitea response headers.
+///
+/// # Errors
+///
+/// This function fails if the header value is not valid ASCII.
+fn total_count(headers: &HeaderMap) -> Result<Option<usize>> {
+    match headers.get("x-total-count") {
+        Some(count) => Ok(count.to_str()?.trim().parse::<usize>().ok()),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Gitea;
+    use crate::LookupError;
+    use mockito::{mock, Matcher};
+
+    const RELEASE_REST: &str = r#", "id": 1, "name": "", "body": "", "url": "", "html_url": "", "tarball_url": "", "zipball_url": "", "draft": false, "prerelease": false, "created_at": "", "published_at": "", "assets": []"#;
+    const PATH: &str = "/repos/foo/bar/releases";
+
+    fn gitea() -> Gitea {
+        Gitea::from_custom(&format!("{}/", mockito::server_url()), "abc").unwrap()
+    }
+
+    #[test]
+    fn test_token_header() {
+        let m = mock("GET", PATH)
+            .match_query(Matcher::Any)
+            .match_header("authorization", "token abc")
+            .with_body("[]")
+            .create();
+        let versions = gitea().get_all_versions("foo/bar").unwrap();
+        assert!(versions.is_empty());
+        m.assert();
+    }
+
+    #[test]
+    fn test_empty_token_sends_no_header() {
+        let m = mock("GET", "/repos/foo/gitea-anonymous/releases")
+            .match_query(Matcher::Any)
+            .match_header("authorization", Matcher::Missing)
+            .with_body("[]")
+            .create();
+        let gitea = Gitea::from_custom(&format!("{}/", mockito::server_url()), "").unwrap();
+        let versions = gitea.get_all_versions("foo/gitea-anonymous").unwrap();
+        assert!(versions.is_empty());
+        m.assert();
+    }
+
+    #[test]
+    fn test_get_latest_version_skips_drafts() {
+        let draft = RELEASE_REST.replace(r#""draft": false"#, r#""draft": true"#);
+        let _m = mock("GET", "/repos/foo/gitea-drafts/releases")
+            .match_query(Matcher::Any)
+            .with_header("x-total-count", "2")
+            .with_body(format!(
+                r#"[{{ "tag_name": "v2.0.0" {draft}}}, {{ "tag_name": "v1.0.0" {RELEASE_REST}}}]"#
+            ))
+            .create();
+        let version = gitea().get_latest_version("foo/gitea-drafts").unwrap();
+        assert_eq!(version, semver::Version::new(1, 0, 0));
+    }
+
+    #[test]
+    fn test_get_all_versions_valid() {
+        let _m = mock("GET", PATH)
+            .match_query(Matcher::Any)
+            .with_header("x-total-count", "3")
+            .with_body(format!(
+                r#"[
+                {{ "tag_name": "v1.0.0" {RELEASE_REST}}},
+                {{ "tag_name": "v1.9.10" {RELEASE_REST}}},
+                {{ "tag_name": "v0.3.0" {RELEASE_REST}}}
+            ]"#
+            ))
+            .create();
+        let versions = gitea().get_all_versions("foo/bar").unwrap();
+        assert_eq!(versions, vec!["v1.0.0", "v1.9.10", "v0.3.0"]);
+    }
+
+    #[test]
+    fn test_query_paginates_with_total_count() {
+        let p1 = mock("GET", PATH)
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_header("x-total-count", "3")
+            .with_body(format!(
+                r#"[{{ "tag_name": "v1.0.0" {RELEASE_REST}}}, {{ "tag_name": "v2.0.0" {RELEASE_REST}}}]"#
+            ))
+            .expect(1)
+            .create();
+        let p2 = mock("GET", PATH)
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_header("x-total-count", "3")
+            .with_body(format!(r#"[{{ "tag_name": "v3.0.0" {RELEASE_REST}}}]"#))
+            .expect(1)
+            .create();
+        let version = gitea().get_latest_version("foo/bar").unwrap();
+        assert_eq!(version, semver::Version::new(3, 0, 0));
+        p1.assert();
+        p2.assert();
+    }
+
+    #[test]
+    fn test_get_latest_version_none() {
+        let _m = mock("GET", PATH)
+            .match_query(Matcher::Any)
+            .with_header("x-total-count", "0")
+            .with_body("[]")
+            .create();
+        let version_res = gitea().get_latest_version("foo/bar");
+        assert!(matches!(version_res, Err(LookupError::NoReleases)));
+    }
+
+    #[test]
+    fn test_repository_not_found() {
+        let _m = mock("GET", PATH)
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        let res = gitea().query("foo/bar");
+        assert!(matches!(res, Err(LookupError::RepositoryNotFound)));
+    }
+}
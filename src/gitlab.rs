@@ -0,0 +1,446 @@
+//! Releases from GitLab projects.
+
+use crate::{
+    error_from_response,
+    page_guard::{PageGuard, DEFAULT_MAX_PAGES},
+    versions::latest_version,
+    LookupError, ReleaseItem, Result, DEFAULT_USER_AGENT, PAGINATION_REQUEST_AMOUNT,
+};
+use log::debug;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::{
+    blocking::{Client, ClientBuilder},
+    header::{self, HeaderMap, HeaderValue},
+};
+use semver::Version;
+use serde::Deserialize;
+
+/// The default GitLab instance API root endpoint.
+///
+/// You can use this exported `String` if you want to query
+/// a private project on <https://gitlab.com>.
+pub const DEFAULT_GITLAB_API_ROOT: &str = "https://gitlab.com/api/v4/";
+
+/// Data for a release in the GitLab API response.
+///
+/// Only the keys that map onto [`ReleaseItem`] are read. For the rest, see
+/// [the GitLab docs](https://docs.gitlab.com/ee/api/releases/#list-releases).
+#[derive(Debug, Deserialize)]
+struct GitLabReleaseItem {
+    tag_name: String,
+    name: Option<String>,
+    description: Option<String>,
+    released_at: Option<String>,
+    #[serde(default)]
+    upcoming_release: bool,
+    #[serde(rename = "_links", default)]
+    links: GitLabReleaseLinks,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct GitLabReleaseLinks {
+    #[serde(rename = "self")]
+    self_url: Option<String>,
+}
+
+impl From<GitLabReleaseItem> for ReleaseItem {
+    /// GitLab has no prerelease flag; releases with a `released_at` in the
+    /// future (`upcoming_release`) are treated as prereleases instead.
+    fn from(item: GitLabReleaseItem) -> Self {
+        Self {
+            tag_name: item.tag_name,
+            name: item.name,
+            body: item.description,
+            prerelease: item.upcoming_release,
+            published_at: item.released_at,
+            html_url: item.links.self_url.unwrap_or_default(),
+        }
+    }
+}
+
+/// Struct to communicate with the GitLab REST API.
+///
+/// This mirrors [`GitHub`](crate::GitHub), but for projects hosted on GitLab.
+/// Projects are identified by their full path, like `"group/subgroup/project"`,
+/// or by their numeric ID as a string.
+///
+/// Only available with the `gitlab` feature.
+#[derive(Debug, Clone)]
+pub struct GitLab {
+    client: Client,
+    api_root: String,
+}
+
+impl GitLab {
+    /// Create a new instance of the struct suitable for public projects on <https://gitlab.com>.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::GitLab;
+    /// let gitlab = GitLab::new().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed.
+    pub fn new() -> Result<Self> {
+        let client = ClientBuilder::new()
+            .default_headers(gitlab_headers(None)?)
+            .build()?;
+        Ok(Self {
+            client,
+            api_root: DEFAULT_GITLAB_API_ROOT.to_owned(),
+        })
+    }
+
+    /// Create a new instance of the struct for private projects and/or a self-hosted GitLab.
+    ///
+    /// The access token should be a [GitLab personal access token] with the `read_api` scope,
+    /// or empty to send no token.
+    /// The `api_endpoint` should be the REST API root, ending in a trailing slash, like
+    /// [`DEFAULT_GITLAB_API_ROOT`] or `"https://gitlab.your_domain_root.com/api/v4/"`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::GitLab;
+    /// let gitlab = GitLab::from_custom("https://gitlab.example.com/api/v4/", "abcdef").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed.
+    ///
+    /// [GitLab personal access token]: https://docs.gitlab.com/ee/user/profile/personal_access_tokens.html
+    pub fn from_custom(api_endpoint: &str, access_token: &str) -> Result<Self> {
+        let client = ClientBuilder::new()
+            .default_headers(gitlab_headers(Some(access_token))?)
+            .build()?;
+        Ok(Self {
+            client,
+            api_root: api_endpoint.to_owned(),
+        })
+    }
+
+    /// Get all releases from the project.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitLab;
+    /// let gitlab = GitLab::new().unwrap();
+    /// let releases_result = gitlab.query("gitlab-org/gitlab-runner");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the HTTP request cannot be sent, the API returns
+    /// a status code indicating something other than a success (outside of the
+    /// 2xx range), of if the returned data does not match the expected model.
+    /// If the pagination headers point back to a page already fetched, or
+    /// there are more than 1000 pages, this function fails with
+    /// [`LookupError::PaginationLoop`].
+    pub fn query(&self, project: &str) -> Result<Vec<ReleaseItem>> {
+        let url = format!(
+            "{}projects/{}/releases",
+            self.api_root,
+            utf8_percent_encode(project, NON_ALPHANUMERIC)
+        );
+        let mut releases = Vec::new();
+        let mut page = 1usize;
+        let mut guard = PageGuard::new(DEFAULT_MAX_PAGES);
+        loop {
+            debug!("Querying GitLab at {url}, page {page}");
+            let query = [("per_page", PAGINATION_REQUEST_AMOUNT), ("page", page)];
+            let response = self.client.get(&url).query(&query).send()?;
+            if !response.status().is_success() {
+                debug!(
+                    "Got status \"{}\" from GitLab release check",
+                    response.status()
+                );
+                return Err(error_from_response(response));
+            }
+            let next = next_page(response.headers(), page)?;
+            // pages only go forward, so a next page at or before this one is a loop
+            let next = guard.follow(page, next.filter(|next| *next <= page), next)?;
+            let items: Vec<GitLabReleaseItem> = response.json()?;
+            releases.extend(items.into_iter().map(ReleaseItem::from));
+            match next {
+                Some(next) => page = next,
+                None => break,
+            }
+        }
+        Ok(releases)
+    }
+
+    /// Get all release version strings from the project.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitLab;
+    /// let gitlab = GitLab::new().unwrap();
+    /// let versions_result = gitlab.get_all_versions("gitlab-org/gitlab-runner");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn get_all_versions(&self, project: &str) -> Result<Vec<String>> {
+        Ok(self
+            .query(project)?
+            .into_iter()
+            .map(|release| release.tag_name)
+            .collect())
+    }
+
+    /// Get the latest release version from the project.
+    ///
+    /// Version selection works the same as [`GitHub::get_latest_version`](crate::GitHub::get_latest_version).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitLab;
+    /// let gitlab = GitLab::new().unwrap();
+    /// let version_result = gitlab.get_latest_version("gitlab-org/gitlab-runner");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions`, or
+    /// if no versions are returned from the API.
+    pub fn get_latest_version(&self, project: &str) -> Result<Version> {
+        let versions = self.get_all_versions(project)?;
+        latest_version(&versions).ok_or(LookupError::NoReleases)
+    }
+}
+
+/// The headers sent with every request to GitLab, with the token if it isn't empty.
+///
+/// # Errors
+///
+/// This function fails if the token can't be sent in a header.
+fn gitlab_headers(token: Option<&str>) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    let _prev = headers.insert(
+        header::USER_AGENT,
+        HeaderValue::from_static(DEFAULT_USER_AGENT),
+    );
+    let _prev = headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+    if let Some(token) = token.filter(|token| !token.is_empty()) {
+        let _prev = headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}"))?,
+        );
+    }
+    Ok(headers)
+}
+
+/// Determine the next page (if any) from the GitLab response headers.
+///
+/// `X-Total-Pages` is preferred, but GitLab leaves it out for very large
+/// collections, in which case `X-Next-Page` is used.
+///
+/// # Errors
+///
+/// This function fails if the header values are not valid ASCII.
+fn next_page(headers: &HeaderMap, page: usize) -> Result<Option<usize>> {
+    if let Some(total) = headers.get("x-total-pages") {
+        let total = total.to_str()?.trim().parse::<usize>().unwrap_or(0);
+        return Ok((page < total).then_some(page + 1));
+    }
+    match headers.get("x-next-page") {
+        Some(next) => Ok(next.to_str()?.trim().parse::<usize>().ok()),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GitLab;
+    use crate::{page_guard::DEFAULT_MAX_PAGES, LookupError};
+    use mockito::{mock, Matcher};
+
+    const RELEASE_REST: &str = r#", "name": "", "description": "", "created_at": "", "released_at": "", "upcoming_release": false, "_links": { "self": "https://gitlab.example.com/foo/bar/-/releases/v1" }"#;
+    const PATH: &str = "/projects/foo%2Fbar/releases";
+
+    fn gitlab() -> GitLab {
+        GitLab::from_custom(&format!("{}/", mockito::server_url()), "").unwrap()
+    }
+
+    #[test]
+    fn test_get_all_versions_none() {
+        let _m = mock("GET", PATH)
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .create();
+        let versions = gitlab().get_all_versions("foo/bar").unwrap();
+        assert!(versions.is_empty());
+    }
+
+    #[test]
+    fn test_get_all_versions_valid() {
+        let _m = mock("GET", PATH)
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                r#"[
+                {{ "tag_name": "v1.0.0" {RELEASE_REST}}},
+                {{ "tag_name": "v1.9.10" {RELEASE_REST}}},
+                {{ "tag_name": "v0.3.0" {RELEASE_REST}}}
+            ]"#
+            ))
+            .create();
+        let versions = gitlab().get_all_versions("foo/bar").unwrap();
+        assert_eq!(versions, vec!["v1.0.0", "v1.9.10", "v0.3.0"]);
+    }
+
+    #[test]
+    fn test_query_maps_fields() {
+        let _m = mock("GET", PATH)
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"[{ "tag_name": "v2.0.0", "name": "Two", "description": "notes", "released_at": "2030-01-01T00:00:00Z", "upcoming_release": true, "_links": { "self": "https://gitlab.example.com/foo/bar/-/releases/v2.0.0" } }]"#,
+            )
+            .create();
+        let releases = gitlab().query("foo/bar").unwrap();
+        assert_eq!(releases.len(), 1);
+        let release = &releases[0];
+        assert_eq!(release.name.as_deref(), Some("Two"));
+        assert_eq!(release.body.as_deref(), Some("notes"));
+        assert!(release.prerelease);
+        assert_eq!(
+            release.published_at.as_deref(),
+            Some("2030-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            release.html_url,
+            "https://gitlab.example.com/foo/bar/-/releases/v2.0.0"
+        );
+    }
+
+    #[test]
+    fn test_query_paginates_with_total_pages() {
+        let _p1 = mock("GET", PATH)
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_header("x-total-pages", "2")
+            .with_body(format!(r#"[{{ "tag_name": "v1.0.0" {RELEASE_REST}}}]"#))
+            .create();
+        let _p2 = mock("GET", PATH)
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_header("x-total-pages", "2")
+            .with_body(format!(r#"[{{ "tag_name": "v2.0.0" {RELEASE_REST}}}]"#))
+            .create();
+        let versions = gitlab().get_all_versions("foo/bar").unwrap();
+        assert_eq!(versions, vec!["v1.0.0", "v2.0.0"]);
+    }
+
+    #[test]
+    fn test_query_paginates_with_next_page() {
+        let _p1 = mock("GET", PATH)
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_header("x-next-page", "2")
+            .with_body(format!(r#"[{{ "tag_name": "v1.0.0" {RELEASE_REST}}}]"#))
+            .create();
+        let _p2 = mock("GET", PATH)
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_header("x-next-page", "")
+            .with_body(format!(r#"[{{ "tag_name": "v2.0.0" {RELEASE_REST}}}]"#))
+            .create();
+        let versions = gitlab().get_all_versions("foo/bar").unwrap();
+        assert_eq!(versions.len(), 2);
+    }
+
+    #[test]
+    fn test_query_stops_repeated_next_page() {
+        let looping = mock("GET", "/projects/foo%2Flooping/releases")
+            .match_query(Matcher::Any)
+            .with_header("x-next-page", "1")
+            .with_body(format!(r#"[{{ "tag_name": "v1.0.0" {RELEASE_REST}}}]"#))
+            .expect(1)
+            .create();
+        assert!(matches!(
+            gitlab().query("foo/looping"),
+            Err(LookupError::PaginationLoop { pages_fetched: 1 })
+        ));
+        looping.assert();
+    }
+
+    #[test]
+    fn test_query_stops_bogus_total_pages() {
+        let _m = mock("GET", "/projects/foo%2Fendless/releases")
+            .match_query(Matcher::Any)
+            .with_header("x-total-pages", "1000000")
+            .with_body("[]")
+            .create();
+        assert!(matches!(
+            gitlab().query("foo/endless"),
+            Err(LookupError::PaginationLoop {
+                pages_fetched: DEFAULT_MAX_PAGES
+            })
+        ));
+    }
+
+    #[test]
+    fn test_headers() {
+        let accept = Matcher::Exact("application/json".to_owned());
+        let anonymous = mock("GET", "/projects/foo%2Fheaders/releases")
+            .match_query(Matcher::Any)
+            .match_header("accept", accept.clone())
+            .match_header("authorization", Matcher::Missing)
+            .with_body("[]")
+            .expect(1)
+            .create();
+        assert!(gitlab().query("foo/headers").unwrap().is_empty());
+        anonymous.assert();
+        drop(anonymous);
+
+        let authenticated = mock("GET", "/projects/foo%2Fheaders/releases")
+            .match_query(Matcher::Any)
+            .match_header("accept", accept)
+            .match_header("authorization", "Bearer abcdef")
+            .with_body("[]")
+            .expect(1)
+            .create();
+        let gitlab = GitLab::from_custom(&format!("{}/", mockito::server_url()), "abcdef").unwrap();
+        assert!(gitlab.query("foo/headers").unwrap().is_empty());
+        authenticated.assert();
+    }
+
+    #[test]
+    fn test_get_latest_version_none() {
+        let _m = mock("GET", PATH)
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .create();
+        let version_res = gitlab().get_latest_version("foo/bar");
+        assert!(matches!(version_res, Err(LookupError::NoReleases)));
+    }
+
+    #[test]
+    fn test_get_latest_version_bad_semvers() {
+        let _m = mock("GET", PATH)
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                r#"[
+                {{ "tag_name": "uhhhh" {RELEASE_REST}}},
+                {{ "tag_name": "v3.0.0-alpha" {RELEASE_REST}}},
+                {{ "tag_name": "v1.9.10" {RELEASE_REST}}}
+            ]"#
+            ))
+            .create();
+        let version = gitlab().get_latest_version("foo/bar").unwrap();
+        assert_eq!(version, semver::Version::parse("3.0.0-alpha").unwrap());
+    }
+
+    #[test]
+    fn test_project_not_found() {
+        let _m = mock("GET", PATH)
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        let res = gitlab().query("foo/bar");
+        assert!(matches!(res, Err(LookupError::RepositoryNotFound)));
+    }
+}
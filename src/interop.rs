@@ -0,0 +1,183 @@
+//! Plain release data for handing off to self-updater crates.
+//!
+//! Crates like `self_update` and `update-informer` each have their own release
+//! types. Rather than depend on them, this module has a [`ReleaseInfo`] with the
+//! fields they need, converted from a [`GitHubReleaseItem`]. Look the release
+//! up with this crate, then fill in the updater's type from it:
+//!
+//! ```rust,no_run
+//! use github_release_check::{interop::ReleaseInfo, GitHub};
+//!
+//! let github = GitHub::new().unwrap();
+//! let release = github
+//!     .get_marked_latest("celeo/github_release_check")
+//!     .unwrap();
+//! let release = ReleaseInfo::from(release);
+//! if let Some(asset) = release.asset_for("x86_64-unknown-linux-gnu") {
+//!     // For self_update, `release.version()` is the release version, and
+//!     // `asset.url` the download URL, which is fetched with an
+//!     // `Accept: application/octet-stream` header and the same token.
+//!     println!("{} from {}", release.version(), asset.url);
+//! }
+//! ```
+
+use crate::{GitHubAssetItem, GitHubReleaseItem};
+use serde::Serialize;
+
+/// A release, with the fields updater crates need.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseInfo {
+    /// Name of the tag the release is for, like `"v1.2.0"`.
+    pub tag: String,
+    /// Display name of the release.
+    pub name: Option<String>,
+    /// The release notes.
+    pub body: Option<String>,
+    /// Whether the release is marked as a pre-release.
+    pub prerelease: bool,
+    /// When the release was published; empty for drafts.
+    pub published_at: String,
+    /// Files attached to the release.
+    pub assets: Vec<AssetInfo>,
+}
+
+/// A file attached to a release, with the fields updater crates need.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AssetInfo {
+    /// File name of the asset.
+    pub name: String,
+    /// API URL of the asset, which gives the file itself when asked for
+    /// `application/octet-stream`; this works for private repositories too.
+    pub url: String,
+    /// Link to download the asset in a browser.
+    pub browser_download_url: String,
+    /// Media type of the asset.
+    pub content_type: String,
+    /// Size of the asset in bytes.
+    pub size: u64,
+}
+
+impl ReleaseInfo {
+    /// The tag without a leading `'v'`, the form updaters compare versions in.
+    #[must_use]
+    pub fn version(&self) -> &str {
+        self.tag.strip_prefix('v').unwrap_or(&self.tag)
+    }
+
+    /// The display name, or the tag if the release doesn't have one.
+    #[must_use]
+    pub fn display_name(&self) -> &str {
+        self.name
+            .as_deref()
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&self.tag)
+    }
+
+    /// The first asset with the target, like `"x86_64-unknown-linux-gnu"`,
+    /// in its name.
+    #[must_use]
+    pub fn asset_for(&self, target: &str) -> Option<&AssetInfo> {
+        self.assets.iter().find(|asset| asset.name.contains(target))
+    }
+}
+
+impl From<GitHubReleaseItem> for ReleaseInfo {
+    fn from(release: GitHubReleaseItem) -> Self {
+        Self {
+            tag: release.tag_name,
+            name: release.name,
+            body: release.body,
+            prerelease: release.prerelease,
+            published_at: release.published_at,
+            assets: release.assets.into_iter().map(AssetInfo::from).collect(),
+        }
+    }
+}
+
+impl From<&GitHubReleaseItem> for ReleaseInfo {
+    fn from(release: &GitHubReleaseItem) -> Self {
+        Self::from(release.clone())
+    }
+}
+
+impl From<GitHubAssetItem> for AssetInfo {
+    fn from(asset: GitHubAssetItem) -> Self {
+        Self {
+            name: asset.name,
+            url: asset.url,
+            browser_download_url: asset.browser_download_url,
+            content_type: asset.content_type,
+            size: asset.size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AssetInfo, ReleaseInfo};
+    use crate::{tests::release_json, GitHubReleaseItem};
+    use serde_json::json;
+
+    fn release() -> GitHubReleaseItem {
+        serde_json::from_value(release_json(
+            "v1.2.0",
+            json!({
+            "name": "Version 1.2", "prerelease": true, "created_at": "2024-01-01T00:00:00Z",
+            "published_at": "2024-01-02T00:00:00Z", "body": "Notes",
+            "assets": [{
+                "url": "https://api.github.com/repos/foo/bar/releases/assets/7",
+                "browser_download_url": "https://github.com/foo/bar/releases/download/v1.2.0/bar-x86_64-unknown-linux-gnu.tar.gz",
+                "id": 7, "name": "bar-x86_64-unknown-linux-gnu.tar.gz", "label": null,
+                "content_type": "application/gzip", "size": 1024, "download_count": 3,
+                "created_at": "", "updated_at": "",
+            }],
+            }),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_release_info_from_release() {
+        let info = ReleaseInfo::from(&release());
+        assert_eq!(
+            info,
+            ReleaseInfo {
+                tag: "v1.2.0".to_owned(),
+                name: Some("Version 1.2".to_owned()),
+                body: Some("Notes".to_owned()),
+                prerelease: true,
+                published_at: "2024-01-02T00:00:00Z".to_owned(),
+                assets: vec![AssetInfo {
+                    name: "bar-x86_64-unknown-linux-gnu.tar.gz".to_owned(),
+                    url: "https://api.github.com/repos/foo/bar/releases/assets/7".to_owned(),
+                    browser_download_url: "https://github.com/foo/bar/releases/download/v1.2.0/bar-x86_64-unknown-linux-gnu.tar.gz".to_owned(),
+                    content_type: "application/gzip".to_owned(),
+                    size: 1024,
+                }],
+            }
+        );
+        assert_eq!(ReleaseInfo::from(release()), info);
+        assert_eq!(info.version(), "1.2.0");
+        assert_eq!(info.display_name(), "Version 1.2");
+        assert_eq!(
+            info.asset_for("x86_64-unknown-linux-gnu"),
+            info.assets.first()
+        );
+        assert_eq!(info.asset_for("aarch64-apple-darwin"), None);
+    }
+
+    #[test]
+    fn test_release_info_without_name() {
+        let mut release = release();
+        release.name = None;
+        release.tag_name = "1.2.0".to_owned();
+        release.body = None;
+        release.assets.clear();
+        let info = ReleaseInfo::from(release);
+        assert_eq!(info.name, None);
+        assert_eq!(info.body, None);
+        assert!(info.assets.is_empty());
+        assert_eq!(info.version(), "1.2.0");
+        assert_eq!(info.display_name(), "1.2.0");
+    }
+}
@@ -0,0 +1,361 @@
+//! Comparing the release GitHub marks as latest with the highest version.
+
+use crate::{
+    redact_url, versions::release_version, Call, GitHub, GitHubReleaseItem, LookupError,
+    RepositorySpec, Result, TaggedVersion,
+};
+use log::debug;
+
+/// The release GitHub marks as latest and the highest version, when they differ.
+///
+/// See [`GitHub::latest_discrepancy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discrepancy {
+    /// The release GitHub marks as latest.
+    pub github_latest: TaggedVersion,
+    /// The highest version of the repository's published, stable releases.
+    pub semver_latest: TaggedVersion,
+}
+
+impl GitHub {
+    /// Get the release that GitHub marks as latest.
+    ///
+    /// Unless a maintainer picked a release, this is the most recently created
+    /// release that isn't a draft or a prerelease.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let release = github.get_marked_latest("celeo/github_release_check").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::NoReleases`] if GitHub doesn't
+    /// mark any release as latest, or for any of the reasons in [`GitHub::query`].
+    pub fn get_marked_latest<R>(&self, repository: R) -> Result<GitHubReleaseItem>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        self.marked_latest(&repository.try_into()?)
+    }
+
+    /// Get the release GitHub marks as latest, as in [`GitHub::get_marked_latest`].
+    pub(crate) fn marked_latest(&self, spec: &RepositorySpec) -> Result<GitHubReleaseItem> {
+        let url = self.spec_url(spec, "releases/latest");
+        debug!("Querying GitHub at {}", redact_url(&url));
+        match self.get(&url, &[], &mut Call::default()) {
+            Ok(response) => self.read_json(response),
+            Err(LookupError::RepositoryNotFound) => Err(LookupError::NoReleases),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the most recently published release, without reading any versions.
+    ///
+    /// This suits repositories whose tags are dates or code names rather than
+    /// versions. Releases are compared by the date set with
+    /// [`GitHubBuilder::date_field`](crate::GitHubBuilder::date_field), and releases
+    /// with the same date as in [`DateField::compare`](crate::DateField::compare).
+    /// Drafts are never picked, and prereleases only if `include_prereleases`
+    /// is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let release = github.get_latest_by_date("celeo/github_release_check", false).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::NoReleases`] if no release is
+    /// eligible, or for any of the reasons in [`GitHub::query`].
+    pub fn get_latest_by_date<R>(
+        &self,
+        repository: R,
+        include_prereleases: bool,
+    ) -> Result<GitHubReleaseItem>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let field = self.settings.date_field;
+        self.query(repository)?
+            .into_iter()
+            .filter(|release| !release.draft && (include_prereleases || !release.prerelease))
+            .max_by(|a, b| field.compare(a, b))
+            .ok_or(LookupError::NoReleases)
+    }
+
+    /// Check whether the release GitHub marks as latest is also the highest version.
+    ///
+    /// GitHub marks the most recently created release as latest, which isn't the
+    /// highest version when older branches get patch releases; then both are
+    /// returned. Only published, stable releases are compared, and versions are read
+    /// the same way as in [`GitHub::get_latest_version`]. If the release GitHub
+    /// marks as latest doesn't have a version, there's nothing to compare and
+    /// `None` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// if let Some(d) = github.latest_discrepancy("celeo/github_release_check").unwrap() {
+    ///     println!("GitHub shows {} as latest, but {} is higher", d.github_latest.tag, d.semver_latest.tag);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in [`GitHub::get_marked_latest`].
+    pub fn latest_discrepancy<R>(&self, repository: R) -> Result<Option<Discrepancy>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec = repository.try_into()?;
+        let source = self.settings.version_source;
+        let prefixes = &self.settings.version_prefixes;
+        let marked = self.marked_latest(&spec)?;
+        let Some(github_latest) =
+            release_version(&marked.tag_name, marked.name.as_deref(), source, prefixes)
+        else {
+            debug!(
+                "Release marked latest has tag {}, which isn't a version",
+                marked.tag_name
+            );
+            return Ok(None);
+        };
+        let semver_latest = self
+            .partial_query(&spec)?
+            .releases
+            .iter()
+            .filter(|release| !release.draft && !release.prerelease)
+            .filter_map(|release| {
+                release_version(&release.tag_name, release.name.as_deref(), source, prefixes)
+            })
+            .filter(|tagged| tagged.version.pre.is_empty())
+            .max_by(|a, b| a.version.cmp(&b.version));
+        Ok(match semver_latest {
+            Some(semver_latest) if semver_latest.version != github_latest.version => {
+                Some(Discrepancy {
+                    github_latest,
+                    semver_latest,
+                })
+            }
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{mock_github, permutations, release_json},
+        DateField, GitHub,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    fn release(tag: &str, prerelease: bool) -> String {
+        release_json(tag, json!({ "prerelease": prerelease })).to_string()
+    }
+
+    fn dated(
+        id: usize,
+        tag: &str,
+        prerelease: bool,
+        created: &str,
+        published: Option<&str>,
+    ) -> String {
+        release_json(
+            tag,
+            json!({
+                "id": id, "prerelease": prerelease,
+                "created_at": created, "published_at": published,
+            }),
+        )
+        .to_string()
+    }
+
+    #[test]
+    fn test_latest_by_date_ties_ignore_order() {
+        let same = Some("2024-05-01T12:00:00Z");
+        let releases = [
+            dated(1, "import-a", false, "2024-05-01T11:59:00Z", same),
+            dated(2, "import-b", false, "2024-05-01T11:58:00Z", same),
+            dated(3, "import-c", false, "2024-05-01T11:59:00Z", same),
+            dated(4, "import-d", false, "2024-04-01T00:00:00Z", same),
+        ];
+        for (index, shuffled) in permutations(&releases).into_iter().enumerate() {
+            let path = format!("/repos/foo/ties-{index}/releases");
+            let _m = mock("GET", path.as_str())
+                .match_query(Matcher::Any)
+                .with_body(format!("[{}]", shuffled.join(", ")))
+                .create();
+            let latest = mock_github()
+                .get_latest_by_date(format!("foo/ties-{index}"), false)
+                .unwrap();
+            assert_eq!(latest.tag_name, "import-c", "{shuffled:?}");
+        }
+    }
+
+    #[test]
+    fn test_latest_discrepancy() {
+        let _latest = mock("GET", "/repos/foo/bar/releases/latest")
+            .with_body(release("v1.8.9", false))
+            .create();
+        let _all = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}, {}]",
+                release("v1.8.9", false),
+                release("v3.0.0-rc.1", true),
+                release("v2.1.0", false)
+            ))
+            .create();
+        let discrepancy = mock_github()
+            .latest_discrepancy("foo/bar")
+            .unwrap()
+            .unwrap();
+        assert_eq!(discrepancy.github_latest.tag, "v1.8.9");
+        assert_eq!(discrepancy.semver_latest.tag, "v2.1.0");
+    }
+
+    #[test]
+    fn test_latest_agrees() {
+        let _latest = mock("GET", "/repos/foo/bar/releases/latest")
+            .with_body(release("v2.1.0", false))
+            .create();
+        let _all = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}, {}]",
+                release("v3.0.0-rc.1", true),
+                release("v2.1.0", false),
+                release("v1.8.9", false)
+            ))
+            .create();
+        assert_eq!(mock_github().latest_discrepancy("foo/bar").unwrap(), None);
+    }
+
+    #[test]
+    fn test_latest_by_date() {
+        let _m = mock("GET", "/repos/foo/dated/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}, {}, {}]",
+                dated(
+                    4,
+                    "nightly-rc",
+                    true,
+                    "2024-05-01T00:00:00Z",
+                    Some("2024-05-01T00:00:00Z")
+                ),
+                dated(3, "2024.03", false, "2024-03-01T00:00:00Z", None),
+                dated(
+                    2,
+                    "2024.04",
+                    false,
+                    "2024-02-01T00:00:00Z",
+                    Some("2024-04-01T00:00:00Z")
+                ),
+                dated(
+                    1,
+                    "2024.01",
+                    false,
+                    "2024-01-01T00:00:00Z",
+                    Some("2024-01-01T00:00:00Z")
+                ),
+            ))
+            .create();
+        let github = mock_github();
+        assert_eq!(
+            github
+                .get_latest_by_date("foo/dated", false)
+                .unwrap()
+                .tag_name,
+            "2024.04"
+        );
+        assert_eq!(
+            github
+                .get_latest_by_date("foo/dated", true)
+                .unwrap()
+                .tag_name,
+            "nightly-rc"
+        );
+    }
+
+    #[test]
+    fn test_latest_by_date_tie() {
+        let _m = mock("GET", "/repos/foo/tied/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}, {}]",
+                dated(7, "bravo", false, "", Some("2024-06-01T12:00:00Z")),
+                dated(9, "charlie", false, "", Some("2024-06-01T12:00:00Z")),
+                dated(8, "alpha", false, "", Some("2024-06-01T12:00:00Z")),
+            ))
+            .create();
+        let latest = mock_github().get_latest_by_date("foo/tied", false).unwrap();
+        assert_eq!(latest.tag_name, "charlie");
+        assert_eq!(latest.id, 9);
+    }
+
+    #[test]
+    fn test_latest_by_date_only_prereleases() {
+        let _m = mock("GET", "/repos/foo/only-nightly/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}]",
+                dated(1, "nightly", true, "2024-01-01T00:00:00Z", None)
+            ))
+            .create();
+        assert!(matches!(
+            mock_github().get_latest_by_date("foo/only-nightly", false),
+            Err(crate::LookupError::NoReleases)
+        ));
+    }
+
+    #[test]
+    fn test_latest_by_date_field() {
+        let _m = mock("GET", "/repos/foo/backfilled/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}]",
+                dated(
+                    2,
+                    "backfilled",
+                    false,
+                    "2023-01-01T00:00:00Z",
+                    Some("2024-06-01T00:00:00Z")
+                ),
+                dated(
+                    1,
+                    "recent",
+                    false,
+                    "2024-01-01T00:00:00Z",
+                    Some("2024-02-01T00:00:00Z")
+                ),
+            ))
+            .create();
+        let latest = |field| {
+            GitHub::builder()
+                .api_root(&format!("{}/", mockito::server_url()))
+                .date_field(field)
+                .build()
+                .unwrap()
+                .get_latest_by_date("foo/backfilled", false)
+                .unwrap()
+                .tag_name
+        };
+        assert_eq!(latest(DateField::Published), "backfilled");
+        assert_eq!(latest(DateField::Created), "recent");
+    }
+}
@@ -36,6 +36,19 @@
 //!
 //! Of course, handling these `Result`s with something other than just unwrapping them is a good idea.
 //!
+//! Further options, like waiting out an exhausted rate limit, are set through [`GitHub::builder`]:
+//!
+//! ```rust,no_run
+//! use github_release_check::GitHub;
+//! use std::time::Duration;
+//!
+//! let github = GitHub::builder()
+//!     .wait_on_rate_limit(Duration::from_secs(300))
+//!     .build()
+//!     .unwrap();
+//! let versions = github.get_all_versions("celeo/github_release_check").unwrap();
+//! ```
+//!
 //! If you wish to gain more information on each release, use the `query` function:
 //!
 //! ```rust,no_run
@@ -61,50 +74,528 @@
     unused_results
 )]
 
-use log::debug;
-use once_cell::sync::Lazy;
+use log::{debug, warn};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use regex::Regex;
 use reqwest::{
-    blocking::{Client, ClientBuilder},
+    blocking::{Client, ClientBuilder, Response},
     header::{self, HeaderMap},
+    StatusCode,
 };
 use semver::Version;
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    fmt,
+    io::Read,
+    net::SocketAddr,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 use thiserror::Error;
 
+use logging::{redact_url, RequestLog};
+
+mod age;
+mod asset_cache;
+mod assets;
+#[cfg(feature = "atom")]
+mod atom;
+mod batch;
+mod body;
+mod breaker;
+mod cadence;
+mod channels;
+mod coalesce;
+mod compact;
+mod comparator;
+mod comparison;
+mod config;
+#[cfg(feature = "crates-io")]
+mod crates_io;
+mod dedupe;
+mod deprecation;
+mod diff;
+mod enterprise;
+mod error_code;
+#[cfg(feature = "test-util")]
+mod fixture;
+#[cfg(feature = "gitea")]
+mod gitea;
+#[cfg(feature = "gitlab")]
+mod gitlab;
+#[cfg(feature = "interop")]
+pub mod interop;
+mod latest;
+#[cfg(feature = "local-git")]
+mod local_git;
+mod logging;
+mod markdown;
+mod notes;
+mod nullable;
+mod options;
+mod org;
+mod page_guard;
+mod pinning;
+pub mod prelude;
+mod provenance;
+mod ratelimit;
+mod release_iter;
+mod repo_id;
+mod resume;
+mod scan;
+mod search;
+mod source;
+mod spec;
+mod stats;
+mod subscriptions;
+mod summary;
+mod tags;
+#[cfg(feature = "keyring")]
+mod token_store;
+mod trace;
+mod update;
+mod versions;
+mod watch;
+#[cfg(feature = "write")]
+mod write;
+
+/// The `semver` crate, at the version used for every [`Version`] in this crate's API.
+///
+/// Use it instead of depending on `semver` yourself to be sure the types match,
+/// or see [`TaggedVersion::to_string_version`] if you need another major version.
+pub use semver;
+
+/// A certificate for [`GitHubBuilder::pin_certificates`], from the `reqwest`
+/// version this crate uses.
+pub use reqwest::Certificate;
+
+pub use asset_cache::prune_asset_cache;
+pub use assets::{AssetPattern, DownloadStats};
+pub use cadence::CadenceStats;
+pub use channels::{Channel, ChannelSummary};
+pub use compact::{CompactAsset, CompactRelease};
+pub use comparator::LatestTag;
+pub use comparison::RepoComparison;
+pub use config::{CircuitBreakerSnapshot, ConfigSnapshot};
+pub use dedupe::{DedupedVersions, VersionAlias};
+pub use deprecation::DeprecationNotice;
+pub use diff::{diff_releases, ReleaseChange, ReleaseDiff, ReleaseRef, ScanDiff, VersionChange};
+pub use enterprise::ServerInfo;
+pub use latest::Discrepancy;
+pub use options::QueryOptions;
+pub use org::{PartialScan, ScanCursor};
+pub use ratelimit::RateLimitInfo;
+pub use release_iter::ReleaseIterExt;
+pub use resume::{PageCursor, ResumableResult};
+pub use scan::{RepoResult, RepositoryScan, ScanOutcome, ScanReport};
+pub use search::SearchQuery;
+pub use source::ReleaseSource;
+pub use spec::RepositorySpec;
+pub use stats::QueryStats;
+pub use subscriptions::{Subscription, SubscriptionStatus, Subscriptions};
+pub use summary::ReleaseSummary;
+pub use trace::{ResolutionStrategy, ResolutionTrace};
+pub use update::{spawn_update_checker, UpdateChecker, UpdateReceiver, UpdateStatus};
+pub use versions::{TaggedVersion, VersionOrigin, VersionSource};
+pub use watch::ReleaseWatcher;
+
+#[cfg(feature = "crates-io")]
+pub use crates_io::{max_of, CratesIo, DEFAULT_CRATES_INDEX_ROOT};
+#[cfg(feature = "gitea")]
+pub use gitea::{Gitea, CODEBERG_API_ROOT};
+#[cfg(feature = "gitlab")]
+pub use gitlab::{GitLab, DEFAULT_GITLAB_API_ROOT};
+#[cfg(feature = "local-git")]
+pub use local_git::{LocalRepo, TagReader};
+#[cfg(feature = "keyring")]
+pub use token_store::{Keyring, TokenBackend, TokenStore, KEYRING_USER};
+#[cfg(feature = "write")]
+pub use write::NewRelease;
+
 /// Errors that may be raised by this crate.
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum LookupError {
     /// May arise from working with the HTTP client.
     #[error("HTTP client error")]
     HttpClient(#[from] reqwest::Error),
     /// May arise from working with the HTTP client.
     #[error("invalid header value")]
-    HeaderValue(#[from] reqwest::header::InvalidHeaderValue),
+    HeaderValue(#[from] header::InvalidHeaderValue),
+    /// May arise if the name given to [`GitHubBuilder::correlation_header`] isn't
+    /// a valid header name.
+    #[error("invalid header name")]
+    HeaderName(#[from] header::InvalidHeaderName),
     /// May arise from working with the HTTP client.
     #[error("could not get header value")]
-    HeaderToString(#[from] reqwest::header::ToStrError),
+    HeaderToString(#[from] header::ToStrError),
+    /// May arise if the "link" header GitHub sends for pagination can't be understood.
+    #[error("invalid pagination link: {0}")]
+    PaginationParse(String),
     /// May arise if the repository does not have any releases.
     #[error("no release found")]
     NoReleases,
     /// May arise from a mis-supplied repository, or from not having access.
     #[error("repository not found")]
     RepositoryNotFound,
+    /// May arise if a repository isn't `"owner/repo"` or a URL of a repository,
+    /// or, with the `crates-io` feature, if a name can't be a crate's.
+    ///
+    /// See [`RepositorySpec`].
+    #[error("invalid repository: {0}")]
+    InvalidRepository(String),
+    /// May arise if the repository has no release or tag with the given name.
+    #[error("release not found")]
+    ReleaseNotFound,
     /// May arise from GitHub API missing or incorrect authentication.
-    #[error("authentication error")]
-    AuthenticationError(u16),
+    #[error("authentication error{}", request_id_note(.request_id.as_deref()))]
+    AuthenticationError {
+        /// The HTTP status code, 401 or 403.
+        status: u16,
+        /// The `x-github-request-id` of the response, if it had one.
+        request_id: Option<String>,
+    },
+    /// May arise when drafts are asked for without a token, since GitHub only
+    /// shows drafts to authenticated users with push access.
+    ///
+    /// See [`QueryOptions::drafts_only`].
+    #[error("draft releases are only visible with a token; set one with GitHubBuilder::token")]
+    AuthenticationRequiredForDrafts,
+    /// May arise when the GitHub API rate limit has been exhausted.
+    ///
+    /// `reset_at` is when the limit resets, if GitHub said so.
+    #[error("rate limit exceeded{}", request_id_note(.request_id.as_deref()))]
+    RateLimited {
+        /// When the rate limit resets.
+        reset_at: Option<SystemTime>,
+        /// The `x-github-request-id` of the response, if there was one; it's
+        /// `None` when the request wasn't sent because the limit was known to be
+        /// used up.
+        request_id: Option<String>,
+    },
+    /// May arise when GitHub's secondary rate limit (abuse detection) is hit.
+    ///
+    /// `retry_after` is how long GitHub asked to wait, if it said so.
+    #[error("secondary rate limit exceeded{}", request_id_note(.request_id.as_deref()))]
+    SecondaryRateLimited {
+        /// How long to wait before retrying.
+        retry_after: Option<Duration>,
+        /// The `x-github-request-id` of the response, if it had one.
+        request_id: Option<String>,
+    },
+    /// May arise when a call needs more requests than its budget allows.
+    ///
+    /// `total_pages` is only known if GitHub returned pagination information.
+    #[error("request budget exceeded after {fetched_pages} page(s)")]
+    BudgetExceeded {
+        /// How many pages were fetched before the budget ran out.
+        fetched_pages: usize,
+        /// How many pages there are in total.
+        total_pages: Option<usize>,
+    },
+    /// May arise if walking the pages would never end, because a page links back
+    /// to one already fetched or there are more pages than allowed.
+    ///
+    /// See [`GitHubBuilder::max_pages`].
+    #[error("pagination loop detected after {pages_fetched} page(s)")]
+    PaginationLoop {
+        /// How many pages were fetched before the walk was stopped.
+        pages_fetched: usize,
+    },
+    /// May arise if a request takes longer than allowed.
+    ///
+    /// See [`GitHubBuilder::connect_timeout`] and [`GitHubBuilder::timeout`].
+    #[error("request timed out while {phase}")]
+    Timeout {
+        /// What the request was doing when it timed out.
+        phase: TimeoutPhase,
+    },
     /// May arise if GitHub returns an error code from the lookup.
-    #[error("received error HTTP response code")]
-    ErrorHttpResponse(u16),
+    #[error(
+        "received error HTTP response code {status}{}{}",
+        message_note(.message.as_deref()),
+        request_id_note(.request_id.as_deref())
+    )]
+    ErrorHttpResponse {
+        /// The HTTP status code.
+        status: u16,
+        /// The `message` GitHub sent with the error, if any, in the language
+        /// asked for with [`GitHubBuilder::accept_language`].
+        message: Option<String>,
+        /// The `x-github-request-id` of the response, if it had one, which
+        /// GitHub support asks for.
+        request_id: Option<String>,
+    },
+    /// May arise if GitHub answers a page request with "304 Not Modified", which
+    /// has no releases in it. This only happens if the request carried a cache
+    /// validator, like `If-None-Match`, that still matches.
+    #[error("page not modified")]
+    NotModified,
+    /// May arise if the API root points at something other than a GitHub API, like
+    /// the website of a GitHub enterprise server, which answers with HTML pages.
+    #[error("{api_root} did not answer like a GitHub API; for GitHub enterprise, the API root usually ends in /api/v3/")]
+    NotAnApiEndpoint {
+        /// The API root that was used.
+        api_root: String,
+    },
+    /// May arise if a response body is larger than the limit.
+    ///
+    /// See [`GitHubBuilder::max_response_bytes`].
+    #[error("response from {url} is larger than the limit of {limit} bytes")]
+    ResponseTooLarge {
+        /// The limit, in bytes.
+        limit: u64,
+        /// The URL of the request.
+        url: String,
+    },
+    /// May arise if a response from GitHub does not match the expected model.
+    #[error("invalid response at line {line}, column {column}: {message}")]
+    InvalidResponse {
+        /// Line of the problem in the response body.
+        line: usize,
+        /// Column of the problem in the response body.
+        column: usize,
+        /// What was wrong.
+        message: String,
+    },
+    /// May arise if a page of results from GitHub is valid JSON but not an array,
+    /// like an error object sent with a success status by a misconfigured server.
+    #[error("expected a JSON array of results, got: {body_snippet}")]
+    UnexpectedBody {
+        /// The start of the body.
+        body_snippet: String,
+    },
+    /// May arise if the server's certificate doesn't chain to any of those set
+    /// with [`GitHubBuilder::pin_certificates`], which may mean the connection is
+    /// being intercepted.
+    #[error("the certificate of {host} does not match any pinned certificate")]
+    CertificatePinFailed {
+        /// The host that was connected to.
+        host: String,
+    },
+    /// May arise if a release feed is not valid Atom XML.
+    #[cfg(feature = "atom")]
+    #[error("invalid release feed: {0}")]
+    InvalidFeed(String),
+    /// May arise if the tags of a local git repository can't be read.
+    #[cfg(feature = "local-git")]
+    #[error("could not read local git repository")]
+    LocalGit(#[source] std::io::Error),
+    /// May arise if GitHub rejects the data sent to it, like a release for a tag
+    /// that already has one.
+    #[cfg(feature = "write")]
+    #[error(
+        "validation failed: {}{}",
+        .messages.join("; "),
+        request_id_note(.request_id.as_deref())
+    )]
+    ValidationFailed {
+        /// What GitHub said was wrong.
+        messages: Vec<String>,
+        /// The `x-github-request-id` of the response, if it had one.
+        request_id: Option<String>,
+    },
+    /// May arise if a [`TokenStore`] has no token for the service.
+    #[cfg(feature = "keyring")]
+    #[error("no token stored for {service}")]
+    TokenNotFound {
+        /// The service the token was looked up for.
+        service: String,
+    },
+    /// May arise if a [`TokenStore`] can't be read or written, like when the
+    /// keychain is locked.
+    #[cfg(feature = "keyring")]
+    #[error("could not use the token store for {service}: {message}")]
+    TokenStore {
+        /// The service the token was looked up or kept for.
+        service: String,
+        /// What the store said was wrong.
+        message: String,
+    },
+    /// May arise if the running version of a program, given to check for updates,
+    /// isn't a version, or if the tag picked with
+    /// [`GitHubBuilder::version_comparator`] isn't a Semantic Version.
+    ///
+    /// See [`GitHub::check_update_against_pkg_version`] and [`GitHub::get_latest_tag`].
+    #[error("invalid version: {0}")]
+    InvalidVersion(String),
+    /// May arise if the state of an [`UpdateChecker`] can't be saved.
+    #[error("could not write update check state to {path}")]
+    StateWrite {
+        /// Path to the state file.
+        path: String,
+        /// Underlying IO error.
+        source: std::io::Error,
+    },
+    /// May arise if the pattern for tags to ignore isn't a valid regular expression.
+    ///
+    /// See [`GitHubBuilder::ignore_tags_matching`].
+    #[error("invalid pattern for ignored tags")]
+    InvalidTagPattern(#[source] regex::Error),
+    /// May arise if a pattern for asset names isn't a valid regular expression.
+    ///
+    /// See [`AssetPattern::regex`].
+    #[error("invalid pattern for asset names")]
+    InvalidAssetPattern(#[source] regex::Error),
+    /// May arise if a pattern for searching releases isn't a valid regular expression.
+    ///
+    /// See [`SearchQuery::regex`].
+    #[error("invalid pattern for searching releases")]
+    InvalidSearchPattern(#[source] regex::Error),
+    /// May arise if no asset of the release matches the pattern.
+    #[error("no matching asset found")]
+    AssetNotFound,
+    /// May arise if more than one asset of the release matches a strict pattern.
+    ///
+    /// See [`AssetPattern::strict`].
+    #[error("more than one asset matches: {}", .names.join(", "))]
+    AmbiguousAsset {
+        /// The names of the matching assets.
+        names: Vec<String>,
+    },
+    /// May arise if an asset name template has a variable without a value.
+    ///
+    /// See [`GitHub::asset_download_url`].
+    #[error("no value for {{{name}}} in the asset name template")]
+    MissingTemplateVariable {
+        /// The name of the variable.
+        name: String,
+    },
+    /// May arise if an asset name template has a `{` that isn't closed, or
+    /// doesn't make a valid URL.
+    ///
+    /// See [`GitHub::asset_download_url`].
+    #[error("invalid asset name template \"{template}\"")]
+    InvalidAssetTemplate {
+        /// The template.
+        template: String,
+    },
+    /// May arise if the asset cache can't be read or written.
+    ///
+    /// See [`GitHub::download_asset_cached`].
+    #[error("could not use asset cache at {path}")]
+    AssetCache {
+        /// Path of the file or directory in the cache.
+        path: String,
+        /// Underlying IO error.
+        source: std::io::Error,
+    },
+    /// May arise if a downloaded asset isn't the size GitHub lists for it.
+    #[error("downloaded {received} bytes of an asset of {expected} bytes")]
+    AssetSizeMismatch {
+        /// The size GitHub lists for the asset.
+        expected: u64,
+        /// How many bytes were downloaded.
+        received: u64,
+    },
+    /// May arise when no GitHub API could be found for an enterprise server.
+    ///
+    /// See [`GitHub::for_enterprise`].
+    #[error("no GitHub API found at {}", .attempted.join(" or "))]
+    ApiRootNotFound {
+        /// The URLs that were tried.
+        attempted: Vec<String>,
+    },
+    /// May arise when the circuit breaker is open after repeated failures.
+    ///
+    /// See [`GitHubBuilder::circuit_breaker`].
+    #[error("circuit breaker open after repeated failures")]
+    CircuitOpen {
+        /// When a request will next be allowed through.
+        retry_at: SystemTime,
+    },
+    /// An error from a coalesced request that can't be copied to every caller.
+    ///
+    /// See [`GitHubBuilder::coalesce_requests`].
+    #[error("{0}")]
+    Shared(Arc<LookupError>),
+    /// May arise when a coalesced request was waiting on a query that panicked.
+    ///
+    /// See [`GitHubBuilder::coalesce_requests`].
+    #[error("the query this request was waiting on panicked")]
+    FetchPanicked,
+    /// May arise if a fixture file cannot be read.
+    #[cfg(feature = "test-util")]
+    #[error("could not read fixture file {path}")]
+    FixtureRead {
+        /// Path to the fixture file.
+        path: String,
+        /// Underlying IO error.
+        source: std::io::Error,
+    },
+    /// May arise if a fixture file does not contain the expected data.
+    #[cfg(feature = "test-util")]
+    #[error("invalid fixture file {path} at line {line}, column {column}: {message}")]
+    InvalidFixture {
+        /// Path to the fixture file.
+        path: String,
+        /// Line of the problem in the file.
+        line: usize,
+        /// Column of the problem in the file.
+        column: usize,
+        /// Description of the problem.
+        message: String,
+    },
 }
 
 type Result<T> = std::result::Result<T, LookupError>;
 
+/// What a request was doing when it timed out.
+///
+/// See [`LookupError::Timeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutPhase {
+    /// Opening the connection.
+    Connect,
+    /// Waiting for the response, after connecting.
+    Response,
+}
+
+impl fmt::Display for TimeoutPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Connect => "connecting",
+            Self::Response => "waiting for the response",
+        })
+    }
+}
+
 const DEFAULT_USER_AGENT: &str = "github.com/celeo/github_version_check";
 const DEFAULT_ACCEPT_HEADER: &str = "application/vnd.github.v3+json";
 const PAGINATION_REQUEST_AMOUNT: usize = 100;
-static PAGE_EXTRACT_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(\w*)page=(\d+)").expect("Could not compile regex"));
+/// How long to wait before the first retry of a server error, doubled for each retry after.
+const SERVER_ERROR_BACKOFF: Duration = Duration::from_millis(200);
+/// Releases below this many are parsed on the current thread, as handing them
+/// to rayon's thread pool would take longer than the parsing.
+#[cfg(feature = "rayon")]
+const PARALLEL_THRESHOLD: usize = 1000;
+/// Characters escaped in a URL path segment. `/` is escaped too, so that
+/// a tag like `releases/v1.2.0` stays a single segment.
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'+')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
 
 /// The default GitHub instance API root endpoint.
 ///
@@ -141,6 +632,7 @@ fn generate_headers(token: Option<&str>) -> Result<HeaderMap> {
 #[allow(missing_docs)]
 pub struct GitHubReleaseItem {
     pub url: String,
+    #[serde(default)]
     pub html_url: String,
     pub assets_url: String,
     pub upload_url: String,
@@ -150,345 +642,3839 @@ pub struct GitHubReleaseItem {
     pub draft: bool,
     pub prerelease: bool,
     pub created_at: String,
+    /// Empty for releases that haven't been published, like drafts.
+    #[serde(deserialize_with = "nullable::null_as_empty")]
     pub published_at: String,
     pub body: Option<String>,
+    #[serde(default, deserialize_with = "nullable::null_as_empty")]
+    pub target_commitish: String,
+    /// Empty if there are none, including when GitHub sends `null`, as some
+    /// GitHub enterprise versions do.
+    #[serde(default, deserialize_with = "nullable::null_as_empty_vec")]
+    pub assets: Vec<GitHubAssetItem>,
+    /// Whether the release is immutable, if GitHub says; its tag and assets
+    /// can't be changed once published.
+    pub immutable: Option<bool>,
 }
 
-/// Struct to communicate with the GitHub REST API.
+impl GitHubReleaseItem {
+    /// Read a release from an untyped JSON object, like those from [`GitHub::query_raw`].
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the object does not match the expected model.
+    pub fn from_value(value: &serde_json::Value) -> std::result::Result<Self, serde_json::Error> {
+        Self::deserialize(value)
+    }
+
+    /// Get the assets that were last changed after the timestamp, like the
+    /// release's `published_at`, to find files replaced after publication.
+    ///
+    /// Timestamps are compared as ISO 8601 UTC strings, the way GitHub writes
+    /// them. Assets without an `updated_at` are left out.
+    #[must_use]
+    pub fn assets_updated_after(&self, timestamp: &str) -> Vec<&GitHubAssetItem> {
+        self.assets
+            .iter()
+            .filter(|asset| !asset.updated_at.is_empty() && asset.updated_at.as_str() > timestamp)
+            .collect()
+    }
+}
+
+/// Data for a file attached to a release in the GitHub API response.
+///
+/// For information on the struct keys, see [the GitHub docs].
+///
+/// [the GitHub docs]: https://docs.github.com/en/rest/releases/assets#list-release-assets
+#[derive(Debug, Deserialize, Clone)]
+#[allow(missing_docs)]
+pub struct GitHubAssetItem {
+    pub url: String,
+    pub browser_download_url: String,
+    pub id: usize,
+    pub name: String,
+    pub label: Option<String>,
+    pub content_type: String,
+    pub size: u64,
+    pub download_count: u64,
+    pub created_at: String,
+    /// Changes when the file is replaced, while the name stays the same.
+    pub updated_at: String,
+    /// Like `"uploaded"`, or `"open"` while an upload is in progress.
+    #[serde(default, deserialize_with = "nullable::null_as_empty")]
+    pub state: String,
+    /// Who uploaded the file, if GitHub still knows; it's `None` for deleted
+    /// accounts.
+    pub uploader: Option<GitHubUser>,
+    /// Digest of the file, like `"sha256:…"`, if GitHub provides one.
+    pub digest: Option<String>,
+}
+
+/// Data for a GitHub account, like the uploader of an asset.
+///
+/// For information on the struct keys, see [the GitHub docs].
+///
+/// [the GitHub docs]: https://docs.github.com/en/rest/releases/assets#get-a-release-asset
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct GitHubUser {
+    pub login: String,
+    pub id: usize,
+    #[serde(default, deserialize_with = "nullable::null_as_empty")]
+    pub html_url: String,
+    /// Like `"User"` or `"Bot"`.
+    #[serde(rename = "type", default, deserialize_with = "nullable::null_as_empty")]
+    pub kind: String,
+}
+
+/// Release data that every supported forge provides.
+///
+/// Forge-specific release structs, like [`GitHubReleaseItem`], carry more
+/// information; this struct is the subset that can be filled in everywhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseItem {
+    /// Name of the tag the release is for.
+    pub tag_name: String,
+    /// Title of the release, if it has one.
+    pub name: Option<String>,
+    /// Release notes, if there are any.
+    pub body: Option<String>,
+    /// Whether the release is marked as not ready for general use.
+    pub prerelease: bool,
+    /// When the release was published, as an ISO 8601 timestamp.
+    pub published_at: Option<String>,
+    /// Link to the release's web page.
+    pub html_url: String,
+}
+
+impl From<GitHubReleaseItem> for ReleaseItem {
+    fn from(item: GitHubReleaseItem) -> Self {
+        Self {
+            tag_name: item.tag_name,
+            name: item.name,
+            body: item.body,
+            prerelease: item.prerelease,
+            published_at: (!item.published_at.is_empty()).then_some(item.published_at),
+            html_url: item.html_url,
+        }
+    }
+}
+
+/// An access token, kept out of `Debug` output.
+#[derive(Clone)]
+struct Token(String);
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Token(<redacted>)")
+    }
+}
+
+/// Optional behavior of a [`GitHub`] instance, set through [`GitHubBuilder`].
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
+struct Settings {
+    wait_on_rate_limit: Option<Duration>,
+    concurrency: Option<usize>,
+    max_requests_per_call: Option<usize>,
+    max_pages: Option<usize>,
+    on_budget: BudgetBehavior,
+    skip_archived: bool,
+    tag_scan_threshold: Option<usize>,
+    ignore_tag_v_prefix: bool,
+    version_source: VersionSource,
+    version_prefixes: Vec<String>,
+    ignored_tags: Vec<String>,
+    ignored_tag_pattern: Option<Regex>,
+    hide_ignored_tags: bool,
+    dedupe_versions: bool,
+    include_drafts: bool,
+    latest_strategy: LatestStrategy,
+    version_comparator: Option<comparator::VersionComparator>,
+    date_field: DateField,
+    restart_on_page_shift: bool,
+    max_response_bytes: Option<u64>,
+    server_error_retries: usize,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    correlation_header: Option<(String, String)>,
+    accept_language: Option<String>,
+    compression: bool,
+    lenient_headers: bool,
+    pinned_certificates: Vec<Certificate>,
+    resolved_hosts: Vec<(String, SocketAddr)>,
+    #[cfg(feature = "atom")]
+    feed_fallback: bool,
+}
+
+/// What to do when a call would need more requests than its budget allows.
+///
+/// See [`GitHubBuilder::max_requests_per_call`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum BudgetBehavior {
+    /// Return the releases fetched so far, flagged as truncated.
+    Truncate,
+    /// Return [`LookupError::BudgetExceeded`].
+    #[default]
+    Error,
+}
+
+/// How the latest-version functions find the latest release.
+///
+/// See [`GitHubBuilder::latest_strategy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum LatestStrategy {
+    /// Fetch every release and pick the highest version.
+    #[default]
+    SemverScan,
+    /// Use the release GitHub marks as latest, which takes a single request.
+    ///
+    /// If that release doesn't have a version or is ignored, every release is
+    /// fetched instead.
+    MarkedLatest,
+}
+
+/// Which of a release's dates to go by when ordering releases by date.
+///
+/// If the chosen date is missing, like `published_at` for a release that hasn't
+/// been published, the other one is used instead.
+///
+/// See [`GitHubBuilder::date_field`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum DateField {
+    /// When the release was published.
+    #[default]
+    Published,
+    /// When the release was created, which for backfilled releases can be long
+    /// before they were published.
+    Created,
+}
+
+impl DateField {
+    /// The release's date, falling back to the other date if it's missing.
+    ///
+    /// The API's timestamps are all in UTC in the same format, so they can be
+    /// compared as strings.
+    #[must_use]
+    pub fn date_of(self, release: &GitHubReleaseItem) -> &str {
+        let (preferred, other) = match self {
+            Self::Published => (&release.published_at, &release.created_at),
+            Self::Created => (&release.created_at, &release.published_at),
+        };
+        if preferred.is_empty() {
+            other
+        } else {
+            preferred
+        }
+    }
+
+    /// The release's date that isn't the chosen one, even if it's missing.
+    fn other_date_of(self, release: &GitHubReleaseItem) -> &str {
+        match self {
+            Self::Published => &release.created_at,
+            Self::Created => &release.published_at,
+        }
+    }
+
+    /// Order the releases by date, oldest first, the same way every time.
+    ///
+    /// Releases are compared by [`DateField::date_of`], then by the other date,
+    /// then by ID, and last by tag name, so that releases sharing a date, like
+    /// those from a bulk import, come out in the same order whatever order they
+    /// were listed in. Everything that orders releases by date uses this.
+    #[must_use]
+    pub fn compare(self, a: &GitHubReleaseItem, b: &GitHubReleaseItem) -> Ordering {
+        self.date_of(a)
+            .cmp(self.date_of(b))
+            .then_with(|| self.other_date_of(a).cmp(self.other_date_of(b)))
+            .then_with(|| a.id.cmp(&b.id))
+            .then_with(|| a.tag_name.cmp(&b.tag_name))
+    }
+}
+
+/// The candidate with the highest version, or the last of those if several
+/// have it.
+///
+/// With the `rayon` feature, long lists are compared on rayon's thread pool.
+fn latest_candidate<T: Send>(candidates: Vec<(TaggedVersion, T)>) -> Option<(TaggedVersion, T)> {
+    #[cfg(feature = "rayon")]
+    if candidates.len() >= PARALLEL_THRESHOLD {
+        return candidates
+            .into_par_iter()
+            .max_by(|a, b| a.0.version.cmp(&b.0.version));
+    }
+    candidates
+        .into_iter()
+        .max_by(|a, b| a.0.version.cmp(&b.0.version))
+}
+
+/// The releases that versions can be selected from, and how many were left out.
 #[derive(Debug)]
-pub struct GitHub {
-    client: Client,
+struct Candidates {
+    /// The versions, each with its release (or `None` if it came from the release feed).
+    releases: Vec<(TaggedVersion, Option<GitHubReleaseItem>)>,
+    /// Whether the versions came from the release feed.
+    from_feed: bool,
+    /// How many releases there were before any were left out.
+    considered: usize,
+    /// How many releases were left out because their tag is ignored.
+    ignored: usize,
+}
+
+/// Bookkeeping for a single public call, which may span many requests.
+#[derive(Debug, Default)]
+struct Call {
+    requests: usize,
+    waited: Duration,
+    retries: usize,
+    not_modified: usize,
+    bytes: u64,
+    rate_limit_remaining: Option<u64>,
+    request_id: Option<String>,
+    content_encoding: Option<String>,
+    /// Whether a 422 means GitHub rejected the data that was sent, which only
+    /// holds for calls that write.
+    #[cfg(feature = "write")]
+    validates: bool,
+}
+
+/// The host a request is sent to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Host {
+    /// The REST API, at the API root.
+    Api,
+    /// The website, at the web root, for feeds and downloads.
+    Web,
+}
+
+/// Releases from a query that may have been cut short by the request budget.
+#[derive(Debug, Clone)]
+pub struct PartialQuery<T = GitHubReleaseItem> {
+    /// The releases that were fetched.
+    pub releases: Vec<T>,
+    /// Whether there were more pages that were not fetched.
+    pub truncated: bool,
+}
+
+/// A single page of releases, with the navigation hints GitHub sent along.
+///
+/// See [`GitHub::query_page`].
+#[derive(Debug, Clone)]
+pub struct PageResult<T = GitHubReleaseItem> {
+    /// The releases on the page.
+    pub releases: Vec<T>,
+    /// The number of the last page, if there's more than one page.
+    pub last_page: Option<usize>,
+    /// The number of the next page, if this isn't the last page.
+    pub next_page: Option<usize>,
+    /// The page size the pagination links ask for, which may be less than the
+    /// size requested if the server caps it.
+    pub per_page: Option<usize>,
+    /// The page's entity tag, for conditional requests.
+    pub etag: Option<String>,
+}
+
+/// Builder for [`GitHub`] instances that need more than the defaults.
+///
+/// # Example
+///
+/// ```rust
+/// use github_release_check::{GitHub, DEFAULT_API_ROOT};
+/// use std::time::Duration;
+///
+/// let github = GitHub::builder()
+///     .api_root(DEFAULT_API_ROOT)
+///     .token("your-access-token")
+///     .wait_on_rate_limit(Duration::from_secs(60))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct GitHubBuilder {
     api_root: String,
+    web_root: Option<String>,
+    token: Option<Token>,
+    settings: Settings,
+    coalesce_requests: bool,
+    circuit_breaker: Option<breaker::Config>,
+    ignored_tag_pattern: Option<String>,
 }
 
-impl GitHub {
-    /// Create a new instance of the struct suitable for public GitHub.
+impl Default for GitHubBuilder {
+    fn default() -> Self {
+        Self {
+            api_root: DEFAULT_API_ROOT.to_owned(),
+            web_root: None,
+            token: None,
+            settings: Settings {
+                compression: true,
+                ..Settings::default()
+            },
+            coalesce_requests: false,
+            circuit_breaker: None,
+            ignored_tag_pattern: None,
+        }
+    }
+}
+
+impl GitHubBuilder {
+    /// Set the REST API root of the GitHub instance.
     ///
-    /// The struct created by this function does not set an access token
-    /// and as such can only get information on public GitHub repositories.
+    /// Defaults to [`DEFAULT_API_ROOT`]. See [`GitHub::from_custom`] for
+    /// what this value should look like for GitHub enterprise.
+    #[must_use]
+    pub fn api_root(mut self, api_root: &str) -> Self {
+        api_root.clone_into(&mut self.api_root);
+        self
+    }
+
+    /// Set the root of the GitHub instance's website, for the few lookups that
+    /// go through the website instead of the API.
     ///
-    /// If you need to access information for private repositories or any
-    /// information from a custom GitHub enterprise instance, use the
-    /// `from_custom` function.
+    /// By default, this is worked out from the API root: `https://github.com/` for
+    /// [`DEFAULT_API_ROOT`], and the API root without its `api/v3/` suffix for
+    /// GitHub enterprise. Note that this URL should end in a trailing slash.
+    #[must_use]
+    pub fn web_root(mut self, web_root: &str) -> Self {
+        self.web_root = Some(web_root.to_owned());
+        self
+    }
+
+    /// Set the [GitHub personal access token] used to authenticate requests.
     ///
-    /// This function may return an `Error` if the HTTP client could
-    /// not be constructed or headers initialized. It should be safe
-    /// to unwrap the `Result`.
+    /// [GitHub personal access token]: https://docs.github.com/en/authentication/keeping-your-account-and-data-secure/creating-a-personal-access-token
+    #[must_use]
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(Token(token.to_owned()));
+        self
+    }
+
+    /// Wait for the primary rate limit to reset instead of failing.
     ///
-    /// # Example
+    /// When GitHub reports that the rate limit is exhausted, the request
+    /// is retried once the limit resets (according to the `x-ratelimit-reset`
+    /// header), and pagination continues from the page that was rate limited.
     ///
-    /// ```rust
-    /// use github_release_check::GitHub;
-    /// let github = GitHub::new().unwrap();
-    /// ```
+    /// At most `max_wait` is spent waiting over a single call; if the limit
+    /// would reset later than that, [`LookupError::RateLimited`] is returned.
+    #[must_use]
+    pub fn wait_on_rate_limit(mut self, max_wait: Duration) -> Self {
+        self.settings.wait_on_rate_limit = Some(max_wait);
+        self
+    }
+
+    /// Set how many repositories batch operations like
+    /// [`GitHub::get_latest_versions`] look up at the same time.
     ///
-    /// # Errors
+    /// Defaults to 4. Values below 1 are treated as 1.
+    #[must_use]
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.settings.concurrency = Some(concurrency.max(1));
+        self
+    }
+
+    /// Limit how many HTTP requests a single call may send.
     ///
-    /// This function fails if the headers cannot be constructed.
-    pub fn new() -> Result<Self> {
-        let client = ClientBuilder::new()
-            .default_headers(generate_headers(None)?)
-            .build()?;
-        Ok(Self {
-            client,
-            api_root: DEFAULT_API_ROOT.to_owned(),
-        })
+    /// Every request counts, including pagination and retries after waiting
+    /// on the rate limit. What happens when the budget runs out is set with
+    /// [`GitHubBuilder::on_budget`].
+    #[must_use]
+    pub fn max_requests_per_call(mut self, max_requests: usize) -> Self {
+        self.settings.max_requests_per_call = Some(max_requests);
+        self
     }
 
-    /// Create a new instance of the struct suitable for accessing any GitHub repository
-    /// that can be viewed with the access key on the GitHub instance.
+    /// Limit how many pages a single walk over a listing may fetch.
     ///
-    /// This function has to be used to construct the struct instance whenever the repository
-    /// that you want to get information from is on a custom GitHub enterprise instance and/or
-    /// is private. The access token passed to this function should be a [GitHub personal access token]
-    /// that has the access to view the repository on that GitHub instance.
+    /// Walks also stop when a page links back to a page already fetched, as a
+    /// buggy server or proxy might. Either way, the call fails with
+    /// [`LookupError::PaginationLoop`]. Defaults to 1000 pages, which is 100,000
+    /// releases. Values below 1 are treated as 1.
+    #[must_use]
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.settings.max_pages = Some(max_pages.max(1));
+        self
+    }
+
+    /// Limit the size of each response body read from GitHub, like a page of releases.
     ///
-    /// For the `api_endpoint` argument, pass in the REST API root of the GitHub instance. For public
-    /// GitHub, this can be found in [`DEFAULT_API_ROOT`].
-    /// Your GitHub enterprise may use a subdomain like `"https://api.github.your_domain_root.com/"`, or
-    /// perhaps something like `"https://github.your_domain_root.com/api/v3/"`. Specify the API root that
-    /// you can otherwise send requests to. Note that this URL should end in a trailing slash.
+    /// Bodies are read as they come in, and reading stops with
+    /// [`LookupError::ResponseTooLarge`] once the limit is passed. Defaults to 20 MiB.
+    #[must_use]
+    pub fn max_response_bytes(mut self, limit: u64) -> Self {
+        self.settings.max_response_bytes = Some(limit);
+        self
+    }
+
+    /// Set what happens when a call runs out of its request budget.
     ///
-    /// # Example
+    /// Defaults to [`BudgetBehavior::Error`].
+    #[must_use]
+    pub fn on_budget(mut self, behavior: BudgetBehavior) -> Self {
+        self.settings.on_budget = behavior;
+        self
+    }
+
+    /// Leave archived repositories out of [`GitHub::get_org_latest_versions`].
     ///
-    /// ```rust
-    /// use github_release_check::GitHub;
-    /// let github = GitHub::from_custom("https://github.example.com/api/v3/", "abcdef").unwrap();
-    /// ```
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn skip_archived(mut self, skip: bool) -> Self {
+        self.settings.skip_archived = skip;
+        self
+    }
+
+    /// Set how many tags [`GitHub::find_releases_for_tags`] looks up one at a time.
     ///
-    /// # Errors
+    /// For more tags than this, all releases are fetched and searched instead.
+    /// Defaults to 10.
+    #[must_use]
+    pub fn tag_scan_threshold(mut self, threshold: usize) -> Self {
+        self.settings.tag_scan_threshold = Some(threshold);
+        self
+    }
+
+    /// Match tags in [`GitHub::find_releases_for_tags`] whether or not they have a
+    /// version prefix, like `'v'` or one set with [`GitHubBuilder::version_prefixes`].
     ///
-    /// This function fails if the headers cannot be constructed.
+    /// When enabled, `"1.0.0"` finds the release for `"v1.0.0"` and the other way
+    /// around. Defaults to `false`.
+    #[must_use]
+    pub fn ignore_tag_v_prefix(mut self, ignore: bool) -> Self {
+        self.settings.ignore_tag_v_prefix = ignore;
+        self
+    }
+
+    /// Set where the latest-version functions read each release's version from.
     ///
-    /// [GitHub personal access token]: https://docs.github.com/en/authentication/keeping-your-account-and-data-secure/creating-a-personal-access-token
-    pub fn from_custom(api_endpoint: &str, access_token: &str) -> Result<Self> {
-        let client = ClientBuilder::new()
-            .default_headers(generate_headers(Some(access_token))?)
-            .build()?;
-        Ok(Self {
-            client,
-            api_root: api_endpoint.to_owned(),
-        })
+    /// Defaults to [`VersionSource::TagOnly`]. With the other sources, a version
+    /// is looked for in the release name as well, like `3.2.0` in `"MyApp 3.2.0"`.
+    #[must_use]
+    pub fn version_source(mut self, source: VersionSource) -> Self {
+        self.settings.version_source = source;
+        self
     }
 
-    /// Get all release versions from the repository.
+    /// Also strip these prefixes from tags before reading them as versions.
     ///
-    /// Note that `repository` should be in the format "owner/repo",
-    /// like `"celeo/github_release_check"`.
+    /// Tags like `v1.2.3`, `rel/1.2.3`, `release/1.2.3`, and `version-1.2.3` are
+    /// understood without this; it's for project-specific prefixes like
+    /// `myapp-`. Prefixes are compared ignoring case, and only the longest
+    /// matching prefix is stripped from a tag.
+    #[must_use]
+    pub fn version_prefixes(mut self, prefixes: &[&str]) -> Self {
+        self.settings
+            .version_prefixes
+            .extend(prefixes.iter().map(|p| (*p).to_owned()));
+        self
+    }
+
+    /// Leave these tags out when selecting versions, like a release published by mistake.
     ///
-    /// # Example
+    /// Version prefixes are ignored on both sides, so `"9.9.9"` covers `v9.9.9` too.
+    /// Ignored tags are never picked by the latest-version functions; to also leave
+    /// them out of [`GitHub::get_all_versions`], see [`GitHubBuilder::hide_ignored_tags`].
+    #[must_use]
+    pub fn ignore_tags(mut self, tags: &[&str]) -> Self {
+        self.settings
+            .ignored_tags
+            .extend(tags.iter().map(|t| (*t).to_owned()));
+        self
+    }
+
+    /// Leave tags matching the regular expression out when selecting versions.
     ///
-    /// ```rust,no_run
-    /// use github_release_check::GitHub;
-    /// let github = GitHub::new().unwrap();
-    /// let versions_result = github.get_all_versions("celeo/github_release_check");
-    /// ```
+    /// The expression is matched against the tag without its version prefix. This
+    /// works alongside [`GitHubBuilder::ignore_tags`].
+    #[must_use]
+    pub fn ignore_tags_matching(mut self, pattern: &str) -> Self {
+        self.ignored_tag_pattern = Some(pattern.to_owned());
+        self
+    }
+
+    /// Also leave ignored tags out of [`GitHub::get_all_versions`].
     ///
-    /// # Errors
+    /// Defaults to `false`, so that `get_all_versions` returns every release.
+    #[must_use]
+    pub fn hide_ignored_tags(mut self, hide: bool) -> Self {
+        self.settings.hide_ignored_tags = hide;
+        self
+    }
+
+    /// Collapse tags of the same version in [`GitHub::get_all_versions`], like
+    /// `1.0.0` and `v1.0.0`.
     ///
-    /// This function fails if the HTTP request cannot be sent, the API returns
-    /// a status code indicating something other than a success (outside of the
-    /// 2xx range), of if the returned data does not match the expected model.
-    pub fn query(&self, repository: &str) -> Result<Vec<GitHubReleaseItem>> {
-        let mut page = 1usize;
-        let mut pages = Vec::<Vec<GitHubReleaseItem>>::new();
-        let mut last_page: Option<usize> = None;
+    /// Defaults to `false`. See [`GitHub::get_all_versions_deduped`] for which
+    /// tag is kept, and to see the tags that were left out.
+    #[must_use]
+    pub fn dedupe_versions(mut self, dedupe: bool) -> Self {
+        self.settings.dedupe_versions = dedupe;
+        self
+    }
 
-        loop {
-            let query = vec![("per_page", PAGINATION_REQUEST_AMOUNT), ("page", page)];
-            let url = format!("{}repos/{}/releases", self.api_root, repository);
-            debug!(
-                "Querying GitHub at {}, page {} of {}",
-                url,
-                page,
-                last_page.map_or_else(|| String::from("?"), |p| p.to_string())
-            );
-            let request = self
-                .client
-                .request(reqwest::Method::GET, &url)
-                .query(&query)
-                .build()?;
-            let response = self.client.execute(request)?;
-            if !response.status().is_success() {
-                debug!(
-                    "Got status \"{}\" from GitHub release check",
-                    response.status()
-                );
-                let stat = response.status().as_u16();
-                if stat == 404 {
-                    return Err(LookupError::RepositoryNotFound);
-                }
-                if stat == 401 || stat == 403 {
-                    return Err(LookupError::AuthenticationError(stat));
-                }
-                return Err(LookupError::ErrorHttpResponse(stat));
-            }
-            if last_page.is_none() {
-                debug!("Determining last page from response headers");
-                last_page = get_last_page(response.headers())?;
-            }
-            pages.push(response.json()?);
-            page += 1;
-            if let Some(last) = last_page {
-                if page >= last {
-                    break;
-                }
-            } else {
-                debug!("No pagination header found (fewer than 100 releases)");
-                break;
-            }
-        }
+    /// Let draft releases be picked as the latest version.
+    ///
+    /// Drafts are only listed for tokens that can push to the repository, so by
+    /// default they're left out of the latest-version functions, which then give
+    /// the same answer with or without a token. Defaults to `false`.
+    #[must_use]
+    pub fn include_drafts(mut self, include: bool) -> Self {
+        self.settings.include_drafts = include;
+        self
+    }
 
-        Ok(pages.iter().flatten().cloned().collect())
+    /// Set how the latest-version functions find the latest release.
+    ///
+    /// Defaults to [`LatestStrategy::SemverScan`]. [`LatestStrategy::MarkedLatest`]
+    /// is much cheaper for repositories with many releases, but trusts GitHub's
+    /// choice over version ordering (see [`GitHub::latest_discrepancy`]).
+    #[must_use]
+    pub fn latest_strategy(mut self, strategy: LatestStrategy) -> Self {
+        self.settings.latest_strategy = strategy;
+        self
     }
 
-    /// Get all release version strings from the repository.
+    /// Pick the latest release by comparing tags with this, rather than by
+    /// reading them as Semantic Versions.
     ///
-    /// Note that `repository` should be in the format "owner/repo",
-    /// like `"celeo/github_release_check"`.
+    /// This is for versioning schemes semver can't express, like epochs in
+    /// `2!1.4.0`. Drafts and ignored tags are still left out. Use
+    /// [`GitHub::get_latest_tag`] to get the winning tag whether or not it's a
+    /// Semantic Version; the other latest-version functions fail with
+    /// [`LookupError::InvalidVersion`] if it isn't.
+    #[must_use]
+    pub fn version_comparator(mut self, compare: Box<comparator::CompareTags>) -> Self {
+        self.settings.version_comparator = Some(comparator::VersionComparator::new(compare));
+        self
+    }
+
+    /// Set which date functions that order releases by date go by.
     ///
-    /// # Example
+    /// Defaults to [`DateField::Published`]. This affects
+    /// [`GitHub::get_latest_by_date`] and the order of releases without versions
+    /// in [`GitHub::get_release_summaries`].
+    #[must_use]
+    pub fn date_field(mut self, field: DateField) -> Self {
+        self.settings.date_field = field;
+        self
+    }
+
+    /// Walk the pages again if releases shift between pages during a query.
     ///
-    /// ```rust,no_run
-    /// use github_release_check::GitHub;
-    /// let github = GitHub::new().unwrap();
-    /// let versions_result = github.get_all_versions("celeo/github_release_check");
-    /// ```
+    /// A release published while [`GitHub::query`] walks the pages pushes every
+    /// release down, so a release shows up on two pages and the new one is missed.
+    /// Repeated releases are always dropped; when enabled, the walk is also started
+    /// over (at most once per query) to pick up the new release. Defaults to `false`.
+    #[must_use]
+    pub fn restart_on_page_shift(mut self, restart: bool) -> Self {
+        self.settings.restart_on_page_shift = restart;
+        self
+    }
+
+    /// Share a single fetch between concurrent queries for the same repository.
     ///
-    /// # Errors
+    /// When enabled, a query made while another query for the same repository is
+    /// already running waits for that one to finish and gets a copy of its result,
+    /// instead of walking the pages again. This applies to every function built on
+    /// [`GitHub::query`], and across clones of the instance.
     ///
-    /// This function fails if the HTTP request cannot be sent, the API returns
-    /// a status code indicating something other than a success (outside of the
-    /// 2xx range), of if the returned data does not match the expected model.
-    pub fn get_all_versions(&self, repository: &str) -> Result<Vec<String>> {
-        Ok(self
-            .query(repository)?
-            .iter()
-            .map(|release| release.tag_name.clone())
-            .collect())
+    /// Errors are passed to every waiting caller. Those that can't be copied (like
+    /// errors from the HTTP client) are wrapped in [`LookupError::Shared`].
+    /// If the query being waited on panics, the callers waiting on it get
+    /// [`LookupError::FetchPanicked`].
+    #[must_use]
+    pub fn coalesce_requests(mut self, coalesce: bool) -> Self {
+        self.coalesce_requests = coalesce;
+        self
     }
 
-    /// Get the latest release version from the repository.
+    /// Retry GET requests that fail with a server error (5xx), up to `retries` times.
     ///
-    /// Note that `repository` should be in the format "owner/repo",
-    /// like `"celeo/github_release_check"`.
+    /// The first retry waits 200 milliseconds, doubling for each retry after that.
+    /// Defaults to 0, so that server errors are returned right away.
+    #[must_use]
+    pub fn retry_server_errors(mut self, retries: usize) -> Self {
+        self.settings.server_error_retries = retries;
+        self
+    }
+
+    /// Give up on connecting to the GitHub instance after `timeout`.
     ///
-    /// As this function needs to select and return the latest release version,
-    /// it makes use of the "semver" crate's `Version` [parse function]. As there's
-    /// no requirement for repositories to use Semantic Versioning, this function may
-    /// not suitable for every repository (thus the `get_all_versions` function which
-    /// just works with `String`s).
+    /// This only covers opening the connection, so it can be short to fail fast
+    /// when the host is unreachable, while [`GitHubBuilder::timeout`] allows for
+    /// slow responses. There's no connect timeout by default. A connect timeout is
+    /// a [`LookupError::Timeout`] in [`TimeoutPhase::Connect`].
+    #[must_use]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.settings.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Give up on a request after `timeout`, from connecting until the response
+    /// body is read.
     ///
-    /// A leading `'v'` character is stripped from the versions
-    /// in order to make more repositories work. For any version string that is not
-    /// able to be loaded into a `Version` struct, it is skipped. Note that this
-    /// may result in no or missing versions.
+    /// Defaults to 30 seconds. Timeouts apply to each request on its own, so
+    /// every page of a walk, and every retry with
+    /// [`GitHubBuilder::retry_server_errors`], gets the full time again. Requests
+    /// that time out aren't retried. A timeout before the response arrives is a
+    /// [`LookupError::Timeout`] in [`TimeoutPhase::Response`].
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.settings.timeout = Some(timeout);
+        self
+    }
+
+    /// Send a header with every request, for tracing calls end-to-end.
     ///
-    /// Effectively, for repositories that are using Semantic Versioning correctly,
-    /// this will work. For those that are not, it's a bit of a toss-up.
+    /// Like `correlation_header("X-Request-Id", "deploy-4821")`. GitHub's own ID
+    /// for each request, which its support asks for, is kept in the errors from
+    /// failed requests and in [`QueryStats::request_id`] either way.
+    #[must_use]
+    pub fn correlation_header(mut self, name: &str, value: &str) -> Self {
+        self.settings.correlation_header = Some((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Send an `Accept-Language` header with every request, like `"de-DE"`.
     ///
-    /// Since this call can fail for a number of reasons including anything related to
-    /// the network at the time of the call, the `Result` from this function should
-    /// be handled appropriately.
+    /// GitHub Enterprise Server can be set up to translate its error messages,
+    /// which are kept in [`LookupError::ErrorHttpResponse`]. Not sent by default.
+    #[must_use]
+    pub fn accept_language(mut self, language: &str) -> Self {
+        self.settings.accept_language = Some(language.to_owned());
+        self
+    }
+
+    /// Ask for compressed responses, which are much smaller for long release lists.
+    ///
+    /// On by default, but it needs the `gzip` or `brotli` feature, or both;
+    /// without them, nothing is asked for and this does nothing. The encoding
+    /// the responses came back in is in [`QueryStats::content_encoding`].
+    #[must_use]
+    pub fn compression(mut self, compression: bool) -> Self {
+        self.settings.compression = compression;
+        self
+    }
+
+    /// Treat response headers that can't be read as if they weren't sent.
+    ///
+    /// Some proxies add headers with bytes that aren't ASCII, or mangle the
+    /// `link` header, which fails the whole lookup by default with
+    /// [`LookupError::HeaderToString`] or [`LookupError::PaginationParse`]. When
+    /// lenient, such headers are logged and ignored instead; without a usable
+    /// `link` header, pages are followed as long as they come back full.
+    #[must_use]
+    pub fn lenient_headers(mut self, lenient: bool) -> Self {
+        self.settings.lenient_headers = lenient;
+        self
+    }
+
+    /// Only trust the certificates for HTTPS connections, instead of the
+    /// system's certificate authorities.
+    ///
+    /// Each may be the server's own certificate or one it chains to, like that
+    /// of a company CA. A server whose certificate isn't signed by any of them
+    /// fails with [`LookupError::CertificatePinFailed`]. An empty list is ignored.
+    ///
+    /// Pinning comes at a cost: when the server's certificate is renewed or
+    /// moves to another CA, every request fails until the pins are updated, and
+    /// for a tool that checks for its own updates, that may mean it can't learn
+    /// of the update that fixes them. Pin a CA rather than the server's own
+    /// certificate where possible, and keep a second pin for the next one.
+    #[must_use]
+    pub fn pin_certificates(mut self, certificates: Vec<Certificate>) -> Self {
+        self.settings.pinned_certificates = certificates;
+        self
+    }
+
+    /// Connect to the address for the host, instead of looking it up in DNS.
+    ///
+    /// This is for hosts that resolve differently depending on the network,
+    /// and for pointing a made-up host name at a local server in tests, without
+    /// editing the hosts file. Set it more than once for more hosts, or for more
+    /// addresses for the same host. The port of the address is ignored; the one
+    /// in the URL is used, as usual.
     ///
     /// # Example
     ///
-    /// ```rust,no_run
+    /// ```rust
     /// use github_release_check::GitHub;
-    /// let github = GitHub::new().unwrap();
-    /// let version_result = github.get_latest_version("celeo/github_release_check");
+    /// let github = GitHub::builder()
+    ///     .api_root("https://github.internal/api/v3/")
+    ///     .resolve_host("github.internal", "10.0.0.5:443".parse().unwrap())
+    ///     .build()
+    ///     .unwrap();
     /// ```
+    #[must_use]
+    pub fn resolve_host(mut self, host: &str, address: SocketAddr) -> Self {
+        self.settings
+            .resolved_hosts
+            .push((host.to_owned(), address));
+        self
+    }
+
+    /// Fail fast while the GitHub instance keeps failing.
     ///
-    /// # Errors
+    /// After `threshold` transient failures (5xx responses, connection errors, and
+    /// timeouts) within `window`, requests fail immediately with
+    /// [`LookupError::CircuitOpen`] for `cool_down`. After that, one request is let
+    /// through as a probe: if it succeeds the breaker closes, otherwise it opens again.
     ///
-    /// This function fails for any of the reasons in `get_all_versions`, or
-    /// if no versions are returned from the API.
+    /// The breaker's state is shared between clones of the instance.
+    #[must_use]
+    pub fn circuit_breaker(
+        mut self,
+        threshold: usize,
+        window: Duration,
+        cool_down: Duration,
+    ) -> Self {
+        self.circuit_breaker = Some(breaker::Config {
+            threshold: threshold.max(1),
+            window,
+            cool_down,
+        });
+        self
+    }
+
+    /// Fall back to the release feed when rate limited.
     ///
-    /// [parse function]: https://docs.rs/semver/latest/semver/struct.Version.html#method.parse
-    pub fn get_latest_version(&self, repository: &str) -> Result<Version> {
-        let versions = self.get_all_versions(repository)?;
-        let latest = versions
-            .iter()
-            .map(|s| {
-                let mut s = s.clone();
-                if s.starts_with('v') {
-                    s = s.chars().skip(1).collect();
-                }
-                Version::parse(&s)
-            })
-            .filter_map(std::result::Result::ok)
-            .max()
-            .ok_or(LookupError::NoReleases)?;
-        Ok(latest)
+    /// When enabled, [`GitHub::get_latest_version`] uses
+    /// [`GitHub::get_versions_from_feed`] if the API reports an exhausted rate limit.
+    /// Note that the feed only holds the 10 most recent releases.
+    ///
+    /// Only available with the `atom` feature.
+    #[cfg(feature = "atom")]
+    #[must_use]
+    pub fn feed_fallback(mut self, fallback: bool) -> Self {
+        self.settings.feed_fallback = fallback;
+        self
     }
-}
 
-/// Determine the last page (if any) from the GitHub response headers.
-///
-/// # Errors
-///
-/// This function fails if the the values in the "link" header
-/// are not valid ASCII.
-fn get_last_page(headers: &HeaderMap) -> Result<Option<usize>> {
-    let links = match headers.get("link") {
-        Some(l) => l.to_str()?,
-        None => return Ok(None),
-    };
-    for page_ref in links.split(',') {
-        if !page_ref.contains("rel=\"last\"") {
-            continue;
+    /// Construct the [`GitHub`] instance.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed, like for an
+    /// [`GitHubBuilder::accept_language`] that isn't a valid header value, or
+    /// the pattern given to [`GitHubBuilder::ignore_tags_matching`] isn't valid.
+    pub fn build(mut self) -> Result<GitHub> {
+        if let Some(pattern) = &self.ignored_tag_pattern {
+            self.settings.ignored_tag_pattern =
+                Some(Regex::new(pattern).map_err(LookupError::InvalidTagPattern)?);
+        }
+        let client =
+            self.http_client(generate_headers(self.token.as_ref().map(|t| t.0.as_str()))?)?;
+        let mut web_headers = HeaderMap::new();
+        let _prev = web_headers.insert(
+            header::USER_AGENT,
+            header::HeaderValue::from_str(DEFAULT_USER_AGENT)?,
+        );
+        let web_client = self.http_client(web_headers)?;
+        let in_flight = self
+            .coalesce_requests
+            .then(|| Arc::new(coalesce::InFlight::default()));
+        let web_root = self
+            .web_root
+            .unwrap_or_else(|| default_web_root(&self.api_root));
+        Ok(GitHub {
+            client,
+            web_client,
+            api_root: self.api_root,
+            web_root,
+            authenticated: self.token.is_some_and(|token| !token.0.is_empty()),
+            settings: self.settings,
+            in_flight,
+            breaker: self
+                .circuit_breaker
+                .map(|config| Arc::new(breaker::CircuitBreaker::new(config))),
+            rate_limit: Arc::default(),
+            deprecation: Arc::default(),
+            scan_budget: None,
+            server_info: None,
+            #[cfg(feature = "test-util")]
+            fixture: None,
+        })
+    }
+
+    /// Create an HTTP client with the default headers, and the headers and
+    /// connection settings from the builder.
+    fn http_client(&self, mut headers: HeaderMap) -> Result<Client> {
+        if let Some((name, value)) = &self.settings.correlation_header {
+            let _prev = headers.insert(
+                header::HeaderName::from_bytes(name.as_bytes())?,
+                header::HeaderValue::from_str(value)?,
+            );
+        }
+        if let Some(language) = &self.settings.accept_language {
+            let _prev = headers.insert(
+                header::ACCEPT_LANGUAGE,
+                header::HeaderValue::from_str(language)?,
+            );
+        }
+        if let Some(encoding) = body::accept_encoding().filter(|_| self.settings.compression) {
+            let _prev = headers.insert(
+                header::ACCEPT_ENCODING,
+                header::HeaderValue::from_static(encoding),
+            );
+        }
+        let mut client = ClientBuilder::new().default_headers(headers);
+        if let Some(timeout) = self.settings.connect_timeout {
+            client = client.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.settings.timeout {
+            client = client.timeout(timeout);
+        }
+        if !self.settings.pinned_certificates.is_empty() {
+            client = client.tls_built_in_root_certs(false);
+            for certificate in &self.settings.pinned_certificates {
+                client = client.add_root_certificate(certificate.clone());
+            }
         }
-        for cap_part in PAGE_EXTRACT_REGEX.captures_iter(page_ref) {
-            if cap_part[1].is_empty() {
-                let page = cap_part[2]
-                    .parse::<usize>()
-                    .expect("Could not get page version from regex");
-                return Ok(Some(page));
+        let mut hosts: Vec<&str> = Vec::new();
+        for (host, _) in &self.settings.resolved_hosts {
+            if !hosts.contains(&host.as_str()) {
+                hosts.push(host);
             }
         }
+        for host in hosts {
+            let addresses: Vec<SocketAddr> = self
+                .settings
+                .resolved_hosts
+                .iter()
+                .filter(|(name, _)| name == host)
+                .map(|(_, address)| *address)
+                .collect();
+            client = client.resolve_to_addrs(host, &addresses);
+        }
+        Ok(client.build()?)
     }
-    Ok(None)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{get_last_page, GitHub};
-    use mockito::mock;
-    use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+/// Struct to communicate with the GitHub REST API.
+///
+/// Clones share the same HTTP client and any shared state, like
+/// the in-flight requests used for coalescing and the rate limit.
+#[derive(Debug, Clone)]
+pub struct GitHub {
+    client: Client,
+    web_client: Client,
+    api_root: String,
+    web_root: String,
+    authenticated: bool,
+    settings: Settings,
+    in_flight: Option<Arc<coalesce::InFlight>>,
+    breaker: Option<Arc<breaker::CircuitBreaker>>,
+    rate_limit: Arc<ratelimit::SharedRateLimit>,
+    deprecation: Arc<deprecation::SharedDeprecation>,
+    scan_budget: Option<Arc<org::ScanBudget>>,
+    server_info: Option<ServerInfo>,
+    #[cfg(feature = "test-util")]
+    fixture: Option<Arc<fixture::Fixture>>,
+}
 
-    #[test]
-    fn test_get_last_page_none() {
-        let map = HeaderMap::new();
-        let last = get_last_page(&map).unwrap();
-        assert!(last.is_none());
+impl GitHub {
+    /// Create a new instance of the struct suitable for public GitHub.
+    ///
+    /// The struct created by this function does not set an access token
+    /// and as such can only get information on public GitHub repositories.
+    ///
+    /// If you need to access information for private repositories or any
+    /// information from a custom GitHub enterprise instance, use the
+    /// `from_custom` function.
+    ///
+    /// This function may return an `Error` if the HTTP client could
+    /// not be constructed or headers initialized. It should be safe
+    /// to unwrap the `Result`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed.
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
     }
 
-    #[test]
-    fn test_get_last_page_some() {
-        let mut map = HeaderMap::new();
-        let _ = map.insert(
-            HeaderName::from_static("link"),
-            HeaderValue::from_static(r#"<https://api.github.com/repositories/275449421/releases?per_page=1&page=2>; rel="next", <https://api.github.com/repositories/275449421/releases?per_page=1&page=10>; rel="last""#)
-        );
-        let last = get_last_page(&map).unwrap();
-        assert_eq!(last, Some(10));
+    /// Create a new instance of the struct suitable for accessing any GitHub repository
+    /// that can be viewed with the access key on the GitHub instance.
+    ///
+    /// This function has to be used to construct the struct instance whenever the repository
+    /// that you want to get information from is on a custom GitHub enterprise instance and/or
+    /// is private. The access token passed to this function should be a [GitHub personal access token]
+    /// that has the access to view the repository on that GitHub instance.
+    ///
+    /// For the `api_endpoint` argument, pass in the REST API root of the GitHub instance. For public
+    /// GitHub, this can be found in [`DEFAULT_API_ROOT`].
+    /// Your GitHub enterprise may use a subdomain like `"https://api.github.your_domain_root.com/"`, or
+    /// perhaps something like `"https://github.your_domain_root.com/api/v3/"`. Specify the API root that
+    /// you can otherwise send requests to. Note that this URL should end in a trailing slash.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::from_custom("https://github.example.com/api/v3/", "abcdef").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the headers cannot be constructed.
+    ///
+    /// [GitHub personal access token]: https://docs.github.com/en/authentication/keeping-your-account-and-data-secure/creating-a-personal-access-token
+    pub fn from_custom(api_endpoint: &str, access_token: &str) -> Result<Self> {
+        Self::builder()
+            .api_root(api_endpoint)
+            .token(access_token)
+            .build()
     }
 
-    #[test]
-    fn test_get_all_versions_none() {
-        let _m = mock("GET", "/repos/foo/bar/releases")
-            .match_query(mockito::Matcher::Any)
-            .with_body("[]")
-            .create();
-        let github = GitHub::from_custom(&format!("{}/", mockito::server_url()), "").unwrap();
-        let versions = github.get_all_versions("foo/bar").unwrap();
-        assert!(versions.is_empty());
+    /// Get the REST API root requests are sent to, like [`DEFAULT_API_ROOT`].
+    #[must_use]
+    pub fn api_root(&self) -> &str {
+        &self.api_root
     }
 
-    #[test]
-    fn test_get_all_versions_valid() {
-        let rest = r#", "url": "", "assets_url": "", "upload_url": "", "html_url": "", "id": 1, "name": "", "draft": false, "prerelease": false, "created_at": "", "published_at": "", "body": """#;
-        let _m = mock("GET", "/repos/foo/bar/releases")
-            .match_query(mockito::Matcher::Any)
-            .with_body(format!(
-                r#"[
-                {{ "tag_name": "v1.0.0"  {rest}}},
-                {{ "tag_name": "v1.9.10"  {rest}}},
-                {{ "tag_name": "v0.3.0" {rest}}}
-            ]"#
-            ))
-            .create();
-        let github = GitHub::from_custom(&format!("{}/", mockito::server_url()), "").unwrap();
-        let versions = github.get_all_versions("foo/bar").unwrap();
-        assert_eq!(versions.len(), 3);
+    /// Whether requests are sent with an access token.
+    ///
+    /// An empty token, like the one in `GitHub::from_custom(root, "")`, doesn't count.
+    #[must_use]
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated
     }
 
-    #[test]
-    fn test_get_latest_version_none() {
-        let _m = mock("GET", "/repos/foo/bar/releases")
-            .match_query(mockito::Matcher::Any)
-            .with_body("[]")
-            .create();
-        let github = GitHub::from_custom(&format!("{}/", mockito::server_url()), "").unwrap();
-        let version_res = github.get_latest_version("foo/bar");
-        assert!(version_res.is_err());
+    /// Get the `User-Agent` header sent with every request.
+    #[must_use]
+    pub fn user_agent(&self) -> &str {
+        DEFAULT_USER_AGENT
     }
 
-    #[test]
-    fn test_get_latest_version_bad_semvers() {
-        let rest = r#", "url": "", "assets_url": "", "upload_url": "", "html_url": "", "id": 1, "name": "", "draft": false, "prerelease": false, "created_at": "", "published_at": "", "body": """#;
-        let _m = mock("GET", "/repos/foo/bar/releases")
-            .match_query(mockito::Matcher::Any)
-            .with_body(format!(
-                r#"[
-                {{ "tag_name": "uhhhh" {rest}}},
-                {{ "tag_name": "v3.0.0-alpha" {rest}}},
-                {{ "tag_name": "v1.9.10" {rest}}}
-            ]"#
-            ))
-            .create();
-        let github = GitHub::from_custom(&format!("{}/", mockito::server_url()), "").unwrap();
-        let version = github.get_latest_version("foo/bar").unwrap();
-        assert_eq!(version, semver::Version::parse("3.0.0-alpha").unwrap());
+    /// Get the root of the GitHub instance's website, like `"https://github.com/"`.
+    ///
+    /// See [`GitHubBuilder::web_root`] for how this is determined.
+    #[must_use]
+    pub fn web_root(&self) -> &str {
+        &self.web_root
+    }
+
+    /// Create a builder to configure a new instance of the struct.
+    ///
+    /// See [`GitHubBuilder`] for the available options.
+    #[must_use]
+    pub fn builder() -> GitHubBuilder {
+        GitHubBuilder::default()
+    }
+
+    /// Send a GET request to the URL, mapping error statuses to errors.
+    ///
+    /// If the rate limit is exhausted and the instance is configured to
+    /// wait for it, the request is repeated once the limit resets.
+    fn get(&self, url: &str, query: &[(&str, usize)], call: &mut Call) -> Result<Response> {
+        self.send(
+            &reqwest::Method::GET,
+            url,
+            query,
+            None::<&()>,
+            &HeaderMap::new(),
+            call,
+        )
+    }
+
+    /// Send a request to the URL, with an optional JSON body and extra headers.
+    ///
+    /// See [`GitHub::get`] for how errors and rate limits are handled. A 304
+    /// response is returned as-is, as it only comes back for conditional requests.
+    fn send<B: Serialize + ?Sized>(
+        &self,
+        method: &reqwest::Method,
+        url: &str,
+        query: &[(&str, usize)],
+        json: Option<&B>,
+        headers: &HeaderMap,
+        call: &mut Call,
+    ) -> Result<Response> {
+        self.send_to(Host::Api, method, url, query, json, headers, call)
+    }
+
+    /// Send a request to the URL on the host, like [`GitHub::send`].
+    ///
+    /// Requests to the website go without the access token and API headers,
+    /// and aren't held back by the API rate limit, which doesn't apply there.
+    /// The request budgets and the circuit breaker apply to both hosts.
+    #[allow(clippy::too_many_arguments)]
+    fn send_to<B: Serialize + ?Sized>(
+        &self,
+        host: Host,
+        method: &reqwest::Method,
+        url: &str,
+        query: &[(&str, usize)],
+        json: Option<&B>,
+        headers: &HeaderMap,
+        call: &mut Call,
+    ) -> Result<Response> {
+        let client = match host {
+            Host::Api => &self.client,
+            Host::Web => &self.web_client,
+        };
+        loop {
+            if let Some(max) = self.settings.max_requests_per_call {
+                if call.requests >= max {
+                    debug!("Request budget of {max} for this call is used up");
+                    return Err(LookupError::BudgetExceeded {
+                        fetched_pages: 0,
+                        total_pages: None,
+                    });
+                }
+            }
+            if let Some(reset_at) = self
+                .rate_limit
+                .exhausted_until()
+                .filter(|_| host == Host::Api)
+            {
+                if self.wait_for_rate_limit(reset_at, call) {
+                    continue;
+                }
+                debug!("Rate limit is used up, not sending the request");
+                return Err(LookupError::RateLimited {
+                    reset_at: Some(reset_at),
+                    request_id: None,
+                });
+            }
+            let mut request = client
+                .request(method.clone(), url)
+                .query(query)
+                .headers(headers.clone());
+            if let Some(json) = json {
+                request = request.json(json);
+            }
+            let request = request.build()?;
+            self.reserve_request()?;
+            call.requests += 1;
+            let response = client.execute(request);
+            if let Some(breaker) = &self.breaker {
+                breaker.record(match &response {
+                    Ok(response) => response.status().is_server_error(),
+                    Err(e) => e.is_connect() || e.is_timeout(),
+                });
+            }
+            let response = response.map_err(|e| self.transport_error(e))?;
+            self.record_response(host, &response, call);
+            if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+                return Ok(response);
+            }
+            if response.status().is_server_error()
+                && *method == reqwest::Method::GET
+                && call.retries < self.settings.server_error_retries
+            {
+                let wait = SERVER_ERROR_BACKOFF * (1u32 << call.retries.min(10));
+                debug!("Got status \"{}\", retrying in {wait:?}", response.status());
+                call.retries += 1;
+                thread::sleep(wait);
+                continue;
+            }
+            debug!(
+                "Got status \"{}\" from GitHub release check",
+                response.status()
+            );
+            if host == Host::Api && is_html(response.headers()) {
+                return Err(self.not_an_api_endpoint());
+            }
+            #[cfg(feature = "write")]
+            if call.validates && response.status() == StatusCode::UNPROCESSABLE_ENTITY {
+                return Err(write::validation_failed(response));
+            }
+            let error = error_from_response(response);
+            if let LookupError::RateLimited {
+                reset_at: Some(reset_at),
+                ..
+            } = error
+            {
+                if self.wait_for_rate_limit(reset_at, call) {
+                    continue;
+                }
+            }
+            return Err(error);
+        }
+    }
+
+    /// Take a request from the scan budget and ask the circuit breaker to let
+    /// it through, right before sending it.
+    ///
+    /// The breaker is asked last, as a probe it allows must record its outcome;
+    /// if it refuses, the request goes back to the budget.
+    fn reserve_request(&self) -> Result<()> {
+        if let Some(budget) = &self.scan_budget {
+            budget.take()?;
+        }
+        if let Some(breaker) = &self.breaker {
+            if let Err(e) = breaker.before_request() {
+                if let Some(budget) = &self.scan_budget {
+                    budget.give_back();
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Note what the response says about the rate limit, deprecations and the
+    /// request in the call.
+    fn record_response(&self, host: Host, response: &Response, call: &mut Call) {
+        if host == Host::Api {
+            self.rate_limit.update(response.headers());
+            self.deprecation.update(response);
+        }
+        if let Some(request_id) = request_id(response.headers()) {
+            call.request_id = Some(request_id);
+        }
+        if let Some(encoding) = body::content_encoding(response) {
+            call.content_encoding = Some(encoding);
+        }
+        if let Some(remaining) = response
+            .headers()
+            .get("x-ratelimit-remaining")
+            .and_then(|v| v.to_str().ok()?.parse().ok())
+        {
+            call.rate_limit_remaining = Some(remaining);
+        }
+        if response.status() == StatusCode::NOT_MODIFIED {
+            call.not_modified += 1;
+        }
+    }
+
+    /// Get all release versions from the repository.
+    ///
+    /// Note that `repository` should be in the format "owner/repo",
+    /// like `"celeo/github_release_check"`.
+    ///
+    /// Pages are followed using the "link" header. If a proxy strips it, pages
+    /// are requested until one isn't full, so a repository with exactly a full
+    /// page of releases costs one extra request.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let versions_result = github.get_all_versions("celeo/github_release_check");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the HTTP request cannot be sent, the API returns
+    /// a status code indicating something other than a success (outside of the
+    /// 2xx range), of if the returned data does not match the expected model.
+    pub fn query<R>(&self, repository: R) -> Result<Vec<GitHubReleaseItem>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        Ok(self.query_partial(repository)?.releases)
+    }
+
+    /// Get all releases from the repository, noting if the request budget cut the walk short.
+    ///
+    /// This is the same as [`GitHub::query`], but when a budget is set with
+    /// [`GitHubBuilder::max_requests_per_call`] and [`BudgetBehavior::Truncate`],
+    /// the returned struct says whether all pages were fetched.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{BudgetBehavior, GitHub};
+    /// let github = GitHub::builder()
+    ///     .max_requests_per_call(20)
+    ///     .on_budget(BudgetBehavior::Truncate)
+    ///     .build()
+    ///     .unwrap();
+    /// let partial = github.query_partial("celeo/github_release_check").unwrap();
+    /// if partial.truncated {
+    ///     println!("only got the first {} releases", partial.releases.len());
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn query_partial<R>(&self, repository: R) -> Result<PartialQuery>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        self.partial_query(&repository.try_into()?)
+    }
+
+    /// Get all releases from the repository, sharing the fetch with concurrent
+    /// calls for it if set with [`GitHubBuilder::coalesce_requests`].
+    pub(crate) fn partial_query(&self, spec: &RepositorySpec) -> Result<PartialQuery> {
+        match &self.in_flight {
+            Some(in_flight) => in_flight.run(&spec.to_string(), || self.fetch_releases(spec)),
+            None => self.fetch_releases(spec),
+        }
+    }
+
+    /// Get a single page of releases from the repository.
+    ///
+    /// This is the building block of [`GitHub::query`], for callers that manage
+    /// pagination themselves. Pages are numbered from 1, and GitHub allows at
+    /// most 100 releases per page.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let mut page = Some(1);
+    /// while let Some(number) = page {
+    ///     let result = github.query_page("celeo/github_release_check", number, 100).unwrap();
+    ///     // ... store result.releases
+    ///     page = result.next_page;
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn query_page<R>(&self, repository: R, page: usize, per_page: usize) -> Result<PageResult>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let url = self.spec_url(&repository.try_into()?, "releases");
+        self.fetch_page(&url, page, per_page, &mut Call::default())
+    }
+
+    /// Fetch a single page of releases from the release list at the URL, as part of the call.
+    fn fetch_page<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        page: usize,
+        per_page: usize,
+        call: &mut Call,
+    ) -> Result<PageResult<T>> {
+        self.fetch_page_with(url, page, per_page, &HeaderMap::new(), call)
+    }
+
+    /// Fetch a single page of releases with extra request headers, like a cache validator.
+    fn fetch_page_with<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        page: usize,
+        per_page: usize,
+        headers: &HeaderMap,
+        call: &mut Call,
+    ) -> Result<PageResult<T>> {
+        let query = [("per_page", per_page), ("page", page)];
+        debug!("Querying GitHub at {}, page {page}", redact_url(url));
+        let started = Instant::now();
+        let response = self.send(
+            &reqwest::Method::GET,
+            url,
+            &query,
+            None::<&()>,
+            headers,
+            call,
+        )?;
+        let log = RequestLog::new(&response, started);
+        if response.status() == StatusCode::NOT_MODIFIED {
+            debug!("{log}");
+            return Err(LookupError::NotModified);
+        }
+        let headers = response.headers();
+        if is_html(headers) {
+            return Err(self.not_an_api_endpoint());
+        }
+        let last_page = self.readable_header("link", get_last_page(headers))?;
+        let next_page = self.readable_header("link", get_link_page(headers, "next"))?;
+        let link_per_page = self.readable_header("link", get_link_per_page(headers))?;
+        let etag = self.readable_header(
+            "etag",
+            headers
+                .get(header::ETAG)
+                .map(|v| v.to_str().map(str::to_owned))
+                .transpose()
+                .map_err(LookupError::from),
+        )?;
+        let releases: Vec<T> = self.read_page_counted(response, &mut call.bytes)?;
+        debug!("{}", log.item_count(releases.len()));
+        Ok(PageResult {
+            releases,
+            last_page,
+            next_page,
+            per_page: link_per_page,
+            etag,
+        })
+    }
+
+    /// The value read from a header, or `None` if it can't be read and
+    /// [`GitHubBuilder::lenient_headers`] is set.
+    fn readable_header<T>(&self, name: &str, value: Result<Option<T>>) -> Result<Option<T>> {
+        match value {
+            Err(e @ (LookupError::HeaderToString(_) | LookupError::PaginationParse(_)))
+                if self.settings.lenient_headers =>
+            {
+                warn!("Ignoring unreadable {name} header: {e}");
+                Ok(None)
+            }
+            other => other,
+        }
+    }
+
+    /// Walk the pages of releases for the repository.
+    fn fetch_releases(&self, spec: &RepositorySpec) -> Result<PartialQuery> {
+        self.fetch_releases_in(spec, &mut Call::default())
+    }
+
+    /// Walk the pages of releases for the repository as part of the call.
+    fn fetch_releases_in(&self, spec: &RepositorySpec, call: &mut Call) -> Result<PartialQuery> {
+        #[cfg(feature = "test-util")]
+        if let Some(fixture) = &self.fixture {
+            return Ok(PartialQuery {
+                releases: fixture.releases(&spec.to_string())?,
+                truncated: false,
+            });
+        }
+        self.paginate(&self.spec_url(spec, "releases"), call)
+    }
+
+    /// Walk the pages of releases for the repository, reading each release as `T`.
+    fn walk_releases<T: DeserializeOwned + PageKey>(
+        &self,
+        spec: &RepositorySpec,
+    ) -> Result<PartialQuery<T>> {
+        self.paginate(&self.spec_url(spec, "releases"), &mut Call::default())
+    }
+
+    /// Walk the pages of the list at the URL as part of the call, reading each item as `T`.
+    ///
+    /// Every paginated endpoint goes through here, so they all follow the
+    /// pagination links the same way, keep to the request budget, cope with
+    /// servers that cap the page size or strip the links, and drop items that
+    /// shifted onto the next page while walking.
+    pub(crate) fn paginate<T: DeserializeOwned + PageKey>(
+        &self,
+        url: &str,
+        call: &mut Call,
+    ) -> Result<PartialQuery<T>> {
+        let mut page = 1usize;
+        let mut per_page = PAGINATION_REQUEST_AMOUNT;
+        let mut pages = Vec::<Vec<T>>::new();
+        let mut last_page: Option<usize> = None;
+        let mut restarted = false;
+        let mut guard = self.page_guard();
+
+        loop {
+            let result: PageResult<T> = match self.fetch_page(url, page, per_page, call) {
+                Err(LookupError::BudgetExceeded { .. }) => {
+                    if self.settings.on_budget == BudgetBehavior::Truncate {
+                        return Ok(PartialQuery {
+                            releases: dedup_pages(pages),
+                            truncated: true,
+                        });
+                    }
+                    return Err(LookupError::BudgetExceeded {
+                        fetched_pages: pages.len(),
+                        total_pages: last_page,
+                    });
+                }
+                other => other?,
+            };
+            if last_page.is_none() {
+                debug!("Determining last page from response headers");
+                last_page = result.last_page;
+            }
+            if page == 1 {
+                per_page = effective_page_size(per_page, &result);
+            }
+            // a release published during the walk pushes the last release of
+            // a page onto the next page, and the new release is missed
+            let shifted = pages
+                .last()
+                .and_then(|previous| previous.last())
+                .zip(result.releases.first())
+                .is_some_and(|(previous, next)| previous.key() == next.key());
+            if shifted && self.settings.restart_on_page_shift && !restarted {
+                debug!(
+                    "Items shifted between pages {} and {page}, restarting",
+                    page - 1
+                );
+                restarted = true;
+                guard.restart();
+                pages.clear();
+                page = 1;
+                last_page = None;
+                continue;
+            }
+            let previous_first = pages.last().and_then(|previous| previous.first());
+            let unlinked_full = is_unlinked_full_page(&result, last_page, per_page, previous_first);
+            pages.push(result.releases);
+            let following = following_page(page, result.next_page, last_page, unlinked_full);
+            if let Some(following) = guard.follow(page, result.next_page, following)? {
+                page = following;
+            } else {
+                debug!("No further pages after page {page}");
+                break;
+            }
+        }
+
+        Ok(PartialQuery {
+            releases: dedup_pages(pages),
+            truncated: false,
+        })
+    }
+
+    /// Get all releases from the repository as untyped JSON objects.
+    ///
+    /// This walks the pages the same way as [`GitHub::query`], but keeps every
+    /// field GitHub sends, including those [`GitHubReleaseItem`] doesn't have (yet).
+    /// Use [`GitHubReleaseItem::from_value`] for typed access to a release.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// for release in github.query_raw("celeo/github_release_check").unwrap() {
+    ///     println!("{}", release["tag_name"]);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn query_raw<R>(&self, repository: R) -> Result<Vec<serde_json::Value>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let url = self.spec_url(&repository.try_into()?, "releases");
+        Ok(self.paginate(&url, &mut Call::default())?.releases)
+    }
+
+    /// Get all release version strings from the repository.
+    ///
+    /// Note that `repository` should be in the format "owner/repo",
+    /// like `"celeo/github_release_check"`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let versions_result = github.get_all_versions("celeo/github_release_check");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the HTTP request cannot be sent, the API returns
+    /// a status code indicating something other than a success (outside of the
+    /// 2xx range), of if the returned data does not match the expected model.
+    pub fn get_all_versions<R>(&self, repository: R) -> Result<Vec<String>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        self.listed_tags(&repository.try_into()?)
+    }
+
+    /// Get the tags of the releases [`GitHub::get_all_versions`] lists.
+    fn listed_tags(&self, spec: &RepositorySpec) -> Result<Vec<String>> {
+        Ok(self
+            .listed_releases(spec)?
+            .into_iter()
+            .map(|release| release.tag_name)
+            .collect())
+    }
+
+    /// Get the releases whose tags [`GitHub::get_all_versions`] lists, with
+    /// hidden ignored tags left out and, if set, tags of the same version
+    /// collapsed.
+    fn listed_releases(&self, spec: &RepositorySpec) -> Result<Vec<GitHubReleaseItem>> {
+        let releases: Vec<_> = self
+            .partial_query(spec)?
+            .releases
+            .into_iter()
+            .filter(|release| {
+                !(self.settings.hide_ignored_tags && self.is_ignored(&release.tag_name))
+            })
+            .collect();
+        if self.settings.dedupe_versions {
+            return Ok(self.dedupe_versions(releases).0);
+        }
+        Ok(releases)
+    }
+
+    /// Get all release version strings from the repository, reading as little of
+    /// each release as possible.
+    ///
+    /// The result is the same as [`GitHub::get_all_versions`], but only the ID and
+    /// tag of each release are kept while walking the pages, rather than the whole
+    /// release with its body and assets. Use this for repositories with many
+    /// thousands of releases.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let versions_result = github.get_all_versions_minimal("rust-lang/rust");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions`.
+    pub fn get_all_versions_minimal<R>(&self, repository: R) -> Result<Vec<String>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec = repository.try_into()?;
+        #[cfg(feature = "test-util")]
+        if self.fixture.is_some() {
+            return self.listed_tags(&spec);
+        }
+        if self.settings.dedupe_versions {
+            // picking which tag to keep needs the publication dates
+            return self.listed_tags(&spec);
+        }
+        Ok(self
+            .walk_releases::<MinimalRelease>(&spec)?
+            .releases
+            .into_iter()
+            .filter(|release| {
+                !(self.settings.hide_ignored_tags && self.is_ignored(&release.tag_name))
+            })
+            .map(|release| release.tag_name)
+            .collect())
+    }
+
+    /// Get all release version strings from the repository, each paired with
+    /// the link to its release page.
+    ///
+    /// The pairs are in the order the API returns them, newest first, and the
+    /// versions are the same as from [`GitHub::get_all_versions`], including
+    /// [`GitHubBuilder::hide_ignored_tags`] and
+    /// [`GitHubBuilder::dedupe_versions`]. The link is empty if GitHub didn't
+    /// send one.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// for (version, url) in github.get_all_versions_with_urls("celeo/github_release_check").unwrap() {
+    ///     println!("* [{version}]({url})");
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions`.
+    pub fn get_all_versions_with_urls<R>(&self, repository: R) -> Result<Vec<(String, String)>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        Ok(self
+            .listed_releases(&repository.try_into()?)?
+            .into_iter()
+            .map(|release| (release.tag_name, release.html_url))
+            .collect())
+    }
+
+    /// Get the latest release version from the repository.
+    ///
+    /// Note that `repository` should be in the format "owner/repo",
+    /// like `"celeo/github_release_check"`.
+    ///
+    /// As this function needs to select and return the latest release version,
+    /// it makes use of the "semver" crate's `Version` [parse function]. As there's
+    /// no requirement for repositories to use Semantic Versioning, this function may
+    /// not suitable for every repository (thus the `get_all_versions` function which
+    /// just works with `String`s).
+    ///
+    /// A leading `'v'` character is stripped from the versions
+    /// in order to make more repositories work. For any version string that is not
+    /// able to be loaded into a `Version` struct, it is skipped. Note that this
+    /// may result in no or missing versions.
+    ///
+    /// Effectively, for repositories that are using Semantic Versioning correctly,
+    /// this will work. For those that are not, it's a bit of a toss-up.
+    ///
+    /// Draft releases are never picked, even when the token can see them, unless
+    /// [`GitHubBuilder::include_drafts`] is set.
+    ///
+    /// Since this call can fail for a number of reasons including anything related to
+    /// the network at the time of the call, the `Result` from this function should
+    /// be handled appropriately.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let version_result = github.get_latest_version("celeo/github_release_check");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions`, or
+    /// if no versions are returned from the API.
+    ///
+    /// [parse function]: https://docs.rs/semver/latest/semver/struct.Version.html#method.parse
+    pub fn get_latest_version<R>(&self, repository: R) -> Result<Version>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        Ok(self.get_latest_tagged_version(repository)?.version)
+    }
+
+    /// Get the latest release version from the repository, along with its tag.
+    ///
+    /// This is the same as [`GitHub::get_latest_version`], but also says which tag
+    /// the version belongs to, and whether it was read from the tag or the release
+    /// name (see [`GitHubBuilder::version_source`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let latest = github.get_latest_tagged_version("celeo/github_release_check").unwrap();
+    /// println!("{} is tagged {}", latest.version, latest.tag);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_latest_version`.
+    pub fn get_latest_tagged_version<R>(&self, repository: R) -> Result<TaggedVersion>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        Ok(self.latest_release(&repository.try_into()?)?.0)
+    }
+
+    /// Get the link to the page of the latest release in the repository.
+    ///
+    /// The release is selected the same way as in [`GitHub::get_latest_version`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let url = github.get_latest_release_url("celeo/github_release_check").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_latest_version`.
+    pub fn get_latest_release_url<R>(&self, repository: R) -> Result<String>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec: RepositorySpec = repository.try_into()?;
+        let (tagged, release) = self.latest_release(&spec)?;
+        let html_url = release.map(|r| r.html_url).unwrap_or_default();
+        Ok(self.release_url(&spec, &tagged.tag, html_url))
+    }
+
+    /// Select the latest release, as its version and the release itself.
+    ///
+    /// The release is `None` if it came from the release feed.
+    fn latest_release(
+        &self,
+        spec: &RepositorySpec,
+    ) -> Result<(TaggedVersion, Option<GitHubReleaseItem>)> {
+        let (tagged, release, _) = self.latest_release_traced(spec)?;
+        Ok((tagged, release))
+    }
+
+    /// Select the latest release, along with how it was found.
+    fn latest_release_traced(
+        &self,
+        spec: &RepositorySpec,
+    ) -> Result<(TaggedVersion, Option<GitHubReleaseItem>, ResolutionTrace)> {
+        if let Some(comparator) = &self.settings.version_comparator {
+            let (latest, release, considered) = self.latest_by_comparator(spec, comparator)?;
+            let version = latest
+                .version
+                .ok_or_else(|| LookupError::InvalidVersion(latest.tag.clone()))?;
+            let tagged = TaggedVersion {
+                tag: latest.tag,
+                version,
+                origin: VersionOrigin::Tag,
+            };
+            let mut trace = ResolutionTrace::new(self, ResolutionStrategy::Comparator)
+                .top_candidates([&tagged]);
+            trace.considered = considered;
+            return Ok((tagged, Some(release), trace));
+        }
+        if self.settings.latest_strategy == LatestStrategy::MarkedLatest {
+            let marked = self.marked_latest(spec)?;
+            match versions::release_version(
+                &marked.tag_name,
+                marked.name.as_deref(),
+                self.settings.version_source,
+                &self.settings.version_prefixes,
+            ) {
+                Some(tagged) if !self.is_ignored(&tagged.tag) => {
+                    let mut trace = ResolutionTrace::new(self, ResolutionStrategy::MarkedLatest)
+                        .top_candidates([&tagged]);
+                    trace.considered = 1;
+                    return Ok((tagged, Some(marked), trace));
+                }
+                _ => debug!("Release marked latest can't be used, scanning all releases"),
+            }
+        }
+        let candidates = self.candidate_releases(spec)?;
+        let strategy = if candidates.from_feed {
+            ResolutionStrategy::ReleaseFeed
+        } else {
+            ResolutionStrategy::SemverScan
+        };
+        let mut trace = ResolutionTrace::new(self, strategy)
+            .top_candidates(candidates.releases.iter().map(|(tagged, _)| tagged));
+        trace.considered = candidates.considered;
+        trace.ignored = candidates.ignored;
+        trace.unparseable = candidates.considered - candidates.ignored - candidates.releases.len();
+        let (tagged, release) =
+            latest_candidate(candidates.releases).ok_or(LookupError::NoReleases)?;
+        Ok((tagged, release, trace))
+    }
+
+    /// Get the versions of the repository's releases that versions can be selected from.
+    fn candidate_versions(&self, spec: &RepositorySpec) -> Result<Vec<TaggedVersion>> {
+        Ok(self
+            .candidate_releases(spec)?
+            .releases
+            .into_iter()
+            .map(|(tagged, _)| tagged)
+            .collect())
+    }
+
+    /// Get the versions of the repository's releases that versions can be selected
+    /// from, each with its release (or `None` if it came from the release feed).
+    ///
+    /// Versions are read as set with [`GitHubBuilder::version_source`], and
+    /// ignored tags are left out, as are drafts unless
+    /// [`GitHubBuilder::include_drafts`] is set.
+    fn candidate_releases(&self, spec: &RepositorySpec) -> Result<Candidates> {
+        let releases = match self.query(spec) {
+            // the feed is only found by name, so lookups by ID can't fall back to it
+            #[cfg(feature = "atom")]
+            Err(LookupError::RateLimited { .. } | LookupError::SecondaryRateLimited { .. })
+                if self.settings.feed_fallback && matches!(spec, RepositorySpec::Name(..)) =>
+            {
+                debug!("Rate limited, falling back to the release feed");
+                let tags = self.get_versions_from_feed(spec)?;
+                let ignored = tags.iter().filter(|tag| self.is_ignored(tag)).count();
+                return Ok(Candidates {
+                    releases: versions::tagged_versions(&tags, &self.settings.version_prefixes)
+                        .into_iter()
+                        .filter(|tagged| !self.is_ignored(&tagged.tag))
+                        .map(|tagged| (tagged, None))
+                        .collect(),
+                    from_feed: true,
+                    considered: tags.len(),
+                    ignored,
+                });
+            }
+            other => other?,
+        };
+        let releases: Vec<_> = releases
+            .into_iter()
+            .filter(|release| self.settings.include_drafts || !release.draft)
+            .collect();
+        let considered = releases.len();
+        let ignored = releases
+            .iter()
+            .filter(|release| self.is_ignored(&release.tag_name))
+            .count();
+        Ok(Candidates {
+            releases: self
+                .release_candidates(releases)
+                .into_iter()
+                .map(|(tagged, release)| (tagged, Some(release)))
+                .collect(),
+            from_feed: false,
+            considered,
+            ignored,
+        })
+    }
+
+    /// Pair the releases with their versions, leaving out those without a
+    /// version and those with ignored tags.
+    ///
+    /// With the `rayon` feature, long lists are parsed on rayon's thread pool.
+    /// Either way, the pairs are in the same order as the releases.
+    fn release_candidates(
+        &self,
+        releases: Vec<GitHubReleaseItem>,
+    ) -> Vec<(TaggedVersion, GitHubReleaseItem)> {
+        #[cfg(feature = "rayon")]
+        if releases.len() >= PARALLEL_THRESHOLD {
+            return self.release_candidates_parallel(releases);
+        }
+        self.release_candidates_sequential(releases)
+    }
+
+    /// Pair the releases with their versions on the current thread.
+    fn release_candidates_sequential(
+        &self,
+        releases: Vec<GitHubReleaseItem>,
+    ) -> Vec<(TaggedVersion, GitHubReleaseItem)> {
+        releases
+            .into_iter()
+            .filter_map(|release| self.release_candidate(release))
+            .collect()
+    }
+
+    /// Pair the releases with their versions on rayon's thread pool.
+    #[cfg(feature = "rayon")]
+    fn release_candidates_parallel(
+        &self,
+        releases: Vec<GitHubReleaseItem>,
+    ) -> Vec<(TaggedVersion, GitHubReleaseItem)> {
+        releases
+            .into_par_iter()
+            .filter_map(|release| self.release_candidate(release))
+            .collect()
+    }
+
+    /// Pair the release with its version, unless it has none or its tag is ignored.
+    fn release_candidate(
+        &self,
+        release: GitHubReleaseItem,
+    ) -> Option<(TaggedVersion, GitHubReleaseItem)> {
+        let tagged = versions::release_version(
+            &release.tag_name,
+            release.name.as_deref(),
+            self.settings.version_source,
+            &self.settings.version_prefixes,
+        )?;
+        (!self.is_ignored(&tagged.tag)).then_some((tagged, release))
+    }
+
+    /// The error for an API root that answers with HTML pages.
+    fn not_an_api_endpoint(&self) -> LookupError {
+        LookupError::NotAnApiEndpoint {
+            api_root: self.api_root.clone(),
+        }
+    }
+
+    /// The link to a release page, built from the web root if GitHub didn't send
+    /// one and the repository is known by name.
+    fn release_url(&self, spec: &RepositorySpec, tag: &str, html_url: String) -> String {
+        if html_url.is_empty() {
+            self.spec_web_url(spec, &format!("releases/tag/{}", encode_segment(tag)))
+                .unwrap_or_default()
+        } else {
+            html_url
+        }
+    }
+
+    /// Whether the tag was set to be ignored with [`GitHubBuilder::ignore_tags`]
+    /// or [`GitHubBuilder::ignore_tags_matching`].
+    fn is_ignored(&self, tag: &str) -> bool {
+        let prefixes = &self.settings.version_prefixes;
+        let tag = versions::normalize_tag(tag, prefixes);
+        self.settings
+            .ignored_tags
+            .iter()
+            .any(|ignored| versions::normalize_tag(ignored, prefixes) == tag)
+            || self
+                .settings
+                .ignored_tag_pattern
+                .as_ref()
+                .is_some_and(|pattern| pattern.is_match(tag))
+    }
+
+    /// Get the release version immediately before the given version.
+    ///
+    /// Versions are read the same way as in [`GitHub::get_latest_version`], and the
+    /// largest version strictly less than `version` is returned. `version` doesn't
+    /// need to be a release of the repository itself. Pre-release versions are only
+    /// considered if `include_prereleases` is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// use semver::Version;
+    /// let github = GitHub::new().unwrap();
+    /// let previous = github
+    ///     .get_previous_version("celeo/github_release_check", &Version::new(0, 2, 1), false)
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_all_versions`.
+    pub fn get_previous_version<R>(
+        &self,
+        repository: R,
+        version: &Version,
+        include_prereleases: bool,
+    ) -> Result<Option<TaggedVersion>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        Ok(versions::tagged_versions(
+            &self.get_all_versions(repository)?,
+            &self.settings.version_prefixes,
+        )
+        .into_iter()
+        .filter(|tagged| include_prereleases || tagged.version.pre.is_empty())
+        .filter(|tagged| tagged.version < *version)
+        .max_by(|a, b| a.version.cmp(&b.version)))
+    }
+}
+
+/// Whether the response is an HTML page, going by its content type.
+fn is_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.to_ascii_lowercase().contains("html"))
+}
+
+/// The page to fetch after `page`: the one the "next" link points to, or else
+/// the one after it if the last page hasn't been reached, or if the page was
+/// full but came without pagination links.
+///
+/// The "next" link is followed even past the last page worked out earlier, as
+/// servers that cap the page size may have more pages than first reported.
+fn following_page(
+    page: usize,
+    next_page: Option<usize>,
+    last_page: Option<usize>,
+    unlinked_full: bool,
+) -> Option<usize> {
+    next_page
+        .filter(|&next| next > page)
+        .or_else(|| last_page.filter(|&last| page < last).map(|_| page + 1))
+        .or_else(|| unlinked_full.then_some(page + 1))
+}
+
+/// Whether the page is full without any pagination links, which happens when
+/// a proxy strips the "link" header.
+///
+/// There may be more pages then, so walks keep going until a page isn't full.
+/// A repository with exactly a full page of releases costs one extra request
+/// for the empty page after it. A page that starts with the same release as the
+/// page before it ends the walk, as the server is ignoring the page number.
+fn is_unlinked_full_page<T: PageKey>(
+    result: &PageResult<T>,
+    last_page: Option<usize>,
+    per_page: usize,
+    previous_first: Option<&T>,
+) -> bool {
+    let unlinked = result.next_page.is_none() && result.per_page.is_none() && last_page.is_none();
+    let repeated = previous_first
+        .zip(result.releases.first())
+        .is_some_and(|(previous, first)| previous.key() == first.key());
+    if unlinked && !repeated && result.releases.len() >= per_page {
+        debug!("Full page without pagination links, checking the next page");
+        return true;
+    }
+    false
+}
+
+/// The page size to keep asking for after the first page, which is smaller than
+/// the size asked for if the server capped it.
+///
+/// The server's pagination links say which size it used; without them, a first
+/// page that isn't the only page holds a full page of releases.
+fn effective_page_size<T>(requested: usize, first: &PageResult<T>) -> usize {
+    let served = first
+        .per_page
+        .or_else(|| first.next_page.map(|_| first.releases.len()))
+        .filter(|&served| served > 0 && served < requested);
+    match served {
+        Some(served) => {
+            debug!("Server lowered the page size from {requested} to {served}");
+            served
+        }
+        None => requested,
+    }
+}
+
+/// Flatten the pages, dropping any item already seen on an earlier page.
+fn dedup_pages<T: PageKey>(pages: Vec<Vec<T>>) -> Vec<T> {
+    let total = pages.iter().map(Vec::len).sum();
+    let mut seen = HashSet::with_capacity(total);
+    let mut items = Vec::with_capacity(total);
+    for item in pages.into_iter().flatten() {
+        let (id, name) = item.key();
+        if seen.insert((id, name.to_owned())) {
+            items.push(item);
+        }
+    }
+    items
+}
+
+/// What identifies an item, like a release, across pages.
+pub(crate) trait PageKey {
+    /// The item's ID and name, like a release's ID and tag name.
+    fn key(&self) -> (usize, &str);
+}
+
+impl PageKey for GitHubReleaseItem {
+    fn key(&self) -> (usize, &str) {
+        (self.id, &self.tag_name)
+    }
+}
+
+impl PageKey for serde_json::Value {
+    fn key(&self) -> (usize, &str) {
+        (
+            self["id"]
+                .as_u64()
+                .and_then(|id| usize::try_from(id).ok())
+                .unwrap_or_default(),
+            self["tag_name"].as_str().unwrap_or_default(),
+        )
+    }
+}
+
+/// Just enough of a release to list its tag, read by [`GitHub::get_all_versions_minimal`].
+#[derive(Debug, Deserialize)]
+struct MinimalRelease {
+    id: usize,
+    tag_name: String,
+}
+
+impl PageKey for MinimalRelease {
+    fn key(&self) -> (usize, &str) {
+        (self.id, &self.tag_name)
+    }
+}
+
+/// Percent-encode a single URL path segment, like a tag name.
+fn encode_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
+}
+
+/// Percent-encode an "owner/repo" repository as two URL path segments.
+#[cfg(feature = "gitea")]
+fn encode_repository(repository: &str) -> String {
+    match repository.split_once('/') {
+        Some((owner, repo)) => format!("{}/{}", encode_segment(owner), encode_segment(repo)),
+        None => encode_segment(repository),
+    }
+}
+
+/// Work out the website root of a GitHub instance from its API root.
+fn default_web_root(api_root: &str) -> String {
+    if api_root == DEFAULT_API_ROOT {
+        return String::from("https://github.com/");
+    }
+    api_root
+        .strip_suffix("api/v3/")
+        .unwrap_or(api_root)
+        .to_owned()
+}
+
+/// Map a timed out request to [`LookupError::Timeout`], and other errors as they are.
+fn timeout_error(error: reqwest::Error) -> LookupError {
+    if !error.is_timeout() {
+        return error.into();
+    }
+    let phase = if error.is_connect() {
+        TimeoutPhase::Connect
+    } else {
+        TimeoutPhase::Response
+    };
+    debug!("Request timed out while {phase}");
+    LookupError::Timeout { phase }
+}
+
+/// Map an unsuccessful response to the matching error.
+fn error_from_response(response: Response) -> LookupError {
+    let stat = response.status().as_u16();
+    let headers = response.headers().clone();
+    let request_id = request_id(&headers);
+    let body = error_body(response);
+    if (stat == 403 || stat == 429) && body.to_lowercase().contains("secondary rate limit") {
+        let retry_after = headers
+            .get(header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        return LookupError::SecondaryRateLimited {
+            retry_after,
+            request_id,
+        };
+    }
+    let rate_limit_exhausted = headers
+        .get("x-ratelimit-remaining")
+        .and_then(|v| v.to_str().ok())
+        == Some("0");
+    if stat == 429 || (stat == 403 && rate_limit_exhausted) {
+        let reset_at = headers
+            .get("x-ratelimit-reset")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+        return LookupError::RateLimited {
+            reset_at,
+            request_id,
+        };
+    }
+    if stat == 404 {
+        return LookupError::RepositoryNotFound;
+    }
+    if stat == 401 || stat == 403 {
+        return LookupError::AuthenticationError {
+            status: stat,
+            request_id,
+        };
+    }
+    LookupError::ErrorHttpResponse {
+        status: stat,
+        message: error_message(&body),
+        request_id,
+    }
+}
+
+/// The ID GitHub gives the request, from its response headers.
+fn request_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("x-github-request-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+}
+
+/// GitHub's message for an error from its response body, like `"Server Error"`.
+fn error_message(body: &str) -> Option<String> {
+    let parsed: serde_json::Value = serde_json::from_str(body).ok()?;
+    let message = parsed.get("message")?.as_str()?.trim();
+    (!message.is_empty()).then(|| message.to_owned())
+}
+
+/// GitHub's message for an error message, if there is one.
+fn message_note(message: Option<&str>) -> String {
+    message.map_or_else(String::new, |message| format!(": {message}"))
+}
+
+/// The request ID for an error message, if there is one.
+fn request_id_note(request_id: Option<&str>) -> String {
+    request_id.map_or_else(String::new, |id| format!(" (GitHub request ID {id})"))
+}
+
+/// Read the start of an error response body, which is all that's needed to tell errors apart.
+fn error_body(response: Response) -> String {
+    const ERROR_BODY_LIMIT: u64 = 64 * 1024;
+    let mut body = Vec::new();
+    let _read = body::decoded(response)
+        .take(ERROR_BODY_LIMIT)
+        .read_to_end(&mut body);
+    error_body_text(body)
+}
+
+/// The error body as text, kept whole even if the read limit cut a character
+/// in half, which only drops that character. Any other bytes that aren't
+/// UTF-8 are replaced.
+fn error_body_text(body: Vec<u8>) -> String {
+    match String::from_utf8(body) {
+        Ok(body) => body,
+        Err(e) => {
+            let utf8 = e.utf8_error();
+            let mut body = e.into_bytes();
+            if utf8.error_len().is_none() {
+                body.truncate(utf8.valid_up_to());
+            }
+            String::from_utf8_lossy(&body).into_owned()
+        }
+    }
+}
+
+/// Sleep for the duration in short chunks rather than a single long sleep.
+fn sleep_chunked(duration: Duration) {
+    const CHUNK: Duration = Duration::from_millis(500);
+    let end = SystemTime::now() + duration;
+    while let Ok(remaining) = end.duration_since(SystemTime::now()) {
+        thread::sleep(remaining.min(CHUNK));
+    }
+}
+
+/// Determine the last page (if any) from the GitHub response headers.
+///
+/// # Errors
+///
+/// This function fails if the the values in the "link" header
+/// are not valid ASCII, or the link to the last page is malformed.
+fn get_last_page(headers: &HeaderMap) -> Result<Option<usize>> {
+    get_link_page(headers, "last")
+}
+
+/// Determine the page size the server used from the `per_page` parameter of
+/// the "next" or "last" link in the GitHub response headers.
+///
+/// # Errors
+///
+/// This function fails if the the values in the "link" header
+/// are not valid ASCII, or the link is malformed.
+fn get_link_per_page(headers: &HeaderMap) -> Result<Option<usize>> {
+    let url = match get_link_url(headers, "next")? {
+        Some(url) => url,
+        None => match get_link_url(headers, "last")? {
+            Some(url) => url,
+            None => return Ok(None),
+        },
+    };
+    url.query_pairs()
+        .find(|(name, _)| name == "per_page")
+        .map(|(_, value)| {
+            value
+                .parse::<usize>()
+                .map_err(|e| LookupError::PaginationParse(format!("{url}: {e}")))
+        })
+        .transpose()
+}
+
+/// Determine the page number of the link with the relation (like `"next"`)
+/// from the GitHub response headers.
+///
+/// The page is read from the `page` query parameter of the link's URL.
+///
+/// # Errors
+///
+/// This function fails if the the values in the "link" header
+/// are not valid ASCII, or the link with the relation is malformed.
+fn get_link_page(headers: &HeaderMap, rel: &str) -> Result<Option<usize>> {
+    let Some(url) = get_link_url(headers, rel)? else {
+        return Ok(None);
+    };
+    let page = url
+        .query_pairs()
+        .find(|(name, _)| name == "page")
+        .ok_or_else(|| LookupError::PaginationParse(format!("{url}: no page parameter")))?;
+    page.1
+        .parse::<usize>()
+        .map(Some)
+        .map_err(|e| LookupError::PaginationParse(format!("{url}: {e}")))
+}
+
+/// Find the URL of the link with the relation in the GitHub response headers.
+///
+/// The header is read as in [RFC 8288]: a comma-separated list of `<url>`
+/// links, each followed by `;`-separated parameters, where the `rel` parameter
+/// name is case-insensitive and may hold several space-separated relations.
+///
+/// [RFC 8288]: https://www.rfc-editor.org/rfc/rfc8288#section-3
+///
+/// # Errors
+///
+/// This function fails if the the values in the "link" header
+/// are not valid ASCII, or the link with the relation is malformed.
+fn get_link_url(headers: &HeaderMap, rel: &str) -> Result<Option<reqwest::Url>> {
+    let links = match headers.get("link") {
+        Some(l) => l.to_str()?,
+        None => return Ok(None),
+    };
+    let mut rest = links.trim();
+    while !rest.is_empty() {
+        let invalid = || LookupError::PaginationParse(links.to_owned());
+        let target = rest.strip_prefix('<').ok_or_else(invalid)?;
+        let (target, after) = target.split_once('>').ok_or_else(invalid)?;
+        // parameters run until the comma starting the next link
+        let (params, next) = after.split_once(',').unwrap_or((after, ""));
+        rest = next.trim_start();
+
+        let has_rel = params.split(';').any(|param| {
+            param.split_once('=').is_some_and(|(name, value)| {
+                name.trim().eq_ignore_ascii_case("rel")
+                    && value
+                        .trim()
+                        .trim_matches('"')
+                        .split_ascii_whitespace()
+                        .any(|value| value.eq_ignore_ascii_case(rel))
+            })
+        });
+        if !has_rel {
+            continue;
+        }
+        return reqwest::Url::parse(target.trim())
+            .map(Some)
+            .map_err(|e| LookupError::PaginationParse(format!("{target}: {e}")));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{
+        error_body_text, error_message, get_last_page, BudgetBehavior, DateField, GitHub,
+        GitHubReleaseItem, LatestStrategy, LookupError, TimeoutPhase, VersionOrigin, VersionSource,
+    };
+    use mockito::{mock, Matcher};
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+    use serde_json::{json, Value};
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    /// A release as the API lists it, with the tag, the values in `fields`,
+    /// and empty values for everything else.
+    pub(crate) fn release_json(tag: &str, fields: Value) -> Value {
+        let mut release = json!({
+            "tag_name": tag, "url": "", "assets_url": "", "upload_url": "", "html_url": "",
+            "id": 1, "name": "", "draft": false, "prerelease": false, "created_at": "",
+            "published_at": "", "body": "",
+        });
+        if let (Some(release), Value::Object(fields)) = (release.as_object_mut(), fields) {
+            release.extend(fields);
+        }
+        release
+    }
+
+    /// An instance without a token that sends requests to the mock server.
+    pub(crate) fn mock_github() -> GitHub {
+        GitHub::from_custom(&format!("{}/", mockito::server_url()), "").unwrap()
+    }
+
+    pub(crate) fn epoch_secs(offset: u64) -> String {
+        (SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + offset)
+            .to_string()
+    }
+
+    #[test]
+    fn test_accessors_new() {
+        let github = GitHub::new().unwrap();
+        assert_eq!(github.api_root(), super::DEFAULT_API_ROOT);
+        assert!(!github.is_authenticated());
+        assert_eq!(github.user_agent(), "github.com/celeo/github_version_check");
+    }
+
+    #[test]
+    fn test_accessors_from_custom() {
+        let github = GitHub::from_custom("https://github.example.com/api/v3/", "abcdef").unwrap();
+        assert_eq!(github.api_root(), "https://github.example.com/api/v3/");
+        assert!(github.is_authenticated());
+        assert_eq!(github.user_agent(), "github.com/celeo/github_version_check");
+
+        let github = GitHub::from_custom("https://github.example.com/api/v3/", "").unwrap();
+        assert!(!github.is_authenticated());
+    }
+
+    #[test]
+    fn test_accessors_builder() {
+        let github = GitHub::builder()
+            .api_root("https://api.github.example.com/")
+            .build()
+            .unwrap();
+        assert_eq!(github.api_root(), "https://api.github.example.com/");
+        assert!(!github.is_authenticated());
+
+        let github = GitHub::builder().token("abcdef").build().unwrap();
+        assert_eq!(github.api_root(), super::DEFAULT_API_ROOT);
+        assert!(github.is_authenticated());
+    }
+
+    #[test]
+    fn test_get_last_page_none() {
+        let map = HeaderMap::new();
+        let last = get_last_page(&map).unwrap();
+        assert!(last.is_none());
+    }
+
+    #[test]
+    fn test_get_last_page_some() {
+        let mut map = HeaderMap::new();
+        let _ = map.insert(
+            HeaderName::from_static("link"),
+            HeaderValue::from_static(r#"<https://api.github.com/repositories/275449421/releases?per_page=1&page=2>; rel="next", <https://api.github.com/repositories/275449421/releases?per_page=1&page=10>; rel="last""#)
+        );
+        let last = get_last_page(&map).unwrap();
+        assert_eq!(last, Some(10));
+    }
+
+    fn link_headers(link: &'static str) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        let _ = map.insert(
+            HeaderName::from_static("link"),
+            HeaderValue::from_static(link),
+        );
+        map
+    }
+
+    #[test]
+    fn test_get_last_page_tricky_headers() {
+        for link in [
+            // page before per_page
+            r#"<https://api.github.com/repos/foo/bar/releases?page=7&per_page=100>; rel="last""#,
+            // uppercase parameter name and extra spaces
+            r#"<https://api.github.com/repos/foo/bar/releases?per_page=100&page=2>;  REL="next" ,   <https://api.github.com/repos/foo/bar/releases?per_page=100&page=7> ;  Rel = "last""#,
+            // page in a fragment doesn't count
+            r#"<https://api.github.com/repos/foo/bar/releases?page=7#page=3>; rel="last""#,
+            // several relations and unquoted values
+            r#"<https://api.github.com/repos/foo/bar/releases?page=1>; rel=first, <https://api.github.com/repos/foo/bar/releases?page=7>; rel="prev last""#,
+            // other parameters before the relation
+            r#"<https://api.github.com/repos/foo/bar/releases?sub_page=3&page=7>; title="end"; rel="last""#,
+        ] {
+            assert_eq!(
+                get_last_page(&link_headers(link)).unwrap(),
+                Some(7),
+                "{link}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_last_page_malformed() {
+        for link in [
+            r#"https://api.github.com/repos/foo/bar/releases?page=7; rel="last""#,
+            r#"<https://api.github.com/repos/foo/bar/releases?page=7; rel="last""#,
+            r#"</repos/foo/bar/releases?page=7>; rel="last""#,
+            r#"<https://api.github.com/repos/foo/bar/releases?per_page=100>; rel="last""#,
+            r#"<https://api.github.com/repos/foo/bar/releases?page=seven>; rel="last""#,
+        ] {
+            assert!(
+                matches!(
+                    get_last_page(&link_headers(link)),
+                    Err(LookupError::PaginationParse(_))
+                ),
+                "{link}"
+            );
+        }
+        assert_eq!(
+            get_last_page(&link_headers(
+                r#"<https://api.github.com/repos/foo/bar/releases?page=2>; rel="next""#
+            ))
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_all_versions_none() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .create();
+        let github = mock_github();
+        let versions = github.get_all_versions("foo/bar").unwrap();
+        assert!(versions.is_empty());
+    }
+
+    #[test]
+    fn test_get_all_versions_valid() {
+        let body = json!(["v1.0.0", "v1.9.10", "v0.3.0"].map(|tag| release_json(tag, json!({}))));
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(body.to_string())
+            .create();
+        let github = mock_github();
+        let versions = github.get_all_versions("foo/bar").unwrap();
+        assert_eq!(versions.len(), 3);
+    }
+
+    #[test]
+    fn test_get_latest_version_none() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .create();
+        let github = mock_github();
+        let version_res = github.get_latest_version("foo/bar");
+        assert!(version_res.is_err());
+    }
+
+    #[test]
+    fn test_get_latest_version_bad_semvers() {
+        let body =
+            json!(["uhhhh", "v3.0.0-alpha", "v1.9.10"].map(|tag| release_json(tag, json!({}))));
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(body.to_string())
+            .create();
+        let github = mock_github();
+        let version = github.get_latest_version("foo/bar").unwrap();
+        assert_eq!(version, semver::Version::parse("3.0.0-alpha").unwrap());
+    }
+
+    #[test]
+    fn test_get_latest_version_skips_drafts() {
+        // drafts are only listed for tokens that can push to the repository
+        let release = |id: u32, tag: &str, draft: bool| {
+            release_json(tag, json!({ "id": id, "draft": draft }))
+        };
+        let _m = mock("GET", "/repos/foo/drafts/releases")
+            .match_query(Matcher::Any)
+            .match_header("authorization", "Bearer secret")
+            .with_body(format!(
+                "[{}, {}, {}]",
+                release(3, "v9.0.0", true),
+                release(2, "v1.2.0", false),
+                release(1, "v1.0.0", false)
+            ))
+            .create();
+        let builder = || {
+            GitHub::builder()
+                .api_root(&format!("{}/", mockito::server_url()))
+                .token("secret")
+        };
+        let github = builder().build().unwrap();
+        assert_eq!(
+            github.get_latest_version("foo/drafts").unwrap(),
+            semver::Version::new(1, 2, 0)
+        );
+        assert_eq!(github.get_all_versions("foo/drafts").unwrap().len(), 3);
+
+        let github = builder().include_drafts(true).build().unwrap();
+        assert_eq!(
+            github.get_latest_version("foo/drafts").unwrap(),
+            semver::Version::new(9, 0, 0)
+        );
+
+        let by_comparator = |include| {
+            builder()
+                .include_drafts(include)
+                .version_comparator(Box::new(|a: &str, b: &str| a.cmp(b)))
+                .build()
+                .unwrap()
+                .get_latest_tag("foo/drafts")
+                .unwrap()
+                .tag
+        };
+        assert_eq!(by_comparator(false), "v1.2.0");
+        assert_eq!(by_comparator(true), "v9.0.0");
+    }
+
+    #[test]
+    fn test_rate_limited_without_wait() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &epoch_secs(0))
+            .create();
+        let github = mock_github();
+        let res = github.query("foo/bar");
+        assert!(matches!(
+            res,
+            Err(LookupError::RateLimited {
+                reset_at: Some(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_wait_on_rate_limit_resumes_pagination() {
+        let link = format!(
+            r#"<{0}/repos/foo/bar/releases?per_page=100&page=2>; rel="next", <{0}/repos/foo/bar/releases?per_page=100&page=2>; rel="last""#,
+            mockito::server_url()
+        );
+        let page_one = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_header("link", &link)
+            .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+            .expect(1)
+            .create();
+        let limited = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &epoch_secs(0))
+            .expect(1)
+            .create();
+        let page_two = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_body(json!([release_json("v2.0.0", json!({}))]).to_string())
+            .expect(1)
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .wait_on_rate_limit(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        let versions = github.get_all_versions("foo/bar").unwrap();
+        assert_eq!(versions, vec!["v1.0.0", "v2.0.0"]);
+        page_one.assert();
+        limited.assert();
+        page_two.assert();
+    }
+
+    #[test]
+    fn test_wait_on_rate_limit_exceeds_max_wait() {
+        let m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &epoch_secs(3600))
+            .expect(1)
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .wait_on_rate_limit(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        let res = github.query("foo/bar");
+        assert!(matches!(res, Err(LookupError::RateLimited { .. })));
+        m.assert();
+    }
+
+    /// Mock every page of a paginated release list, with one release per page.
+    fn mock_pages(path: &str, pages: usize) -> Vec<mockito::Mock> {
+        (1..=pages)
+            .map(|page| {
+                let link = format!(
+                    r#"<{0}{path}?per_page=100&page={pages}>; rel="last""#,
+                    mockito::server_url()
+                );
+                mock("GET", path)
+                    .match_query(Matcher::UrlEncoded("page".into(), page.to_string()))
+                    .with_header("link", &link)
+                    .with_body(
+                        json!([release_json(&format!("v{page}.0.0"), json!({}))]).to_string(),
+                    )
+                    .create()
+            })
+            .collect()
+    }
+
+    /// A page of releases numbered from `first`, without a "link" header.
+    fn unlinked_page(path: &str, page: usize, first: usize, count: usize) -> mockito::Mock {
+        let body = (first..first + count)
+            .map(|n| release_json(&format!("v0.{n}.0"), json!({})).to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        mock("GET", path)
+            .match_query(Matcher::UrlEncoded("page".into(), page.to_string()))
+            .with_body(format!("[{body}]"))
+            .expect(1)
+            .create()
+    }
+
+    #[test]
+    fn test_lenient_headers_with_bad_link_header() {
+        let path = "/repos/foo/mangled-link/releases";
+        let body = (0..100)
+            .map(|n| release_json(&format!("v0.{n}.0"), json!({})).to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let first = mock("GET", path)
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_header("link", "<http://proxy.example/\u{e9}t\u{e9}>; rel=\"next\"")
+            .with_body(format!("[{body}]"))
+            .expect(2)
+            .create();
+        let second = unlinked_page(path, 2, 100, 30);
+
+        let strict = mock_github();
+        assert!(matches!(
+            strict.query("foo/mangled-link"),
+            Err(LookupError::HeaderToString(_))
+        ));
+
+        let lenient = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .lenient_headers(true)
+            .build()
+            .unwrap();
+        let releases = lenient.query("foo/mangled-link").unwrap();
+        assert_eq!(releases.len(), 130);
+        first.assert();
+        second.assert();
+    }
+
+    #[test]
+    fn test_stripped_link_header_pages() {
+        let path = "/repos/foo/stripped/releases";
+        let pages = [
+            unlinked_page(path, 1, 0, 100),
+            unlinked_page(path, 2, 100, 30),
+        ];
+        let github = mock_github();
+        let releases = github.query("foo/stripped").unwrap();
+        assert_eq!(releases.len(), 130);
+        assert_eq!(releases[129].tag_name, "v0.129.0");
+        for m in &pages {
+            m.assert();
+        }
+    }
+
+    #[test]
+    fn test_full_single_page_costs_one_more_request() {
+        let path = "/repos/foo/exactly-full/releases";
+        let pages = [
+            unlinked_page(path, 1, 0, 100),
+            unlinked_page(path, 2, 100, 0),
+        ];
+        let github = mock_github();
+        let (releases, stats) = github.query_with_stats("foo/exactly-full").unwrap();
+        assert_eq!(releases.len(), 100);
+        assert_eq!(stats.requests, 2);
+        for m in &pages {
+            m.assert();
+        }
+    }
+
+    #[test]
+    fn test_get_all_versions_minimal_matches_full() {
+        let _m = mock_pages("/repos/foo/bar/releases", 3);
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .ignore_tags(&["2.0.0"])
+            .hide_ignored_tags(true)
+            .build()
+            .unwrap();
+        let minimal = github.get_all_versions_minimal("foo/bar").unwrap();
+        assert_eq!(minimal, vec!["v1.0.0", "v3.0.0"]);
+        assert_eq!(minimal, github.get_all_versions("foo/bar").unwrap());
+    }
+
+    #[test]
+    fn test_query_all_pages() {
+        let _m = mock_pages("/repos/foo/bar/releases", 5);
+        let github = mock_github();
+        let versions = github.get_all_versions("foo/bar").unwrap();
+        assert_eq!(versions.len(), 5);
+    }
+
+    #[test]
+    fn test_query_fetches_the_last_page() {
+        let path = "/repos/foo/two-pages/releases";
+        let link = format!(
+            r#"<{}{path}?per_page=100&page=2>; rel="last""#,
+            mockito::server_url()
+        );
+        let mocks: Vec<_> = (1..=2)
+            .map(|page| {
+                mock("GET", path)
+                    .match_query(Matcher::UrlEncoded("page".into(), page.to_string()))
+                    .with_header("link", &link)
+                    .with_body(
+                        json!([release_json(&format!("v{page}.0.0"), json!({}))]).to_string(),
+                    )
+                    .expect(1)
+                    .create()
+            })
+            .collect();
+        let github = mock_github();
+        let versions = github.get_all_versions("foo/two-pages").unwrap();
+        assert_eq!(versions, vec!["v1.0.0", "v2.0.0"]);
+        for m in &mocks {
+            m.assert();
+        }
+    }
+
+    #[test]
+    fn test_large_page_matches_buffered() {
+        let body = (1..=100)
+            .map(|id| {
+                release_json(
+                    &format!("v{id}.0.0"),
+                    json!({ "id": id, "body": "notes ".repeat(2000) }),
+                )
+            })
+            .collect::<Value>()
+            .to_string();
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(&body)
+            .create();
+        let github = mock_github();
+        let streamed = github.query("foo/bar").unwrap();
+        let buffered: Vec<GitHubReleaseItem> = serde_json::from_str(&body).unwrap();
+        assert_eq!(streamed.len(), 100);
+        for (streamed, buffered) in streamed.iter().zip(&buffered) {
+            assert_eq!(streamed.id, buffered.id);
+            assert_eq!(streamed.tag_name, buffered.tag_name);
+            assert_eq!(streamed.body, buffered.body);
+        }
+        assert_eq!(
+            github.get_all_versions_minimal("foo/bar").unwrap(),
+            github.get_all_versions("foo/bar").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_not_modified_page() {
+        let _m = mock("GET", "/repos/foo/unchanged/releases")
+            .match_query(Matcher::Any)
+            .with_status(304)
+            .with_body("")
+            .create();
+        let github = mock_github();
+        assert!(matches!(
+            github.query("foo/unchanged"),
+            Err(LookupError::NotModified)
+        ));
+        assert!(matches!(
+            github.query_page("foo/unchanged", 1, 100),
+            Err(LookupError::NotModified)
+        ));
+    }
+
+    #[test]
+    fn test_html_page_ok() {
+        let _m = mock("GET", "/repos/foo/web-ok/releases")
+            .match_query(Matcher::Any)
+            .with_header("content-type", "text/html; charset=utf-8")
+            .with_body("<!DOCTYPE html><html><body>Sign in</body></html>")
+            .create();
+        let root = format!("{}/", mockito::server_url());
+        let github = GitHub::from_custom(&root, "").unwrap();
+        let err = github.query("foo/web-ok").unwrap_err();
+        assert!(matches!(&err, LookupError::NotAnApiEndpoint { api_root } if *api_root == root));
+        assert!(err.to_string().contains("/api/v3/"));
+    }
+
+    #[test]
+    fn test_html_page_not_found() {
+        let _m = mock("GET", "/repos/foo/web-missing/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .with_header("content-type", "text/html")
+            .with_body("<html><body>Not Found</body></html>")
+            .create();
+        let github = mock_github();
+        assert!(matches!(
+            github.query("foo/web-missing"),
+            Err(LookupError::NotAnApiEndpoint { .. })
+        ));
+    }
+
+    #[test]
+    fn test_html_body_without_content_type() {
+        let _m = mock("GET", "/repos/foo/web-untyped/releases")
+            .match_query(Matcher::Any)
+            .with_body("\n  <!DOCTYPE html>\n<html></html>")
+            .create();
+        let github = mock_github();
+        assert!(matches!(
+            github.query("foo/web-untyped"),
+            Err(LookupError::NotAnApiEndpoint { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_page() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body("[\n  { \"tag_name\": 1 }\n]")
+            .create();
+        let github = mock_github();
+        let err = github.query("foo/bar").unwrap_err();
+        assert!(matches!(err, LookupError::InvalidResponse { line: 2, .. }));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_candidates_match_sequential() {
+        let mut state = 0x2545_f491_u64;
+        let releases: Vec<GitHubReleaseItem> = (0..5000)
+            .map(|id| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1);
+                let (major, minor, patch) = (state >> 58, (state >> 40) % 20, (state >> 20) % 20);
+                let tag = match state % 4 {
+                    0 => format!("v{major}.{minor}.{patch}"),
+                    1 => format!("{major}.{minor}.{patch}-rc.{}", state % 3),
+                    2 => format!("nightly-{id}"),
+                    _ => format!("{major}.{minor}.{patch}"),
+                };
+                GitHubReleaseItem::from_value(&release_json(&tag, json!({ "id": id }))).unwrap()
+            })
+            .collect();
+        let github = GitHub::builder()
+            .ignore_tags_matching("^1\\.")
+            .build()
+            .unwrap();
+        let key = |candidates: Vec<(super::TaggedVersion, GitHubReleaseItem)>| {
+            candidates
+                .into_iter()
+                .map(|(tagged, release)| (tagged, release.id))
+                .collect::<Vec<_>>()
+        };
+        let parallel = key(github.release_candidates_parallel(releases.clone()));
+        let sequential = key(github.release_candidates_sequential(releases.clone()));
+        assert!(parallel.len() > 1000);
+        assert_eq!(parallel, sequential);
+
+        let latest = super::latest_candidate(github.release_candidates(releases.clone()));
+        let sequential = github
+            .release_candidates_sequential(releases)
+            .into_iter()
+            .max_by(|a, b| a.0.version.cmp(&b.0.version));
+        assert_eq!(
+            latest.map(|(tagged, release)| (tagged, release.id)),
+            sequential.map(|(tagged, release)| (tagged, release.id))
+        );
+    }
+
+    #[test]
+    fn test_query_pages_in_order() {
+        let _m = mock_pages("/repos/foo/bar/releases", 4);
+        let github = mock_github();
+        let tags: Vec<_> = github
+            .query("foo/bar")
+            .unwrap()
+            .into_iter()
+            .map(|release| release.tag_name)
+            .collect();
+        assert_eq!(tags, vec!["v1.0.0", "v2.0.0", "v3.0.0", "v4.0.0"]);
+        assert_eq!(
+            github.get_latest_version("foo/bar").unwrap(),
+            semver::Version::new(4, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_get_all_versions_with_urls() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!([
+                    release_json(
+                        "v2.0.0",
+                        json!({ "id": 2, "html_url": "https://github.com/foo/bar/releases/tag/v2.0.0" })
+                    ),
+                    release_json("v1.0.0", json!({})),
+                ])
+                .to_string(),
+            )
+            .create();
+        let github = mock_github();
+        let pairs = github.get_all_versions_with_urls("foo/bar").unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                (
+                    "v2.0.0".to_owned(),
+                    "https://github.com/foo/bar/releases/tag/v2.0.0".to_owned()
+                ),
+                ("v1.0.0".to_owned(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_all_versions_with_urls_filters_like_get_all_versions() {
+        let _m = mock_tags(&["v3.0.0", "v2.0.0", "2.0.0", "v1.0.0"]);
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .ignore_tags(&["3.0.0"])
+            .hide_ignored_tags(true)
+            .dedupe_versions(true)
+            .build()
+            .unwrap();
+        let tags: Vec<_> = github
+            .get_all_versions_with_urls("foo/bar")
+            .unwrap()
+            .into_iter()
+            .map(|(tag, _)| tag)
+            .collect();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags, github.get_all_versions("foo/bar").unwrap());
+        assert!(!tags.contains(&"v3.0.0".to_owned()));
+    }
+
+    fn mock_tags(tags: &[&str]) -> mockito::Mock {
+        let body = tags
+            .iter()
+            .map(|t| release_json(t, json!({})).to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!("[{body}]"))
+            .create()
+    }
+
+    #[test]
+    fn test_latest_version_from_release_name() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!([
+                    release_json("build-20240715", json!({ "id": 2, "name": "MyApp 3.2.0" })),
+                    release_json("v3.1.0", json!({})),
+                ])
+                .to_string(),
+            )
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .version_source(VersionSource::NameFallback)
+            .build()
+            .unwrap();
+        let latest = github.get_latest_tagged_version("foo/bar").unwrap();
+        assert_eq!(latest.tag, "build-20240715");
+        assert_eq!(latest.version, semver::Version::new(3, 2, 0));
+        assert_eq!(latest.origin, VersionOrigin::Name);
+
+        let github = mock_github();
+        let latest = github.get_latest_tagged_version("foo/bar").unwrap();
+        assert_eq!(latest.tag, "v3.1.0");
+        assert_eq!(latest.origin, VersionOrigin::Tag);
+    }
+
+    #[test]
+    fn test_ignored_tags() {
+        let _m = mock_tags(&["v1.2.0", "9.9.9", "v1.3.0", "v8.0.0-bogus"]);
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .ignore_tags(&["v9.9.9"])
+            .ignore_tags_matching("bogus$")
+            .build()
+            .unwrap();
+        assert_eq!(
+            github.get_latest_version("foo/bar").unwrap(),
+            semver::Version::new(1, 3, 0)
+        );
+        assert_eq!(github.get_all_versions("foo/bar").unwrap().len(), 4);
+
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .ignore_tags(&["9.9.9"])
+            .hide_ignored_tags(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            github.get_all_versions("foo/bar").unwrap(),
+            vec!["v1.2.0", "v1.3.0", "v8.0.0-bogus"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_ignore_pattern() {
+        let res = GitHub::builder().ignore_tags_matching("(").build();
+        assert!(matches!(res, Err(LookupError::InvalidTagPattern(_))));
+    }
+
+    #[test]
+    fn test_get_latest_release_url() {
+        let linked = |id: usize, tag: &str| {
+            let html_url = format!("https://github.com/foo/bar/releases/tag/{tag}");
+            release_json(tag, json!({ "id": id, "html_url": html_url }))
+        };
+        let _all = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!([
+                    linked(3, "v1.8.9"),
+                    linked(2, "v2.1.0"),
+                    release_json("v2.0.0", json!({})),
+                ])
+                .to_string(),
+            )
+            .create();
+        let _latest = mock("GET", "/repos/foo/bar/releases/latest")
+            .with_body(linked(3, "v1.8.9").to_string())
+            .create();
+
+        let github = mock_github();
+        assert_eq!(
+            github.get_latest_release_url("foo/bar").unwrap(),
+            "https://github.com/foo/bar/releases/tag/v2.1.0"
+        );
+
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .latest_strategy(LatestStrategy::MarkedLatest)
+            .build()
+            .unwrap();
+        assert_eq!(
+            github.get_latest_release_url("foo/bar").unwrap(),
+            "https://github.com/foo/bar/releases/tag/v1.8.9"
+        );
+    }
+
+    #[test]
+    fn test_latest_release_url_built_when_missing() {
+        let _m = mock_tags(&["v0.9.0", "v1.0.0"]);
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .web_root("https://github.example.com/")
+            .build()
+            .unwrap();
+        assert_eq!(
+            github.get_latest_release_url("foo/bar").unwrap(),
+            "https://github.example.com/foo/bar/releases/tag/v1.0.0"
+        );
+    }
+
+    #[test]
+    fn test_get_previous_version() {
+        let _m = mock_tags(&["v1.0.0", "v1.1.0", "v1.2.0-rc.1", "v1.2.0", "v2.0.0"]);
+        let github = mock_github();
+
+        let previous = github
+            .get_previous_version("foo/bar", &semver::Version::new(2, 0, 0), false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(previous.tag, "v1.2.0");
+
+        let previous = github
+            .get_previous_version("foo/bar", &semver::Version::new(1, 2, 0), false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(previous.tag, "v1.1.0");
+        let previous = github
+            .get_previous_version("foo/bar", &semver::Version::new(1, 2, 0), true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(previous.tag, "v1.2.0-rc.1");
+    }
+
+    #[test]
+    fn test_get_previous_version_oldest() {
+        let _m = mock_tags(&["v1.0.0", "v1.1.0"]);
+        let github = mock_github();
+        let previous = github
+            .get_previous_version("foo/bar", &semver::Version::new(1, 0, 0), true)
+            .unwrap();
+        assert_eq!(previous, None);
+    }
+
+    #[test]
+    fn test_get_previous_version_not_a_release() {
+        let _m = mock_tags(&["v1.0.0", "v1.1.0", "v3.0.0"]);
+        let github = mock_github();
+        let previous = github
+            .get_previous_version("foo/bar", &semver::Version::new(2, 5, 0), false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(previous.version, semver::Version::new(1, 1, 0));
+    }
+
+    #[test]
+    fn test_query_page_middle() {
+        let link = format!(
+            r#"<{0}/repos/foo/bar/releases?per_page=1&page=3>; rel="next", <{0}/repos/foo/bar/releases?per_page=1&page=5>; rel="last", <{0}/repos/foo/bar/releases?per_page=1&page=1>; rel="first""#,
+            mockito::server_url()
+        );
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("page".into(), "2".into()),
+                Matcher::UrlEncoded("per_page".into(), "1".into()),
+            ]))
+            .with_header("link", &link)
+            .with_header("etag", "W/\"abc\"")
+            .with_body(json!([release_json("v2.0.0", json!({}))]).to_string())
+            .create();
+        let github = mock_github();
+        let page = github.query_page("foo/bar", 2, 1).unwrap();
+        assert_eq!(page.releases.len(), 1);
+        assert_eq!(page.next_page, Some(3));
+        assert_eq!(page.last_page, Some(5));
+        assert_eq!(page.etag.as_deref(), Some("W/\"abc\""));
+    }
+
+    #[test]
+    fn test_query_page_final() {
+        let link = format!(
+            r#"<{0}/repos/foo/bar/releases?per_page=1&page=4>; rel="prev", <{0}/repos/foo/bar/releases?per_page=1&page=1>; rel="first""#,
+            mockito::server_url()
+        );
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "5".into()))
+            .with_header("link", &link)
+            .with_body(json!([release_json("v5.0.0", json!({}))]).to_string())
+            .create();
+        let github = mock_github();
+        let page = github.query_page("foo/bar", 5, 1).unwrap();
+        assert_eq!(page.releases.len(), 1);
+        assert_eq!(page.next_page, None);
+        assert_eq!(page.last_page, None);
+        assert_eq!(page.etag, None);
+    }
+
+    fn release_with_id(id: usize) -> String {
+        release_json(&format!("v{id}.0.0"), json!({ "id": id })).to_string()
+    }
+
+    fn mock_page(page: usize, ids: &[usize]) -> mockito::Mock {
+        let link = format!(
+            r#"<{}/repos/foo/bar/releases?per_page=100&page=2>; rel="last""#,
+            mockito::server_url()
+        );
+        let body: Vec<_> = ids.iter().map(|id| release_with_id(*id)).collect();
+        mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), page.to_string()))
+            .with_header("link", &link)
+            .with_body(format!("[{}]", body.join(",")))
+            .expect(1)
+            .create()
+    }
+
+    #[test]
+    fn test_query_capped_page_size() {
+        let pages = [
+            (1..=30, Some(2), "100"),
+            (31..=60, Some(3), "30"),
+            (61..=70, None, "30"),
+        ];
+        let mocks: Vec<_> = pages
+            .into_iter()
+            .enumerate()
+            .map(|(i, (ids, next, per_page))| {
+                let body: Vec<_> = ids.map(release_with_id).collect();
+                let mut mock = mock("GET", "/repos/foo/capped/releases")
+                    .match_query(Matcher::AllOf(vec![
+                        Matcher::UrlEncoded("page".into(), (i + 1).to_string()),
+                        Matcher::UrlEncoded("per_page".into(), per_page.into()),
+                    ]))
+                    .with_body(format!("[{}]", body.join(",")))
+                    .expect(1);
+                if let Some(next) = next {
+                    mock = mock.with_header(
+                        "link",
+                        &format!(
+                            r#"<{}/repos/foo/capped/releases?per_page=30&page={next}>; rel="next""#,
+                            mockito::server_url()
+                        ),
+                    );
+                }
+                mock.create()
+            })
+            .collect();
+        let github = mock_github();
+        let releases = github.query("foo/capped").unwrap();
+        assert_eq!(releases.len(), 70);
+        assert!(releases.iter().map(|r| r.id).eq(1..=70));
+        for mock in &mocks {
+            mock.assert();
+        }
+    }
+
+    #[test]
+    fn test_query_drops_shifted_duplicates() {
+        let _m = [mock_page(1, &[3, 2]), mock_page(2, &[2, 1])];
+        let github = mock_github();
+        let versions = github.get_all_versions("foo/bar").unwrap();
+        assert_eq!(versions, vec!["v3.0.0", "v2.0.0", "v1.0.0"]);
+    }
+
+    #[test]
+    fn test_query_restarts_once_on_shift() {
+        // a fourth release is published between the first two requests, and
+        // a fifth during the second walk
+        let first = [mock_page(1, &[3, 2]), mock_page(2, &[2, 1])];
+        let second = [mock_page(1, &[4, 3]), mock_page(2, &[3, 2, 1])];
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .restart_on_page_shift(true)
+            .build()
+            .unwrap();
+        let versions = github.get_all_versions("foo/bar").unwrap();
+        assert_eq!(versions, vec!["v4.0.0", "v3.0.0", "v2.0.0", "v1.0.0"]);
+        for m in first.iter().chain(&second) {
+            m.assert();
+        }
+    }
+
+    #[test]
+    fn test_query_raw_keeps_unknown_fields() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!([release_json(
+                    "v1.0.0",
+                    json!({ "attestation": { "bundle": "abc" } })
+                )])
+                .to_string(),
+            )
+            .create();
+        let github = mock_github();
+        let raw = github.query_raw("foo/bar").unwrap();
+        assert_eq!(raw.len(), 1);
+        assert_eq!(raw[0]["attestation"]["bundle"], "abc");
+
+        let typed = GitHubReleaseItem::from_value(&raw[0]).unwrap();
+        assert_eq!(typed.tag_name, "v1.0.0");
+        assert_eq!(github.query("foo/bar").unwrap()[0].tag_name, typed.tag_name);
+    }
+
+    #[test]
+    fn test_query_raw_all_pages() {
+        let _m = mock_pages("/repos/foo/bar/releases", 3);
+        let github = mock_github();
+        let tags: Vec<_> = github
+            .query_raw("foo/bar")
+            .unwrap()
+            .iter()
+            .map(|r| r["tag_name"].as_str().unwrap().to_owned())
+            .collect();
+        assert_eq!(tags, vec!["v1.0.0", "v2.0.0", "v3.0.0"]);
+    }
+
+    /// Three pages of items linked by "next" only, where the first item of the
+    /// second page is the last item of the first page shifted onto it.
+    fn mock_shared_pages(path: &str, item: fn(usize) -> String) -> Vec<mockito::Mock> {
+        let pages: [&[usize]; 3] = [&[1, 2], &[2, 3], &[4]];
+        pages
+            .iter()
+            .enumerate()
+            .map(|(index, items)| {
+                let page = index + 1;
+                let body = items.iter().map(|&n| item(n)).collect::<Vec<_>>().join(",");
+                let mut m = mock("GET", path)
+                    .match_query(Matcher::UrlEncoded("page".into(), page.to_string()))
+                    .with_body(format!("[{body}]"))
+                    .expect(1);
+                if page < pages.len() {
+                    let next = format!(
+                        r#"<{}{path}?per_page=100&page={}>; rel="next""#,
+                        mockito::server_url(),
+                        page + 1
+                    );
+                    m = m.with_header("link", &next);
+                }
+                m.create()
+            })
+            .collect()
+    }
+
+    fn shared_release(n: usize) -> String {
+        release_json(&format!("v{n}.0.0"), json!({ "id": n })).to_string()
+    }
+
+    fn shared_repository(n: usize) -> String {
+        format!(r#"{{ "id": {n}, "full_name": "paged/repo-{n}" }}"#)
+    }
+
+    #[test]
+    fn test_pagination_shared_across_endpoints() {
+        let github = mock_github();
+
+        let mocks = mock_shared_pages("/repos/foo/shared-pages/releases", shared_release);
+        let tags: Vec<_> = github
+            .query("foo/shared-pages")
+            .unwrap()
+            .into_iter()
+            .map(|r| r.tag_name)
+            .collect();
+        assert_eq!(tags, vec!["v1.0.0", "v2.0.0", "v3.0.0", "v4.0.0"]);
+        mocks.iter().for_each(mockito::Mock::assert);
+
+        let mocks = mock_shared_pages("/orgs/shared-pages/repos", shared_repository);
+        let names: Vec<_> = github
+            .org_repositories("shared-pages")
+            .unwrap()
+            .into_iter()
+            .map(|r| r.full_name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "paged/repo-1",
+                "paged/repo-2",
+                "paged/repo-3",
+                "paged/repo-4"
+            ]
+        );
+        mocks.iter().for_each(mockito::Mock::assert);
+    }
+
+    #[test]
+    fn test_pagination_budget_shared_across_endpoints() {
+        let _releases = mock_shared_pages("/repos/foo/shared-budget/releases", shared_release);
+        let _repos = mock_shared_pages("/orgs/shared-budget/repos", shared_repository);
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .max_requests_per_call(2)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            github.query("foo/shared-budget"),
+            Err(LookupError::BudgetExceeded {
+                fetched_pages: 2,
+                ..
+            })
+        ));
+        assert!(matches!(
+            github.org_repositories("shared-budget"),
+            Err(LookupError::BudgetExceeded {
+                fetched_pages: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_budget_truncate() {
+        let _m = mock_pages("/repos/foo/bar/releases", 5);
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .max_requests_per_call(2)
+            .on_budget(BudgetBehavior::Truncate)
+            .build()
+            .unwrap();
+        let partial = github.query_partial("foo/bar").unwrap();
+        assert!(partial.truncated);
+        assert_eq!(partial.releases.len(), 2);
+    }
+
+    #[test]
+    fn test_budget_error() {
+        let _m = mock_pages("/repos/foo/bar/releases", 5);
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .max_requests_per_call(2)
+            .build()
+            .unwrap();
+        let res = github.query("foo/bar");
+        assert!(matches!(
+            res,
+            Err(LookupError::BudgetExceeded {
+                fetched_pages: 2,
+                total_pages: Some(5)
+            })
+        ));
+    }
+
+    #[test]
+    fn test_budget_counts_retries() {
+        let limited = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &epoch_secs(0))
+            .expect(1)
+            .create();
+        let _m = mock_pages("/repos/foo/bar/releases", 5);
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .wait_on_rate_limit(Duration::from_secs(5))
+            .max_requests_per_call(2)
+            .build()
+            .unwrap();
+        let res = github.query("foo/bar");
+        assert!(matches!(
+            res,
+            Err(LookupError::BudgetExceeded {
+                fetched_pages: 1,
+                ..
+            })
+        ));
+        limited.assert();
+    }
+
+    #[test]
+    fn test_default_web_root() {
+        assert_eq!(GitHub::new().unwrap().web_root(), "https://github.com/");
+        let github = GitHub::from_custom("https://github.example.com/api/v3/", "").unwrap();
+        assert_eq!(github.web_root(), "https://github.example.com/");
+    }
+
+    /// Every ordering of the items, for checking that a result doesn't depend
+    /// on the order the API lists things in.
+    pub(crate) fn permutations<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+        if items.len() <= 1 {
+            return vec![items.to_vec()];
+        }
+        let mut all = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let mut rest = items.to_vec();
+            let _removed = rest.remove(index);
+            for mut permutation in permutations(&rest) {
+                permutation.insert(0, item.clone());
+                all.push(permutation);
+            }
+        }
+        all
+    }
+
+    #[test]
+    fn test_date_field_compare_ignores_order() {
+        let release = |id: usize, tag: &str, created: &str, published: &str| {
+            GitHubReleaseItem::from_value(&release_json(
+                tag,
+                json!({
+                    "id": id, "name": null, "body": null,
+                    "created_at": created, "published_at": published,
+                }),
+            ))
+            .unwrap()
+        };
+        let same = "2024-01-01T00:00:00Z";
+        let releases = [
+            release(1, "by-date", "2023-12-01T00:00:00Z", "2023-12-31T00:00:00Z"),
+            release(9, "by-created", "2023-12-02T00:00:00Z", same),
+            release(2, "by-id", "2023-12-03T00:00:00Z", same),
+            release(3, "by-tag-b", "2023-12-03T00:00:00Z", same),
+            release(3, "by-tag-a", "2023-12-03T00:00:00Z", same),
+        ];
+        for field in [DateField::Published, DateField::Created] {
+            let mut orders = permutations(&releases).into_iter().map(|mut shuffled| {
+                shuffled.sort_by(|a, b| field.compare(a, b));
+                shuffled
+                    .into_iter()
+                    .map(|release| release.tag_name)
+                    .collect::<Vec<_>>()
+            });
+            let first = orders.next().unwrap();
+            assert!(orders.all(|order| order == first), "{field:?}");
+        }
+        let mut sorted = releases.to_vec();
+        sorted.sort_by(|a, b| DateField::Published.compare(a, b));
+        let tags: Vec<_> = sorted.iter().map(|r| r.tag_name.as_str()).collect();
+        assert_eq!(
+            tags,
+            ["by-date", "by-created", "by-id", "by-tag-a", "by-tag-b"]
+        );
+    }
+
+    #[test]
+    fn test_date_field_falls_back() {
+        let release = GitHubReleaseItem::from_value(&release_json(
+            "draft",
+            json!({
+                "name": null, "draft": true, "body": null,
+                "created_at": "2024-01-01T00:00:00Z", "published_at": null,
+            }),
+        ))
+        .unwrap();
+        assert_eq!(
+            DateField::Published.date_of(&release),
+            "2024-01-01T00:00:00Z"
+        );
+        assert_eq!(DateField::Created.date_of(&release), "2024-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_latest_version_mixed_prefixes() {
+        let _m = mock("GET", "/repos/foo/prefixed/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}]",
+                [
+                    "V1.2.3",
+                    "rel/1.3.0",
+                    "release/1.1.0",
+                    "version-1.4.0",
+                    "myapp-2.0.0"
+                ]
+                .map(|tag| release_json(tag, json!({})).to_string())
+                .join(", ")
+            ))
+            .create();
+        let github = |prefixes: &[&str]| {
+            GitHub::builder()
+                .api_root(&format!("{}/", mockito::server_url()))
+                .version_prefixes(prefixes)
+                .ignore_tags(&["v1.3.0"])
+                .build()
+                .unwrap()
+        };
+        assert_eq!(
+            github(&[]).get_latest_version("foo/prefixed").unwrap(),
+            semver::Version::new(1, 4, 0)
+        );
+        assert_eq!(
+            github(&["MYAPP-"])
+                .get_latest_version("foo/prefixed")
+                .unwrap(),
+            semver::Version::new(2, 0, 0)
+        );
+        let tagged = github(&[]).get_all_versions("foo/prefixed").unwrap();
+        assert_eq!(tagged.len(), 5);
+    }
+
+    #[test]
+    fn test_request_id_in_error() {
+        let _m = mock("GET", "/repos/foo/request-id/releases")
+            .match_query(Matcher::Any)
+            .match_header("x-request-id", "deploy-4821")
+            .with_status(500)
+            .with_header("x-github-request-id", "C0DE:4C8E:1B2A3:2F4D5:65A7B8C9")
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .correlation_header("X-Request-Id", "deploy-4821")
+            .build()
+            .unwrap();
+        let error = github.query("foo/request-id").unwrap_err();
+        assert!(matches!(
+            &error,
+            LookupError::ErrorHttpResponse {
+                status: 500,
+                message: None,
+                request_id: Some(id),
+            } if id == "C0DE:4C8E:1B2A3:2F4D5:65A7B8C9"
+        ));
+        assert_eq!(
+            error.to_string(),
+            "received error HTTP response code 500 (GitHub request ID C0DE:4C8E:1B2A3:2F4D5:65A7B8C9)"
+        );
+    }
+
+    #[test]
+    fn test_localized_error_message() {
+        let _m = mock("GET", "/repos/foo/localized/releases")
+            .match_query(Matcher::Any)
+            .match_header("accept-language", "ja-JP")
+            .with_status(500)
+            .with_header("content-type", "application/json; charset=utf-8")
+            .with_body(r#"{"message": "サーバーエラー：しばらくしてから再試行してください ✓"}"#)
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .accept_language("ja-JP")
+            .build()
+            .unwrap();
+        let error = github.query("foo/localized").unwrap_err();
+        assert!(matches!(
+            &error,
+            LookupError::ErrorHttpResponse {
+                status: 500,
+                message: Some(message),
+                request_id: None,
+            } if message == "サーバーエラー：しばらくしてから再試行してください ✓"
+        ));
+        assert_eq!(
+            error.to_string(),
+            "received error HTTP response code 500: サーバーエラー：しばらくしてから再試行してください ✓"
+        );
+    }
+
+    #[test]
+    fn test_error_body_text() {
+        let text = "Überprüfung fehlgeschlagen";
+        assert_eq!(error_body_text(text.as_bytes().to_vec()), text);
+        // cut in the middle of the "ü"
+        assert_eq!(error_body_text(text.as_bytes()[..8].to_vec()), "Überpr");
+        assert_eq!(
+            error_body_text(b"bad \xff byte".to_vec()),
+            "bad \u{FFFD} byte"
+        );
+        assert_eq!(error_message(r#"{"message": "  "}"#), None);
+        assert_eq!(error_message("<html>Bad Gateway</html>"), None);
+    }
+
+    #[test]
+    fn test_resolve_host() {
+        let _m = mock("GET", "/repos/foo/resolved/releases")
+            .match_query(Matcher::Any)
+            .match_header("host", Matcher::Regex("^github.internal:".into()))
+            .with_body("[]")
+            .create();
+        let port = mockito::server_address().port();
+        let unreachable = "192.0.2.1:80".parse().unwrap();
+        let github = GitHub::builder()
+            .api_root(&format!("http://github.internal:{port}/"))
+            .resolve_host("github.internal", mockito::server_address())
+            .resolve_host("other.internal", unreachable)
+            .build()
+            .unwrap();
+        assert!(github.query("foo/resolved").unwrap().is_empty());
+        assert_eq!(
+            github.config_snapshot().resolved_hosts,
+            vec![
+                ("github.internal".to_owned(), mockito::server_address()),
+                ("other.internal".to_owned(), unreachable),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_accept_language() {
+        assert!(matches!(
+            GitHub::builder().accept_language("de\nDE").build(),
+            Err(LookupError::HeaderValue(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_correlation_header() {
+        assert!(matches!(
+            GitHub::builder()
+                .correlation_header("bad header", "x")
+                .build(),
+            Err(LookupError::HeaderName(_))
+        ));
+    }
+
+    #[test]
+    fn test_response_timeout() {
+        // accepts the connection, but never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let _server = std::thread::spawn(move || {
+            let (_stream, _) = listener.accept().unwrap();
+            std::thread::sleep(Duration::from_secs(5));
+        });
+        let github = GitHub::builder()
+            .api_root(&format!("http://{address}/"))
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let started = Instant::now();
+        assert!(matches!(
+            github.query("foo/slow"),
+            Err(LookupError::Timeout {
+                phase: TimeoutPhase::Response
+            })
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_connect_timeout() {
+        // not routable, so connecting hangs until the timeout
+        let github = GitHub::builder()
+            .api_root("http://10.255.255.1/")
+            .connect_timeout(Duration::from_millis(200))
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap();
+        let started = Instant::now();
+        match github.query("foo/unreachable") {
+            Err(LookupError::Timeout { phase }) => assert_eq!(phase, TimeoutPhase::Connect),
+            // some networks, like sandboxes, drop the connection right away instead
+            Err(LookupError::HttpClient(_)) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_asset_without_uploader() {
+        let asset = |uploader: Value| {
+            json!({
+                "url": "", "browser_download_url": "", "id": 1, "name": "tool.tar.gz",
+                "label": null, "content_type": "", "size": 1, "download_count": 0,
+                "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z",
+                "state": "uploaded", "uploader": uploader
+            })
+        };
+        let release = GitHubReleaseItem::from_value(&release_json(
+            "v1.0.0",
+            json!({
+                "name": null, "published_at": "2024-01-01T00:00:00Z", "body": null,
+                "assets": [
+                    asset(Value::Null),
+                    asset(json!({ "login": "octocat", "id": 1, "type": "User" })),
+                ],
+            }),
+        ))
+        .unwrap();
+        assert_eq!(release.assets[0].uploader, None);
+        let uploader = release.assets[1].uploader.as_ref().unwrap();
+        assert_eq!(
+            (uploader.login.as_str(), uploader.kind.as_str()),
+            ("octocat", "User")
+        );
+        assert!(release
+            .assets_updated_after(&release.published_at)
+            .is_empty());
     }
 }
@@ -0,0 +1,238 @@
+//! Release versions from the tags of a local git repository.
+
+use crate::{versions::latest_version, LookupError, Result};
+use log::debug;
+use semver::Version;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// How [`LocalRepo`] reads the repository's tags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TagReader {
+    /// Read `refs/tags` and `packed-refs` from the git directory directly.
+    #[default]
+    Files,
+    /// Run `git tag --list` in the repository, which requires `git` on the `PATH`.
+    GitCommand,
+}
+
+/// A git repository on disk, as a source of release versions.
+///
+/// Versions are selected from tags with the same rules as
+/// [`GitHub::get_latest_version`](crate::GitHub::get_latest_version), without any network access.
+///
+/// Only available with the `local-git` feature.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::LocalRepo;
+///
+/// let repo = LocalRepo::open("path/to/clone").unwrap();
+/// let version = repo.get_latest_version().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocalRepo {
+    work_dir: PathBuf,
+    git_dir: PathBuf,
+    reader: TagReader,
+}
+
+impl LocalRepo {
+    /// Open the git repository at the path, which can be a working
+    /// copy or a bare repository.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::RepositoryNotFound`]
+    /// if there's no git repository at the path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let git_dir = if path.join(".git").is_dir() {
+            path.join(".git")
+        } else if path.join("refs").is_dir() && path.join("HEAD").is_file() {
+            path.to_path_buf()
+        } else {
+            return Err(LookupError::RepositoryNotFound);
+        };
+        Ok(Self {
+            work_dir: path.to_path_buf(),
+            git_dir,
+            reader: TagReader::default(),
+        })
+    }
+
+    /// Set how the tags are read. Defaults to [`TagReader::Files`].
+    #[must_use]
+    pub fn with_reader(mut self, reader: TagReader) -> Self {
+        self.reader = reader;
+        self
+    }
+
+    /// Get the names of all tags in the repository, sorted.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the tags can't be read.
+    pub fn get_all_versions(&self) -> Result<Vec<String>> {
+        let mut tags = match self.reader {
+            TagReader::Files => self.read_tag_files()?,
+            TagReader::GitCommand => self.run_git_tag()?,
+        };
+        tags.sort();
+        tags.dedup();
+        Ok(tags)
+    }
+
+    /// Get the latest (Semantic Versioned) version from the repository's tags.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the tags can't be read, or if none of
+    /// them can be parsed as a version.
+    pub fn get_latest_version(&self) -> Result<Version> {
+        let versions = self.get_all_versions()?;
+        latest_version(&versions).ok_or(LookupError::NoReleases)
+    }
+
+    /// Read tag names from loose refs and `packed-refs`.
+    fn read_tag_files(&self) -> Result<Vec<String>> {
+        let tags_dir = self.git_dir.join("refs").join("tags");
+        let mut tags = Vec::new();
+        if tags_dir.is_dir() {
+            collect_loose_refs(&tags_dir, "", &mut tags).map_err(LookupError::LocalGit)?;
+        }
+        match fs::read_to_string(self.git_dir.join("packed-refs")) {
+            Ok(packed) => tags.extend(
+                packed
+                    .lines()
+                    .filter(|l| !l.starts_with('#') && !l.starts_with('^'))
+                    .filter_map(|l| l.split_once(' '))
+                    .filter_map(|(_, name)| name.strip_prefix("refs/tags/"))
+                    .map(str::to_owned),
+            ),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(LookupError::LocalGit(e)),
+        }
+        Ok(tags)
+    }
+
+    /// Read tag names from `git tag --list`.
+    fn run_git_tag(&self) -> Result<Vec<String>> {
+        debug!("Running git tag in {}", self.work_dir.display());
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.work_dir)
+            .args(["tag", "--list"])
+            .output()
+            .map_err(LookupError::LocalGit)?;
+        if !output.status.success() {
+            return Err(LookupError::LocalGit(io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(str::to_owned)
+            .collect())
+    }
+}
+
+/// Recursively collect tag names under `refs/tags`, as tags can contain `/`.
+fn collect_loose_refs(dir: &Path, prefix: &str, tags: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+        if entry.file_type()?.is_dir() {
+            collect_loose_refs(&entry.path(), &format!("{name}/"), tags)?;
+        } else {
+            tags.push(name);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocalRepo, TagReader};
+    use crate::{
+        tests::{mock_github, release_json},
+        LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::{json, Value};
+    use std::{path::PathBuf, process::Command};
+
+    const TAGS: [&str; 5] = ["v1.0.0", "v1.9.10", "0.3.0", "v2.0.0-rc.1", "nightly"];
+
+    fn git(dir: &PathBuf, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn temp_repo(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "github_release_check_{}_{name}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        git(&dir, &["init", "-q"]);
+        git(&dir, &["commit", "-q", "--allow-empty", "-m", "initial"]);
+        for tag in TAGS {
+            git(&dir, &["tag", tag]);
+        }
+        dir
+    }
+
+    #[test]
+    fn test_not_a_repository() {
+        let res = LocalRepo::open(std::env::temp_dir().join("definitely-not-a-repo"));
+        assert!(matches!(res, Err(LookupError::RepositoryNotFound)));
+    }
+
+    #[test]
+    fn test_readers_agree_with_packed_refs() {
+        let dir = temp_repo("readers");
+        let loose = LocalRepo::open(&dir).unwrap().get_all_versions().unwrap();
+        git(&dir, &["pack-refs", "--all"]);
+        let packed = LocalRepo::open(&dir).unwrap().get_all_versions().unwrap();
+        let command = LocalRepo::open(&dir)
+            .unwrap()
+            .with_reader(TagReader::GitCommand)
+            .get_all_versions()
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(loose.len(), TAGS.len());
+        assert_eq!(loose, packed);
+        assert_eq!(loose, command);
+    }
+
+    #[test]
+    fn test_latest_version_matches_github() {
+        let dir = temp_repo("parity");
+        let local = LocalRepo::open(&dir).unwrap().get_latest_version().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let body: Value = TAGS.iter().map(|t| release_json(t, json!({}))).collect();
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(body.to_string())
+            .create();
+        let github = mock_github();
+        let remote = github.get_latest_version("foo/bar").unwrap();
+        assert_eq!(local, remote);
+        assert_eq!(local, semver::Version::parse("2.0.0-rc.1").unwrap());
+    }
+}
@@ -0,0 +1,121 @@
+//! Rendering release notes with GitHub's Markdown renderer.
+
+use crate::{redact_url, Call, GitHub, GitHubReleaseItem, LookupError, RepositorySpec, Result};
+use log::debug;
+use reqwest::header::HeaderMap;
+use serde_json::json;
+
+impl GitHub {
+    /// Render the release's body to HTML, the same way GitHub shows it.
+    ///
+    /// The body is rendered as GitHub Flavored Markdown in the context of the
+    /// repository, so that references like `#123` and `@user` become links.
+    /// The renderer only knows repositories by name, so for a repository given
+    /// by ID, `#123` isn't linked. A release without a body renders to an empty string without sending a request.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let releases = github.query("celeo/github_release_check").unwrap();
+    /// let html = github.render_release_body("celeo/github_release_check", &releases[0]).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the HTTP request cannot be sent, or the API
+    /// returns a status code indicating something other than a success.
+    pub fn render_release_body<R>(
+        &self,
+        repository: R,
+        release: &GitHubReleaseItem,
+    ) -> Result<String>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec = repository.try_into()?;
+        let body = release.body.as_deref().unwrap_or_default();
+        if body.is_empty() {
+            return Ok(String::new());
+        }
+        let url = format!("{}markdown", self.api_root);
+        debug!(
+            "Rendering the body of {} at {}",
+            release.tag_name,
+            redact_url(&url)
+        );
+        let mut payload = json!({
+            "text": body,
+            "mode": "gfm",
+        });
+        if let RepositorySpec::Name(..) = spec {
+            payload["context"] = json!(spec.to_string());
+        }
+        let response = self.send(
+            &reqwest::Method::POST,
+            &url,
+            &[],
+            Some(&payload),
+            &HeaderMap::new(),
+            &mut Call::default(),
+        )?;
+        self.read_text(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHubReleaseItem,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    fn release(body: Option<&str>) -> GitHubReleaseItem {
+        serde_json::from_value(release_json("v1.0.0", json!({ "body": body }))).unwrap()
+    }
+
+    #[test]
+    fn test_render_release_body() {
+        let m = mock("POST", "/markdown")
+            .match_body(Matcher::Json(json!({
+                "text": "Fixes #1",
+                "mode": "gfm",
+                "context": "foo/bar",
+            })))
+            .with_header("content-type", "text/html;charset=utf-8")
+            .with_body(r#"<p>Fixes <a href="https://github.com/foo/bar/issues/1">#1</a></p>"#)
+            .create();
+        let github = mock_github();
+        let html = github
+            .render_release_body("foo/bar", &release(Some("Fixes #1")))
+            .unwrap();
+        assert_eq!(
+            html,
+            r#"<p>Fixes <a href="https://github.com/foo/bar/issues/1">#1</a></p>"#
+        );
+        m.assert();
+    }
+
+    #[test]
+    fn test_render_empty_body_sends_nothing() {
+        let m = mock("POST", "/markdown").expect(0).create();
+        let github = mock_github();
+        assert_eq!(
+            github
+                .render_release_body("foo/bar", &release(None))
+                .unwrap(),
+            ""
+        );
+        assert_eq!(
+            github
+                .render_release_body("foo/bar", &release(Some("")))
+                .unwrap(),
+            ""
+        );
+        m.assert();
+    }
+}
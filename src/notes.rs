@@ -0,0 +1,247 @@
+//! Cleaning up release notes for showing as plain text.
+
+use crate::GitHubReleaseItem;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// HTML comments, and `<script>` and `<style>` elements along with their content.
+static HIDDEN_HTML: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<!--.*?(-->|\z)|<script\b.*?(</script\s*>|\z)|<style\b.*?(</style\s*>|\z)")
+        .expect("Could not compile regex")
+});
+
+/// Any other HTML tag, keeping what's between an opening and closing tag.
+static HTML_TAG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"</?[a-zA-Z][^>]*>").expect("Could not compile regex"));
+
+/// Images and links, keeping the alt text or link text.
+static LINK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").expect("Could not compile regex"));
+
+/// Heading, quote, and list markers at the start of a line.
+static LINE_MARKER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(#{1,6}\s+|>\s?|[-*+]\s+)").expect("Could not compile regex"));
+
+/// Emphasis and code markers around text.
+static EMPHASIS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\*\*|__|~~|`+|\*([^*\s][^*]*)\*|\b_([^_\s][^_]*)_\b")
+        .expect("Could not compile regex")
+});
+
+/// Whether the character is drawn as part of the character before it, like
+/// an accent, a variation selector, a skin tone, or anything after a zero
+/// width joiner.
+fn extends_previous(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{200D}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+/// Cut the text to at most `max_len` characters, ending with an ellipsis if
+/// anything was cut. A character is never split from the accents or joiners
+/// drawn along with it.
+fn truncate(text: &str, max_len: usize) -> String {
+    if text.chars().count() <= max_len {
+        return text.to_owned();
+    }
+    if max_len == 0 {
+        return String::new();
+    }
+    let keep = max_len - 1;
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut cut = keep;
+    while cut > 0 && (extends_previous(chars[cut].1) || chars[cut - 1].1 == '\u{200D}') {
+        cut -= 1;
+    }
+    let mut truncated = text[..chars[cut].0].trim_end().to_owned();
+    truncated.push('…');
+    truncated
+}
+
+/// Whether the line is a heading of the second level or above, like `## Changes`.
+fn is_section_heading(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("# ") || line.starts_with("## ") || matches!(line.trim_end(), "#" | "##")
+}
+
+impl GitHubReleaseItem {
+    /// Get the release's body as plain text, with Markdown and HTML removed.
+    ///
+    /// HTML comments, scripts, and styles are dropped along with their content,
+    /// other tags are dropped keeping their content, and links and images are
+    /// replaced by their text. Line endings become `\n`, and runs of blank lines
+    /// become one. With `max_len`, the text is cut to at most that many
+    /// characters, ending with `'…'`. A release without a body gives an empty string.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let release = &github.query("celeo/github_release_check").unwrap()[0];
+    /// println!("{}", release.body_plaintext(Some(280)));
+    /// ```
+    #[must_use]
+    pub fn body_plaintext(&self, max_len: Option<usize>) -> String {
+        let body = self
+            .body
+            .as_deref()
+            .unwrap_or_default()
+            .replace("\r\n", "\n");
+        let body = HIDDEN_HTML.replace_all(&body, "");
+        let body = HTML_TAG.replace_all(&body, "");
+        let mut lines: Vec<String> = Vec::new();
+        for line in body.lines() {
+            let line = LINK.replace_all(line, "$1");
+            let line = LINE_MARKER.replace(&line, "");
+            let line = EMPHASIS.replace_all(&line, "$1$2");
+            let line = line.trim_end();
+            let blank = line.trim().is_empty();
+            if blank && lines.last().map_or(true, String::is_empty) {
+                continue;
+            }
+            lines.push(if blank {
+                String::new()
+            } else {
+                line.to_owned()
+            });
+        }
+        let text = lines.join("\n").trim().to_owned();
+        match max_len {
+            Some(max_len) => truncate(&text, max_len),
+            None => text,
+        }
+    }
+
+    /// Get the first section of the release's body, up to the first `##` heading.
+    ///
+    /// A heading on the very first line is taken as the title of the first
+    /// section and left out, so notes starting with `## What's Changed` give
+    /// what's under it. Returns `None` if the release has no body or the first
+    /// section is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let release = &github.query("celeo/github_release_check").unwrap()[0];
+    /// if let Some(summary) = release.body_first_section() {
+    ///     println!("{summary}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn body_first_section(&self) -> Option<&str> {
+        let body = self.body.as_deref()?.trim_start();
+        let mut start = 0;
+        let mut end = body.len();
+        let mut offset = 0;
+        for (index, line) in body.split_inclusive('\n').enumerate() {
+            if is_section_heading(line) {
+                if index == 0 {
+                    start = line.len();
+                } else {
+                    end = offset;
+                    break;
+                }
+            }
+            offset += line.len();
+        }
+        let section = body[start..end].trim();
+        (!section.is_empty()).then_some(section)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::release_json, GitHubReleaseItem};
+    use serde_json::json;
+
+    fn release(body: Option<&str>) -> GitHubReleaseItem {
+        serde_json::from_value(release_json("v1.0.0", json!({ "body": body }))).unwrap()
+    }
+
+    #[test]
+    fn test_body_plaintext() {
+        let cases: &[(Option<&str>, Option<usize>, &str)] = &[
+            (None, None, ""),
+            (Some(""), Some(10), ""),
+            (Some("Just text"), None, "Just text"),
+            (
+                Some("## What's Changed\r\n\r\n* **Fast** `query` by @a in [#1](https://x/1)\r\n"),
+                None,
+                "What's Changed\n\nFast query by @a in #1",
+            ),
+            (
+                Some("<!-- hidden -->Hello <b>there</b><script>alert(1)</script>\n\n\n\nBye"),
+                None,
+                "Hello there\n\nBye",
+            ),
+            (
+                Some("![logo](https://x/logo.png) > _quoted_"),
+                None,
+                "logo > quoted",
+            ),
+            (Some("> quoted *line*\n- item"), None, "quoted line\nitem"),
+            (Some("<!-- never closed"), None, ""),
+            (Some("snake_case_name stays"), None, "snake_case_name stays"),
+            (Some("A long line of notes"), Some(8), "A long…"),
+            (
+                Some("A long line of notes"),
+                Some(20),
+                "A long line of notes",
+            ),
+            (Some("cafe\u{301}s"), Some(6), "cafe\u{301}s"),
+            (Some("cafe\u{301}s and more"), Some(5), "caf…"),
+            (Some("ok 👍🏽👍🏽"), Some(6), "ok 👍🏽…"),
+            (Some("ok 👍🏽👍🏽"), Some(5), "ok…"),
+            (Some("ab"), Some(0), ""),
+        ];
+        for (body, max_len, expected) in cases {
+            assert_eq!(
+                release(*body).body_plaintext(*max_len),
+                *expected,
+                "body {body:?} cut to {max_len:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_body_first_section() {
+        let cases: &[(Option<&str>, Option<&str>)] = &[
+            (None, None),
+            (Some(""), None),
+            (Some("Only a summary"), Some("Only a summary")),
+            (
+                Some("Summary line\r\nmore\r\n\r\n## Changes\r\n- a\r\n"),
+                Some("Summary line\r\nmore"),
+            ),
+            (
+                Some("## What's Changed\n* one\n### Details\nstuff\n## New Contributors\n* @a"),
+                Some("* one\n### Details\nstuff"),
+            ),
+            (Some("## Empty\n## Next\ntext"), None),
+            (Some("# Title\nintro\n## Rest"), Some("intro")),
+            (
+                Some("#hashtag is not a heading\n## Rest"),
+                Some("#hashtag is not a heading"),
+            ),
+        ];
+        for (body, expected) in cases {
+            assert_eq!(
+                release(*body).body_first_section(),
+                *expected,
+                "body {body:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Reading fields that GitHub sometimes sends as `null` instead of leaving out.
+
+use serde::{Deserialize, Deserializer};
+
+/// Read a string that may be `null`, as an empty string.
+pub(crate) fn null_as_empty<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Read a list that may be `null`, as an empty list.
+pub(crate) fn null_as_empty_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::release_json, GitHubReleaseItem};
+    use serde_json::{json, Value};
+
+    fn asset() -> Value {
+        json!({
+            "url": "https://github.example.com/api/v3/repos/foo/bar/releases/assets/7",
+            "browser_download_url": "https://github.example.com/foo/bar/releases/download/v1.0.0/app.tar.gz",
+            "id": 7, "name": "app.tar.gz", "label": "Linux build",
+            "content_type": "application/gzip", "size": 1024, "download_count": 3,
+            "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z",
+            "state": "uploaded", "digest": "sha256:abc",
+            "uploader": {
+                "login": "octocat", "id": 1, "type": "User",
+                "html_url": "https://github.example.com/octocat"
+            },
+        })
+    }
+
+    fn release(assets: &Value) -> Value {
+        release_json(
+            "v1.0.0",
+            json!({
+                "created_at": "2024-01-01T00:00:00Z", "published_at": "2024-01-01T00:00:00Z",
+                "target_commitish": "main", "assets": assets,
+            }),
+        )
+    }
+
+    /// `asset()` with the fields set to `null`, like `"uploader.html_url"`.
+    fn asset_with_nulls(fields: &[&str]) -> Value {
+        let mut asset = asset();
+        for field in fields {
+            match field.split_once('.') {
+                Some((outer, inner)) => asset[outer][inner] = Value::Null,
+                None => asset[field] = Value::Null,
+            }
+        }
+        asset
+    }
+
+    #[test]
+    fn test_null_assets() {
+        let mut missing = release(&Value::Null);
+        let _removed = missing.as_object_mut().unwrap().remove("assets");
+        for (name, value) in [
+            ("null", release(&Value::Null)),
+            ("empty", release(&json!([]))),
+            ("missing", missing),
+        ] {
+            let release = GitHubReleaseItem::from_value(&value).unwrap();
+            assert!(release.assets.is_empty(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_null_asset_fields() {
+        let cases: &[&[&str]] = &[
+            &[],
+            &["label"],
+            &["uploader"],
+            &["digest"],
+            &["state"],
+            &["label", "uploader"],
+            &["label", "uploader", "digest", "state"],
+            &["uploader.html_url"],
+            &["uploader.type"],
+            &["label", "uploader.html_url", "uploader.type"],
+        ];
+        for nulls in cases {
+            let value = release(&json!([asset_with_nulls(nulls), asset()]));
+            let release = GitHubReleaseItem::from_value(&value)
+                .unwrap_or_else(|e| panic!("nulls {nulls:?}: {e}"));
+            let asset = &release.assets[0];
+            assert_eq!(asset.name, "app.tar.gz", "{nulls:?}");
+            assert_eq!(asset.label.is_none(), nulls.contains(&"label"), "{nulls:?}");
+            assert_eq!(
+                asset.uploader.is_none(),
+                nulls.contains(&"uploader"),
+                "{nulls:?}"
+            );
+            if nulls.contains(&"state") {
+                assert_eq!(asset.state, "");
+            }
+            if let Some(uploader) = &asset.uploader {
+                assert_eq!(uploader.login, "octocat");
+            }
+            assert_eq!(release.assets[1].label.as_deref(), Some("Linux build"));
+        }
+    }
+
+    #[test]
+    fn test_ghes_fixtures() {
+        let fixtures = [
+            (
+                "3.4",
+                include_str!("../tests/fixtures/ghes/3.4-null-assets.json"),
+            ),
+            (
+                "3.9",
+                include_str!("../tests/fixtures/ghes/3.9-null-asset-fields.json"),
+            ),
+        ];
+        for (version, fixture) in fixtures {
+            let releases: Vec<GitHubReleaseItem> =
+                serde_json::from_str(fixture).unwrap_or_else(|e| panic!("GHES {version}: {e}"));
+            assert_eq!(releases.len(), 2, "GHES {version}");
+            assert!(releases[1].assets.is_empty(), "GHES {version}");
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Narrowing down which releases a query returns.
+
+use crate::{latest_candidate, GitHub, GitHubReleaseItem, LookupError, RepositorySpec, Result};
+use semver::Version;
+
+/// Filters for [`GitHub::query_with`].
+///
+/// # Example
+///
+/// ```rust
+/// use github_release_check::QueryOptions;
+///
+/// let options = QueryOptions::default().target_commitish("release/1.x");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct QueryOptions {
+    target_commitish: Option<String>,
+    drafts_only: bool,
+}
+
+impl QueryOptions {
+    /// Only return releases created from this branch or commit.
+    ///
+    /// This is compared exactly with each release's `target_commitish`, so releases
+    /// created from a commit SHA only match a filter for that same SHA, never a branch.
+    #[must_use]
+    pub fn target_commitish(mut self, target: &str) -> Self {
+        self.target_commitish = Some(target.to_owned());
+        self
+    }
+
+    /// Only return draft releases.
+    ///
+    /// GitHub only shows drafts to authenticated users with push access, so
+    /// without a token this fails rather than returning nothing.
+    #[must_use]
+    pub fn drafts_only(mut self) -> Self {
+        self.drafts_only = true;
+        self
+    }
+
+    /// Whether the release passes every filter.
+    fn matches(&self, release: &GitHubReleaseItem) -> bool {
+        self.target_commitish
+            .as_ref()
+            .map_or(true, |target| *target == release.target_commitish)
+            && (!self.drafts_only || release.draft)
+    }
+}
+
+impl GitHub {
+    /// Get the releases from the repository that pass the filters.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{GitHub, QueryOptions};
+    /// let github = GitHub::new().unwrap();
+    /// let options = QueryOptions::default().target_commitish("main");
+    /// let releases = github.query_with("celeo/github_release_check", &options).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::AuthenticationRequiredForDrafts`]
+    /// if drafts are asked for without a token, or for any of the reasons in `query`.
+    pub fn query_with<R>(
+        &self,
+        repository: R,
+        options: &QueryOptions,
+    ) -> Result<Vec<GitHubReleaseItem>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        if options.drafts_only && !self.is_authenticated() {
+            return Err(LookupError::AuthenticationRequiredForDrafts);
+        }
+        let mut releases = self.query(repository)?;
+        releases.retain(|release| options.matches(release));
+        Ok(releases)
+    }
+
+    /// Get the latest release version from the repository's releases created from the branch.
+    ///
+    /// Versions are read the same way as in [`GitHub::get_latest_version`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let version = github.get_latest_version_on_branch("celeo/github_release_check", "master");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`, or if no
+    /// release from the branch has a version.
+    pub fn get_latest_version_on_branch<R>(&self, repository: R, branch: &str) -> Result<Version>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let releases = self.query_with(
+            repository,
+            &QueryOptions::default().target_commitish(branch),
+        )?;
+        latest_candidate(self.release_candidates(releases))
+            .map(|(tagged, _)| tagged.version)
+            .ok_or(LookupError::NoReleases)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryOptions;
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHub, LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+
+    const SHA: &str = "9fceb02d0ae598e95dc970b74767f19372d61af8";
+
+    fn mock_releases() -> mockito::Mock {
+        let body = [
+            ("v2.1.0", "main"),
+            ("v1.4.2", "release/1.x"),
+            ("v1.4.1", SHA),
+            ("v1.4.0", "release/1.x"),
+            ("v2.0.0", "main"),
+        ]
+        .iter()
+        .map(|(tag, target)| release_json(tag, json!({ "target_commitish": target })).to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+        mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!("[{body}]"))
+            .create()
+    }
+
+    fn mock_drafts(repository: &str, drafts: &[bool]) -> mockito::Mock {
+        let body = drafts
+            .iter()
+            .enumerate()
+            .map(|(i, draft)| {
+                release_json(&format!("v1.{i}.0"), json!({ "id": i, "draft": draft })).to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        mock("GET", format!("/repos/{repository}/releases").as_str())
+            .match_query(Matcher::Any)
+            .with_body(format!("[{body}]"))
+            .create()
+    }
+
+    #[test]
+    fn test_drafts_without_token() {
+        let m = mock_drafts("foo/drafts-anon", &[false]).expect(0);
+        assert!(matches!(
+            mock_github().query_with("foo/drafts-anon", &QueryOptions::default().drafts_only()),
+            Err(LookupError::AuthenticationRequiredForDrafts)
+        ));
+        m.assert();
+    }
+
+    #[test]
+    fn test_drafts_with_token() {
+        let _m = mock_drafts("foo/drafts-auth", &[false, true, false]);
+        let _none = mock_drafts("foo/drafts-none", &[false, false]);
+        let github = GitHub::from_custom(&format!("{}/", mockito::server_url()), "abc").unwrap();
+        let drafts = github
+            .query_with("foo/drafts-auth", &QueryOptions::default().drafts_only())
+            .unwrap();
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].tag_name, "v1.1.0");
+        assert!(github
+            .query_with("foo/drafts-none", &QueryOptions::default().drafts_only())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_query_by_branch() {
+        let _m = mock_releases();
+        let tags = |target: &str| -> Vec<String> {
+            mock_github()
+                .query_with("foo/bar", &QueryOptions::default().target_commitish(target))
+                .unwrap()
+                .into_iter()
+                .map(|r| r.tag_name)
+                .collect()
+        };
+        assert_eq!(tags("release/1.x"), vec!["v1.4.2", "v1.4.0"]);
+        assert_eq!(tags(SHA), vec!["v1.4.1"]);
+        assert!(tags(&SHA[..7]).is_empty());
+        assert_eq!(
+            mock_github()
+                .query_with("foo/bar", &QueryOptions::default())
+                .unwrap()
+                .len(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_latest_version_on_branch() {
+        let _m = mock_releases();
+        let github = mock_github();
+        assert_eq!(
+            github
+                .get_latest_version_on_branch("foo/bar", "release/1.x")
+                .unwrap(),
+            Version::new(1, 4, 2)
+        );
+        assert_eq!(
+            github
+                .get_latest_version_on_branch("foo/bar", "main")
+                .unwrap(),
+            Version::new(2, 1, 0)
+        );
+        assert!(matches!(
+            github.get_latest_version_on_branch("foo/bar", "develop"),
+            Err(LookupError::NoReleases)
+        ));
+    }
+}
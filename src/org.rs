@@ -0,0 +1,507 @@
+//! Looking up every repository of an organization.
+
+use crate::{
+    batch::{run_batch, DEFAULT_CONCURRENCY},
+    encode_segment, BudgetBehavior, Call, GitHub, LookupError, PageKey, Result,
+};
+use log::debug;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// The lookups from an organization scan that may have been cut short.
+///
+/// See [`GitHub::get_org_latest_versions`].
+#[derive(Debug)]
+pub struct PartialScan {
+    /// The repositories that were looked up, with their own results, in the
+    /// order they were listed.
+    pub completed: Vec<(String, Result<Version>)>,
+    /// The repositories that are left to look up.
+    pub remaining: Vec<String>,
+    /// Where to continue, or `None` if the scan is complete.
+    pub resume: Option<ScanCursor>,
+}
+
+/// Where an organization scan left off.
+///
+/// Cursors can be serialized so that the scan continues in a later run. See
+/// [`GitHub::resume_org_scan`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanCursor {
+    /// The organization being scanned.
+    pub org: String,
+    /// The repositories that are left to look up.
+    pub remaining: Vec<String>,
+}
+
+/// The requests a scan may still send, shared between the lookups of the scan.
+#[derive(Debug)]
+pub(crate) struct ScanBudget {
+    sent: AtomicUsize,
+    max_requests: Option<usize>,
+}
+
+impl ScanBudget {
+    /// Count a request about to be sent, or fail if the budget is used up.
+    pub(crate) fn take(&self) -> Result<()> {
+        let _sent = self
+            .sent
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |sent| {
+                self.max_requests
+                    .map_or(true, |max| sent < max)
+                    .then_some(sent + 1)
+            })
+            .map_err(|_| LookupError::BudgetExceeded {
+                fetched_pages: 0,
+                total_pages: None,
+            })?;
+        Ok(())
+    }
+
+    /// Return a request that was taken but not sent.
+    pub(crate) fn give_back(&self) {
+        let _sent = self.sent.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Whether the budget is bounded and none of it has been used.
+    fn is_whole(&self) -> bool {
+        self.max_requests.is_some() && self.sent.load(Ordering::SeqCst) == 0
+    }
+
+    /// How many requests are left.
+    fn left(&self) -> usize {
+        self.max_requests.map_or(usize::MAX, |max| {
+            max.saturating_sub(self.sent.load(Ordering::SeqCst))
+        })
+    }
+}
+
+/// The fields of a repository from the organization's repository list.
+#[derive(Debug, Deserialize)]
+pub(crate) struct OrgRepository {
+    #[serde(default)]
+    id: usize,
+    pub(crate) full_name: String,
+    #[serde(default)]
+    archived: bool,
+}
+
+impl PageKey for OrgRepository {
+    fn key(&self) -> (usize, &str) {
+        (self.id, &self.full_name)
+    }
+}
+
+impl GitHub {
+    /// List the organization's repositories, walking every page.
+    pub(crate) fn org_repositories(&self, org: &str) -> Result<Vec<OrgRepository>> {
+        let url = format!("{}orgs/{}/repos", self.api_root, encode_segment(org));
+        Ok(self.paginate(&url, &mut Call::default())?.releases)
+    }
+
+    /// Get the latest release version of every repository in the organization.
+    ///
+    /// The organization's repositories are listed first, then looked up the same way as
+    /// [`GitHub::get_latest_versions`]. Archived repositories are left out when
+    /// [`GitHubBuilder::skip_archived`](crate::GitHubBuilder::skip_archived) is set.
+    ///
+    /// At most `max_requests` requests are sent, counting the ones for the listing,
+    /// or any number with `None`. Once the budget runs out, or GitHub's rate limit
+    /// is used up, the lookups that finished are returned along with the
+    /// repositories that are left and a cursor for [`GitHub::resume_org_scan`].
+    /// A repository that needs more than the whole of `max_requests` is returned
+    /// in [`PartialScan::completed`] with [`LookupError::BudgetExceeded`]
+    /// rather than left to resume, so that every resumed scan gets further.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let mut scan = github.get_org_latest_versions("rust-lang", Some(50)).unwrap();
+    /// let mut versions = scan.completed;
+    /// while let Some(cursor) = scan.resume {
+    ///     // ... or save the cursor, and continue in the next run
+    ///     scan = github.resume_org_scan(&cursor, Some(50));
+    ///     versions.extend(scan.completed);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the repositories can't be listed, including if the
+    /// budget runs out while listing them. A failed lookup doesn't stop the scan;
+    /// it's returned with the repository in [`PartialScan::completed`].
+    pub fn get_org_latest_versions(
+        &self,
+        org: &str,
+        max_requests: Option<usize>,
+    ) -> Result<PartialScan> {
+        let github = self.with_scan_budget(max_requests);
+        let names: Vec<String> = github
+            .org_repositories(org)?
+            .into_iter()
+            .filter(|repository| !(self.settings.skip_archived && repository.archived))
+            .map(|repository| repository.full_name)
+            .collect();
+        Ok(github.scan_org(org, names))
+    }
+
+    /// Continue an organization scan from where [`GitHub::get_org_latest_versions`]
+    /// or an earlier call left off.
+    ///
+    /// The repositories aren't listed again, so all of `max_requests` goes to
+    /// looking up the ones in the cursor.
+    #[must_use]
+    pub fn resume_org_scan(&self, cursor: &ScanCursor, max_requests: Option<usize>) -> PartialScan {
+        self.with_scan_budget(max_requests)
+            .scan_org(&cursor.org, cursor.remaining.clone())
+    }
+
+    /// A clone that sends at most `max_requests` requests between all of its calls.
+    fn with_scan_budget(&self, max_requests: Option<usize>) -> GitHub {
+        let mut github = self.clone();
+        github.scan_budget = Some(Arc::new(ScanBudget {
+            sent: AtomicUsize::new(0),
+            max_requests,
+        }));
+        // a lookup cut short by the budget would give the wrong latest version
+        github.settings.on_budget = BudgetBehavior::Error;
+        github
+    }
+
+    /// Look up the repositories a round at a time, until they're done or the
+    /// budget or rate limit stops the scan.
+    fn scan_org(&self, org: &str, names: Vec<String>) -> PartialScan {
+        let budget = self
+            .scan_budget
+            .as_ref()
+            .expect("Scans run on a clone with a budget");
+        let concurrency = self.settings.concurrency.unwrap_or(DEFAULT_CONCURRENCY);
+        let mut remaining = VecDeque::from(names);
+        let mut completed = Vec::new();
+        while !remaining.is_empty() {
+            // every lookup sends at least one request
+            let mut round = budget.left().min(concurrency).min(remaining.len());
+            // a lookup that has the whole budget to itself and is still cut short
+            // would be cut short on every resume, so it's given up on instead
+            let whole = budget.is_whole();
+            if whole {
+                round = round.min(1);
+            }
+            if round == 0 {
+                debug!("Request budget for the scan of {org} is used up");
+                break;
+            }
+            let batch: Vec<String> = remaining.drain(..round).collect();
+            let batch: Vec<&str> = batch.iter().map(String::as_str).collect();
+            let mut stopped = Vec::new();
+            for (repository, result) in run_batch(self, &batch, |repository| {
+                self.get_latest_version(repository)
+            }) {
+                match result {
+                    Err(e @ LookupError::BudgetExceeded { .. }) if whole => {
+                        debug!("{repository} needs more than the whole request budget");
+                        completed.push((repository, Err(e)));
+                    }
+                    Err(LookupError::BudgetExceeded { .. } | LookupError::RateLimited { .. }) => {
+                        stopped.push(repository);
+                    }
+                    result => completed.push((repository, result)),
+                }
+            }
+            if !stopped.is_empty() {
+                debug!(
+                    "Stopping the scan of {org} with {} lookup(s) cut short",
+                    stopped.len()
+                );
+                for repository in stopped.into_iter().rev() {
+                    remaining.push_front(repository);
+                }
+                break;
+            }
+        }
+        let remaining: Vec<String> = remaining.into();
+        PartialScan {
+            completed,
+            resume: (!remaining.is_empty()).then(|| ScanCursor {
+                org: org.to_owned(),
+                remaining: remaining.clone(),
+            }),
+            remaining,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScanCursor;
+    use crate::{
+        tests::{epoch_secs, mock_github, release_json},
+        GitHub, LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn mock_org() -> Vec<mockito::Mock> {
+        let last = format!(
+            r#"<{}/orgs/foo/repos?per_page=100&page=2>; rel="last""#,
+            mockito::server_url()
+        );
+        vec![
+            mock("GET", "/orgs/foo/repos")
+                .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+                .with_header("link", &last)
+                .with_body(
+                    r#"[{"full_name": "foo/a", "archived": false}, {"full_name": "foo/b", "archived": true}]"#,
+                )
+                .create(),
+            mock("GET", "/orgs/foo/repos")
+                .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+                .with_header("link", &last)
+                .with_body(r#"[{"full_name": "foo/c", "archived": false}]"#)
+                .create(),
+            mock("GET", "/repos/foo/a/releases")
+                .match_query(Matcher::Any)
+                .with_body(json!([release_json("v1.2.0", json!({}))]).to_string())
+                .create(),
+            mock("GET", "/repos/foo/b/releases")
+                .match_query(Matcher::Any)
+                .with_body(json!([release_json("v0.1.0", json!({}))]).to_string())
+                .create(),
+            mock("GET", "/repos/foo/c/releases")
+                .match_query(Matcher::Any)
+                .with_body(json!([release_json("latest", json!({}))]).to_string())
+                .create(),
+        ]
+    }
+
+    #[test]
+    fn test_org_scan_across_pages() {
+        let _mocks = mock_org();
+        let github = mock_github();
+        let scan = github.get_org_latest_versions("foo", None).unwrap();
+        assert!(scan.remaining.is_empty());
+        assert!(scan.resume.is_none());
+        let names: Vec<_> = scan
+            .completed
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["foo/a", "foo/b", "foo/c"]);
+        assert_eq!(
+            *scan.completed[0].1.as_ref().unwrap(),
+            Version::new(1, 2, 0)
+        );
+        assert_eq!(
+            *scan.completed[1].1.as_ref().unwrap(),
+            Version::new(0, 1, 0)
+        );
+        assert!(matches!(scan.completed[2].1, Err(LookupError::NoReleases)));
+    }
+
+    #[test]
+    fn test_org_scan_skips_archived() {
+        let _mocks = mock_org();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .skip_archived(true)
+            .build()
+            .unwrap();
+        let names: Vec<_> = github
+            .get_org_latest_versions("foo", None)
+            .unwrap()
+            .completed
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["foo/a", "foo/c"]);
+    }
+
+    /// Mock an organization with a single page of repositories, each with a
+    /// single release, returning the mocks for the repositories' releases.
+    fn mock_releases(org: &str, repositories: &[&str]) -> (mockito::Mock, Vec<mockito::Mock>) {
+        let listed: Vec<_> = repositories
+            .iter()
+            .map(|name| format!(r#"{{"full_name": "{org}/{name}"}}"#))
+            .collect();
+        let listing = mock("GET", format!("/orgs/{org}/repos").as_str())
+            .match_query(Matcher::Any)
+            .with_body(format!("[{}]", listed.join(", ")))
+            .create();
+        let releases = repositories
+            .iter()
+            .map(|name| {
+                mock("GET", format!("/repos/{org}/{name}/releases").as_str())
+                    .match_query(Matcher::Any)
+                    .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+                    .expect(1)
+                    .create()
+            })
+            .collect();
+        (listing, releases)
+    }
+
+    #[test]
+    fn test_org_scan_budget_split_and_resume() {
+        let (_listing, releases) = mock_releases("split", &["a", "b", "c"]);
+        let github = mock_github();
+
+        // one request for the listing, leaving two of the three lookups
+        let scan = github.get_org_latest_versions("split", Some(3)).unwrap();
+        let names: Vec<_> = scan
+            .completed
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["split/a", "split/b"]);
+        assert!(scan.completed.iter().all(|(_, result)| result.is_ok()));
+        assert_eq!(scan.remaining, vec!["split/c"]);
+        let cursor = scan.resume.unwrap();
+        assert_eq!(cursor.org, "split");
+        assert_eq!(cursor.remaining, scan.remaining);
+
+        let saved = serde_json::to_string(&cursor).unwrap();
+        let loaded: ScanCursor = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded, cursor);
+        let scan = github.resume_org_scan(&loaded, Some(1));
+        assert_eq!(scan.completed.len(), 1);
+        assert_eq!(scan.completed[0].0, "split/c");
+        assert_eq!(
+            *scan.completed[0].1.as_ref().unwrap(),
+            Version::new(1, 0, 0)
+        );
+        assert!(scan.remaining.is_empty());
+        assert!(scan.resume.is_none());
+        // every repository was looked up exactly once
+        for release in releases {
+            release.assert();
+        }
+    }
+
+    #[test]
+    fn test_org_scan_budget_across_rate_limit_wait() {
+        let _listing = mock("GET", "/orgs/waited/repos")
+            .match_query(Matcher::Any)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &epoch_secs(2))
+            .with_body(r#"[{"full_name": "waited/a"}, {"full_name": "waited/b"}]"#)
+            .create();
+        let lookups: Vec<_> = ["a", "b"]
+            .iter()
+            .map(|name| {
+                mock("GET", format!("/repos/waited/{name}/releases").as_str())
+                    .match_query(Matcher::Any)
+                    .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+                    .expect(1)
+                    .create()
+            })
+            .collect();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .wait_on_rate_limit(Duration::from_secs(10))
+            .build()
+            .unwrap();
+
+        // waiting for the limit to reset doesn't use up any of the budget, so
+        // the listing and both lookups fit in three requests
+        let scan = github.get_org_latest_versions("waited", Some(3)).unwrap();
+        assert_eq!(scan.completed.len(), 2, "{:?}", scan.remaining);
+        assert!(scan.completed.iter().all(|(_, result)| result.is_ok()));
+        assert!(scan.resume.is_none());
+        for lookup in lookups {
+            lookup.assert();
+        }
+    }
+
+    #[test]
+    fn test_org_scan_lookup_cut_short() {
+        let _listing = mock("GET", "/orgs/cut/repos")
+            .match_query(Matcher::Any)
+            .with_body(r#"[{"full_name": "cut/a"}, {"full_name": "cut/b"}]"#)
+            .create();
+        let last = format!(
+            r#"<{}/repos/cut/a/releases?per_page=100&page=2>; rel="last""#,
+            mockito::server_url()
+        );
+        let _a = mock("GET", "/repos/cut/a/releases")
+            .match_query(Matcher::Any)
+            .with_header("link", &last)
+            .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .concurrency(1)
+            .build()
+            .unwrap();
+
+        // the second page of cut/a is over the budget, so it's left to resume
+        let scan = github.get_org_latest_versions("cut", Some(2)).unwrap();
+        assert!(scan.completed.is_empty());
+        assert_eq!(scan.remaining, vec!["cut/a", "cut/b"]);
+        assert!(scan.resume.is_some());
+
+        assert!(matches!(
+            github.get_org_latest_versions("cut", Some(0)),
+            Err(LookupError::BudgetExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_org_scan_repository_over_whole_budget() {
+        let _listing = mock("GET", "/orgs/big/repos")
+            .match_query(Matcher::Any)
+            .with_body(
+                r#"[{"full_name": "big/a"}, {"full_name": "big/b"}, {"full_name": "big/c"}]"#,
+            )
+            .create();
+        let last = format!(
+            r#"<{}/repos/big/a/releases?per_page=100&page=2>; rel="last""#,
+            mockito::server_url()
+        );
+        let _a = mock("GET", "/repos/big/a/releases")
+            .match_query(Matcher::Any)
+            .with_header("link", &last)
+            .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+            .create();
+        let _b = mock("GET", "/repos/big/b/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v2.0.0", json!({}))]).to_string())
+            .create();
+        let _c = mock("GET", "/repos/big/c/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v3.0.0", json!({}))]).to_string())
+            .create();
+        let github = mock_github();
+
+        // the listing uses the whole budget
+        let mut scan = github.get_org_latest_versions("big", Some(1)).unwrap();
+        assert!(scan.completed.is_empty());
+        let mut completed = Vec::new();
+        let mut resumes = 0;
+        while let Some(cursor) = scan.resume {
+            resumes += 1;
+            assert!(resumes <= 3, "the scan never finished");
+            scan = github.resume_org_scan(&cursor, Some(1));
+            // every resume gets further
+            assert_eq!(scan.completed.len(), 1);
+            completed.extend(scan.completed);
+        }
+        let names: Vec<_> = completed.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["big/a", "big/b", "big/c"]);
+        assert!(matches!(
+            completed[0].1,
+            Err(LookupError::BudgetExceeded { .. })
+        ));
+        assert_eq!(*completed[2].1.as_ref().unwrap(), Version::new(3, 0, 0));
+    }
+}
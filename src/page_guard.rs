@@ -0,0 +1,132 @@
+//! Stopping walks over pages that would never end.
+
+use crate::{GitHub, LookupError, Result};
+use log::debug;
+use std::collections::HashSet;
+
+/// The most pages a walk fetches when no limit is set.
+pub(crate) const DEFAULT_MAX_PAGES: usize = 1000;
+
+/// The pages a walk has fetched, so that it can be stopped before fetching a
+/// page twice or more pages than allowed.
+#[derive(Debug)]
+pub(crate) struct PageGuard {
+    visited: HashSet<usize>,
+    fetched: usize,
+    max_pages: usize,
+}
+
+impl PageGuard {
+    /// A guard for a walk that fetches at most `max_pages` pages.
+    pub(crate) fn new(max_pages: usize) -> Self {
+        Self {
+            visited: HashSet::new(),
+            fetched: 0,
+            max_pages,
+        }
+    }
+
+    /// Record that `page` was fetched, with the page its "next" link points
+    /// to, and check the page the walk would fetch after it.
+    ///
+    /// Returns `following` if the walk can go on. A "next" link back to a page
+    /// already fetched fails even if the walk would have ended there.
+    pub(crate) fn follow(
+        &mut self,
+        page: usize,
+        next_link: Option<usize>,
+        following: Option<usize>,
+    ) -> Result<Option<usize>> {
+        self.fetched += 1;
+        let _new = self.visited.insert(page);
+        if let Some(next) = next_link.filter(|next| self.visited.contains(next)) {
+            debug!("Page {page} links to page {next}, which was already fetched");
+        } else if let Some(following) = following {
+            if self.visited.contains(&following) {
+                debug!("Page {following} was already fetched");
+            } else if self.fetched >= self.max_pages {
+                debug!("Fetched {} page(s), the most allowed", self.fetched);
+            } else {
+                return Ok(Some(following));
+            }
+        } else {
+            return Ok(None);
+        }
+        Err(LookupError::PaginationLoop {
+            pages_fetched: self.fetched,
+        })
+    }
+
+    /// Forget which pages were fetched, for a walk starting over from the
+    /// first page. The pages still count towards the limit.
+    pub(crate) fn restart(&mut self) {
+        self.visited.clear();
+    }
+}
+
+impl GitHub {
+    /// A guard for a new walk over pages.
+    pub(crate) fn page_guard(&self) -> PageGuard {
+        PageGuard::new(self.settings.max_pages.unwrap_or(DEFAULT_MAX_PAGES))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHub, LookupError, SearchQuery,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    fn release(id: usize) -> String {
+        release_json(&format!("v1.0.{id}"), json!({ "id": id })).to_string()
+    }
+
+    #[test]
+    fn test_self_referential_next_link() {
+        let next = format!(
+            r#"<{}/repos/foo/loop/releases?per_page=100&page=2>; rel="next""#,
+            mockito::server_url()
+        );
+        let _m = mock("GET", "/repos/foo/loop/releases")
+            .match_query(Matcher::Any)
+            .with_header("link", &next)
+            .with_body(format!("[{}]", release(1)))
+            .create();
+        let github = mock_github();
+        assert!(matches!(
+            github.query("foo/loop"),
+            Err(LookupError::PaginationLoop { pages_fetched: 2 })
+        ));
+        assert!(matches!(
+            github.search_releases("foo/loop", &SearchQuery::substring("v9")),
+            Err(LookupError::PaginationLoop { pages_fetched: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_max_pages() {
+        let last = format!(
+            r#"<{}/repos/foo/endless/releases?per_page=100&page=1000000>; rel="last""#,
+            mockito::server_url()
+        );
+        let m = mock("GET", "/repos/foo/endless/releases")
+            .match_query(Matcher::Any)
+            .with_header("link", &last)
+            .with_body(format!("[{}]", release(1)))
+            .expect(3)
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .max_pages(3)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            github.query("foo/endless"),
+            Err(LookupError::PaginationLoop { pages_fetched: 3 })
+        ));
+        m.assert();
+    }
+}
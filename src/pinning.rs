@@ -0,0 +1,128 @@
+//! Telling a pinned certificate that didn't match apart from other connection errors.
+
+use crate::{timeout_error, GitHub, LookupError};
+use log::debug;
+use std::error::Error;
+
+/// Whether the error, or any error it came from, is about a certificate.
+///
+/// The TLS libraries don't share an error type, so this goes by the message,
+/// like OpenSSL's "certificate verify failed" or rustls' "invalid peer certificate".
+fn is_certificate_error(error: &reqwest::Error) -> bool {
+    let mut source: Option<&dyn Error> = Some(error);
+    while let Some(error) = source {
+        if error.to_string().to_lowercase().contains("certificate") {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+impl GitHub {
+    /// Map an error sending a request to the matching error, like
+    /// [`LookupError::CertificatePinFailed`] when certificates are pinned.
+    pub(crate) fn transport_error(&self, error: reqwest::Error) -> LookupError {
+        if !self.settings.pinned_certificates.is_empty()
+            && error.is_connect()
+            && is_certificate_error(&error)
+        {
+            let host = error
+                .url()
+                .and_then(|url| url.host_str())
+                .unwrap_or_default()
+                .to_owned();
+            debug!("The certificate of {host} doesn't match a pinned certificate");
+            return LookupError::CertificatePinFailed { host };
+        }
+        timeout_error(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Certificate, GitHub, LookupError};
+    use native_tls::{Identity, TlsAcceptor};
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    const SERVER_CERT: &[u8] = include_bytes!("../tests/fixtures/tls/server.pem");
+    const SERVER_KEY: &[u8] = include_bytes!("../tests/fixtures/tls/server.key");
+    const OTHER_CERT: &[u8] = include_bytes!("../tests/fixtures/tls/other.pem");
+
+    /// Serve an empty page of releases over HTTPS to each of `connections`,
+    /// returning the API root.
+    fn serve_tls(connections: usize) -> String {
+        let identity = Identity::from_pkcs8(SERVER_CERT, SERVER_KEY).unwrap();
+        let acceptor = TlsAcceptor::new(identity).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let _server = thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                let Ok(mut stream) = acceptor.accept(stream.unwrap()) else {
+                    continue;
+                };
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buffer) {
+                        Ok(0) | Err(_) => break,
+                        Ok(read) => request.extend_from_slice(&buffer[..read]),
+                    }
+                }
+                let _written = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 2\r\nconnection: close\r\n\r\n[]",
+                );
+            }
+        });
+        format!("https://localhost:{port}/")
+    }
+
+    fn pinned(api_root: &str, pem: &[u8]) -> GitHub {
+        GitHub::builder()
+            .api_root(api_root)
+            .pin_certificates(vec![Certificate::from_pem(pem).unwrap()])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pin_certificates_builds() {
+        let github = pinned("https://localhost/", SERVER_CERT);
+        assert_eq!(github.config_snapshot().pinned_certificates, 1);
+        let github = GitHub::builder()
+            .pin_certificates(Vec::new())
+            .build()
+            .unwrap();
+        assert_eq!(github.config_snapshot().pinned_certificates, 0);
+    }
+
+    #[test]
+    fn test_pinned_certificate_matches() {
+        let api_root = serve_tls(1);
+        let releases = pinned(&api_root, SERVER_CERT).query("foo/pinned").unwrap();
+        assert!(releases.is_empty());
+    }
+
+    #[test]
+    fn test_pinned_certificate_mismatch() {
+        let api_root = serve_tls(2);
+        let error = pinned(&api_root, OTHER_CERT)
+            .query("foo/pinned")
+            .unwrap_err();
+        assert!(
+            matches!(&error, LookupError::CertificatePinFailed { host } if host == "localhost"),
+            "{error:?}"
+        );
+
+        // without pinning, the unknown certificate is a plain connection error
+        let error = GitHub::from_custom(&api_root, "")
+            .unwrap()
+            .query("foo/pinned")
+            .unwrap_err();
+        assert!(matches!(error, LookupError::HttpClient(_)), "{error:?}");
+    }
+}
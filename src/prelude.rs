@@ -0,0 +1,19 @@
+//! The types most lookups need, for a single glob import.
+//!
+//! ```rust,no_run
+//! use github_release_check::prelude::*;
+//!
+//! let github = GitHub::new().unwrap();
+//! let latest = github
+//!     .query("celeo/github_release_check")
+//!     .unwrap()
+//!     .into_iter()
+//!     .stable()
+//!     .max_semver();
+//! ```
+
+pub use crate::{
+    BudgetBehavior, DateField, GitHub, GitHubAssetItem, GitHubBuilder, GitHubReleaseItem,
+    LatestStrategy, LookupError, PageCursor, QueryOptions, QueryStats, ReleaseIterExt,
+    ReleaseSource, RepositorySpec, SearchQuery, TaggedVersion, VersionSource,
+};
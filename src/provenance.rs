@@ -0,0 +1,164 @@
+//! Reading the digests GitHub records for release assets, for supply-chain checks.
+
+use crate::{redact_url, Call, GitHub, GitHubReleaseItem, LookupError, RepositorySpec, Result};
+use log::debug;
+use std::collections::HashMap;
+
+impl GitHub {
+    /// Get the release with the ID from the repository.
+    fn release_by_id(&self, spec: &RepositorySpec, release_id: usize) -> Result<GitHubReleaseItem> {
+        #[cfg(feature = "test-util")]
+        if self.fixture.is_some() {
+            return self
+                .query(spec)?
+                .into_iter()
+                .find(|release| release.id == release_id)
+                .ok_or(LookupError::ReleaseNotFound);
+        }
+        let url = self.spec_url(spec, &format!("releases/{release_id}"));
+        debug!("Looking up release at {}", redact_url(&url));
+        match self.get(&url, &[], &mut Call::default()) {
+            Ok(response) => self.read_json(response),
+            Err(LookupError::RepositoryNotFound) => Err(LookupError::ReleaseNotFound),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the digests of the release's assets, keyed by asset name.
+    ///
+    /// Digests are written like `"sha256:…"`, as GitHub provides them. Assets
+    /// without a digest, like those uploaded before GitHub started recording
+    /// them, are left out, so the map is empty if none has one. Whether the
+    /// release itself can still change is in [`GitHubReleaseItem::immutable`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let digests = github
+    ///     .get_asset_digests("celeo/github_release_check", 68_890_274)
+    ///     .unwrap();
+    /// for (name, digest) in &digests {
+    ///     println!("{name}: {digest}");
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::ReleaseNotFound`] if the repository
+    /// has no release with the ID, or for any of the reasons in [`GitHub::query`].
+    pub fn get_asset_digests<R>(
+        &self,
+        repository: R,
+        release_id: usize,
+    ) -> Result<HashMap<String, String>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        Ok(self
+            .release_by_id(&repository.try_into()?, release_id)?
+            .assets
+            .into_iter()
+            .filter_map(|asset| Some((asset.name, asset.digest?)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{mock_github, release_json},
+        LookupError,
+    };
+    use mockito::mock;
+    use serde_json::{json, Value};
+
+    fn asset(name: &str, digest: Option<&str>) -> String {
+        let digest = digest.map_or(String::new(), |d| format!(r#", "digest": "{d}""#));
+        format!(
+            r#"{{ "url": "", "browser_download_url": "", "id": 1, "name": "{name}", "label": null, "content_type": "application/octet-stream", "size": 1, "download_count": 0, "created_at": "", "updated_at": ""{digest} }}"#
+        )
+    }
+
+    fn release(id: usize, fields: Value, assets: &[String]) -> String {
+        let assets: Vec<Value> = assets
+            .iter()
+            .map(|a| serde_json::from_str(a).unwrap())
+            .collect();
+        let mut release = release_json("v1.0.0", fields);
+        release["id"] = json!(id);
+        release["assets"] = json!(assets);
+        release.to_string()
+    }
+
+    #[test]
+    fn test_digests_present() {
+        let _m = mock("GET", "/repos/foo/digests/releases/7")
+            .with_body(release(
+                7,
+                json!({ "immutable": true }),
+                &[
+                    asset("tool.tar.gz", Some("sha256:abc123")),
+                    asset("tool.sig", None),
+                ],
+            ))
+            .create();
+        let digests = mock_github().get_asset_digests("foo/digests", 7).unwrap();
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests["tool.tar.gz"], "sha256:abc123");
+    }
+
+    #[test]
+    fn test_digests_absent() {
+        let _m = mock("GET", "/repos/foo/no-digests/releases/8")
+            .with_body(release(8, json!({}), &[asset("tool.tar.gz", None)]))
+            .create();
+        assert!(mock_github()
+            .get_asset_digests("foo/no-digests", 8)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_digests_unknown_release() {
+        let _m = mock("GET", "/repos/foo/digests-missing/releases/9")
+            .with_status(404)
+            .create();
+        assert!(matches!(
+            mock_github().get_asset_digests("foo/digests-missing", 9),
+            Err(LookupError::ReleaseNotFound)
+        ));
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_fixture_with_and_without_fields() {
+        let github = crate::GitHub::from_fixture(std::path::Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/releases.json"
+        )))
+        .unwrap();
+        let releases = github.query("foo/attested").unwrap();
+        assert_eq!(releases[0].immutable, Some(true));
+        assert_eq!(releases[1].immutable, None);
+        assert_eq!(releases[1].assets[0].digest, None);
+
+        let digests = github.get_asset_digests("foo/attested", 12).unwrap();
+        assert_eq!(
+            digests.get("tool-linux.tar.gz").map(String::as_str),
+            Some("sha256:9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08")
+        );
+        assert!(!digests.contains_key("tool.msi"));
+        assert!(github
+            .get_asset_digests("foo/attested", 11)
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            github.get_asset_digests("foo/attested", 99),
+            Err(LookupError::ReleaseNotFound)
+        ));
+        assert_eq!(github.query("foo/bar").unwrap()[0].immutable, None);
+    }
+}
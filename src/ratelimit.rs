@@ -0,0 +1,165 @@
+//! What the API last said about the rate limit, shared between clones.
+
+use crate::{Call, GitHub};
+use log::debug;
+use reqwest::header::HeaderMap;
+use std::{
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The state of the rate limit, as of the most recent response.
+///
+/// See [`GitHub::shared_rate_limit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// How many requests are allowed per window, if the response said.
+    pub limit: Option<u64>,
+    /// How many requests are left in the current window.
+    pub remaining: u64,
+    /// When the current window ends, if the response said.
+    pub reset_at: Option<SystemTime>,
+}
+
+impl RateLimitInfo {
+    /// Read the rate limit headers, if the response has them.
+    ///
+    /// Only the main limit is read; search and the other resources have
+    /// limits of their own that don't hold other requests back.
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let resource = headers
+            .get("x-ratelimit-resource")
+            .and_then(|v| v.to_str().ok());
+        if resource.is_some_and(|resource| resource != "core") {
+            return None;
+        }
+        let number = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+        Some(Self {
+            limit: number("x-ratelimit-limit"),
+            remaining: number("x-ratelimit-remaining")?,
+            reset_at: number("x-ratelimit-reset")
+                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+        })
+    }
+}
+
+/// The most recent rate limit state, shared between clones of a `GitHub` instance.
+#[derive(Debug, Default)]
+pub(crate) struct SharedRateLimit(Mutex<Option<RateLimitInfo>>);
+
+impl SharedRateLimit {
+    /// The most recent state.
+    fn get(&self) -> Option<RateLimitInfo> {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Keep the state from the response headers, if they have it.
+    pub(crate) fn update(&self, headers: &HeaderMap) {
+        if let Some(info) = RateLimitInfo::from_headers(headers) {
+            *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(info);
+        }
+    }
+
+    /// When the limit resets, if it's used up and hasn't reset yet.
+    pub(crate) fn exhausted_until(&self) -> Option<SystemTime> {
+        self.get()
+            .filter(|info| info.remaining == 0)
+            .and_then(|info| info.reset_at)
+            .filter(|reset_at| *reset_at > SystemTime::now())
+    }
+}
+
+impl GitHub {
+    /// Get the state of the rate limit as of the most recent response.
+    ///
+    /// The state is shared between clones of the instance, so once one clone
+    /// finds the limit used up, the others fail with
+    /// [`LookupError::RateLimited`](crate::LookupError::RateLimited) (or wait, see
+    /// [`GitHubBuilder::wait_on_rate_limit`](crate::GitHubBuilder::wait_on_rate_limit))
+    /// without sending a request. It's `None` until a response says.
+    #[must_use]
+    pub fn shared_rate_limit(&self) -> Option<RateLimitInfo> {
+        self.rate_limit.get()
+    }
+
+    /// Wait for the rate limit to reset, if that's within the wait allowed for the call.
+    ///
+    /// Returns whether it waited.
+    pub(crate) fn wait_for_rate_limit(&self, reset_at: SystemTime, call: &mut Call) -> bool {
+        let Some(max_wait) = self.settings.wait_on_rate_limit else {
+            return false;
+        };
+        // a second is added to absorb clock differences with the server
+        let wait = reset_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+            + Duration::from_secs(1);
+        if call.waited + wait > max_wait {
+            debug!("Rate limit resets after the maximum wait of {max_wait:?}");
+            return false;
+        }
+        debug!("Rate limited, waiting {wait:?} for the limit to reset");
+        crate::sleep_chunked(wait);
+        call.waited += wait;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::mock_github, LookupError};
+    use mockito::mock;
+    use std::{
+        thread,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn test_clones_share_exhausted_limit() {
+        let reset = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3600;
+        let m = mock("GET", "/repos/foo/shared-limit/releases/latest")
+            .with_status(403)
+            .with_header("x-ratelimit-limit", "60")
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &reset.to_string())
+            .expect(1)
+            .create();
+        let github = mock_github();
+        assert_eq!(github.shared_rate_limit(), None);
+
+        let first = github.clone();
+        let result = thread::spawn(move || first.get_marked_latest("foo/shared-limit"))
+            .join()
+            .unwrap();
+        assert!(matches!(result, Err(LookupError::RateLimited { .. })));
+
+        let handles: Vec<_> = (0..9)
+            .map(|_| {
+                let github = github.clone();
+                thread::spawn(move || github.get_marked_latest("foo/shared-limit"))
+            })
+            .collect();
+        for handle in handles {
+            match handle.join().unwrap() {
+                Err(LookupError::RateLimited { reset_at, .. }) => {
+                    assert_eq!(reset_at, Some(UNIX_EPOCH + Duration::from_secs(reset)));
+                }
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+        m.assert();
+
+        let info = github.shared_rate_limit().unwrap();
+        assert_eq!(info.limit, Some(60));
+        assert_eq!(info.remaining, 0);
+    }
+}
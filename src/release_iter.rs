@@ -0,0 +1,197 @@
+//! Filtering and sorting releases with iterator adapters, wherever they came from.
+
+use crate::{versions::parse_tag, GitHubReleaseItem};
+use semver::Version;
+use std::time::{Duration, SystemTime};
+
+/// The version in the release's tag, stripping the built-in version prefixes.
+fn tag_version(release: &GitHubReleaseItem) -> Option<Version> {
+    parse_tag(&release.tag_name, &[])
+}
+
+/// Adapters for iterators of releases, like those from [`GitHub::query`](crate::GitHub::query).
+///
+/// Versions are read from the tags, with the built-in version prefixes like
+/// `v` stripped; releases whose tag isn't a Semantic Version are skipped by the
+/// adapters that need a version. Prefixes set with
+/// [`GitHubBuilder::version_prefixes`](crate::GitHubBuilder::version_prefixes)
+/// don't apply, as the releases don't carry the builder's settings.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::prelude::*;
+/// use semver::Version;
+///
+/// let github = GitHub::new().unwrap();
+/// let newest = github
+///     .query("celeo/github_release_check")
+///     .unwrap()
+///     .into_iter()
+///     .stable()
+///     .newer_than(&Version::new(0, 2, 0))
+///     .max_semver();
+/// if let Some((version, release)) = newest {
+///     println!("{version} is out: {}", release.html_url);
+/// }
+/// ```
+pub trait ReleaseIterExt: Iterator<Item = GitHubReleaseItem> + Sized {
+    /// Keep only releases that aren't drafts or pre-releases.
+    ///
+    /// A release counts as a pre-release if it's marked as one, or if its tag
+    /// is a Semantic Version with a pre-release part, like `v1.0.0-rc.1`.
+    fn stable(self) -> impl Iterator<Item = GitHubReleaseItem> {
+        self.filter(|release| {
+            !release.draft
+                && !release.prerelease
+                && tag_version(release).map_or(true, |version| version.pre.is_empty())
+        })
+    }
+
+    /// Pair each release with the version in its tag, skipping those without one.
+    fn semver_parsed(self) -> impl Iterator<Item = (Version, GitHubReleaseItem)> {
+        self.filter_map(|release| Some((tag_version(&release)?, release)))
+    }
+
+    /// Keep only releases whose tag is a version newer than `version`.
+    fn newer_than(self, version: &Version) -> impl Iterator<Item = GitHubReleaseItem> {
+        let version = version.clone();
+        self.filter(move |release| tag_version(release).is_some_and(|v| v > version))
+    }
+
+    /// The release with the highest version in its tag, along with the version.
+    ///
+    /// If several releases have the same version, the last of them is returned.
+    fn max_semver(self) -> Option<(Version, GitHubReleaseItem)> {
+        self.semver_parsed().max_by(|(a, _), (b, _)| a.cmp(b))
+    }
+
+    /// Keep only releases published at most `window` before `now`.
+    ///
+    /// Releases without an [age](GitHubReleaseItem::age), like drafts, are skipped.
+    fn released_within(
+        self,
+        window: Duration,
+        now: SystemTime,
+    ) -> impl Iterator<Item = GitHubReleaseItem> {
+        self.filter(move |release| release.age(now).is_some_and(|age| age <= window))
+    }
+}
+
+impl<I: Iterator<Item = GitHubReleaseItem>> ReleaseIterExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::ReleaseIterExt;
+    use crate::{tests::release_json, GitHubReleaseItem};
+    use semver::Version;
+    use serde_json::json;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn release(tag: &str, draft: bool, prerelease: bool) -> GitHubReleaseItem {
+        serde_json::from_value(release_json(
+            tag,
+            json!({ "draft": draft, "prerelease": prerelease }),
+        ))
+        .unwrap()
+    }
+
+    fn releases() -> Vec<GitHubReleaseItem> {
+        vec![
+            release("v1.2.0", false, false),
+            release("v2.0.0-rc.1", false, false),
+            release("v1.4.0", false, true),
+            release("v1.3.0", true, false),
+            release("nightly", false, false),
+            release("1.10.0", false, false),
+            release("v0.9.0", false, false),
+        ]
+    }
+
+    fn tags(releases: impl Iterator<Item = GitHubReleaseItem>) -> Vec<String> {
+        releases.map(|release| release.tag_name).collect()
+    }
+
+    #[test]
+    fn test_stable() {
+        assert_eq!(
+            tags(releases().into_iter().stable()),
+            vec!["v1.2.0", "nightly", "1.10.0", "v0.9.0"]
+        );
+    }
+
+    #[test]
+    fn test_semver_parsed() {
+        let parsed: Vec<_> = releases()
+            .into_iter()
+            .semver_parsed()
+            .map(|(version, release)| (version.to_string(), release.tag_name))
+            .collect();
+        assert_eq!(parsed.len(), 6);
+        assert_eq!(parsed[0], ("1.2.0".to_owned(), "v1.2.0".to_owned()));
+        assert_eq!(
+            parsed[1],
+            ("2.0.0-rc.1".to_owned(), "v2.0.0-rc.1".to_owned())
+        );
+        assert!(!parsed.iter().any(|(_, tag)| tag == "nightly"));
+    }
+
+    #[test]
+    fn test_newer_than() {
+        assert_eq!(
+            tags(releases().into_iter().newer_than(&Version::new(1, 3, 0))),
+            vec!["v2.0.0-rc.1", "v1.4.0", "1.10.0"]
+        );
+        assert!(releases()
+            .into_iter()
+            .newer_than(&Version::new(3, 0, 0))
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_max_semver() {
+        let (version, release) = releases().into_iter().max_semver().unwrap();
+        assert_eq!(version, Version::parse("2.0.0-rc.1").unwrap());
+        assert_eq!(release.tag_name, "v2.0.0-rc.1");
+        assert!(Vec::new().into_iter().max_semver().is_none());
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let (version, release) = releases()
+            .into_iter()
+            .stable()
+            .newer_than(&Version::new(1, 0, 0))
+            .max_semver()
+            .unwrap();
+        assert_eq!(version, Version::new(1, 10, 0));
+        assert_eq!(release.tag_name, "1.10.0");
+    }
+
+    #[test]
+    fn test_released_within() {
+        let published = |tag: &str, published_at: &str| {
+            let mut release = release(tag, published_at.is_empty(), false);
+            release.published_at = published_at.to_owned();
+            release
+        };
+        let releases = vec![
+            published("v1.3.0", "2024-03-07T09:00:00+02:00"),
+            published("v1.2.0", "2024-02-29T23:00:00-03:00"),
+            published("v1.1.0", "2024-02-01T00:00:00Z"),
+            published("v1.4.0", ""),
+        ];
+        // 2024-03-08T00:00:00Z
+        let now = UNIX_EPOCH + Duration::from_secs(474_960 * 3600);
+        let week = Duration::from_secs(7 * 24 * 3600);
+        assert_eq!(
+            tags(releases.clone().into_iter().released_within(week, now)),
+            vec!["v1.3.0", "v1.2.0"]
+        );
+        assert_eq!(
+            tags(releases.into_iter().released_within(Duration::ZERO, now)),
+            Vec::<String>::new()
+        );
+    }
+}
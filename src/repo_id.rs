@@ -0,0 +1,155 @@
+//! Looking up repositories by their numeric ID, which survives renames.
+
+use crate::{redact_url, Call, GitHub, GitHubReleaseItem, LookupError, RepositorySpec, Result};
+use log::debug;
+use semver::Version;
+use serde::Deserialize;
+
+/// Body of the repository endpoint, of which only the ID is needed.
+#[derive(Debug, Deserialize)]
+struct RepositoryId {
+    id: u64,
+}
+
+impl GitHub {
+    /// Get the numeric ID of the repository.
+    ///
+    /// Unlike `"owner/repo"`, the ID stays the same when a repository is renamed
+    /// or transferred, so it can be looked up once and stored for
+    /// [`GitHub::query_by_repo_id`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let id = github.resolve_repo_id("celeo/github_release_check").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::RepositoryNotFound`] if there's no
+    /// such repository, or for any of the reasons in `query`.
+    pub fn resolve_repo_id<R>(&self, repository: R) -> Result<u64>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        self.repository_id(&repository.try_into()?)
+    }
+
+    /// Get the numeric ID of the repository, failing if it doesn't exist.
+    pub(crate) fn repository_id(&self, spec: &RepositorySpec) -> Result<u64> {
+        let url = self.spec_url(spec, "");
+        let url = url.trim_end_matches('/');
+        debug!("Looking up repository at {}", redact_url(url));
+        let response = self.get(url, &[], &mut Call::default())?;
+        Ok(self.read_json::<RepositoryId>(response)?.id)
+    }
+
+    /// Get all releases from the repository with the numeric ID.
+    ///
+    /// This is the same as passing the ID to [`GitHub::query`]. See
+    /// [`GitHub::resolve_repo_id`] for getting the ID.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let releases = github.query_by_repo_id(494_509_686).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::RepositoryNotFound`] if there's no
+    /// repository with the ID, or for any of the reasons in `query`.
+    pub fn query_by_repo_id(&self, repo_id: u64) -> Result<Vec<GitHubReleaseItem>> {
+        self.query(repo_id)
+    }
+
+    /// Get all release version strings from the repository with the numeric ID.
+    ///
+    /// This is the same as passing the ID to [`GitHub::get_all_versions`].
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query_by_repo_id`.
+    pub fn get_all_versions_by_repo_id(&self, repo_id: u64) -> Result<Vec<String>> {
+        self.get_all_versions(repo_id)
+    }
+
+    /// Get the latest release version from the repository with the numeric ID.
+    ///
+    /// This is the same as passing the ID to [`GitHub::get_latest_version`].
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_latest_version`.
+    pub fn get_latest_version_by_repo_id(&self, repo_id: u64) -> Result<Version> {
+        self.get_latest_version(repo_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{mock_github, release_json},
+        LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve_and_query_by_id() {
+        let _repo = mock("GET", "/repos/foo/renamed")
+            .with_body(r#"{ "id": 4242, "name": "renamed", "full_name": "foo/renamed" }"#)
+            .create();
+        let _releases = mock("GET", "/repositories/4242/releases")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!([
+                    release_json("v1.10.0", json!({ "id": 2 })),
+                    release_json("v1.9.0", json!({})),
+                ])
+                .to_string(),
+            )
+            .create();
+        let github = mock_github();
+        let id = github.resolve_repo_id("foo/renamed").unwrap();
+        assert_eq!(id, 4242);
+        assert_eq!(github.query_by_repo_id(id).unwrap().len(), 2);
+        assert_eq!(
+            github.get_all_versions_by_repo_id(id).unwrap(),
+            vec!["v1.10.0", "v1.9.0"]
+        );
+        assert_eq!(
+            github.get_latest_version_by_repo_id(id).unwrap(),
+            Version::new(1, 10, 0)
+        );
+    }
+
+    #[test]
+    fn test_unknown_repo_id() {
+        let _m = mock("GET", "/repositories/999999/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        assert!(matches!(
+            mock_github().query_by_repo_id(999_999),
+            Err(LookupError::RepositoryNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_resolve_unknown_repository() {
+        let _m = mock("GET", "/repos/foo/missing-id")
+            .with_status(404)
+            .create();
+        assert!(matches!(
+            mock_github().resolve_repo_id("foo/missing-id"),
+            Err(LookupError::RepositoryNotFound)
+        ));
+    }
+}
@@ -0,0 +1,274 @@
+//! Walking the release pages across several calls, for jobs that can't finish in one.
+
+use crate::{
+    effective_page_size, following_page, is_unlinked_full_page, Call, GitHub, GitHubReleaseItem,
+    LookupError, PageResult, RepositorySpec, Result, PAGINATION_REQUEST_AMOUNT,
+};
+use log::debug;
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+/// Where a resumable walk of the release pages left off.
+///
+/// Cursors can be serialized so that the walk continues in a later run. See
+/// [`GitHub::query_resumable`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageCursor {
+    /// The page to fetch next.
+    pub next_page: usize,
+    /// The page size the walk uses, which the server may have lowered.
+    pub per_page: usize,
+    /// The entity tag of the first page, to tell whether the list has changed.
+    pub first_etag: Option<String>,
+}
+
+/// The releases from one call of a resumable walk.
+///
+/// See [`GitHub::query_resumable`].
+#[derive(Debug, Clone)]
+pub struct ResumableResult {
+    /// The releases fetched in this call.
+    pub releases: Vec<GitHubReleaseItem>,
+    /// Where to continue, or `None` if the walk is complete.
+    pub next_cursor: Option<PageCursor>,
+    /// Whether the list changed since the cursor was made, so that the walk
+    /// started over from the first page. Releases kept from earlier calls should
+    /// be dropped then.
+    pub restarted: bool,
+}
+
+impl GitHub {
+    /// Fetch the first page again, unless it's unchanged since the cursor's entity tag.
+    fn changed_first_page(
+        &self,
+        url: &str,
+        etag: &str,
+        per_page: usize,
+        call: &mut Call,
+    ) -> Result<Option<PageResult>> {
+        let mut headers = HeaderMap::new();
+        let _prev = headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(etag)?);
+        match self.fetch_page_with(url, 1, per_page, &headers, call) {
+            Err(LookupError::NotModified) => Ok(None),
+            other => other.map(Some),
+        }
+    }
+
+    /// Get the releases from the repository a few pages at a time, continuing
+    /// from where an earlier call left off.
+    ///
+    /// Without a cursor, the walk starts from the first page. At most
+    /// `budget_pages` pages are fetched (at least one), and the returned cursor
+    /// says where to continue. When resuming, the first page is checked with a
+    /// conditional request first: if it changed since the walk started, or the
+    /// cursor has no entity tag to check against, the walk starts over and
+    /// [`ResumableResult::restarted`] is set. An unchanged first page doesn't
+    /// count against the budget.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let mut cursor = None;
+    /// let mut releases = Vec::new();
+    /// loop {
+    ///     let result = github
+    ///         .query_resumable("celeo/github_release_check", cursor, 10)
+    ///         .unwrap();
+    ///     if result.restarted {
+    ///         releases.clear();
+    ///     }
+    ///     releases.extend(result.releases);
+    ///     cursor = result.next_cursor;
+    ///     if cursor.is_none() {
+    ///         break;
+    ///     }
+    ///     // ... or save the cursor, and continue in the next run
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn query_resumable<R>(
+        &self,
+        repository: R,
+        cursor: Option<PageCursor>,
+        budget_pages: usize,
+    ) -> Result<ResumableResult>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let url = self.spec_url(&repository.try_into()?, "releases");
+        let mut call = Call::default();
+        let mut restarted = false;
+        let mut pending: Option<PageResult> = None;
+        let cursor = match cursor {
+            Some(PageCursor {
+                first_etag: None, ..
+            }) => {
+                debug!("Cursor has no entity tag to check, restarting");
+                restarted = true;
+                None
+            }
+            Some(cursor) => {
+                let etag = cursor.first_etag.as_deref().unwrap_or_default();
+                match self.changed_first_page(&url, etag, cursor.per_page, &mut call)? {
+                    None => Some(cursor),
+                    Some(first) => {
+                        debug!("First page changed since the cursor was made, restarting");
+                        restarted = true;
+                        pending = Some(first);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let (mut page, mut per_page, mut first_etag) = match cursor {
+            Some(cursor) => (cursor.next_page, cursor.per_page, cursor.first_etag),
+            None => (1, PAGINATION_REQUEST_AMOUNT, None),
+        };
+        let budget = budget_pages.max(1);
+        let mut fetched = 0;
+        let mut last_page: Option<usize> = None;
+        let mut releases = Vec::new();
+        let mut page_start = 0;
+        let mut guard = self.page_guard();
+
+        loop {
+            let result = match pending.take() {
+                Some(result) => result,
+                None => self.fetch_page(&url, page, per_page, &mut call)?,
+            };
+            fetched += 1;
+            if page == 1 {
+                per_page = effective_page_size(per_page, &result);
+                first_etag.clone_from(&result.etag);
+            }
+            last_page = last_page.or(result.last_page);
+            let previous_first = releases.get(page_start);
+            let unlinked_full = is_unlinked_full_page(&result, last_page, per_page, previous_first);
+            let following = guard.follow(
+                page,
+                result.next_page,
+                following_page(page, result.next_page, last_page, unlinked_full),
+            )?;
+            page_start = releases.len();
+            releases.extend(result.releases);
+            let Some(following) = following else {
+                return Ok(ResumableResult {
+                    releases,
+                    next_cursor: None,
+                    restarted,
+                });
+            };
+            page = following;
+            if fetched >= budget {
+                debug!("Fetched {fetched} page(s), stopping before page {page}");
+                return Ok(ResumableResult {
+                    releases,
+                    next_cursor: Some(PageCursor {
+                        next_page: page,
+                        per_page,
+                        first_etag,
+                    }),
+                    restarted,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PageCursor;
+    use crate::tests::{mock_github, release_json};
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    /// Mock page `page` of `pages`, with an entity tag on the first page.
+    fn mock_page(path: &str, page: usize, pages: usize, etag: &str) -> mockito::Mock {
+        let link = format!(
+            r#"<{0}{path}?per_page=100&page={pages}>; rel="last""#,
+            mockito::server_url()
+        );
+        let m = mock("GET", path)
+            .match_query(Matcher::UrlEncoded("page".into(), page.to_string()))
+            .with_header("link", &link)
+            .with_body(json!([release_json(&format!("v{page}.0.0"), json!({}))]).to_string());
+        if page == 1 {
+            m.with_header("etag", etag).create()
+        } else {
+            m.create()
+        }
+    }
+
+    fn tags(result: &super::ResumableResult) -> Vec<&str> {
+        result
+            .releases
+            .iter()
+            .map(|release| release.tag_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_resume_across_calls() {
+        let path = "/repos/foo/resumable/releases";
+        let not_modified = mock("GET", path)
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .expect(1)
+            .create();
+        let _pages: Vec<_> = (1..=3)
+            .map(|page| mock_page(path, page, 3, "\"v1\""))
+            .collect();
+
+        let first = mock_github()
+            .query_resumable("foo/resumable", None, 2)
+            .unwrap();
+        assert_eq!(tags(&first), vec!["v1.0.0", "v2.0.0"]);
+        assert!(!first.restarted);
+        let cursor = first.next_cursor.unwrap();
+        assert_eq!(cursor.next_page, 3);
+        assert_eq!(cursor.first_etag.as_deref(), Some("\"v1\""));
+
+        // saved and loaded between runs
+        let saved = serde_json::to_string(&cursor).unwrap();
+        let loaded: PageCursor = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded, cursor);
+
+        let second = mock_github()
+            .query_resumable("foo/resumable", Some(loaded), 2)
+            .unwrap();
+        not_modified.assert();
+        assert_eq!(tags(&second), vec!["v3.0.0"]);
+        assert!(!second.restarted);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_resume_restarts_on_changed_list() {
+        let path = "/repos/foo/resumable-changed/releases";
+        let _pages: Vec<_> = (1..=3)
+            .map(|page| mock_page(path, page, 3, "\"v2\""))
+            .collect();
+        let cursor = PageCursor {
+            next_page: 3,
+            per_page: 100,
+            first_etag: Some("\"v1\"".to_owned()),
+        };
+        let result = mock_github()
+            .query_resumable("foo/resumable-changed", Some(cursor), 2)
+            .unwrap();
+        assert!(result.restarted);
+        assert_eq!(tags(&result), vec!["v1.0.0", "v2.0.0"]);
+        let cursor = result.next_cursor.unwrap();
+        assert_eq!(cursor.next_page, 3);
+        assert_eq!(cursor.first_etag.as_deref(), Some("\"v2\""));
+    }
+}
@@ -0,0 +1,363 @@
+//! Recording the latest release of many repositories, as a report to keep.
+
+use crate::{batch::run_batch, GitHub, LookupError, RepositorySpec, Result, TaggedVersion};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// What a scan found for a single repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepositoryScan {
+    /// The latest release version, unless the lookup failed.
+    pub latest_version: Option<Version>,
+    /// The tag of the latest release, unless the lookup failed.
+    pub latest_tag: Option<String>,
+    /// The link to the page of the latest release, unless the lookup failed.
+    pub html_url: Option<String>,
+    /// When the repository was looked up, in seconds since the Unix epoch.
+    pub scanned_at: u64,
+    /// Why the lookup failed, if it did.
+    pub error: Option<String>,
+    /// The [code](crate::LookupError::code) of the error, if the lookup failed.
+    pub error_code: Option<String>,
+}
+
+/// The latest releases of many repositories, from [`GitHub::scan`].
+///
+/// Repositories are kept sorted by name and fields are always written in the
+/// same order, so the JSON of two scans can be diffed line by line.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanReport {
+    /// What was found for each repository, by `"owner/repo"`.
+    pub repositories: BTreeMap<String, RepositoryScan>,
+}
+
+/// A repository whose lookup succeeded in [`GitHub::try_scan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoResult {
+    /// The repository, as it was given.
+    pub repository: String,
+    /// The latest release version, with its tag.
+    pub latest: TaggedVersion,
+    /// The link to the page of the latest release.
+    pub html_url: String,
+}
+
+/// The lookups of [`GitHub::try_scan`], split into those that succeeded and
+/// those that failed.
+///
+/// Both keep the order the repositories were given in.
+#[derive(Debug, Default)]
+pub struct ScanOutcome {
+    /// The repositories that were looked up.
+    pub successes: Vec<RepoResult>,
+    /// The repositories whose lookups failed, with why.
+    pub failures: Vec<(String, LookupError)>,
+}
+
+impl ScanOutcome {
+    /// Whether every lookup succeeded.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl ScanReport {
+    /// Write the report as indented JSON.
+    ///
+    /// # Panics
+    ///
+    /// This function doesn't panic in practice: everything in a report can be
+    /// written as JSON.
+    #[must_use]
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Scan reports always serialize")
+    }
+
+    /// Read a report written by [`ScanReport::to_json_pretty`].
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the JSON isn't a scan report.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+impl GitHub {
+    /// Look up the latest release of each of the repositories, as a report.
+    ///
+    /// Releases are selected the same way as in [`GitHub::get_latest_version`],
+    /// and lookups run concurrently like in [`GitHub::get_latest_versions`]. A
+    /// failed lookup is recorded in [`RepositoryScan::error`] rather than
+    /// stopping the scan.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let report = github.scan(&["celeo/github_release_check", "rust-lang/rust"]);
+    /// std::fs::write("releases.json", report.to_json_pretty()).unwrap();
+    /// ```
+    #[must_use]
+    pub fn scan(&self, repositories: &[&str]) -> ScanReport {
+        let results = run_batch(self, repositories, |repository| {
+            self.scan_repository(repository)
+        });
+        let repositories = results
+            .into_iter()
+            .map(|(repository, result)| {
+                let scanned_at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let scan = match result {
+                    Ok((tagged, html_url)) => RepositoryScan {
+                        latest_version: Some(tagged.version),
+                        latest_tag: Some(tagged.tag),
+                        html_url: Some(html_url),
+                        scanned_at,
+                        error: None,
+                        error_code: None,
+                    },
+                    Err(e) => RepositoryScan {
+                        latest_version: None,
+                        latest_tag: None,
+                        html_url: None,
+                        scanned_at,
+                        error: Some(e.to_string()),
+                        error_code: Some(e.code().to_owned()),
+                    },
+                };
+                (repository, scan)
+            })
+            .collect();
+        ScanReport { repositories }
+    }
+
+    /// Look up the latest release of each of the repositories, keeping the
+    /// errors apart from the results.
+    ///
+    /// This is [`GitHub::scan`] for callers that want the [`LookupError`]s
+    /// themselves rather than a report: a failed lookup, like a
+    /// [`LookupError::RepositoryNotFound`], goes to [`ScanOutcome::failures`] and
+    /// the rest of the repositories are still looked up. Lookups run
+    /// concurrently and back off from the secondary rate limit like in
+    /// [`GitHub::get_latest_versions`], and each is held to
+    /// [`GitHubBuilder::max_requests_per_call`](crate::GitHubBuilder::max_requests_per_call).
+    /// Clones of the instance share the rate limit, so once it's used up the
+    /// remaining lookups fail with [`LookupError::RateLimited`] without sending
+    /// requests, unless [`GitHubBuilder::wait_on_rate_limit`](crate::GitHubBuilder::wait_on_rate_limit)
+    /// is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let outcome = github.try_scan(&["celeo/github_release_check", "rust-lang/rust"]);
+    /// for result in &outcome.successes {
+    ///     println!("{} {}", result.repository, result.latest.version);
+    /// }
+    /// for (repository, error) in &outcome.failures {
+    ///     eprintln!("{repository}: {error}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn try_scan(&self, repositories: &[&str]) -> ScanOutcome {
+        let mut outcome = ScanOutcome::default();
+        for (repository, result) in run_batch(self, repositories, |repository| {
+            self.scan_repository(repository)
+        }) {
+            match result {
+                Ok((latest, html_url)) => outcome.successes.push(RepoResult {
+                    repository,
+                    latest,
+                    html_url,
+                }),
+                Err(e) => outcome.failures.push((repository, e)),
+            }
+        }
+        outcome
+    }
+
+    /// Look up the latest release of the repository, with the link to its page.
+    fn scan_repository(&self, repository: &str) -> Result<(TaggedVersion, String)> {
+        let spec = RepositorySpec::try_from(repository)?;
+        let (tagged, release) = self.latest_release(&spec)?;
+        let html_url = release.map(|r| r.html_url).unwrap_or_default();
+        let html_url = self.release_url(&spec, &tagged.tag, html_url);
+        Ok((tagged, html_url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScanReport;
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHub, LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+
+    /// A list with one release of the repository, linked to its release page.
+    fn linked_release(repository: &str, tag: &str) -> String {
+        let html_url = format!("https://github.com/{repository}/releases/tag/{tag}");
+        json!([release_json(tag, json!({ "html_url": html_url }))]).to_string()
+    }
+
+    #[test]
+    fn test_scan_records_results_and_errors() {
+        let _found = mock("GET", "/repos/foo/scanned/releases")
+            .match_query(Matcher::Any)
+            .with_body(linked_release("foo/scanned", "v1.4.0"))
+            .create();
+        let _missing = mock("GET", "/repos/foo/scan-missing/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        let report = mock_github().scan(&["foo/scanned", "foo/scan-missing"]);
+        assert_eq!(report.repositories.len(), 2);
+
+        let found = &report.repositories["foo/scanned"];
+        assert_eq!(found.latest_version, Some(Version::new(1, 4, 0)));
+        assert_eq!(found.latest_tag.as_deref(), Some("v1.4.0"));
+        assert_eq!(
+            found.html_url.as_deref(),
+            Some("https://github.com/foo/scanned/releases/tag/v1.4.0")
+        );
+        assert!(found.scanned_at > 0);
+        assert_eq!(found.error, None);
+
+        let missing = &report.repositories["foo/scan-missing"];
+        assert_eq!(missing.latest_version, None);
+        assert_eq!(missing.error.as_deref(), Some("repository not found"));
+        assert_eq!(missing.error_code.as_deref(), Some("repo_not_found"));
+    }
+
+    #[test]
+    fn test_stable_serialization() {
+        let _a = mock("GET", "/repos/foo/scan-a/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+            .create();
+        let _b = mock("GET", "/repos/foo/scan-b/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v2.0.0", json!({}))]).to_string())
+            .create();
+        let github = mock_github();
+        let mut report = github.scan(&["foo/scan-b", "foo/scan-a"]);
+        for scan in report.repositories.values_mut() {
+            scan.scanned_at = 1_700_000_000;
+        }
+        let mut reversed = github.scan(&["foo/scan-a", "foo/scan-b"]);
+        for scan in reversed.repositories.values_mut() {
+            scan.scanned_at = 1_700_000_000;
+        }
+        let json = report.to_json_pretty();
+        assert_eq!(json, reversed.to_json_pretty());
+
+        let a = json.find("foo/scan-a").unwrap();
+        let b = json.find("foo/scan-b").unwrap();
+        assert!(a < b);
+        let fields: Vec<_> = [
+            "latest_version",
+            "latest_tag",
+            "html_url",
+            "scanned_at",
+            "error",
+            "error_code",
+        ]
+        .iter()
+        .map(|field| json.find(field).unwrap())
+        .collect();
+        assert!(fields.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert_eq!(ScanReport::from_json(&json).unwrap(), report);
+        assert!(ScanReport::from_json("[]").is_err());
+    }
+
+    #[test]
+    fn test_try_scan_buckets_failures() {
+        let _missing = mock("GET", "/repos/foo/try-missing/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .expect(1)
+            .create();
+        let _forbidden = mock("GET", "/repos/foo/try-forbidden/releases")
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .with_body(r#"{"message": "Resource not accessible by integration"}"#)
+            .expect(1)
+            .create();
+        let found: Vec<_> = ["try-a", "try-b"]
+            .iter()
+            .map(|name| {
+                mock("GET", format!("/repos/foo/{name}/releases").as_str())
+                    .match_query(Matcher::Any)
+                    .with_body(linked_release(&format!("foo/{name}"), "v1.0.0"))
+                    .expect(1)
+                    .create()
+            })
+            .collect();
+        // one at a time, so the failures come first
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .concurrency(1)
+            .build()
+            .unwrap();
+        let outcome = github.try_scan(&[
+            "foo/try-missing",
+            "foo/try-forbidden",
+            "foo/try-a",
+            "not a repository",
+            "foo/try-b",
+        ]);
+        assert!(!outcome.is_complete());
+
+        let successes: Vec<_> = outcome
+            .successes
+            .iter()
+            .map(|result| (result.repository.as_str(), result.latest.version.clone()))
+            .collect();
+        assert_eq!(
+            successes,
+            vec![
+                ("foo/try-a", Version::new(1, 0, 0)),
+                ("foo/try-b", Version::new(1, 0, 0)),
+            ]
+        );
+        assert_eq!(
+            outcome.successes[1].html_url,
+            "https://github.com/foo/try-b/releases/tag/v1.0.0"
+        );
+
+        let failures: Vec<_> = outcome
+            .failures
+            .iter()
+            .map(|(repository, error)| (repository.as_str(), error.code()))
+            .collect();
+        assert_eq!(
+            failures,
+            vec![
+                ("foo/try-missing", "repo_not_found"),
+                ("foo/try-forbidden", "auth"),
+                ("not a repository", "invalid_repo"),
+            ]
+        );
+        assert!(matches!(
+            outcome.failures[1].1,
+            LookupError::AuthenticationError { status: 403, .. }
+        ));
+        for mock in found {
+            mock.assert();
+        }
+    }
+}
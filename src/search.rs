@@ -0,0 +1,228 @@
+//! Searching release notes.
+
+use crate::{
+    following_page, is_unlinked_full_page, Call, GitHub, GitHubReleaseItem, LookupError,
+    RepositorySpec, Result, PAGINATION_REQUEST_AMOUNT,
+};
+use log::debug;
+use regex::{Regex, RegexBuilder};
+
+/// What [`GitHub::search_releases`] looks for in each release's tag, name, and body.
+///
+/// # Example
+///
+/// ```rust
+/// use github_release_check::SearchQuery;
+///
+/// let first_mention = SearchQuery::substring("dark mode").limit(1);
+/// let cves = SearchQuery::regex(r"CVE-\d{4}-\d+").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    regex: Regex,
+    limit: Option<usize>,
+}
+
+impl SearchQuery {
+    /// Look for the text, ignoring case.
+    #[must_use]
+    pub fn substring(text: &str) -> Self {
+        Self {
+            regex: case_insensitive(&regex::escape(text)),
+            limit: None,
+        }
+    }
+
+    /// Look for matches of the regular expression.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::InvalidSearchPattern`] if the
+    /// pattern isn't a valid regular expression.
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Ok(Self {
+            regex: Regex::new(pattern).map_err(LookupError::InvalidSearchPattern)?,
+            limit: None,
+        })
+    }
+
+    /// Stop after this many matching releases, without fetching further pages.
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether the release's tag, name, or body matches.
+    fn matches(&self, release: &GitHubReleaseItem) -> bool {
+        [
+            Some(release.tag_name.as_str()),
+            release.name.as_deref(),
+            release.body.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|text| self.regex.is_match(text))
+    }
+}
+
+/// Compile a pattern that is known to be valid, ignoring case.
+fn case_insensitive(pattern: &str) -> Regex {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .expect("Escaped patterns are valid")
+}
+
+impl GitHub {
+    /// Get the releases whose tag, name, or body match the query, newest first.
+    ///
+    /// Pages are fetched until every release has been searched, or until the
+    /// query's [limit](SearchQuery::limit) is reached.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{GitHub, SearchQuery};
+    /// let github = GitHub::new().unwrap();
+    /// let found = github
+    ///     .search_releases("celeo/github_release_check", &SearchQuery::substring("rate limit"))
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn search_releases<R>(
+        &self,
+        repository: R,
+        needle: &SearchQuery,
+    ) -> Result<Vec<GitHubReleaseItem>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let url = self.spec_url(&repository.try_into()?, "releases");
+        let mut call = Call::default();
+        let mut found = Vec::new();
+        let mut page = 1usize;
+        let mut last_page: Option<usize> = None;
+        let mut previous_first: Option<GitHubReleaseItem> = None;
+        let mut guard = self.page_guard();
+
+        loop {
+            let result = self.fetch_page(&url, page, PAGINATION_REQUEST_AMOUNT, &mut call)?;
+            last_page = last_page.or(result.last_page);
+            let unlinked_full = is_unlinked_full_page(
+                &result,
+                last_page,
+                PAGINATION_REQUEST_AMOUNT,
+                previous_first.as_ref(),
+            );
+            previous_first = result.releases.first().cloned();
+            for release in result.releases {
+                if needle.matches(&release) {
+                    found.push(release);
+                    if needle.limit.is_some_and(|limit| found.len() >= limit) {
+                        debug!(
+                            "Found {} matching releases, stopping at page {page}",
+                            found.len()
+                        );
+                        return Ok(found);
+                    }
+                }
+            }
+            let following = following_page(page, result.next_page, last_page, unlinked_full);
+            match guard.follow(page, result.next_page, following)? {
+                Some(following) => page = following,
+                None => break,
+            }
+        }
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SearchQuery;
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHub,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    fn release(tag: &str, name: Option<&str>, body: Option<&str>) -> String {
+        release_json(tag, json!({ "name": name, "body": body })).to_string()
+    }
+
+    fn mock_pages() -> Vec<mockito::Mock> {
+        let last = format!(
+            r#"<{}/repos/foo/bar/releases?per_page=100&page=3>; rel="last""#,
+            mockito::server_url()
+        );
+        let pages = [
+            format!(
+                "[{}, {}]",
+                release("v3.0.0", Some("Dark Mode"), None),
+                release("v2.9.0", None, Some("Bug fixes"))
+            ),
+            format!(
+                "[{}, {}]",
+                release("v2.0.0", None, Some("Adds dark mode for the settings page")),
+                release("v1.9.0", Some("Security"), Some("Fixes CVE-2024-1234"))
+            ),
+            format!(
+                "[{}]",
+                release("v1.0.0", None, Some("First release with dark mode"))
+            ),
+        ];
+        pages
+            .iter()
+            .enumerate()
+            .map(|(index, body)| {
+                mock("GET", "/repos/foo/bar/releases")
+                    .match_query(Matcher::UrlEncoded("page".into(), (index + 1).to_string()))
+                    .with_header("link", &last)
+                    .with_body(body)
+                    .create()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_search_across_pages() {
+        let _m = mock_pages();
+        let tags: Vec<_> = mock_github()
+            .search_releases("foo/bar", &SearchQuery::substring("DARK MODE"))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.tag_name)
+            .collect();
+        assert_eq!(tags, vec!["v3.0.0", "v2.0.0", "v1.0.0"]);
+
+        let found = mock_github()
+            .search_releases("foo/bar", &SearchQuery::regex(r"CVE-\d{4}-\d+").unwrap())
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].tag_name, "v1.9.0");
+    }
+
+    #[test]
+    fn test_search_stops_at_limit() {
+        let _m = mock_pages();
+        // a third request would fail with `BudgetExceeded`
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .max_requests_per_call(2)
+            .build()
+            .unwrap();
+        let found = github
+            .search_releases("foo/bar", &SearchQuery::substring("dark mode").limit(2))
+            .unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(github
+            .search_releases("foo/bar", &SearchQuery::substring("dark mode"))
+            .is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! A common interface over every supported forge.
+
+use crate::{GitHub, ReleaseItem, Result};
+use semver::Version;
+
+/// Something that can look up the releases of a repository.
+///
+/// This lets an application hold a `Box<dyn ReleaseSource>` without caring which
+/// forge a project lives on. It's implemented by [`GitHub`], and by the other
+/// forge clients when their features are enabled.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::{GitHub, ReleaseSource};
+///
+/// let source: Box<dyn ReleaseSource> = Box::new(GitHub::new().unwrap());
+/// let latest = source.latest("celeo/github_release_check").unwrap();
+/// ```
+pub trait ReleaseSource {
+    /// Get all release version strings from the repository.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the releases can't be retrieved.
+    fn versions(&self, repository: &str) -> Result<Vec<String>>;
+
+    /// Get the latest (Semantic Versioned) release version from the repository.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the releases can't be retrieved,
+    /// or if none of them have a parseable version.
+    fn latest(&self, repository: &str) -> Result<Version>;
+
+    /// Get all releases from the repository.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the releases can't be retrieved.
+    fn releases(&self, repository: &str) -> Result<Vec<ReleaseItem>>;
+}
+
+impl ReleaseSource for GitHub {
+    fn versions(&self, repository: &str) -> Result<Vec<String>> {
+        self.get_all_versions(repository)
+    }
+
+    fn latest(&self, repository: &str) -> Result<Version> {
+        self.get_latest_version(repository)
+    }
+
+    fn releases(&self, repository: &str) -> Result<Vec<ReleaseItem>> {
+        Ok(self
+            .query(repository)?
+            .into_iter()
+            .map(ReleaseItem::from)
+            .collect())
+    }
+}
+
+#[cfg(feature = "gitlab")]
+impl ReleaseSource for crate::GitLab {
+    fn versions(&self, repository: &str) -> Result<Vec<String>> {
+        self.get_all_versions(repository)
+    }
+
+    fn latest(&self, repository: &str) -> Result<Version> {
+        self.get_latest_version(repository)
+    }
+
+    fn releases(&self, repository: &str) -> Result<Vec<ReleaseItem>> {
+        self.query(repository)
+    }
+}
+
+#[cfg(feature = "gitea")]
+impl ReleaseSource for crate::Gitea {
+    fn versions(&self, repository: &str) -> Result<Vec<String>> {
+        self.get_all_versions(repository)
+    }
+
+    fn latest(&self, repository: &str) -> Result<Version> {
+        self.get_latest_version(repository)
+    }
+
+    fn releases(&self, repository: &str) -> Result<Vec<ReleaseItem>> {
+        self.query(repository)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReleaseSource;
+    use crate::{
+        tests::{mock_github, release_json},
+        LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::{json, Value};
+
+    fn release(tag: &str, html_url: &str) -> Value {
+        release_json(
+            tag,
+            json!({ "html_url": html_url, "published_at": "2022-01-01T00:00:00Z", "body": "notes" }),
+        )
+    }
+
+    fn source() -> Box<dyn ReleaseSource> {
+        Box::new(mock_github())
+    }
+
+    #[test]
+    fn test_trait_object_versions_and_latest() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release("v1.0.0", ""), release("v1.9.10", "")]).to_string())
+            .create();
+        let source = source();
+        assert_eq!(
+            source.versions("foo/bar").unwrap(),
+            vec!["v1.0.0", "v1.9.10"]
+        );
+        assert_eq!(
+            source.latest("foo/bar").unwrap(),
+            semver::Version::new(1, 9, 10)
+        );
+    }
+
+    #[test]
+    fn test_trait_object_releases() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!([release(
+                    "v1.0.0",
+                    "https://github.com/foo/bar/releases/tag/v1.0.0"
+                )])
+                .to_string(),
+            )
+            .create();
+        let releases = source().releases("foo/bar").unwrap();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].tag_name, "v1.0.0");
+        assert_eq!(releases[0].body.as_deref(), Some("notes"));
+        assert_eq!(
+            releases[0].html_url,
+            "https://github.com/foo/bar/releases/tag/v1.0.0"
+        );
+        assert_eq!(
+            releases[0].published_at.as_deref(),
+            Some("2022-01-01T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_trait_object_draft_has_no_date() {
+        let _m = mock("GET", "/repos/foo/source-draft/releases")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!([
+                    release_json("v2.0.0", json!({ "draft": true, "published_at": null })),
+                    release("v1.0.0", ""),
+                ])
+                .to_string(),
+            )
+            .create();
+        let releases = source().releases("foo/source-draft").unwrap();
+        assert_eq!(releases[0].tag_name, "v2.0.0");
+        assert_eq!(releases[0].published_at, None);
+        assert_eq!(
+            releases[1].published_at.as_deref(),
+            Some("2022-01-01T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_trait_object_errors() {
+        let _m = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        assert!(matches!(
+            source().latest("foo/bar"),
+            Err(LookupError::RepositoryNotFound)
+        ));
+    }
+}
@@ -0,0 +1,356 @@
+//! Naming a repository by `"owner/repo"`, by owner and name, by ID, or by URL.
+
+use crate::{encode_segment, GitHub, LookupError};
+use reqwest::Url;
+use std::{convert::Infallible, fmt};
+
+/// A repository to look up.
+///
+/// Functions that take a repository accept anything that converts into one:
+///
+/// - `"owner/repo"`, as a `&str` or `String`
+/// - a URL of the repository, like `"https://github.com/owner/repo"` or its
+///   `.git` clone URL
+/// - an `(owner, repo)` pair of `String`s
+/// - the numeric ID of the repository, as a `u64`, see
+///   [`GitHub::resolve_repo_id`]
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::{GitHub, RepositorySpec};
+/// let github = GitHub::new().unwrap();
+/// let by_name = github.get_latest_version("celeo/github_release_check").unwrap();
+/// let by_url = github
+///     .get_latest_version("https://github.com/celeo/github_release_check")
+///     .unwrap();
+/// let by_id = github.get_latest_version(494_509_686_u64).unwrap();
+/// let spec = RepositorySpec::Name("celeo".into(), "github_release_check".into());
+/// let by_spec = github.get_latest_version(spec).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RepositorySpec {
+    /// The owner and name of the repository.
+    Name(String, String),
+    /// The numeric ID of the repository, which stays the same across renames.
+    Id(u64),
+}
+
+impl RepositorySpec {
+    /// Read `"owner/repo"`, or the owner and name from a URL of the repository.
+    ///
+    /// For URLs, the first two path segments are the owner and name, after a
+    /// leading `repos/` like in API URLs. Anything after them, like
+    /// `/releases`, is ignored, as is a `.git` suffix.
+    fn parse(repository: &str) -> Option<Self> {
+        let repository = repository.trim();
+        let path = if repository.contains("://") {
+            let url = Url::parse(repository).ok()?;
+            let segments: Vec<_> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+            let segments = match segments.as_slice() {
+                ["repos", rest @ ..] => rest.to_vec(),
+                _ => segments,
+            };
+            match segments.as_slice() {
+                [owner, repo, ..] => format!("{owner}/{repo}"),
+                _ => return None,
+            }
+        } else {
+            repository.to_owned()
+        };
+        let (owner, repo) = path.split_once('/')?;
+        let repo = repo.trim_end_matches('/');
+        let repo = repo.strip_suffix(".git").unwrap_or(repo);
+        if owner.is_empty() || repo.is_empty() || repo.contains('/') {
+            return None;
+        }
+        Some(Self::Name(owner.to_owned(), repo.to_owned()))
+    }
+}
+
+impl fmt::Display for RepositorySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(owner, repo) => write!(f, "{owner}/{repo}"),
+            Self::Id(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+impl TryFrom<&str> for RepositorySpec {
+    type Error = LookupError;
+
+    fn try_from(repository: &str) -> Result<Self, Self::Error> {
+        Self::parse(repository).ok_or_else(|| LookupError::InvalidRepository(repository.to_owned()))
+    }
+}
+
+impl TryFrom<&String> for RepositorySpec {
+    type Error = LookupError;
+
+    fn try_from(repository: &String) -> Result<Self, Self::Error> {
+        Self::try_from(repository.as_str())
+    }
+}
+
+impl TryFrom<String> for RepositorySpec {
+    type Error = LookupError;
+
+    fn try_from(repository: String) -> Result<Self, Self::Error> {
+        Self::try_from(repository.as_str())
+    }
+}
+
+impl From<(String, String)> for RepositorySpec {
+    fn from((owner, repo): (String, String)) -> Self {
+        Self::Name(owner, repo)
+    }
+}
+
+impl From<u64> for RepositorySpec {
+    fn from(id: u64) -> Self {
+        Self::Id(id)
+    }
+}
+
+impl From<&RepositorySpec> for RepositorySpec {
+    fn from(spec: &RepositorySpec) -> Self {
+        spec.clone()
+    }
+}
+
+impl From<Infallible> for LookupError {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
+
+impl GitHub {
+    /// Build the API URL of the path under the repository.
+    pub(crate) fn spec_url(&self, spec: &RepositorySpec, path: &str) -> String {
+        match spec {
+            RepositorySpec::Name(owner, repo) => format!(
+                "{}repos/{}/{}/{path}",
+                self.api_root,
+                encode_segment(owner),
+                encode_segment(repo)
+            ),
+            RepositorySpec::Id(id) => format!("{}repositories/{id}/{path}", self.api_root),
+        }
+    }
+
+    /// Build the website URL of the path under the repository.
+    ///
+    /// The website only knows repositories by name, so this fails with
+    /// [`LookupError::InvalidRepository`] for an ID.
+    pub(crate) fn spec_web_url(
+        &self,
+        spec: &RepositorySpec,
+        path: &str,
+    ) -> Result<String, LookupError> {
+        match spec {
+            RepositorySpec::Name(owner, repo) => Ok(format!(
+                "{}{}/{}/{path}",
+                self.web_root,
+                encode_segment(owner),
+                encode_segment(repo)
+            )),
+            RepositorySpec::Id(id) => Err(LookupError::InvalidRepository(id.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RepositorySpec;
+    use crate::{
+        tests::{mock_github, release_json},
+        LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn name(owner: &str, repo: &str) -> RepositorySpec {
+        RepositorySpec::Name(owner.to_owned(), repo.to_owned())
+    }
+
+    #[test]
+    fn test_parse() {
+        let parse = |s: &str| RepositorySpec::try_from(s).ok();
+        assert_eq!(parse("foo/bar"), Some(name("foo", "bar")));
+        assert_eq!(parse(" foo/bar "), Some(name("foo", "bar")));
+        assert_eq!(
+            parse("https://github.com/foo/bar"),
+            Some(name("foo", "bar"))
+        );
+        assert_eq!(
+            parse("https://github.com/foo/bar.git"),
+            Some(name("foo", "bar"))
+        );
+        assert_eq!(
+            parse("https://github.com/foo/bar/releases/tag/v1.0.0"),
+            Some(name("foo", "bar"))
+        );
+        assert_eq!(
+            parse("https://api.github.com/repos/foo/bar"),
+            Some(name("foo", "bar"))
+        );
+        assert_eq!(parse("foo"), None);
+        assert_eq!(parse("foo/"), None);
+        assert_eq!(parse("foo/bar/baz"), None);
+        assert_eq!(parse("https://github.com/foo"), None);
+        assert!(matches!(
+            RepositorySpec::try_from("foo"),
+            Err(LookupError::InvalidRepository(repository)) if repository == "foo"
+        ));
+    }
+
+    #[test]
+    fn test_conversions_compile() {
+        fn accepts<R>(repository: R) -> RepositorySpec
+        where
+            R: TryInto<RepositorySpec>,
+            LookupError: From<R::Error>,
+        {
+            repository.try_into().map_err(LookupError::from).unwrap()
+        }
+        let owned = String::from("foo/bar");
+        assert_eq!(accepts("foo/bar"), name("foo", "bar"));
+        assert_eq!(accepts(&owned), name("foo", "bar"));
+        assert_eq!(accepts(owned), name("foo", "bar"));
+        assert_eq!(
+            accepts((String::from("foo"), String::from("bar"))),
+            name("foo", "bar")
+        );
+        assert_eq!(accepts(42_u64), RepositorySpec::Id(42));
+        let spec = RepositorySpec::Id(7);
+        assert_eq!(accepts(&spec), spec);
+        assert_eq!(accepts(spec.clone()), spec);
+        assert_eq!(name("foo", "bar").to_string(), "foo/bar");
+        assert_eq!(spec.to_string(), "7");
+    }
+
+    #[test]
+    fn test_query_each_variant() {
+        let body = json!([release_json("v2.1.0", json!({}))]).to_string();
+        let by_name = mock("GET", "/repos/foo/spec/releases")
+            .match_query(Matcher::Any)
+            .with_body(&body)
+            .expect(3)
+            .create();
+        let by_id = mock("GET", "/repositories/8080/releases")
+            .match_query(Matcher::Any)
+            .with_body(&body)
+            .create();
+        let github = mock_github();
+        let expected = Version::new(2, 1, 0);
+        assert_eq!(github.get_latest_version("foo/spec").unwrap(), expected);
+        assert_eq!(
+            github
+                .get_latest_version(format!("{}/foo/spec.git", mockito::server_url()))
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            github
+                .get_latest_version((String::from("foo"), String::from("spec")))
+                .unwrap(),
+            expected
+        );
+        assert_eq!(github.get_latest_version(8080_u64).unwrap(), expected);
+        by_name.assert();
+        by_id.assert();
+    }
+
+    #[test]
+    fn test_marked_latest_by_id() {
+        let _m = mock("GET", "/repositories/8081/releases/latest")
+            .with_body(release_json("v0.3.0", json!({})).to_string())
+            .create();
+        assert_eq!(
+            mock_github().get_marked_latest(8081_u64).unwrap().tag_name,
+            "v0.3.0"
+        );
+    }
+
+    #[test]
+    fn test_other_lookups_by_id_and_url() {
+        let body = json!([release_json(
+            "v1.4.0",
+            json!({ "html_url": "https://github.com/foo/spec-ids/releases/tag/v1.4.0" })
+        )])
+        .to_string();
+        let by_id = mock("GET", "/repositories/8082/releases")
+            .match_query(Matcher::Any)
+            .with_body(&body)
+            .expect(4)
+            .create();
+        let by_url = mock("GET", "/repos/foo/spec-urls/releases")
+            .match_query(Matcher::Any)
+            .with_body(&body)
+            .expect(2)
+            .create();
+        let github = mock_github();
+        let url = "https://github.com/foo/spec-urls";
+        assert_eq!(
+            github.query_page(8082_u64, 1, 100).unwrap().releases.len(),
+            1
+        );
+        assert_eq!(
+            github.get_all_versions_minimal(8082_u64).unwrap(),
+            ["v1.4.0"]
+        );
+        assert_eq!(
+            github.get_latest_release_url(8082_u64).unwrap(),
+            "https://github.com/foo/spec-ids/releases/tag/v1.4.0"
+        );
+        assert_eq!(
+            github
+                .get_previous_version(8082_u64, &Version::new(2, 0, 0), false)
+                .unwrap()
+                .map(|tagged| tagged.tag),
+            Some("v1.4.0".to_owned())
+        );
+        assert_eq!(github.query_raw(url).unwrap().len(), 1);
+        assert_eq!(
+            github.get_latest_by_date(url, false).unwrap().tag_name,
+            "v1.4.0"
+        );
+        by_id.assert();
+        by_url.assert();
+    }
+
+    #[test]
+    fn test_web_links_need_a_name() {
+        let github = mock_github();
+        assert!(matches!(
+            github.asset_download_url(8083_u64, "v1.0.0", "tool.tar.gz", &HashMap::new(), false),
+            Err(LookupError::InvalidRepository(id)) if id == "8083"
+        ));
+        assert_eq!(
+            github
+                .asset_download_url(
+                    "https://github.com/foo/spec-links",
+                    "v1.0.0",
+                    "tool.tar.gz",
+                    &HashMap::new(),
+                    false
+                )
+                .unwrap(),
+            format!(
+                "{}foo/spec-links/releases/download/v1.0.0/tool.tar.gz",
+                github.web_root()
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_repository() {
+        assert!(matches!(
+            mock_github().query("not-a-repository"),
+            Err(LookupError::InvalidRepository(_))
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+//! Counting what a query cost, for capacity planning.
+
+use crate::{Call, GitHub, GitHubReleaseItem, LookupError, RepositorySpec, Result};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// What a single query cost.
+///
+/// See [`GitHub::query_with_stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct QueryStats {
+    /// How many HTTP requests were sent, including retries.
+    pub requests: usize,
+    /// How many of the requests were retries of server errors.
+    pub retries: usize,
+    /// How many responses were 304 Not Modified.
+    pub not_modified: usize,
+    /// How many bytes of release data were received.
+    pub bytes: u64,
+    /// How long the query took, including any waiting.
+    pub elapsed: Duration,
+    /// The rate limit remaining according to the last response, if it said.
+    pub rate_limit_remaining: Option<u64>,
+    /// The `x-github-request-id` of the last response that had one.
+    pub request_id: Option<String>,
+    /// The `Content-Encoding` of the last response that had one, like `"gzip"`.
+    ///
+    /// See [`GitHubBuilder::compression`](crate::GitHubBuilder::compression).
+    pub content_encoding: Option<String>,
+}
+
+impl QueryStats {
+    /// Take the counters from the call.
+    fn new(call: &Call, elapsed: Duration) -> Self {
+        Self {
+            requests: call.requests,
+            retries: call.retries,
+            not_modified: call.not_modified,
+            bytes: call.bytes,
+            elapsed,
+            rate_limit_remaining: call.rate_limit_remaining,
+            request_id: call.request_id.clone(),
+            content_encoding: call.content_encoding.clone(),
+        }
+    }
+}
+
+impl GitHub {
+    /// Get all releases from the repository, with what fetching them cost.
+    ///
+    /// The releases are the same as from [`GitHub::query`]. Unlike `query`, this
+    /// call always sends its own requests, even with
+    /// [`GitHubBuilder::coalesce_requests`](crate::GitHubBuilder::coalesce_requests)
+    /// set, so that the counts are its own.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let (releases, stats) = github.query_with_stats("celeo/github_release_check").unwrap();
+    /// println!("{} releases in {} requests", releases.len(), stats.requests);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn query_with_stats<R>(&self, repository: R) -> Result<(Vec<GitHubReleaseItem>, QueryStats)>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec = repository.try_into()?;
+        let started = Instant::now();
+        let mut call = Call::default();
+        let releases = self.fetch_releases_in(&spec, &mut call)?.releases;
+        Ok((releases, QueryStats::new(&call, started.elapsed())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::release_json, GitHub};
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    fn github(retries: usize) -> GitHub {
+        GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .retry_server_errors(retries)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_stats_across_pages() {
+        let link = |page: usize| {
+            format!(
+                "<{}/repos/foo/counted/releases?per_page=100&page={page}>; rel=\"next\", <{}/repos/foo/counted/releases?per_page=100&page=3>; rel=\"last\"",
+                mockito::server_url(),
+                mockito::server_url()
+            )
+        };
+        let body = |id: usize| json!([release_json(&format!("v{id}.0.0"), json!({}))]).to_string();
+        let mocks: Vec<_> = (1..=3)
+            .map(|page| {
+                let m = mock("GET", "/repos/foo/counted/releases")
+                    .match_query(Matcher::UrlEncoded("page".into(), page.to_string()))
+                    .with_header("x-ratelimit-remaining", &(5000 - page).to_string())
+                    .with_header("x-github-request-id", &format!("ABCD:{page}"))
+                    .with_body(body(page));
+                if page < 3 {
+                    m.with_header("link", &link(page + 1)).create()
+                } else {
+                    m.create()
+                }
+            })
+            .collect();
+        let (releases, stats) = github(0).query_with_stats("foo/counted").unwrap();
+        assert_eq!(releases.len(), 3);
+        assert_eq!(stats.requests, 3);
+        assert_eq!(stats.retries, 0);
+        assert_eq!(stats.not_modified, 0);
+        assert_eq!(
+            stats.bytes,
+            (1..=3).map(|id| body(id).len() as u64).sum::<u64>()
+        );
+        assert_eq!(stats.rate_limit_remaining, Some(4997));
+        assert_eq!(stats.request_id.as_deref(), Some("ABCD:3"));
+        for m in &mocks {
+            m.assert();
+        }
+    }
+
+    #[test]
+    fn test_stats_with_retry() {
+        let failing = mock("GET", "/repos/foo/flaky/releases")
+            .match_query(Matcher::Any)
+            .with_status(502)
+            .expect(1)
+            .create();
+        let _ok = mock("GET", "/repos/foo/flaky/releases")
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json("v1.0.0", json!({}))]).to_string())
+            .create();
+        let (releases, stats) = github(2).query_with_stats("foo/flaky").unwrap();
+        failing.assert();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.retries, 1);
+    }
+
+    #[test]
+    fn test_server_error_without_retries() {
+        let _failing = mock("GET", "/repos/foo/down/releases")
+            .match_query(Matcher::Any)
+            .with_status(502)
+            .create();
+        assert!(github(0).query_with_stats("foo/down").is_err());
+    }
+}
@@ -0,0 +1,401 @@
+//! Following many repositories, each with its own rules for which releases count.
+
+use crate::{
+    batch::run_batch, versions, GitHub, LookupError, RepositorySpec, Result, TaggedVersion,
+};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+/// A repository to follow, with the version in use and which releases count.
+///
+/// # Example
+///
+/// ```rust
+/// use github_release_check::Subscription;
+/// use semver::{Version, VersionReq};
+///
+/// let subscription = Subscription {
+///     version_req: Some(VersionReq::parse("2.x").unwrap()),
+///     ..Subscription::new("celeo/github_release_check", Version::new(2, 4, 0))
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subscription {
+    /// The repository, as `"owner/repo"`.
+    pub repository: String,
+    /// The version in use, which releases are compared against.
+    pub current: Version,
+    /// Only releases with versions matching this count, like `"2.x"` to stay
+    /// on version 2.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_req: Option<VersionReq>,
+    /// Whether pre-releases count. A pre-release is matched against
+    /// `version_req` as the version it comes before, so `2.1.0-beta.1` matches
+    /// `"2.x"`.
+    #[serde(default)]
+    pub include_prereleases: bool,
+    /// Tags that never count, in addition to those ignored by the [`GitHub`]
+    /// instance. They're compared the same way as in
+    /// [`GitHubBuilder::ignore_tags`](crate::GitHubBuilder::ignore_tags).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignored_tags: Vec<String>,
+}
+
+impl Subscription {
+    /// Follow every stable release of the repository.
+    #[must_use]
+    pub fn new(repository: &str, current: Version) -> Self {
+        Self {
+            repository: repository.to_owned(),
+            current,
+            version_req: None,
+            include_prereleases: false,
+            ignored_tags: Vec::new(),
+        }
+    }
+
+    /// The highest of the versions that count for this subscription.
+    fn latest<'a>(
+        &self,
+        candidates: &'a [(TaggedVersion, bool)],
+        prefixes: &[String],
+    ) -> Option<&'a TaggedVersion> {
+        candidates
+            .iter()
+            .filter(|(_, prerelease)| self.include_prereleases || !prerelease)
+            .map(|(tagged, _)| tagged)
+            .filter(|tagged| {
+                let tag = versions::normalize_tag(&tagged.tag, prefixes);
+                !self
+                    .ignored_tags
+                    .iter()
+                    .any(|ignored| versions::normalize_tag(ignored, prefixes) == tag)
+            })
+            .filter(|tagged| {
+                self.version_req.as_ref().map_or(true, |req| {
+                    let mut version = tagged.version.clone();
+                    version.pre = semver::Prerelease::EMPTY;
+                    req.matches(&version)
+                })
+            })
+            .max_by(|a, b| a.version.cmp(&b.version))
+    }
+}
+
+/// What [`Subscriptions::check_all`] found for a subscription.
+///
+/// It's written as JSON with the variant name in a `"status"` field, like
+/// `{"status": "Outdated", "repository": "foo/bar", "current": "1.0.0", "latest": "1.1.0"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status")]
+pub enum SubscriptionStatus {
+    /// No release that counts is newer than the version in use.
+    UpToDate {
+        /// The repository.
+        repository: String,
+        /// The version in use.
+        current: Version,
+    },
+    /// A newer release that counts is available.
+    Outdated {
+        /// The repository.
+        repository: String,
+        /// The version in use.
+        current: Version,
+        /// The highest release version that counts.
+        latest: Version,
+        /// The tag of that release.
+        tag: String,
+    },
+    /// The releases couldn't be looked up, or none of them count.
+    Error {
+        /// The repository.
+        repository: String,
+        /// Why the lookup failed.
+        message: String,
+        /// The [code](crate::LookupError::code) of the error.
+        code: String,
+    },
+}
+
+impl SubscriptionStatus {
+    /// The repository the status is for.
+    #[must_use]
+    pub fn repository(&self) -> &str {
+        match self {
+            Self::UpToDate { repository, .. }
+            | Self::Outdated { repository, .. }
+            | Self::Error { repository, .. } => repository,
+        }
+    }
+}
+
+/// A list of repositories to follow, to keep in a file.
+///
+/// The list serializes as an object with a `subscriptions` array. It can be
+/// kept as JSON with [`Subscriptions::to_json_pretty`] and
+/// [`Subscriptions::from_json`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subscriptions {
+    /// The repositories, in the order they're checked and reported.
+    pub subscriptions: Vec<Subscription>,
+}
+
+impl Subscriptions {
+    /// Write the list as indented JSON.
+    ///
+    /// # Panics
+    ///
+    /// This function doesn't panic in practice: everything in the list can be
+    /// written as JSON.
+    #[must_use]
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Subscriptions always serialize")
+    }
+
+    /// Read a list written by [`Subscriptions::to_json_pretty`].
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the JSON isn't a list of subscriptions.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Check every subscription for a newer release that counts.
+    ///
+    /// Versions are read the same way as in [`GitHub::get_latest_version`],
+    /// including the tags the instance ignores, and then narrowed down by each
+    /// subscription's rules. Lookups run concurrently like in
+    /// [`GitHub::get_latest_versions`]. A failed lookup, or a repository with
+    /// no release that counts, is reported as [`SubscriptionStatus::Error`]
+    /// rather than stopping the check.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{GitHub, SubscriptionStatus, Subscriptions};
+    /// let github = GitHub::new().unwrap();
+    /// let subscriptions =
+    ///     Subscriptions::from_json(&std::fs::read_to_string("subscriptions.json").unwrap())
+    ///         .unwrap();
+    /// for status in subscriptions.check_all(&github) {
+    ///     if let SubscriptionStatus::Outdated { repository, latest, .. } = status {
+    ///         println!("{repository} has {latest}");
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn check_all(&self, github: &GitHub) -> Vec<SubscriptionStatus> {
+        let repositories: Vec<&str> = self
+            .subscriptions
+            .iter()
+            .map(|subscription| subscription.repository.as_str())
+            .collect();
+        let prefixes = &github.settings.version_prefixes;
+        run_batch(github, &repositories, |repository| {
+            github.subscription_candidates(repository)
+        })
+        .into_iter()
+        .zip(&self.subscriptions)
+        .map(|((repository, candidates), subscription)| {
+            let latest = candidates.and_then(|candidates| {
+                subscription
+                    .latest(&candidates, prefixes)
+                    .cloned()
+                    .ok_or(LookupError::NoReleases)
+            });
+            match latest {
+                Ok(latest) if latest.version > subscription.current => {
+                    SubscriptionStatus::Outdated {
+                        repository,
+                        current: subscription.current.clone(),
+                        latest: latest.version,
+                        tag: latest.tag,
+                    }
+                }
+                Ok(_) => SubscriptionStatus::UpToDate {
+                    repository,
+                    current: subscription.current.clone(),
+                },
+                Err(e) => SubscriptionStatus::Error {
+                    repository,
+                    message: e.to_string(),
+                    code: e.code().to_owned(),
+                },
+            }
+        })
+        .collect()
+    }
+}
+
+impl GitHub {
+    /// Get the versions of the repository's releases, each with whether it's
+    /// a pre-release, either by its version or by how the release is marked.
+    fn subscription_candidates(&self, repository: &str) -> Result<Vec<(TaggedVersion, bool)>> {
+        Ok(self
+            .candidate_releases(&RepositorySpec::try_from(repository)?)?
+            .releases
+            .into_iter()
+            .map(|(tagged, release)| {
+                let prerelease =
+                    !tagged.version.pre.is_empty() || release.is_some_and(|r| r.prerelease);
+                (tagged, prerelease)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Subscription, SubscriptionStatus, Subscriptions};
+    use crate::tests::{mock_github, release_json};
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+
+    const SUBSCRIPTIONS: &str = include_str!("../tests/fixtures/subscriptions.json");
+
+    fn release(tag: &str, prerelease: bool) -> String {
+        release_json(tag, json!({ "prerelease": prerelease })).to_string()
+    }
+
+    fn mock_releases(repository: &str, releases: &[String]) -> mockito::Mock {
+        mock("GET", format!("/repos/{repository}/releases").as_str())
+            .match_query(Matcher::Any)
+            .with_body(format!("[{}]", releases.join(", ")))
+            .create()
+    }
+
+    #[test]
+    fn test_check_all() {
+        let _pinned = mock_releases(
+            "foo/sub-pinned",
+            &[
+                release("v3.0.0", false),
+                release("v2.5.0", false),
+                release("v2.4.0", false),
+            ],
+        );
+        let _prerelease = mock_releases(
+            "foo/sub-prerelease",
+            &[
+                release("v1.1.0-beta.1", true),
+                release("v1.0.1", true),
+                release("v1.0.0", false),
+            ],
+        );
+        let _ignored = mock_releases(
+            "foo/sub-ignored",
+            &[
+                release("v1.3.0", false),
+                release("v1.2.0", false),
+                release("v1.2.1-rc.1", false),
+            ],
+        );
+        let subscriptions = Subscriptions::from_json(SUBSCRIPTIONS).unwrap();
+        assert_eq!(subscriptions.subscriptions.len(), 3);
+        assert_eq!(
+            subscriptions.check_all(&mock_github()),
+            vec![
+                // stays on 2.x, so 3.0.0 doesn't count
+                SubscriptionStatus::Outdated {
+                    repository: "foo/sub-pinned".to_owned(),
+                    current: Version::new(2, 4, 0),
+                    latest: Version::new(2, 5, 0),
+                    tag: "v2.5.0".to_owned(),
+                },
+                // pre-releases count, whether by version or by how they're marked
+                SubscriptionStatus::Outdated {
+                    repository: "foo/sub-prerelease".to_owned(),
+                    current: Version::new(1, 0, 0),
+                    latest: Version::parse("1.1.0-beta.1").unwrap(),
+                    tag: "v1.1.0-beta.1".to_owned(),
+                },
+                // 1.3.0 is ignored, and the release candidate is a pre-release
+                SubscriptionStatus::UpToDate {
+                    repository: "foo/sub-ignored".to_owned(),
+                    current: Version::new(1, 2, 0),
+                },
+            ]
+        );
+
+        // without the rules, 3.0.0 and 1.3.0 count, and no pre-release does
+        let plain = Subscriptions {
+            subscriptions: subscriptions
+                .subscriptions
+                .iter()
+                .map(|s| Subscription::new(&s.repository, s.current.clone()))
+                .collect(),
+        };
+        let latest: Vec<_> = plain
+            .check_all(&mock_github())
+            .into_iter()
+            .map(|status| match status {
+                SubscriptionStatus::Outdated { latest, .. } => Some(latest.to_string()),
+                SubscriptionStatus::UpToDate { .. } => None,
+                SubscriptionStatus::Error { .. } => panic!("unexpected status: {status:?}"),
+            })
+            .collect();
+        assert_eq!(
+            latest,
+            vec![Some("3.0.0".to_owned()), None, Some("1.3.0".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_check_all_errors() {
+        let _missing = mock("GET", "/repos/foo/sub-missing/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        let _old = mock_releases("foo/sub-old", &[release("v1.0.0", false)]);
+        let subscriptions = Subscriptions {
+            subscriptions: vec![
+                Subscription::new("foo/sub-missing", Version::new(1, 0, 0)),
+                Subscription {
+                    version_req: Some("^2".parse().unwrap()),
+                    ..Subscription::new("foo/sub-old", Version::new(2, 0, 0))
+                },
+            ],
+        };
+        let statuses = subscriptions.check_all(&mock_github());
+        let codes: Vec<_> = statuses
+            .iter()
+            .map(|status| match status {
+                SubscriptionStatus::Error { code, .. } => code.as_str(),
+                other => panic!("unexpected status: {other:?}"),
+            })
+            .collect();
+        assert_eq!(codes, vec!["repo_not_found", "no_releases"]);
+        assert_eq!(statuses[1].repository(), "foo/sub-old");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let subscriptions = Subscriptions::from_json(SUBSCRIPTIONS).unwrap();
+        let json = subscriptions.to_json_pretty();
+        assert_eq!(Subscriptions::from_json(&json).unwrap(), subscriptions);
+        assert!(!json.contains("version_req\": null"));
+
+        let minimal = Subscriptions::from_json(
+            r#"{"subscriptions": [{"repository": "foo/bar", "current": "1.0.0"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            minimal.subscriptions,
+            vec![Subscription::new("foo/bar", Version::new(1, 0, 0))]
+        );
+        assert!(Subscriptions::from_json("[]").is_err());
+
+        let status = serde_json::to_value(SubscriptionStatus::UpToDate {
+            repository: "foo/bar".to_owned(),
+            current: Version::new(1, 0, 0),
+        })
+        .unwrap();
+        assert_eq!(
+            status,
+            serde_json::json!({"status": "UpToDate", "repository": "foo/bar", "current": "1.0.0"})
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Releases paired with the names users know them by.
+
+use crate::{versions, GitHub, LookupError, RepositorySpec, Result};
+use semver::Version;
+use std::cmp::Ordering;
+
+/// A release's version alongside what to show for it.
+///
+/// See [`GitHub::get_release_summaries`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseSummary {
+    /// The version parsed from the tag, if the tag is a version.
+    pub version: Option<Version>,
+    /// The tag name.
+    pub tag: String,
+    /// The release name, or the tag name if the release has no name.
+    pub display_name: String,
+    /// When the release was published, if it has been.
+    pub published_at: Option<String>,
+    /// Link to the release page.
+    pub html_url: String,
+}
+
+impl GitHub {
+    /// Get a summary of every release from the repository, for display.
+    ///
+    /// The summaries are ordered by version, newest first, with releases whose
+    /// tags aren't versions after them, most recent first by the date set with
+    /// [`GitHubBuilder::date_field`](crate::GitHubBuilder::date_field) and then as
+    /// in [`DateField::compare`](crate::DateField::compare).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// for summary in github.get_release_summaries("celeo/github_release_check").unwrap() {
+    ///     println!("{} ({})", summary.display_name, summary.tag);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `query`.
+    pub fn get_release_summaries<R>(&self, repository: R) -> Result<Vec<ReleaseSummary>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec: RepositorySpec = repository.try_into()?;
+        let field = self.settings.date_field;
+        let mut releases = self.partial_query(&spec)?.releases;
+        // most recent first, which the sort by version below keeps for ties
+        releases.sort_by(|a, b| field.compare(b, a));
+        let mut summaries: Vec<_> = releases
+            .into_iter()
+            .map(|release| {
+                let html_url = self.release_url(&spec, &release.tag_name, release.html_url);
+                ReleaseSummary {
+                    version: versions::parse_tag(
+                        &release.tag_name,
+                        &self.settings.version_prefixes,
+                    ),
+                    display_name: release
+                        .name
+                        .filter(|name| !name.trim().is_empty())
+                        .unwrap_or_else(|| release.tag_name.clone()),
+                    tag: release.tag_name,
+                    published_at: Some(release.published_at).filter(|at| !at.is_empty()),
+                    html_url,
+                }
+            })
+            .collect();
+        summaries.sort_by(display_order);
+        Ok(summaries)
+    }
+}
+
+/// Versions newest first, then releases without versions.
+///
+/// Releases without versions are all equal, so a stable sort keeps them in the
+/// order they're already in.
+fn display_order(a: &ReleaseSummary, b: &ReleaseSummary) -> Ordering {
+    match (&a.version, &b.version) {
+        (Some(a), Some(b)) => b.cmp(a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{mock_github, release_json};
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+
+    #[test]
+    fn test_release_summaries() {
+        let _m = mock("GET", "/repos/foo/summaries/releases")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!([
+                    release_json(
+                        "nightly-2",
+                        json!({ "id": 5, "name": null, "published_at": "2024-03-02T00:00:00Z", "prerelease": true }),
+                    ),
+                    release_json(
+                        "v1.10.0",
+                        json!({
+                            "id": 4, "name": "MyApp 1.10 — Aurora", "published_at": "2024-02-01T00:00:00Z",
+                            "html_url": "https://github.com/foo/summaries/releases/tag/v1.10.0",
+                        }),
+                    ),
+                    release_json(
+                        "nightly-1",
+                        json!({ "id": 3, "name": "  ", "published_at": "2024-03-01T00:00:00Z", "prerelease": true }),
+                    ),
+                    release_json("draft-thing", json!({ "id": 2, "name": "Draft", "draft": true })),
+                    release_json(
+                        "v1.9.0",
+                        json!({ "name": null, "published_at": "2024-01-01T00:00:00Z" }),
+                    ),
+                ])
+                .to_string(),
+            )
+            .create();
+        let github = mock_github();
+        let summaries = github.get_release_summaries("foo/summaries").unwrap();
+
+        let tags: Vec<_> = summaries.iter().map(|s| s.tag.as_str()).collect();
+        assert_eq!(
+            tags,
+            vec!["v1.10.0", "v1.9.0", "nightly-2", "nightly-1", "draft-thing"]
+        );
+        let names: Vec<_> = summaries.iter().map(|s| s.display_name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "MyApp 1.10 \u{2014} Aurora",
+                "v1.9.0",
+                "nightly-2",
+                "nightly-1",
+                "Draft"
+            ]
+        );
+        assert_eq!(summaries[0].version, Some(Version::new(1, 10, 0)));
+        assert_eq!(summaries[2].version, None);
+        assert_eq!(summaries[4].published_at, None);
+        assert_eq!(
+            summaries[0].html_url,
+            "https://github.com/foo/summaries/releases/tag/v1.10.0"
+        );
+        assert!(summaries[1]
+            .html_url
+            .ends_with("/foo/summaries/releases/tag/v1.9.0"));
+    }
+}
@@ -0,0 +1,412 @@
+//! Following release tags to their releases and the commits they point at.
+
+use crate::{
+    encode_segment, redact_url, versions, Call, GitHub, GitHubReleaseItem, LookupError,
+    RepositorySpec, Result,
+};
+use log::debug;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Annotated tags can point at other tags; this limits how far they're followed.
+const MAX_TAG_DEPTH: usize = 8;
+const DEFAULT_TAG_SCAN_THRESHOLD: usize = 10;
+
+/// A git object reference, from either a ref or an annotated tag.
+#[derive(Debug, Deserialize)]
+struct GitObject {
+    sha: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// Body of the ref and tag object endpoints, which both point at an object.
+#[derive(Debug, Deserialize)]
+struct Pointer {
+    object: GitObject,
+}
+
+/// The tag without its version prefix, if prefixes are being ignored.
+fn comparable<'a>(tag: &'a str, prefixes: Option<&[String]>) -> &'a str {
+    prefixes.map_or(tag, |prefixes| versions::normalize_tag(tag, prefixes))
+}
+
+impl GitHub {
+    /// Get the release for the tag from the by-tag endpoint.
+    pub(crate) fn release_by_tag(
+        &self,
+        spec: &RepositorySpec,
+        tag: &str,
+    ) -> Result<Option<GitHubReleaseItem>> {
+        let url = self.spec_url(spec, &format!("releases/tags/{}", encode_segment(tag)));
+        debug!("Looking up release at {}", redact_url(&url));
+        match self.get(&url, &[], &mut Call::default()) {
+            Ok(response) => Ok(Some(self.read_json(response)?)),
+            Err(LookupError::RepositoryNotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Find which of the tags have releases in the repository.
+    ///
+    /// Tags up to the number set with
+    /// [`GitHubBuilder::tag_scan_threshold`](crate::GitHubBuilder::tag_scan_threshold)
+    /// are looked up one at a time; for more, all releases are fetched and searched.
+    /// Every requested tag is a key in the returned map, with `None` if it has no
+    /// release. To match tags with or without a version prefix, see
+    /// [`GitHubBuilder::ignore_tag_v_prefix`](crate::GitHubBuilder::ignore_tag_v_prefix);
+    /// one at a time, only the bare version and the version with a `'v'` are tried.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let found = github
+    ///     .find_releases_for_tags("celeo/github_release_check", &["v0.2.0", "v0.2.1"])
+    ///     .unwrap();
+    /// let missing: Vec<_> = found.iter().filter(|(_, r)| r.is_none()).map(|(t, _)| t).collect();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::RepositoryNotFound`] if there's no
+    /// such repository, or for any of the reasons in [`GitHub::query`].
+    pub fn find_releases_for_tags<R>(
+        &self,
+        repository: R,
+        tags: &[&str],
+    ) -> Result<HashMap<String, Option<GitHubReleaseItem>>>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec: RepositorySpec = repository.try_into()?;
+        let prefixes = self
+            .settings
+            .ignore_tag_v_prefix
+            .then_some(self.settings.version_prefixes.as_slice());
+        let threshold = self
+            .settings
+            .tag_scan_threshold
+            .unwrap_or(DEFAULT_TAG_SCAN_THRESHOLD);
+
+        if tags.len() > threshold {
+            debug!("Scanning all releases for {} tags", tags.len());
+            let releases = self.partial_query(&spec)?.releases;
+            return Ok(tags
+                .iter()
+                .map(|tag| {
+                    let wanted = comparable(tag, prefixes);
+                    let release = releases
+                        .iter()
+                        .find(|r| comparable(&r.tag_name, prefixes) == wanted)
+                        .cloned();
+                    ((*tag).to_owned(), release)
+                })
+                .collect());
+        }
+
+        let mut found = HashMap::with_capacity(tags.len());
+        for tag in tags {
+            let mut release = self.release_by_tag(&spec, tag)?;
+            if let Some(prefixes) = prefixes {
+                let bare = comparable(tag, Some(prefixes));
+                for other in [bare.to_owned(), format!("v{bare}")] {
+                    if release.is_some() {
+                        break;
+                    }
+                    if other != *tag {
+                        release = self.release_by_tag(&spec, &other)?;
+                    }
+                }
+            }
+            let _prev = found.insert((*tag).to_owned(), release);
+        }
+        if found.values().any(Option::is_none) {
+            // the by-tag endpoint answers 404 for a missing repository too
+            let _id = self.repository_id(&spec)?;
+        }
+        Ok(found)
+    }
+
+    /// Get the SHA of the commit that a release's tag points at.
+    ///
+    /// Both lightweight and annotated tags are supported; annotated tags are
+    /// followed through to their commit.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let sha = github.get_release_commit("celeo/github_release_check", "v0.2.1").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::ReleaseNotFound`] if the tag doesn't
+    /// exist, or for any of the reasons in [`GitHub::query`].
+    pub fn get_release_commit<R>(&self, repository: R, tag: &str) -> Result<String>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let spec = repository.try_into()?;
+        let mut call = Call::default();
+        let url = self.spec_url(&spec, &format!("git/ref/tags/{}", encode_segment(tag)));
+        debug!("Resolving tag {tag} at {}", redact_url(&url));
+        let mut object = match self.get(&url, &[], &mut call) {
+            Err(LookupError::RepositoryNotFound) => return Err(LookupError::ReleaseNotFound),
+            other => self.read_json::<Pointer>(other?)?.object,
+        };
+        for _ in 0..MAX_TAG_DEPTH {
+            if object.kind != "tag" {
+                break;
+            }
+            let url = self.spec_url(&spec, &format!("git/tags/{}", object.sha));
+            debug!("Following annotated tag at {}", redact_url(&url));
+            object = self
+                .read_json::<Pointer>(self.get(&url, &[], &mut call)?)?
+                .object;
+        }
+        if object.kind == "commit" {
+            Ok(object.sha)
+        } else {
+            debug!("Tag {tag} points at a {} object", object.kind);
+            Err(LookupError::ReleaseNotFound)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHub, LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    fn release(tag: &str) -> String {
+        release_json(tag, json!({})).to_string()
+    }
+
+    #[test]
+    fn test_find_releases_by_tag() {
+        let _found = mock("GET", "/repos/foo/bar/releases/tags/v1.0.0")
+            .with_body(release("v1.0.0"))
+            .create();
+        let _missing = mock("GET", "/repos/foo/bar/releases/tags/v9.0.0")
+            .with_status(404)
+            .create();
+        let _bare = mock("GET", "/repos/foo/bar/releases/tags/2.0.0")
+            .with_status(404)
+            .create();
+        let _prefixed = mock("GET", "/repos/foo/bar/releases/tags/v2.0.0")
+            .with_body(release("v2.0.0"))
+            .create();
+        let _repo = mock("GET", "/repos/foo/bar")
+            .with_body(r#"{"id": 1}"#)
+            .create();
+        let scan = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .expect(0)
+            .create();
+
+        let found = mock_github()
+            .find_releases_for_tags("foo/bar", &["v1.0.0", "v9.0.0", "2.0.0"])
+            .unwrap();
+        assert_eq!(found.len(), 3);
+        assert_eq!(found["v1.0.0"].as_ref().unwrap().tag_name, "v1.0.0");
+        assert!(found["v9.0.0"].is_none());
+        assert!(found["2.0.0"].is_none());
+
+        let loose = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .ignore_tag_v_prefix(true)
+            .build()
+            .unwrap();
+        let found = loose.find_releases_for_tags("foo/bar", &["2.0.0"]).unwrap();
+        assert_eq!(found["2.0.0"].as_ref().unwrap().tag_name, "v2.0.0");
+        scan.assert();
+    }
+
+    #[test]
+    fn test_find_releases_by_scan() {
+        let scan = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!("[{}, {}]", release("v1.1.0"), release("1.0.0")))
+            .expect(1)
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .tag_scan_threshold(1)
+            .ignore_tag_v_prefix(true)
+            .build()
+            .unwrap();
+        let found = github
+            .find_releases_for_tags("foo/bar", &["1.1.0", "v1.0.0", "v3.0.0"])
+            .unwrap();
+        assert_eq!(found["1.1.0"].as_ref().unwrap().tag_name, "v1.1.0");
+        assert_eq!(found["v1.0.0"].as_ref().unwrap().tag_name, "1.0.0");
+        assert!(found["v3.0.0"].is_none());
+        scan.assert();
+    }
+
+    #[test]
+    fn test_find_releases_missing_repository() {
+        let _by_tag = mock("GET", "/repos/foo/tags-gone/releases/tags/v1.0.0")
+            .with_status(404)
+            .create();
+        let _repo = mock("GET", "/repos/foo/tags-gone")
+            .with_status(404)
+            .create();
+        let _scan = mock("GET", "/repos/foo/tags-gone/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        assert!(matches!(
+            mock_github().find_releases_for_tags("foo/tags-gone", &["v1.0.0"]),
+            Err(LookupError::RepositoryNotFound)
+        ));
+        let scanning = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .tag_scan_threshold(0)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            scanning.find_releases_for_tags("foo/tags-gone", &["v1.0.0"]),
+            Err(LookupError::RepositoryNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_find_releases_with_version_prefixes() {
+        let _custom = mock("GET", "/repos/foo/tags-prefixed/releases/tags/myapp-2.0.0")
+            .with_status(404)
+            .create();
+        let _bare = mock("GET", "/repos/foo/tags-prefixed/releases/tags/2.0.0")
+            .with_status(404)
+            .create();
+        let by_tag = mock("GET", "/repos/foo/tags-prefixed/releases/tags/v2.0.0")
+            .with_body(release("v2.0.0"))
+            .expect(1)
+            .create();
+        let scan = mock("GET", "/repos/foo/tags-prefixed/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}, {}]",
+                release("V1.1.0"),
+                release("myapp-1.0.0")
+            ))
+            .expect(1)
+            .create();
+        let builder = || {
+            GitHub::builder()
+                .api_root(&format!("{}/", mockito::server_url()))
+                .ignore_tag_v_prefix(true)
+                .version_prefixes(&["myapp-"])
+        };
+
+        let found = builder()
+            .build()
+            .unwrap()
+            .find_releases_for_tags("foo/tags-prefixed", &["myapp-2.0.0"])
+            .unwrap();
+        assert_eq!(found["myapp-2.0.0"].as_ref().unwrap().tag_name, "v2.0.0");
+        by_tag.assert();
+
+        let found = builder()
+            .tag_scan_threshold(0)
+            .build()
+            .unwrap()
+            .find_releases_for_tags("foo/tags-prefixed", &["myapp-1.1.0", "v1.0.0"])
+            .unwrap();
+        assert_eq!(found["myapp-1.1.0"].as_ref().unwrap().tag_name, "V1.1.0");
+        assert_eq!(found["v1.0.0"].as_ref().unwrap().tag_name, "myapp-1.0.0");
+        scan.assert();
+    }
+
+    #[test]
+    fn test_lightweight_tag() {
+        let _m = mock("GET", "/repos/foo/bar/git/ref/tags/v1.0.0")
+            .with_body(r#"{"ref": "refs/tags/v1.0.0", "object": {"sha": "c0ffee", "type": "commit", "url": ""}}"#)
+            .create();
+        assert_eq!(
+            mock_github()
+                .get_release_commit("foo/bar", "v1.0.0")
+                .unwrap(),
+            "c0ffee"
+        );
+    }
+
+    #[test]
+    fn test_annotated_tag() {
+        let _ref = mock("GET", "/repos/foo/bar/git/ref/tags/v2.0.0")
+            .with_body(r#"{"ref": "refs/tags/v2.0.0", "object": {"sha": "7a9", "type": "tag", "url": ""}}"#)
+            .create();
+        let tag = mock("GET", "/repos/foo/bar/git/tags/7a9")
+            .with_body(r#"{"tag": "v2.0.0", "sha": "7a9", "message": "", "object": {"sha": "beef", "type": "commit", "url": ""}}"#)
+            .create();
+        assert_eq!(
+            mock_github()
+                .get_release_commit("foo/bar", "v2.0.0")
+                .unwrap(),
+            "beef"
+        );
+        tag.assert();
+    }
+
+    #[test]
+    fn test_missing_tag() {
+        let _m = mock("GET", "/repos/foo/bar/git/ref/tags/v9.0.0")
+            .with_status(404)
+            .create();
+        assert!(matches!(
+            mock_github().get_release_commit("foo/bar", "v9.0.0"),
+            Err(LookupError::ReleaseNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_tag_path_is_encoded() {
+        let by_tag = mock(
+            "GET",
+            "/repos/foo/bar/releases/tags/releases%2Fv1.2.0%2Bbuild.5",
+        )
+        .with_body(release("releases/v1.2.0+build.5"))
+        .create();
+        let git_ref = mock(
+            "GET",
+            "/repos/foo/bar/git/ref/tags/releases%2Fv1.2.0%2Bbuild.5",
+        )
+        .with_body(r#"{"ref": "", "object": {"sha": "c0ffee", "type": "commit", "url": ""}}"#)
+        .create();
+        let found = mock_github()
+            .find_releases_for_tags("foo/bar", &["releases/v1.2.0+build.5"])
+            .unwrap();
+        assert!(found["releases/v1.2.0+build.5"].is_some());
+        assert_eq!(
+            mock_github()
+                .get_release_commit("foo/bar", "releases/v1.2.0+build.5")
+                .unwrap(),
+            "c0ffee"
+        );
+        by_tag.assert();
+        git_ref.assert();
+    }
+
+    #[test]
+    fn test_repository_path_is_encoded() {
+        let m = mock("GET", "/repos/foo/my%20repo/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        assert!(matches!(
+            mock_github().query("foo/my repo"),
+            Err(LookupError::RepositoryNotFound)
+        ));
+        m.assert();
+    }
+}
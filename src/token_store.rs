@@ -0,0 +1,301 @@
+//! Keeping access tokens in a credential store instead of the environment.
+
+use crate::{GitHub, LookupError, Result};
+use log::debug;
+
+/// Where a [`TokenStore`] keeps tokens, like the system keychain.
+///
+/// A backend's `load` returns `Ok(None)` when there's no token for the
+/// service, and an error when the store can't be used, like when it's locked.
+///
+/// Only available with the `keyring` feature.
+pub trait TokenBackend: Send + Sync {
+    /// Read the token for the service.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::TokenStore`] if the store can't
+    /// be read.
+    fn load(&self, service: &str) -> Result<Option<String>>;
+
+    /// Keep the token for the service, replacing any it had.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::TokenStore`] if the store can't
+    /// be written.
+    fn save(&self, service: &str, token: &str) -> Result<()>;
+}
+
+/// The system's credential store: the macOS Keychain, the Windows Credential
+/// Manager, or the Linux kernel keyring.
+///
+/// Tokens are kept under the service name, for the user
+/// [`KEYRING_USER`]. On Linux the kernel keyring is cleared when the machine
+/// restarts, so tokens have to be saved again after that.
+///
+/// Only available with the `keyring` feature.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Keyring;
+
+/// The user tokens are kept for in the [`Keyring`].
+pub const KEYRING_USER: &str = "github_release_check";
+
+impl Keyring {
+    /// The entry for the service's token.
+    fn entry(service: &str) -> Result<keyring::Entry> {
+        keyring::Entry::new(service, KEYRING_USER).map_err(|e| store_error(service, &e))
+    }
+}
+
+/// A [`LookupError::TokenStore`] for an error from the keyring.
+fn store_error(service: &str, error: &keyring::Error) -> LookupError {
+    LookupError::TokenStore {
+        service: service.to_owned(),
+        message: error.to_string(),
+    }
+}
+
+impl TokenBackend for Keyring {
+    fn load(&self, service: &str) -> Result<Option<String>> {
+        match Self::entry(service)?.get_password() {
+            Ok(token) => Ok(Some(token)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(store_error(service, &e)),
+        }
+    }
+
+    fn save(&self, service: &str, token: &str) -> Result<()> {
+        Self::entry(service)?
+            .set_password(token)
+            .map_err(|e| store_error(service, &e))
+    }
+}
+
+/// Access tokens kept by service name, for [`GitHub::from_keyring`] and
+/// [`GitHub::from_token_store`].
+///
+/// Only available with the `keyring` feature.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::{GitHub, TokenStore, DEFAULT_API_ROOT};
+///
+/// let store = TokenStore::keyring();
+/// if store.load("my-tool").is_none() {
+///     store.save("my-tool", "ghp_...").unwrap();
+/// }
+/// let github = GitHub::from_keyring(DEFAULT_API_ROOT, "my-tool").unwrap();
+/// ```
+pub struct TokenStore {
+    backend: Box<dyn TokenBackend>,
+}
+
+impl std::fmt::Debug for TokenStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenStore").finish_non_exhaustive()
+    }
+}
+
+impl TokenStore {
+    /// Keep tokens in the backend.
+    #[must_use]
+    pub fn new(backend: impl TokenBackend + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+        }
+    }
+
+    /// Keep tokens in the system's credential store.
+    #[must_use]
+    pub fn keyring() -> Self {
+        Self::new(Keyring)
+    }
+
+    /// Keep the token for the service, replacing any it had.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::TokenStore`] if the store can't
+    /// be written.
+    pub fn save(&self, service: &str, token: &str) -> Result<()> {
+        self.backend.save(service, token)
+    }
+
+    /// Read the token for the service, if there is one and the store can be
+    /// read.
+    ///
+    /// To tell a missing token from a store that can't be read, see
+    /// [`GitHub::from_token_store`].
+    #[must_use]
+    pub fn load(&self, service: &str) -> Option<String> {
+        match self.backend.load(service) {
+            Ok(token) => token,
+            Err(e) => {
+                debug!("Could not read the token for {service}: {e}");
+                None
+            }
+        }
+    }
+}
+
+impl GitHub {
+    /// Create an instance with the token kept for the service in the system's
+    /// credential store.
+    ///
+    /// This is [`GitHub::from_token_store`] with [`TokenStore::keyring`].
+    ///
+    /// Only available with the `keyring` feature.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::TokenNotFound`] if no token was
+    /// saved for the service, [`LookupError::TokenStore`] if the keychain
+    /// can't be read, like when it's locked, or for any of the reasons in
+    /// [`GitHubBuilder::build`](crate::GitHubBuilder::build).
+    pub fn from_keyring(api_root: &str, service_name: &str) -> Result<Self> {
+        Self::from_token_store(api_root, &TokenStore::keyring(), service_name)
+    }
+
+    /// Create an instance with the token kept for the service in the store.
+    ///
+    /// This is [`GitHub::from_custom`] with the stored token, so the token
+    /// doesn't have to be put in an environment variable or a config file.
+    ///
+    /// Only available with the `keyring` feature.
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::TokenNotFound`] if the store
+    /// has no token for the service, [`LookupError::TokenStore`] if the store
+    /// can't be read, like when it's locked, or for any of the reasons in
+    /// [`GitHubBuilder::build`](crate::GitHubBuilder::build).
+    pub fn from_token_store(api_root: &str, store: &TokenStore, service: &str) -> Result<Self> {
+        let token = store
+            .backend
+            .load(service)?
+            .ok_or_else(|| LookupError::TokenNotFound {
+                service: service.to_owned(),
+            })?;
+        Self::from_custom(api_root, &token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{store_error, TokenBackend, TokenStore};
+    use crate::{GitHub, LookupError, Result};
+    use std::{collections::HashMap, sync::Mutex};
+
+    /// Tokens kept in memory, or a store that is locked.
+    #[derive(Default)]
+    struct MemoryBackend {
+        tokens: Mutex<HashMap<String, String>>,
+        locked: bool,
+    }
+
+    impl MemoryBackend {
+        fn locked_error(service: &str) -> LookupError {
+            LookupError::TokenStore {
+                service: service.to_owned(),
+                message: "the keychain is locked".to_owned(),
+            }
+        }
+    }
+
+    impl TokenBackend for MemoryBackend {
+        fn load(&self, service: &str) -> Result<Option<String>> {
+            if self.locked {
+                return Err(Self::locked_error(service));
+            }
+            Ok(self.tokens.lock().unwrap().get(service).cloned())
+        }
+
+        fn save(&self, service: &str, token: &str) -> Result<()> {
+            if self.locked {
+                return Err(Self::locked_error(service));
+            }
+            let _prev = self
+                .tokens
+                .lock()
+                .unwrap()
+                .insert(service.to_owned(), token.to_owned());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let store = TokenStore::new(MemoryBackend::default());
+        assert_eq!(store.load("my-tool"), None);
+        store.save("my-tool", "first").unwrap();
+        store.save("my-tool", "second").unwrap();
+        assert_eq!(store.load("my-tool").as_deref(), Some("second"));
+        assert_eq!(store.load("other-tool"), None);
+
+        let github =
+            GitHub::from_token_store("https://github.example.com/api/v3/", &store, "my-tool")
+                .unwrap();
+        assert!(github.is_authenticated());
+        assert_eq!(github.api_root(), "https://github.example.com/api/v3/");
+    }
+
+    #[test]
+    fn test_missing_token() {
+        let store = TokenStore::new(MemoryBackend::default());
+        let error =
+            GitHub::from_token_store(crate::DEFAULT_API_ROOT, &store, "my-tool").unwrap_err();
+        assert!(
+            matches!(&error, LookupError::TokenNotFound { service } if service == "my-tool"),
+            "{error:?}"
+        );
+        assert_eq!(error.code(), "token_not_found");
+    }
+
+    #[test]
+    fn test_locked_store() {
+        let store = TokenStore::new(MemoryBackend {
+            locked: true,
+            ..MemoryBackend::default()
+        });
+        assert!(matches!(
+            store.save("my-tool", "token"),
+            Err(LookupError::TokenStore { .. })
+        ));
+        assert_eq!(store.load("my-tool"), None);
+        let error =
+            GitHub::from_token_store(crate::DEFAULT_API_ROOT, &store, "my-tool").unwrap_err();
+        assert!(
+            matches!(&error, LookupError::TokenStore { message, .. } if message.contains("locked")),
+            "{error:?}"
+        );
+        assert_eq!(error.code(), "token_store");
+    }
+
+    #[test]
+    fn test_keyring_without_a_token() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let store = TokenStore::keyring();
+        assert_eq!(store.load("my-tool"), None);
+        let error = GitHub::from_keyring(crate::DEFAULT_API_ROOT, "my-tool").unwrap_err();
+        assert!(
+            matches!(&error, LookupError::TokenNotFound { service } if service == "my-tool"),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn test_keyring_errors() {
+        let error = store_error(
+            "my-tool",
+            &keyring::Error::NoStorageAccess("the keychain is locked".into()),
+        );
+        assert!(
+            matches!(&error, LookupError::TokenStore { service, message }
+                if service == "my-tool" && message.contains("locked")),
+            "{error:?}"
+        );
+        assert_eq!(error.code(), "token_store");
+    }
+}
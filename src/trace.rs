@@ -0,0 +1,238 @@
+//! Recording how the latest version was found, for bug reports.
+
+use crate::{
+    ConfigSnapshot, GitHub, LookupError, RepositorySpec, Result, TaggedVersion, VersionSource,
+};
+use serde::Serialize;
+
+/// How many candidates a [`ResolutionTrace`] keeps.
+const TOP_CANDIDATES: usize = 3;
+
+/// Where the latest version was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ResolutionStrategy {
+    /// Every release was fetched and the highest version picked.
+    SemverScan,
+    /// The release GitHub marks as latest was used.
+    MarkedLatest,
+    /// Tags were compared with
+    /// [`GitHubBuilder::version_comparator`](crate::GitHubBuilder::version_comparator).
+    Comparator,
+    /// Releases couldn't be fetched, so the highest version in the release feed
+    /// was picked.
+    ReleaseFeed,
+}
+
+/// How the latest version was found, and what else was considered.
+///
+/// See [`GitHub::get_latest_with_trace`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolutionTrace {
+    /// Where the version was found.
+    pub strategy: ResolutionStrategy,
+    /// Where each release's version was read from.
+    pub version_source: VersionSource,
+    /// How many releases were looked at.
+    pub considered: usize,
+    /// How many releases were skipped because their tag is ignored.
+    pub ignored: usize,
+    /// How many releases were skipped because they don't have a version.
+    pub unparseable: usize,
+    /// The version prefixes set with
+    /// [`GitHubBuilder::version_prefixes`](crate::GitHubBuilder::version_prefixes).
+    pub version_prefixes: Vec<String>,
+    /// The tags set with [`GitHubBuilder::ignore_tags`](crate::GitHubBuilder::ignore_tags).
+    pub ignored_tags: Vec<String>,
+    /// The expression set with
+    /// [`GitHubBuilder::ignore_tags_matching`](crate::GitHubBuilder::ignore_tags_matching).
+    pub ignored_tag_pattern: Option<String>,
+    /// The highest versions that were candidates, highest first, up to three.
+    pub top_candidates: Vec<TaggedVersion>,
+    /// How the instance is configured, if added with [`ResolutionTrace::with_config`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<ConfigSnapshot>,
+}
+
+impl ResolutionTrace {
+    /// Start a trace with the filters the instance applies.
+    pub(crate) fn new(github: &GitHub, strategy: ResolutionStrategy) -> Self {
+        Self {
+            strategy,
+            version_source: github.settings.version_source,
+            considered: 0,
+            ignored: 0,
+            unparseable: 0,
+            version_prefixes: github.settings.version_prefixes.clone(),
+            ignored_tags: github.settings.ignored_tags.clone(),
+            ignored_tag_pattern: github
+                .settings
+                .ignored_tag_pattern
+                .as_ref()
+                .map(|pattern| pattern.as_str().to_owned()),
+            top_candidates: Vec::new(),
+            config: None,
+        }
+    }
+
+    /// Add the whole configuration of the instance, for bug reports that need
+    /// more than the filters.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let (_, trace) = github
+    ///     .get_latest_with_trace("celeo/github_release_check")
+    ///     .unwrap();
+    /// let report = serde_json::to_string_pretty(&trace.with_config(&github)).unwrap();
+    /// ```
+    #[must_use]
+    pub fn with_config(mut self, github: &GitHub) -> Self {
+        self.config = Some(github.config_snapshot());
+        self
+    }
+
+    /// Keep the highest of the candidates.
+    pub(crate) fn top_candidates<'a>(
+        mut self,
+        candidates: impl IntoIterator<Item = &'a TaggedVersion>,
+    ) -> Self {
+        let mut candidates: Vec<_> = candidates.into_iter().collect();
+        candidates.sort_by(|a, b| b.version.cmp(&a.version));
+        self.top_candidates = candidates
+            .into_iter()
+            .take(TOP_CANDIDATES)
+            .cloned()
+            .collect();
+        self
+    }
+}
+
+impl GitHub {
+    /// Get the latest release version from the repository, with how it was found.
+    ///
+    /// The version is the same as from [`GitHub::get_latest_tagged_version`]. The
+    /// trace says which strategy found it, how many releases were looked at and
+    /// skipped, the filters that were applied, and the closest runners-up. It can
+    /// be serialized for bug reports.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// let github = GitHub::new().unwrap();
+    /// let (latest, trace) = github
+    ///     .get_latest_with_trace("celeo/github_release_check")
+    ///     .unwrap();
+    /// println!("picked {} via {:?}", latest.tag, trace.strategy);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in `get_latest_version`.
+    pub fn get_latest_with_trace<R>(
+        &self,
+        repository: R,
+    ) -> Result<(TaggedVersion, ResolutionTrace)>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let (tagged, _, trace) = self.latest_release_traced(&repository.try_into()?)?;
+        Ok((tagged, trace))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResolutionStrategy;
+    use crate::{tests::release_json, GitHub, LatestStrategy};
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    fn release(tag: &str) -> String {
+        release_json(tag, json!({})).to_string()
+    }
+
+    #[test]
+    fn test_trace_semver_scan() {
+        let _m = mock("GET", "/repos/foo/traced/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!(
+                "[{}]",
+                [
+                    "v9.9.9",
+                    "nightly",
+                    "v1.2.0",
+                    "v2.0.0",
+                    "myapp-1.5.0",
+                    "v1.0.0"
+                ]
+                .map(release)
+                .join(", ")
+            ))
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .ignore_tags(&["9.9.9"])
+            .version_prefixes(&["myapp-"])
+            .build()
+            .unwrap();
+        let (latest, trace) = github.get_latest_with_trace("foo/traced").unwrap();
+        assert_eq!(latest.tag, "v2.0.0");
+        assert_eq!(trace.strategy, ResolutionStrategy::SemverScan);
+        assert_eq!(trace.considered, 6);
+        assert_eq!(trace.ignored, 1);
+        assert_eq!(trace.unparseable, 1);
+        assert_eq!(trace.ignored_tags, vec!["9.9.9"]);
+        assert_eq!(trace.version_prefixes, vec!["myapp-"]);
+        let top: Vec<_> = trace
+            .top_candidates
+            .iter()
+            .map(|t| t.tag.as_str())
+            .collect();
+        assert_eq!(top, vec!["v2.0.0", "myapp-1.5.0", "v1.2.0"]);
+
+        let json = serde_json::to_value(&trace).unwrap();
+        assert!(json.get("config").is_none());
+        assert_eq!(json["strategy"], "SemverScan");
+        assert_eq!(json["top_candidates"][0]["version"], "2.0.0");
+    }
+
+    #[test]
+    fn test_trace_marked_latest() {
+        let _latest = mock("GET", "/repos/foo/traced-marked/releases/latest")
+            .with_body(release("v1.8.9"))
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .latest_strategy(LatestStrategy::MarkedLatest)
+            .build()
+            .unwrap();
+        let (latest, trace) = github.get_latest_with_trace("foo/traced-marked").unwrap();
+        assert_eq!(latest.tag, "v1.8.9");
+        assert_eq!(trace.strategy, ResolutionStrategy::MarkedLatest);
+        assert_eq!(trace.considered, 1);
+        assert_eq!(trace.top_candidates, vec![latest]);
+    }
+
+    #[test]
+    fn test_trace_with_config() {
+        let _m = mock("GET", "/repos/foo/traced-config/releases")
+            .match_query(Matcher::Any)
+            .with_body(format!("[{}]", release("v1.0.0")))
+            .create();
+        let github = GitHub::builder()
+            .api_root(&format!("{}/", mockito::server_url()))
+            .token("trace-secret")
+            .build()
+            .unwrap();
+        let (_, trace) = github.get_latest_with_trace("foo/traced-config").unwrap();
+        let trace = trace.with_config(&github);
+        assert_eq!(trace.config, Some(github.config_snapshot()));
+        let json = serde_json::to_string(&trace).unwrap();
+        assert!(json.contains(r#""authenticated":true"#));
+        assert!(!json.contains("trace-secret"));
+    }
+}
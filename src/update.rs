@@ -0,0 +1,670 @@
+//! Checking whether a program built from a repository is out of date.
+
+use crate::{versions, GitHub, LookupError, RepositorySpec, Result};
+use log::{debug, warn};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, TryRecvError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// How often a background checker waiting out its interval looks for a
+/// dropped receiver.
+const STOP_POLL: Duration = Duration::from_millis(50);
+
+/// How a running version compares to the latest release.
+///
+/// See [`GitHub::check_update_against_pkg_version`]. It's written as JSON with
+/// the variant name in a `"status"` field, like
+/// `{"status": "Outdated", "current": "1.0.0", "latest": "1.1.0"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status")]
+pub enum UpdateStatus {
+    /// The running version is the latest release, or newer.
+    UpToDate,
+    /// A newer release is available.
+    Outdated {
+        /// The running version.
+        current: Version,
+        /// The latest release version.
+        latest: Version,
+    },
+    /// The latest release couldn't be looked up.
+    ///
+    /// Only returned by [`GitHub::check_update_quietly`].
+    Unknown,
+}
+
+/// Parse a version the way packages often write it: with or without a
+/// version prefix like `'v'`, surrounding whitespace, or missing minor and
+/// patch numbers.
+fn parse_current(current: &str) -> Result<Version> {
+    let trimmed = versions::normalize_tag(current, &[]);
+    if let Ok(version) = Version::parse(trimmed) {
+        return Ok(version);
+    }
+    let parts: Vec<_> = trimmed.split('.').collect();
+    let padded = match parts.as_slice() {
+        [major] => format!("{major}.0.0"),
+        [major, minor] => format!("{major}.{minor}.0"),
+        _ => String::new(),
+    };
+    Version::parse(&padded).map_err(|_| LookupError::InvalidVersion(current.to_owned()))
+}
+
+impl GitHub {
+    /// Compare the running version of a program with the latest release of its repository.
+    ///
+    /// `current_pkg_version` is usually `env!("CARGO_PKG_VERSION")`, which
+    /// [`check_self_update!`](crate::check_self_update) passes in for you. It may
+    /// have a leading `'v'` or leave out the minor and patch numbers.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{GitHub, UpdateStatus};
+    /// let github = GitHub::new().unwrap();
+    /// let status = github
+    ///     .check_update_against_pkg_version("celeo/github_release_check", env!("CARGO_PKG_VERSION"))
+    ///     .unwrap();
+    /// if let UpdateStatus::Outdated { latest, .. } = status {
+    ///     println!("version {latest} is available");
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails with [`LookupError::InvalidVersion`] if the current version
+    /// can't be read, or for any of the reasons in [`GitHub::get_latest_version`].
+    pub fn check_update_against_pkg_version<R>(
+        &self,
+        repository: R,
+        current_pkg_version: &str,
+    ) -> Result<UpdateStatus>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let current = parse_current(current_pkg_version)?;
+        let latest = self.get_latest_version(repository)?;
+        Ok(compare(current, latest))
+    }
+
+    /// Compare the running version of a program with the latest release of its
+    /// repository, returning [`UpdateStatus::Unknown`] if the lookup fails.
+    ///
+    /// This is [`GitHub::check_update_against_pkg_version`] for programs that
+    /// shouldn't complain when they're offline or rate limited.
+    ///
+    /// # Errors
+    ///
+    /// This function only fails with [`LookupError::InvalidVersion`] if the
+    /// current version can't be read.
+    pub fn check_update_quietly<R>(
+        &self,
+        repository: R,
+        current_pkg_version: &str,
+    ) -> Result<UpdateStatus>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let current = parse_current(current_pkg_version)?;
+        let latest = repository
+            .try_into()
+            .map_err(LookupError::from)
+            .and_then(|spec| self.latest_release(&spec));
+        match latest {
+            Ok((latest, _)) => Ok(compare(current, latest.version)),
+            Err(e) => {
+                debug!("Could not check for updates: {e}");
+                Ok(UpdateStatus::Unknown)
+            }
+        }
+    }
+}
+
+/// Compare the running version with the latest release version.
+fn compare(current: Version, latest: Version) -> UpdateStatus {
+    if latest > current {
+        UpdateStatus::Outdated { current, latest }
+    } else {
+        UpdateStatus::UpToDate
+    }
+}
+
+/// What [`UpdateChecker`] remembers between runs.
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckState {
+    /// When the latest release was last looked up, in seconds since the Unix epoch.
+    last_checked: u64,
+    /// The latest release version at that time.
+    last_seen_version: String,
+}
+
+/// Update checks that look up the latest release at most once per interval,
+/// remembering the result in a file in between.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::{GitHub, UpdateChecker, UpdateStatus};
+/// use std::time::Duration;
+///
+/// let checker = UpdateChecker::new(
+///     GitHub::new().unwrap(),
+///     "celeo/github_release_check",
+///     "/home/me/.cache/my-app/update-check.json",
+///     Duration::from_secs(24 * 3600),
+/// );
+/// if let Ok(UpdateStatus::Outdated { latest, .. }) = checker.check(env!("CARGO_PKG_VERSION")) {
+///     println!("version {latest} is available");
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UpdateChecker {
+    github: GitHub,
+    repository: String,
+    state_path: PathBuf,
+    interval: Duration,
+}
+
+impl UpdateChecker {
+    /// Create a checker for the repository's releases, remembering the last check
+    /// in the file at `state_path`.
+    pub fn new(
+        github: GitHub,
+        repository: &str,
+        state_path: impl AsRef<Path>,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            github,
+            repository: repository.to_owned(),
+            state_path: state_path.as_ref().to_owned(),
+            interval,
+        }
+    }
+
+    /// Compare the running version with the latest release.
+    ///
+    /// If the last check was less than the interval ago, the release version seen
+    /// then is used, without a request. Otherwise the latest release is looked up
+    /// and remembered. A missing or unreadable state file counts as never checked.
+    ///
+    /// # Errors
+    ///
+    /// This function fails for any of the reasons in
+    /// [`GitHub::check_update_against_pkg_version`], or with
+    /// [`LookupError::StateWrite`] if the state file can't be written.
+    pub fn check(&self, current_pkg_version: &str) -> Result<UpdateStatus> {
+        let current = parse_current(current_pkg_version)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if let Some(state) = self.read_state() {
+            let fresh = now.saturating_sub(state.last_checked) < self.interval.as_secs();
+            if let (true, Ok(latest)) = (fresh, Version::parse(&state.last_seen_version)) {
+                debug!(
+                    "Checked {} for updates {}s ago, not checking again",
+                    self.repository,
+                    now.saturating_sub(state.last_checked)
+                );
+                return Ok(compare(current, latest));
+            }
+        }
+        let latest = self.github.get_latest_version(&self.repository)?;
+        self.write_state(&CheckState {
+            last_checked: now,
+            last_seen_version: latest.to_string(),
+        })?;
+        Ok(compare(current, latest))
+    }
+
+    /// Read the state file, if there's a valid one.
+    fn read_state(&self) -> Option<CheckState> {
+        let content = fs::read_to_string(&self.state_path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                debug!(
+                    "Ignoring invalid update state at {}: {e}",
+                    self.state_path.display()
+                );
+                None
+            }
+        }
+    }
+
+    /// Replace the state file, writing to a temporary file first so that the
+    /// state is never left half-written.
+    fn write_state(&self, state: &CheckState) -> Result<()> {
+        let mut temp = self.state_path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        let error = |source| LookupError::StateWrite {
+            path: self.state_path.display().to_string(),
+            source,
+        };
+        let content = serde_json::to_string(state).unwrap_or_default();
+        fs::write(&temp, content).map_err(error)?;
+        fs::rename(&temp, &self.state_path).map_err(error)
+    }
+}
+
+/// The statuses sent by a background checker, from [`spawn_update_checker`].
+///
+/// Dropping it stops the checker, which ends its thread without making
+/// another request.
+#[derive(Debug)]
+pub struct UpdateReceiver {
+    receiver: Receiver<UpdateStatus>,
+    stop: Arc<AtomicBool>,
+}
+
+impl UpdateReceiver {
+    /// Wait for the next status.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the checker's thread has ended.
+    pub fn recv(&self) -> std::result::Result<UpdateStatus, mpsc::RecvError> {
+        self.receiver.recv()
+    }
+
+    /// Get the next status if one was sent, without waiting.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if no status is waiting, or the checker's thread
+    /// has ended.
+    pub fn try_recv(&self) -> std::result::Result<UpdateStatus, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Wait up to `timeout` for the next status.
+    ///
+    /// # Errors
+    ///
+    /// This function fails if no status is sent in time, or the checker's
+    /// thread has ended.
+    pub fn recv_timeout(
+        &self,
+        timeout: Duration,
+    ) -> std::result::Result<UpdateStatus, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Iterate over the statuses as they're sent, waiting for each.
+    #[must_use]
+    pub fn iter(&self) -> mpsc::Iter<'_, UpdateStatus> {
+        self.receiver.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a UpdateReceiver {
+    type Item = UpdateStatus;
+    type IntoIter = mpsc::Iter<'a, UpdateStatus>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Drop for UpdateReceiver {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Check the repository for updates every `interval` on a background thread,
+/// sending the status whenever it changes.
+///
+/// The first check always sends a status. Failed checks are logged and
+/// skipped, so [`UpdateStatus::Unknown`] is never sent. Once the receiver is
+/// dropped, the thread ends within a moment, without checking again.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::{spawn_update_checker, GitHub, UpdateStatus};
+/// use semver::Version;
+/// use std::time::Duration;
+///
+/// let (_handle, updates) = spawn_update_checker(
+///     GitHub::new().unwrap(),
+///     String::from("celeo/github_release_check"),
+///     Version::parse(env!("CARGO_PKG_VERSION")).unwrap(),
+///     Duration::from_secs(3600),
+/// );
+/// for status in &updates {
+///     if let UpdateStatus::Outdated { latest, .. } = status {
+///         println!("version {latest} is available");
+///     }
+/// }
+/// ```
+#[must_use]
+pub fn spawn_update_checker(
+    github: GitHub,
+    repository: String,
+    current: Version,
+    interval: Duration,
+) -> (JoinHandle<()>, UpdateReceiver) {
+    let (sender, receiver) = mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::clone(&stop);
+    let handle = thread::spawn(move || {
+        let mut last = None;
+        loop {
+            if stopped.load(Ordering::Relaxed) {
+                debug!("Update receiver for {repository} dropped, stopping");
+                return;
+            }
+            match github.get_latest_version(&repository) {
+                Ok(latest) => {
+                    let status = compare(current.clone(), latest);
+                    if last.as_ref() != Some(&status) {
+                        if sender.send(status.clone()).is_err() {
+                            debug!("Update receiver for {repository} dropped, stopping");
+                            return;
+                        }
+                        last = Some(status);
+                    }
+                }
+                Err(e) => warn!("Checking {repository} for updates failed: {e}"),
+            }
+            // wait in short steps, so a dropped receiver is noticed before the next check
+            let next = Instant::now() + interval;
+            while !stopped.load(Ordering::Relaxed) {
+                let left = next.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    break;
+                }
+                thread::sleep(left.min(STOP_POLL));
+            }
+        }
+    });
+    (handle, UpdateReceiver { receiver, stop })
+}
+
+/// Compare the version of the calling crate with the latest release of its repository.
+///
+/// This calls [`GitHub::check_update_against_pkg_version`] with the
+/// `CARGO_PKG_VERSION` of the crate the macro is used in. Add `quietly` to
+/// call [`GitHub::check_update_quietly`] instead.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use github_release_check::{check_self_update, GitHub, UpdateStatus};
+/// let github = GitHub::new().unwrap();
+/// if let Ok(UpdateStatus::Outdated { latest, .. }) =
+///     check_self_update!(github, "celeo/github_release_check", quietly)
+/// {
+///     println!("version {latest} is available");
+/// }
+/// ```
+#[macro_export]
+macro_rules! check_self_update {
+    ($github:expr, $repository:expr) => {
+        $github.check_update_against_pkg_version($repository, env!("CARGO_PKG_VERSION"))
+    };
+    ($github:expr, $repository:expr, quietly) => {
+        $github.check_update_quietly($repository, env!("CARGO_PKG_VERSION"))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_current, spawn_update_checker, UpdateChecker, UpdateStatus};
+    use crate::{
+        tests::{mock_github, release_json},
+        GitHub, LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use semver::Version;
+    use serde_json::json;
+    use std::{
+        fs,
+        path::PathBuf,
+        sync::mpsc::RecvTimeoutError,
+        time::{Duration, Instant},
+    };
+
+    fn mock_latest(repository: &str, tag: &str) -> mockito::Mock {
+        mock("GET", format!("/repos/{repository}/releases").as_str())
+            .match_query(Matcher::Any)
+            .with_body(json!([release_json(tag, json!({}))]).to_string())
+            .create()
+    }
+
+    #[test]
+    fn test_parse_current_tolerant() {
+        assert_eq!(parse_current("1.2.3").unwrap(), Version::new(1, 2, 3));
+        assert_eq!(parse_current(" v1.2.3\n").unwrap(), Version::new(1, 2, 3));
+        assert_eq!(parse_current("1.2").unwrap(), Version::new(1, 2, 0));
+        assert_eq!(parse_current("v2").unwrap(), Version::new(2, 0, 0));
+        assert!(matches!(
+            parse_current("latest"),
+            Err(LookupError::InvalidVersion(v)) if v == "latest"
+        ));
+    }
+
+    #[test]
+    fn test_outdated() {
+        let _m = mock_latest("foo/update-outdated", "v1.3.0");
+        let status = mock_github()
+            .check_update_against_pkg_version("foo/update-outdated", "1.2.0")
+            .unwrap();
+        assert_eq!(
+            status,
+            UpdateStatus::Outdated {
+                current: Version::new(1, 2, 0),
+                latest: Version::new(1, 3, 0),
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&status).unwrap(),
+            serde_json::json!({ "status": "Outdated", "current": "1.2.0", "latest": "1.3.0" })
+        );
+        assert_eq!(
+            serde_json::to_value(UpdateStatus::UpToDate).unwrap(),
+            serde_json::json!({ "status": "UpToDate" })
+        );
+    }
+
+    #[test]
+    fn test_current() {
+        let _m = mock_latest("foo/update-current", "v1.2.0");
+        let github = mock_github();
+        for current in ["1.2.0", "v1.2", "1.3.0-beta.1"] {
+            assert_eq!(
+                github
+                    .check_update_against_pkg_version("foo/update-current", current)
+                    .unwrap(),
+                UpdateStatus::UpToDate
+            );
+        }
+    }
+
+    #[test]
+    fn test_network_failure() {
+        let github = GitHub::builder()
+            .api_root("http://127.0.0.1:9/")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            github.check_update_against_pkg_version("foo/bar", "1.0.0"),
+            Err(LookupError::HttpClient(_))
+        ));
+        assert_eq!(
+            github.check_update_quietly("foo/bar", "1.0.0").unwrap(),
+            UpdateStatus::Unknown
+        );
+        assert!(matches!(
+            github.check_update_quietly("foo/bar", "not a version"),
+            Err(LookupError::InvalidVersion(_))
+        ));
+    }
+
+    fn state_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "github_release_check_{}_update_{name}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_checker_first_run_and_skip() {
+        let m = mock_latest("foo/checker-skip", "v1.3.0").expect(1);
+        let path = state_path("skip");
+        let checker = UpdateChecker::new(
+            mock_github(),
+            "foo/checker-skip",
+            &path,
+            Duration::from_secs(24 * 3600),
+        );
+        let outdated = UpdateStatus::Outdated {
+            current: Version::new(1, 2, 0),
+            latest: Version::new(1, 3, 0),
+        };
+        assert_eq!(checker.check("1.2.0").unwrap(), outdated);
+        assert!(fs::read_to_string(&path).unwrap().contains("1.3.0"));
+        // within the interval, the remembered version is used
+        assert_eq!(checker.check("1.2.0").unwrap(), outdated);
+        assert_eq!(checker.check("1.3.0").unwrap(), UpdateStatus::UpToDate);
+        m.assert();
+    }
+
+    #[test]
+    fn test_checker_refreshes_after_interval() {
+        let m = mock_latest("foo/checker-refresh", "v2.0.0").expect(1);
+        let path = state_path("refresh");
+        fs::write(
+            &path,
+            r#"{"last_checked": 1000, "last_seen_version": "1.0.0"}"#,
+        )
+        .unwrap();
+        let checker = UpdateChecker::new(
+            mock_github(),
+            "foo/checker-refresh",
+            &path,
+            Duration::from_secs(24 * 3600),
+        );
+        assert_eq!(
+            checker.check("1.0.0").unwrap(),
+            UpdateStatus::Outdated {
+                current: Version::new(1, 0, 0),
+                latest: Version::new(2, 0, 0),
+            }
+        );
+        assert!(fs::read_to_string(&path).unwrap().contains("2.0.0"));
+        m.assert();
+    }
+
+    #[test]
+    fn test_checker_corrupt_state() {
+        let m = mock_latest("foo/checker-corrupt", "v1.0.0").expect(1);
+        let path = state_path("corrupt");
+        fs::write(&path, "{ not json").unwrap();
+        let checker = UpdateChecker::new(
+            mock_github(),
+            "foo/checker-corrupt",
+            &path,
+            Duration::from_secs(24 * 3600),
+        );
+        assert_eq!(checker.check("1.0.0").unwrap(), UpdateStatus::UpToDate);
+        m.assert();
+    }
+
+    #[test]
+    fn test_background_checker_sends_changes() {
+        let _unchanged = mock_latest("foo/background", "v1.0.0").expect(2);
+        let _failing = mock("GET", "/repos/foo/background/releases")
+            .match_query(Matcher::Any)
+            .with_status(500)
+            .expect(1)
+            .create();
+        let released = mock_latest("foo/background", "v1.1.0");
+
+        let (handle, updates) = spawn_update_checker(
+            mock_github(),
+            String::from("foo/background"),
+            Version::new(1, 0, 0),
+            Duration::from_millis(20),
+        );
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            updates.recv_timeout(timeout).unwrap(),
+            UpdateStatus::UpToDate
+        );
+        assert_eq!(
+            updates.recv_timeout(timeout).unwrap(),
+            UpdateStatus::Outdated {
+                current: Version::new(1, 0, 0),
+                latest: Version::new(1, 1, 0),
+            }
+        );
+        assert_eq!(
+            updates.recv_timeout(Duration::from_millis(200)),
+            Err(RecvTimeoutError::Timeout)
+        );
+
+        // pulling the release changes the status, and the thread notices the receiver is gone
+        drop(updates);
+        drop(released);
+        let _pulled = mock_latest("foo/background", "v1.0.0");
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_background_checker_stops_without_changes() {
+        let m = mock_latest("foo/background-steady", "v1.0.0").expect(1);
+        let (handle, updates) = spawn_update_checker(
+            mock_github(),
+            String::from("foo/background-steady"),
+            Version::new(1, 0, 0),
+            Duration::from_secs(3600),
+        );
+        assert_eq!(
+            updates.recv_timeout(Duration::from_secs(5)).unwrap(),
+            UpdateStatus::UpToDate
+        );
+        // the status never changes, but dropping the receiver still ends the thread
+        drop(updates);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !handle.is_finished() {
+            assert!(Instant::now() < deadline, "the checker kept running");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        handle.join().unwrap();
+        m.assert();
+    }
+
+    #[test]
+    fn test_macro_uses_package_version() {
+        let _m = mock_latest("foo/update-macro", "v0.0.1");
+        let github = mock_github();
+        assert_eq!(
+            check_self_update!(github, "foo/update-macro").unwrap(),
+            UpdateStatus::UpToDate
+        );
+        assert_eq!(
+            check_self_update!(&github, "foo/update-macro", quietly).unwrap(),
+            UpdateStatus::UpToDate
+        );
+    }
+}
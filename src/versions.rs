@@ -0,0 +1,266 @@
+//! Picking versions out of tag names.
+//!
+//! Every source of tags goes through these functions, so that
+//! the same tags always produce the same versions.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use semver::Version;
+use serde::Serialize;
+
+static NAME_VERSION_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\d+\.\d+\.\d+[^\s]*").expect("Could not compile regex"));
+
+/// Prefixes stripped from tags before they're read as versions, besides any
+/// set with [`GitHubBuilder::version_prefixes`](crate::GitHubBuilder::version_prefixes).
+const VERSION_PREFIXES: [&str; 4] = ["v", "rel/", "release/", "version-"];
+
+/// Where the version of a release is read from.
+///
+/// See [`GitHubBuilder::version_source`](crate::GitHubBuilder::version_source).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum VersionSource {
+    /// Only the tag name.
+    #[default]
+    TagOnly,
+    /// The tag name, or the release name if the tag isn't a version.
+    NameFallback,
+    /// The release name, or the tag name if the name doesn't hold a version.
+    NameFirst,
+}
+
+/// Which part of a release a version was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum VersionOrigin {
+    /// The tag name.
+    Tag,
+    /// The release name.
+    Name,
+}
+
+/// A version parsed from a release, along with its tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaggedVersion {
+    /// The tag name, as it appears in the repository.
+    pub tag: String,
+    /// The version parsed from the release.
+    pub version: Version,
+    /// Whether the version came from the tag or the release name.
+    pub origin: VersionOrigin,
+}
+
+impl TaggedVersion {
+    /// Get the version as a string, without any prefix the tag had, like
+    /// `"1.4.0-rc.1"` for the tag `v1.4.0-rc.1`.
+    ///
+    /// The string can be read by any version of the `semver` crate, for when
+    /// the [`semver`](crate::semver) this crate uses isn't the one you depend on.
+    #[must_use]
+    pub fn to_string_version(&self) -> String {
+        self.version.to_string()
+    }
+
+    /// Read the version with a parser of your own, like that of another major
+    /// version of the `semver` crate.
+    ///
+    /// The parser is given [`TaggedVersion::to_string_version`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use github_release_check::{semver::Version, TaggedVersion, VersionOrigin};
+    /// let tagged = TaggedVersion {
+    ///     tag: "v1.4.0".to_owned(),
+    ///     version: Version::new(1, 4, 0),
+    ///     origin: VersionOrigin::Tag,
+    /// };
+    /// let parts = tagged.parse_with(|version| {
+    ///     let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    ///     Some((parts.next()??, parts.next()??, parts.next()??))
+    /// });
+    /// assert_eq!(parts, Some((1, 4, 0)));
+    /// ```
+    pub fn parse_with<T>(&self, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+        parse(&self.to_string_version())
+    }
+}
+
+/// The tag without surrounding whitespace and its version prefix, like `v` or
+/// `release/`.
+///
+/// Prefixes are compared ignoring case, and out of the built-in ones and
+/// `extra`, only the longest that matches is stripped. Every function that reads
+/// versions from tags goes through this, so that they all agree.
+pub(crate) fn normalize_tag<'a>(tag: &'a str, extra: &[String]) -> &'a str {
+    let tag = tag.trim();
+    let prefix = VERSION_PREFIXES
+        .into_iter()
+        .chain(extra.iter().map(String::as_str))
+        .filter(|prefix| {
+            tag.get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        })
+        .max_by_key(|prefix| prefix.len());
+    prefix.map_or(tag, |prefix| &tag[prefix.len()..])
+}
+
+/// Parse a tag name into a Semantic Version, stripping its version prefix.
+pub(crate) fn parse_tag(tag: &str, prefixes: &[String]) -> Option<Version> {
+    Version::parse(normalize_tag(tag, prefixes)).ok()
+}
+
+/// Find a Semantic Version in a release name, like `"MyApp 3.2.0"`.
+fn parse_name(name: &str) -> Option<Version> {
+    NAME_VERSION_REGEX
+        .find_iter(name)
+        .find_map(|m| Version::parse(m.as_str()).ok())
+}
+
+/// Read the version of a release from its tag and name.
+pub(crate) fn release_version(
+    tag: &str,
+    name: Option<&str>,
+    source: VersionSource,
+    prefixes: &[String],
+) -> Option<TaggedVersion> {
+    let from_tag = || parse_tag(tag, prefixes).map(|v| (v, VersionOrigin::Tag));
+    let from_name = || name.and_then(parse_name).map(|v| (v, VersionOrigin::Name));
+    let (version, origin) = match source {
+        VersionSource::TagOnly => from_tag(),
+        VersionSource::NameFallback => from_tag().or_else(from_name),
+        VersionSource::NameFirst => from_name().or_else(from_tag),
+    }?;
+    Some(TaggedVersion {
+        tag: tag.to_owned(),
+        version,
+        origin,
+    })
+}
+
+/// Parse every tag that holds a version, skipping the rest.
+pub(crate) fn tagged_versions(tags: &[String], prefixes: &[String]) -> Vec<TaggedVersion> {
+    tags.iter()
+        .filter_map(|tag| release_version(tag, None, VersionSource::TagOnly, prefixes))
+        .collect()
+}
+
+/// Select the highest Semantic Version from the version strings.
+///
+/// The built-in version prefixes are stripped from each string, and strings
+/// that still can't be parsed are skipped.
+#[cfg(any(feature = "gitea", feature = "gitlab", feature = "local-git"))]
+pub(crate) fn latest_version(versions: &[String]) -> Option<Version> {
+    versions.iter().filter_map(|s| parse_tag(s, &[])).max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        normalize_tag, parse_tag, release_version, TaggedVersion, VersionOrigin, VersionSource,
+    };
+    use semver::Version;
+
+    #[test]
+    fn test_only_name_has_version() {
+        let name = Some("MyApp 3.2.0");
+        assert_eq!(
+            release_version("build-20240715", name, VersionSource::TagOnly, &[]),
+            None
+        );
+        for source in [VersionSource::NameFallback, VersionSource::NameFirst] {
+            let tagged = release_version("build-20240715", name, source, &[]).unwrap();
+            assert_eq!(tagged.tag, "build-20240715");
+            assert_eq!(tagged.version, Version::new(3, 2, 0));
+            assert_eq!(tagged.origin, VersionOrigin::Name);
+        }
+    }
+
+    #[test]
+    fn test_only_tag_has_version() {
+        for source in [
+            VersionSource::TagOnly,
+            VersionSource::NameFallback,
+            VersionSource::NameFirst,
+        ] {
+            let tagged = release_version("v1.4.0", Some("Spring release"), source, &[]).unwrap();
+            assert_eq!(tagged.version, Version::new(1, 4, 0));
+            assert_eq!(tagged.origin, VersionOrigin::Tag);
+        }
+    }
+
+    #[test]
+    fn test_tag_and_name_disagree() {
+        let name = Some("MyApp 2.0.0-beta.1 (preview)");
+        let tagged = release_version("v1.9.0", name, VersionSource::NameFallback, &[]).unwrap();
+        assert_eq!(tagged.version, Version::new(1, 9, 0));
+        assert_eq!(tagged.origin, VersionOrigin::Tag);
+        let tagged = release_version("v1.9.0", name, VersionSource::NameFirst, &[]).unwrap();
+        assert_eq!(tagged.version, Version::parse("2.0.0-beta.1").unwrap());
+        assert_eq!(tagged.origin, VersionOrigin::Name);
+    }
+
+    #[test]
+    fn test_normalize_tag() {
+        let cases = [
+            ("1.2.3", "1.2.3"),
+            ("v1.2.3", "1.2.3"),
+            ("V1.2.3", "1.2.3"),
+            ("rel/1.2.3", "1.2.3"),
+            ("REL/1.2.3", "1.2.3"),
+            ("release/1.2.3", "1.2.3"),
+            ("Release/1.2.3", "1.2.3"),
+            ("version-1.2.3", "1.2.3"),
+            ("Version-1.2.3", "1.2.3"),
+            ("  v1.2.3\n", "1.2.3"),
+            // only one prefix is stripped
+            ("vv1.2.3", "v1.2.3"),
+            ("release/v1.2.3", "v1.2.3"),
+            // the rest of the tag keeps its case
+            ("v1.2.3-RC.1", "1.2.3-RC.1"),
+            // prefixes have to match in full
+            ("rel1.2.3", "rel1.2.3"),
+            ("nightly", "nightly"),
+            ("", ""),
+            ("v", ""),
+            ("ünïcode", "ünïcode"),
+        ];
+        for (tag, normalized) in cases {
+            assert_eq!(normalize_tag(tag, &[]), normalized, "tag {tag:?}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_tag_extra_prefixes() {
+        let extra = [String::from("myapp-"), String::from("myapp-v")];
+        let cases = [
+            ("myapp-1.2.3", "1.2.3"),
+            ("MyApp-1.2.3", "1.2.3"),
+            ("myapp-v1.2.3", "1.2.3"),
+            ("v1.2.3", "1.2.3"),
+            ("otherapp-1.2.3", "otherapp-1.2.3"),
+        ];
+        for (tag, normalized) in cases {
+            assert_eq!(normalize_tag(tag, &extra), normalized, "tag {tag:?}");
+        }
+        assert_eq!(
+            parse_tag("MyApp-v2.0.0", &extra),
+            Some(Version::new(2, 0, 0))
+        );
+        assert_eq!(parse_tag("MyApp-v2.0.0", &[]), None);
+    }
+
+    #[test]
+    fn test_to_string_version() {
+        let tagged = TaggedVersion {
+            tag: "release/1.4.0-rc.1+build.5".to_owned(),
+            version: parse_tag("release/1.4.0-rc.1+build.5", &[]).unwrap(),
+            origin: VersionOrigin::Tag,
+        };
+        assert_eq!(tagged.to_string_version(), "1.4.0-rc.1+build.5");
+        assert_eq!(
+            tagged.parse_with(|version| version.split_once('-').map(|(core, _)| core.to_owned())),
+            Some("1.4.0".to_owned())
+        );
+        assert_eq!(tagged.parse_with(|_| None::<u64>), None);
+    }
+}
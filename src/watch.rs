@@ -0,0 +1,273 @@
+//! Polling a repository for new releases in the background.
+
+use crate::{
+    Call, GitHub, GitHubReleaseItem, LookupError, RepositorySpec, Result, PAGINATION_REQUEST_AMOUNT,
+};
+use log::{debug, warn};
+use reqwest::{
+    header::{self, HeaderMap, HeaderValue},
+    StatusCode,
+};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Condvar, Mutex, PoisonError},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Handle to a background thread started by [`GitHub::watch_releases`].
+///
+/// The thread stops when [`ReleaseWatcher::stop`] is called or the handle is dropped.
+#[derive(Debug)]
+pub struct ReleaseWatcher {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ReleaseWatcher {
+    /// Stop polling, waiting for a poll in progress to finish.
+    pub fn stop(mut self) {
+        self.shut_down();
+    }
+
+    fn shut_down(&mut self) {
+        let (stopped, wake) = &*self.stop;
+        *stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        wake.notify_all();
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                warn!("Release watcher thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for ReleaseWatcher {
+    fn drop(&mut self) {
+        self.shut_down();
+    }
+}
+
+/// What a poll of the newest releases found.
+struct Poll {
+    releases: Option<Vec<GitHubReleaseItem>>,
+    etag: Option<HeaderValue>,
+}
+
+impl GitHub {
+    /// Fetch the newest page of releases, unless it hasn't changed since `etag`.
+    fn poll_releases(&self, spec: &RepositorySpec, etag: Option<&HeaderValue>) -> Result<Poll> {
+        let url = self.spec_url(spec, "releases");
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = etag {
+            let _prev = headers.insert(header::IF_NONE_MATCH, etag.clone());
+        }
+        let response = self.send(
+            &reqwest::Method::GET,
+            &url,
+            &[("per_page", PAGINATION_REQUEST_AMOUNT), ("page", 1)],
+            None::<&()>,
+            &headers,
+            &mut Call::default(),
+        )?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(Poll {
+                releases: None,
+                etag: etag.cloned(),
+            });
+        }
+        let etag = response.headers().get(header::ETAG).cloned();
+        Ok(Poll {
+            releases: Some(self.read_json(response)?),
+            etag,
+        })
+    }
+
+    /// Call `callback` for every new release in the repository, polling every `interval`.
+    ///
+    /// The releases that exist when watching starts are not passed to the callback;
+    /// after that, each new release is passed exactly once, oldest first. Polls are
+    /// conditional requests, so polls that find nothing new don't count against the
+    /// rate limit. Errors are logged and polling continues; use
+    /// [`GitHub::watch_releases_with_errors`] to handle them.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::GitHub;
+    /// use std::time::Duration;
+    /// let github = GitHub::new().unwrap();
+    /// let watcher = github.watch_releases(
+    ///     "celeo/github_release_check",
+    ///     Duration::from_secs(300),
+    ///     |release| println!("released {}", release.tag_name),
+    /// );
+    /// // ... later
+    /// watcher.stop();
+    /// ```
+    pub fn watch_releases<R>(
+        &self,
+        repository: R,
+        interval: Duration,
+        callback: impl FnMut(&GitHubReleaseItem) + Send + 'static,
+    ) -> ReleaseWatcher
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        self.watch_releases_with_errors(repository, interval, callback, |error| {
+            warn!("Polling for releases failed: {error}");
+        })
+    }
+
+    /// The same as [`GitHub::watch_releases`], but errors from polls are passed to `on_error`.
+    ///
+    /// The watcher keeps polling after an error. If the repository can't be
+    /// read, `on_error` gets [`LookupError::InvalidRepository`] and the watcher
+    /// stops without polling.
+    pub fn watch_releases_with_errors<R>(
+        &self,
+        repository: R,
+        interval: Duration,
+        mut callback: impl FnMut(&GitHubReleaseItem) + Send + 'static,
+        mut on_error: impl FnMut(LookupError) + Send + 'static,
+    ) -> ReleaseWatcher
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let github = self.clone();
+        let spec = repository.try_into().map_err(LookupError::from);
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_stop = Arc::clone(&stop);
+
+        let handle = thread::spawn(move || {
+            let spec = match spec {
+                Ok(spec) => spec,
+                Err(error) => return on_error(error),
+            };
+            let (stopped, wake) = &*thread_stop;
+            let mut seen: Option<HashSet<usize>> = None;
+            let mut etag = None;
+            loop {
+                match github.poll_releases(&spec, etag.as_ref()) {
+                    Ok(poll) => {
+                        etag = poll.etag;
+                        if let Some(releases) = poll.releases {
+                            match &mut seen {
+                                None => seen = Some(releases.iter().map(|r| r.id).collect()),
+                                Some(seen) => {
+                                    // the API lists the newest releases first
+                                    for release in releases.iter().rev() {
+                                        if seen.insert(release.id) {
+                                            debug!("New release {} in {spec}", release.tag_name);
+                                            callback(release);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Err(error) => on_error(error),
+                }
+                let guard = stopped.lock().unwrap_or_else(PoisonError::into_inner);
+                let (guard, _timeout) = wake
+                    .wait_timeout_while(guard, interval, |stopped| !*stopped)
+                    .unwrap_or_else(PoisonError::into_inner);
+                if *guard {
+                    break;
+                }
+            }
+        });
+
+        ReleaseWatcher {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{mock_github, release_json},
+        LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+    use std::{
+        sync::mpsc,
+        time::{Duration, Instant},
+    };
+
+    fn release(id: usize) -> String {
+        release_json(&format!("v{id}.0.0"), json!({ "id": id })).to_string()
+    }
+
+    #[test]
+    fn test_watch_delivers_new_releases_once() {
+        let first = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .with_header("etag", "\"a\"")
+            .with_body(format!("[{}]", release(1)))
+            .expect(1)
+            .create();
+        let _unchanged = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .match_header("if-none-match", "\"a\"")
+            .with_status(304)
+            .expect(1)
+            .create();
+        let changed = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .match_header("if-none-match", "\"a\"")
+            .with_header("etag", "\"b\"")
+            .with_body(format!("[{}, {}, {}]", release(3), release(2), release(1)))
+            .expect(1)
+            .create();
+        let _idle = mock("GET", "/repos/foo/bar/releases")
+            .match_query(Matcher::Any)
+            .match_header("if-none-match", "\"b\"")
+            .with_status(304)
+            .create();
+
+        let (sender, receiver) = mpsc::channel();
+        let watcher =
+            mock_github().watch_releases("foo/bar", Duration::from_millis(20), move |r| {
+                sender.send(r.tag_name.clone()).unwrap();
+            });
+        let delivered: Vec<_> = (0..2)
+            .map(|_| receiver.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        assert_eq!(delivered, vec!["v2.0.0", "v3.0.0"]);
+        std::thread::sleep(Duration::from_millis(100));
+
+        let start = Instant::now();
+        watcher.stop();
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(receiver.try_recv().is_err());
+        first.assert();
+        changed.assert();
+    }
+
+    #[test]
+    fn test_watch_reports_errors_and_keeps_polling() {
+        let _m = mock("GET", "/repos/foo/missing/releases")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create();
+        let (sender, receiver) = mpsc::channel();
+        let watcher = mock_github().watch_releases_with_errors(
+            "foo/missing",
+            Duration::from_millis(20),
+            |_| panic!("no releases to deliver"),
+            move |error| {
+                let _ = sender.send(matches!(error, LookupError::RepositoryNotFound));
+            },
+        );
+        for _ in 0..2 {
+            assert!(receiver.recv_timeout(Duration::from_secs(5)).unwrap());
+        }
+        drop(watcher);
+    }
+}
@@ -0,0 +1,246 @@
+//! Publishing releases.
+
+use crate::{
+    error_body, redact_url, request_id, Call, GitHub, GitHubReleaseItem, LookupError,
+    RepositorySpec, Result,
+};
+use log::debug;
+use reqwest::{blocking::Response, header::HeaderMap};
+use serde::{Deserialize, Serialize};
+
+/// A release to create with [`GitHub::create_release`].
+///
+/// For information on the struct keys, see [the GitHub docs].
+///
+/// Only available with the `write` feature.
+///
+/// [the GitHub docs]: https://docs.github.com/en/rest/releases/releases#create-a-release
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NewRelease {
+    /// Name of the tag to release; it's created if it doesn't exist yet.
+    pub tag_name: String,
+    /// Branch or commit to create the tag from, if it doesn't exist yet.
+    /// Defaults to the repository's default branch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_commitish: Option<String>,
+    /// Title of the release.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Release notes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Whether to create an unpublished draft.
+    pub draft: bool,
+    /// Whether to mark the release as not ready for general use.
+    pub prerelease: bool,
+    /// Whether GitHub should generate the name and notes from the changes since the last release.
+    pub generate_release_notes: bool,
+}
+
+/// Body of a 422 response.
+#[derive(Debug, Deserialize)]
+struct ValidationBody {
+    message: String,
+    #[serde(default)]
+    errors: Vec<ValidationItem>,
+}
+
+/// One of the problems listed in a 422 response.
+#[derive(Debug, Deserialize)]
+struct ValidationItem {
+    message: Option<String>,
+    field: Option<String>,
+    code: Option<String>,
+}
+
+/// The error for a 422 response to a call that writes, with GitHub's reasons.
+pub(crate) fn validation_failed(response: Response) -> LookupError {
+    let request_id = request_id(response.headers());
+    LookupError::ValidationFailed {
+        messages: validation_messages(&error_body(response)),
+        request_id,
+    }
+}
+
+/// Pull the human-readable messages out of a 422 response body.
+fn validation_messages(body: &str) -> Vec<String> {
+    let Ok(parsed) = serde_json::from_str::<ValidationBody>(body) else {
+        return vec![body.trim().to_owned()];
+    };
+    if parsed.errors.is_empty() {
+        return vec![parsed.message];
+    }
+    parsed
+        .errors
+        .into_iter()
+        .map(|item| match item {
+            ValidationItem {
+                message: Some(message),
+                ..
+            } => message,
+            ValidationItem { field, code, .. } => format!(
+                "{} {}",
+                field.as_deref().unwrap_or("release"),
+                code.as_deref().unwrap_or("invalid")
+            ),
+        })
+        .collect()
+}
+
+impl GitHub {
+    /// Create a release in the repository.
+    ///
+    /// Note that `repository` should be in the format "owner/repo",
+    /// like `"celeo/github_release_check"`, and that the instance
+    /// needs a token that can write to the repository.
+    ///
+    /// Only available with the `write` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use github_release_check::{GitHub, NewRelease};
+    /// let github = GitHub::from_custom("https://api.github.com/", "your-access-token").unwrap();
+    /// let release = github
+    ///     .create_release(
+    ///         "celeo/github_release_check",
+    ///         &NewRelease {
+    ///             tag_name: "v1.0.0".to_owned(),
+    ///             generate_release_notes: true,
+    ///             ..NewRelease::default()
+    ///         },
+    ///     )
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function fails if the HTTP request cannot be sent, the API returns
+    /// a status code indicating something other than a success, or the returned
+    /// data does not match the expected model. If GitHub rejects the release,
+    /// like when the tag already has a release, [`crate::LookupError::ValidationFailed`]
+    /// holds its reasons.
+    pub fn create_release<R>(&self, repository: R, params: &NewRelease) -> Result<GitHubReleaseItem>
+    where
+        R: TryInto<RepositorySpec>,
+        LookupError: From<R::Error>,
+    {
+        let url = self.spec_url(&repository.try_into()?, "releases");
+        debug!(
+            "Creating release {} at {}",
+            params.tag_name,
+            redact_url(&url)
+        );
+        let response = self.send(
+            &reqwest::Method::POST,
+            &url,
+            &[],
+            Some(params),
+            &HeaderMap::new(),
+            &mut Call {
+                validates: true,
+                ..Call::default()
+            },
+        )?;
+        self.read_json(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NewRelease;
+    use crate::{
+        tests::{mock_github, release_json},
+        LookupError,
+    };
+    use mockito::{mock, Matcher};
+    use serde_json::json;
+
+    #[test]
+    fn test_create_release() {
+        let m = mock("POST", "/repos/foo/bar/releases")
+            .match_body(Matcher::Json(json!({
+                "tag_name": "v1.0.0",
+                "name": "First",
+                "draft": false,
+                "prerelease": true,
+                "generate_release_notes": false,
+            })))
+            .with_status(201)
+            .with_body(
+                release_json(
+                    "v1.0.0",
+                    json!({ "id": 7, "name": "First", "prerelease": true, "body": null }),
+                )
+                .to_string(),
+            )
+            .create();
+        let release = mock_github()
+            .create_release(
+                "foo/bar",
+                &NewRelease {
+                    tag_name: "v1.0.0".to_owned(),
+                    name: Some("First".to_owned()),
+                    prerelease: true,
+                    ..NewRelease::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(release.id, 7);
+        assert!(release.prerelease);
+        m.assert();
+    }
+
+    #[test]
+    fn test_create_release_already_exists() {
+        let _m = mock("POST", "/repos/foo/bar/releases")
+            .with_status(422)
+            .with_header("x-github-request-id", "CAFE:0422")
+            .with_body(
+                r#"{"message": "Validation Failed", "errors": [{"resource": "Release", "code": "already_exists", "field": "tag_name"}]}"#,
+            )
+            .create();
+        let res = mock_github().create_release(
+            "foo/bar",
+            &NewRelease {
+                tag_name: "v1.0.0".to_owned(),
+                ..NewRelease::default()
+            },
+        );
+        match res {
+            Err(LookupError::ValidationFailed {
+                messages,
+                request_id,
+            }) => {
+                assert_eq!(messages, vec!["tag_name already_exists"]);
+                assert_eq!(request_id.as_deref(), Some("CAFE:0422"));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_read_422_is_not_validation() {
+        let _m = mock("GET", "/repos/foo/write-read-422/releases")
+            .match_query(Matcher::Any)
+            .with_status(422)
+            .with_header("x-github-request-id", "CAFE:0423")
+            .with_body(r#"{"message": "Only the first 1000 results are available."}"#)
+            .create();
+        match mock_github().get_all_versions("foo/write-read-422") {
+            Err(LookupError::ErrorHttpResponse {
+                status,
+                message,
+                request_id,
+            }) => {
+                assert_eq!(status, 422);
+                assert_eq!(
+                    message.as_deref(),
+                    Some("Only the first 1000 results are available.")
+                );
+                assert_eq!(request_id.as_deref(), Some("CAFE:0423"));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
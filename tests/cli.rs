@@ -0,0 +1,248 @@
+#![cfg(feature = "cli")]
+
+use mockito::{mock, Matcher};
+use std::process::{Command, Output};
+
+const RELEASE_REST: &str = r#", "url": "", "assets_url": "", "upload_url": "", "html_url": "", "id": 1, "name": "", "draft": false, "prerelease": false, "created_at": "", "published_at": "", "body": """#;
+
+fn mock_releases(repo: &str) -> mockito::Mock {
+    mock("GET", format!("/repos/foo/{repo}/releases").as_str())
+        .match_query(Matcher::Any)
+        .with_body(format!(
+            r#"[{{ "tag_name": "v1.3.0" {RELEASE_REST}}}, {{ "tag_name": "v1.2.0" {RELEASE_REST}}}]"#
+        ))
+        .create()
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_github-release-check"))
+        .arg("--api-root")
+        .arg(format!("{}/", mockito::server_url()))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn test_latest() {
+    let _m = mock_releases("cli-latest");
+    let output = run(&["--repo", "foo/cli-latest", "latest"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "1.3.0\n");
+
+    let output = run(&["--repo", "foo/cli-latest", "--json", "latest"]);
+    let value: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(value["latest"], "1.3.0");
+    assert_eq!(value["repository"], "foo/cli-latest");
+}
+
+#[test]
+fn test_list() {
+    let _m = mock_releases("cli-list");
+    let output = run(&["list", "--repo", "foo/cli-list"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "v1.3.0\nv1.2.0\n");
+
+    let output = run(&["--json", "--repo", "foo/cli-list", "list"]);
+    let value: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(value["versions"], serde_json::json!(["v1.3.0", "v1.2.0"]));
+}
+
+#[test]
+fn test_check_exit_codes() {
+    let _m = mock_releases("cli-check");
+    let output = run(&["--repo", "foo/cli-check", "check", "--current", "1.3.0"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "up to date: 1.3.0\n");
+
+    let output = run(&["--repo", "foo/cli-check", "check", "--current", "v1.2.0"]);
+    assert_eq!(output.status.code(), Some(10));
+    assert_eq!(stdout(&output), "update available: 1.2.0 -> 1.3.0\n");
+
+    let output = run(&[
+        "--repo",
+        "foo/cli-check",
+        "--json",
+        "check",
+        "--current",
+        "1.2.0",
+    ]);
+    assert_eq!(output.status.code(), Some(10));
+    let value: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(value["update_available"], true);
+}
+
+#[test]
+fn test_token_env() {
+    let m = mock("GET", "/repos/foo/cli-token/releases")
+        .match_query(Matcher::Any)
+        .match_header("authorization", "Bearer secret")
+        .with_body(format!(r#"[{{ "tag_name": "v2.0.0" {RELEASE_REST}}}]"#))
+        .expect(1)
+        .create();
+    let output = Command::new(env!("CARGO_BIN_EXE_github-release-check"))
+        .args(["--api-root", &format!("{}/", mockito::server_url())])
+        .args([
+            "--repo",
+            "foo/cli-token",
+            "--token-env",
+            "CLI_TEST_TOKEN",
+            "latest",
+        ])
+        .env("CLI_TEST_TOKEN", "secret")
+        .output()
+        .unwrap();
+    assert_eq!(stdout(&output), "2.0.0\n");
+    m.assert();
+}
+
+#[test]
+fn test_errors() {
+    let _m = mock("GET", "/repos/foo/cli-missing/releases")
+        .match_query(Matcher::Any)
+        .with_status(404)
+        .create();
+    let output = run(&["--repo", "foo/cli-missing", "latest"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error:"));
+
+    let output = run(&["--repo", "foo/cli-missing", "--json", "latest"]);
+    assert_eq!(output.status.code(), Some(1));
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"], "repository not found");
+    assert_eq!(error["code"], "repo_not_found");
+
+    assert_eq!(run(&["latest"]).status.code(), Some(1));
+    assert_eq!(run(&["--repo", "foo/bar", "check"]).status.code(), Some(1));
+    assert_eq!(
+        run(&["--repo", "foo/bar", "check", "--current", "soon"])
+            .status
+            .code(),
+        Some(1)
+    );
+    assert_eq!(run(&["--help"]).status.code(), Some(0));
+}
+
+#[test]
+fn test_json_uses_library_structs() {
+    let _m = mock_releases("cli-structs");
+    let output = run(&["--repo", "foo/cli-structs", "--json", "latest"]);
+    let value: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(value["tag"], "v1.3.0");
+    assert_eq!(value["version"], "1.3.0");
+    assert_eq!(value["origin"], "Tag");
+
+    let output = run(&[
+        "--repo",
+        "foo/cli-structs",
+        "--output",
+        "json",
+        "check",
+        "--current",
+        "1.3.0",
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    let value: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(value["status"], "UpToDate");
+    assert_eq!(value["update_available"], false);
+}
+
+#[test]
+fn test_versions_only() {
+    let _m = mock_releases("cli-versions-only");
+    let output = run(&[
+        "--repo",
+        "foo/cli-versions-only",
+        "--output",
+        "versions-only",
+        "latest",
+    ]);
+    assert_eq!(stdout(&output), "v1.3.0\n");
+    let output = run(&[
+        "--repo",
+        "foo/cli-versions-only",
+        "--output",
+        "versions-only",
+        "list",
+    ]);
+    assert_eq!(stdout(&output), "v1.3.0\nv1.2.0\n");
+    let output = run(&[
+        "--repo",
+        "foo/cli-versions-only",
+        "--output",
+        "versions-only",
+        "check",
+        "--current",
+        "1.0.0",
+    ]);
+    assert_eq!(output.status.code(), Some(10));
+    assert_eq!(stdout(&output), "v1.3.0\n");
+    assert_eq!(
+        run(&["--repo", "foo/bar", "--output", "yaml", "latest"])
+            .status
+            .code(),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_scan() {
+    let _found = mock_releases("cli-scan-found");
+    let _missing = mock("GET", "/repos/foo/cli-scan-missing/releases")
+        .match_query(Matcher::Any)
+        .with_status(404)
+        .create();
+    let output = run(&["--repo", "foo/cli-scan-found", "scan"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "foo/cli-scan-found 1.3.0\n");
+
+    let output = run(&[
+        "--repo",
+        "foo/cli-scan-missing",
+        "--repo",
+        "foo/cli-scan-found",
+        "--json",
+        "scan",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    let report = github_release_check::ScanReport::from_json(&stdout(&output)).unwrap();
+    assert_eq!(
+        report.repositories["foo/cli-scan-found"]
+            .latest_tag
+            .as_deref(),
+        Some("v1.3.0")
+    );
+    assert_eq!(
+        report.repositories["foo/cli-scan-missing"]
+            .error_code
+            .as_deref(),
+        Some("repo_not_found")
+    );
+
+    let output = run(&["--repo", "foo/a", "--repo", "foo/b", "latest"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_rate_limited_exit_code() {
+    let _m = mock("GET", "/repos/foo/cli-limited/releases")
+        .match_query(Matcher::Any)
+        .with_status(403)
+        .with_header("x-ratelimit-remaining", "0")
+        .with_header("x-ratelimit-reset", "4102444800")
+        .create();
+    let output = run(&["--repo", "foo/cli-limited", "latest"]);
+    assert_eq!(output.status.code(), Some(3));
+
+    let output = run(&["--repo", "foo/cli-limited", "--json", "latest"]);
+    assert_eq!(output.status.code(), Some(3));
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["code"], "rate_limited");
+
+    let output = run(&["--repo", "foo/cli-limited", "scan"]);
+    assert_eq!(output.status.code(), Some(3));
+}
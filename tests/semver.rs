@@ -0,0 +1,29 @@
+//! The re-exported `semver` crate works with the versions from the API, without
+//! depending on `semver` directly.
+
+use github_release_check::{
+    semver::{Version, VersionReq},
+    GitHub,
+};
+use mockito::{mock, Matcher};
+
+#[test]
+fn test_semver_reexport() {
+    let _m = mock("GET", "/repos/foo/reexport/releases")
+        .match_query(Matcher::Any)
+        .with_body(
+            r#"[{ "tag_name": "v1.4.0", "url": "", "assets_url": "", "upload_url": "", "html_url": "", "id": 1, "name": "", "draft": false, "prerelease": false, "created_at": "", "published_at": "", "body": "" }]"#,
+        )
+        .create();
+    let github = GitHub::from_custom(&format!("{}/", mockito::server_url()), "").unwrap();
+
+    let latest: Version = github.get_latest_version("foo/reexport").unwrap();
+    assert!(VersionReq::parse("^1.2").unwrap().matches(&latest));
+
+    let tagged = github.get_latest_tagged_version("foo/reexport").unwrap();
+    assert_eq!(tagged.to_string_version(), "1.4.0");
+    assert_eq!(
+        tagged.parse_with(|version| Version::parse(version).ok()),
+        Some(latest)
+    );
+}